@@ -0,0 +1,55 @@
+//! Runtime event model shared by every NAUX engine and renderer.
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the serialized event schema. Bump it whenever an event's shape changes.
+pub const EVENTS_VERSION: u32 = 1;
+
+/// One observable step of a running script, serialized as `{"type": <variant>, "value": <payload>}`:
+///
+/// | type     | value                                        |
+/// |----------|----------------------------------------------|
+/// | `Say`    | string                                       |
+/// | `SetVar` | `{ "name": string, "value": string }`        |
+/// | `Ask`    | `{ "prompt": string, "answer": string }`     |
+/// | `Fetch`  | `{ "target": string }`                       |
+/// | `Ui`     | `{ "kind": string, "props": [[key, value]] }` |
+/// | `UiEnd`  | absent                                       |
+/// | `Text`   | string                                       |
+/// | `Button` | string                                       |
+/// | `Log`    | string                                       |
+/// | `Table`  | `{ "columns": [string], "rows": [[string]] }` |
+/// | `Progress` | number in `[0, 1]`                         |
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum RuntimeEvent {
+    Say(String),
+    /// Variable assignment; the value is already rendered to text.
+    SetVar { name: String, value: String },
+    /// Oracle round-trip: the question asked and the answer received.
+    Ask { prompt: String, answer: String },
+    Fetch { target: String },
+    /// Opens a UI block, closed by `UiEnd` or by the next `Ui`.
+    Ui { kind: String, props: Vec<(String, String)> },
+    UiEnd,
+    Text(String),
+    Button(String),
+    Log(String),
+    /// Rows are aligned with `columns`; missing cells are empty strings.
+    Table { columns: Vec<String>, rows: Vec<Vec<String>> },
+    /// Completion fraction, clamped to `[0, 1]` by the emitter.
+    Progress(f64),
+}
+
+/// Top-level JSON document: `{ "naux_events_version": 1, "events": [...] }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventStream {
+    pub naux_events_version: u32,
+    pub events: Vec<RuntimeEvent>,
+}
+
+impl EventStream {
+    pub fn new(events: Vec<RuntimeEvent>) -> Self {
+        Self { naux_events_version: EVENTS_VERSION, events }
+    }
+}
@@ -0,0 +1,37 @@
+use naux_events::{EventStream, RuntimeEvent, EVENTS_VERSION};
+
+#[test]
+fn every_variant_round_trips_through_json() {
+    let events = vec![
+        RuntimeEvent::Say("hi".into()),
+        RuntimeEvent::SetVar { name: "x".into(), value: "42".into() },
+        RuntimeEvent::Ask { prompt: "why?".into(), answer: "because".into() },
+        RuntimeEvent::Fetch { target: "https://example.com".into() },
+        RuntimeEvent::Ui { kind: "card".into(), props: vec![("title".into(), "Hello".into())] },
+        RuntimeEvent::UiEnd,
+        RuntimeEvent::Text("body".into()),
+        RuntimeEvent::Button("ok".into()),
+        RuntimeEvent::Log("done".into()),
+        RuntimeEvent::Table { columns: vec!["a".into()], rows: vec![vec!["1".into()]] },
+        RuntimeEvent::Progress(0.25),
+    ];
+    let json = serde_json::to_string(&events).unwrap();
+    let back: Vec<RuntimeEvent> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, events);
+}
+
+#[test]
+fn events_use_type_value_tags() {
+    let json = serde_json::to_value(RuntimeEvent::Say("hi".into())).unwrap();
+    assert_eq!(json, serde_json::json!({ "type": "Say", "value": "hi" }));
+    let json = serde_json::to_value(RuntimeEvent::UiEnd).unwrap();
+    assert_eq!(json, serde_json::json!({ "type": "UiEnd" }));
+}
+
+#[test]
+fn stream_envelope_carries_version() {
+    let stream = EventStream::new(vec![RuntimeEvent::Say("hi".into())]);
+    let json = serde_json::to_value(&stream).unwrap();
+    assert_eq!(json["naux_events_version"], EVENTS_VERSION);
+    assert_eq!(json["events"][0]["type"], "Say");
+}
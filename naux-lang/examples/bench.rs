@@ -83,7 +83,7 @@ fn main() {
         let prog = compile_script(&ast);
         let start_vm = Instant::now();
         for _ in 0..iters {
-            if let Err(e) = run_program(&prog, &builtins) {
+            if let Err(e) = run_program(&prog, &builtins, code, name) {
                 eprintln!("vm error: {}", e);
                 break;
             }
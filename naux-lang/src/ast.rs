@@ -1,18 +1,53 @@
 #![allow(dead_code)]
 
-#[derive(Debug, Clone)]
+use serde::Serialize;
+
+/// Source range; `end_line`/`end_column` point at the last character (inclusive).
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Span {
     pub line: usize,
     pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
 }
 
-#[derive(Debug, Clone)]
+impl Span {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column, end_line: line, end_column: column }
+    }
+
+    /// Same start, covering `len` columns on the start line.
+    pub fn with_len(&self, len: usize) -> Self {
+        Self { end_line: self.line, end_column: self.column + len.max(1) - 1, ..self.clone() }
+    }
+
+    /// From the start of `self` to the end of `end`.
+    pub fn to(&self, end: &Span) -> Self {
+        Self { end_line: end.end_line, end_column: end.end_column, ..self.clone() }
+    }
+
+    /// Columns underlined on the start line; multi-line spans mark only the start.
+    pub fn width(&self) -> usize {
+        if self.end_line == self.line && self.end_column >= self.column {
+            self.end_column - self.column + 1
+        } else {
+            1
+        }
+    }
+
+    /// Caret line underlining the span, e.g. `    ^^^`.
+    pub fn caret(&self) -> String {
+        format!("{}{}", " ".repeat(self.column.saturating_sub(1)), "^".repeat(self.width()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Expr {
     pub kind: ExprKind,
     pub span: Option<Span>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum ExprKind {
     Number(f64),
     Bool(bool),
@@ -41,9 +76,30 @@ pub enum ExprKind {
         target: Box<Expr>,
         field: String,
     },
+    /// `cond ? then_expr : else_expr`; only the taken branch is evaluated.
+    Ternary {
+        cond: Box<Expr>,
+        then_expr: Box<Expr>,
+        else_expr: Box<Expr>,
+    },
+    /// `[elem for $var in iter if cond]`; `cond` is optional.
+    Comprehension {
+        elem: Box<Expr>,
+        var: String,
+        iter: Box<Expr>,
+        cond: Option<Box<Expr>>,
+    },
+    /// `"text {expr} text"`; each expression is formatted like `!say` output and spliced in.
+    Interpolate(Vec<InterpolPart>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum InterpolPart {
+    Text(String),
+    Expr(Expr),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum BinaryOp {
     Add,
     Sub,
@@ -60,14 +116,14 @@ pub enum BinaryOp {
     Or,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum UnaryOp {
     Neg,
     Not,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum Stmt {
     Rite {
         body: Vec<Stmt>,
@@ -81,6 +137,8 @@ pub enum Stmt {
         name: String,
         params: Vec<String>,
         body: Vec<Stmt>,
+        /// `~ fn f($n) memo`: cache results per argument values for the current run.
+        memo: bool,
         span: Option<Span>,
     },
     Assign {
@@ -100,6 +158,8 @@ pub enum Stmt {
         span: Option<Span>,
     },
     Each {
+        /// `~ each $i, $v in ...` binds the element position to `index` as well.
+        index: Option<String>,
         var: String,
         iter: Expr,
         body: Vec<Stmt>,
@@ -118,6 +178,22 @@ pub enum Stmt {
         value: Option<Expr>,
         span: Option<Span>,
     },
+    /// `~ break`: leave the innermost `loop`/`each`/`while`.
+    Break {
+        span: Option<Span>,
+    },
+    /// `~ continue`: skip to the next iteration of the innermost loop.
+    Continue {
+        span: Option<Span>,
+    },
+    /// `~ try … ~ catch $err … ~ end`: the first runtime error raised by `try_block` stops it
+    /// and runs `catch_block` with `catch_var` bound to `{message, code, trace}`.
+    TryCatch {
+        try_block: Vec<Stmt>,
+        catch_var: String,
+        catch_block: Vec<Stmt>,
+        span: Option<Span>,
+    },
     Import {
         module: String,
         span: Option<Span>,
@@ -125,7 +201,7 @@ pub enum Stmt {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum ActionKind {
     Say {
         value: Expr,
@@ -149,6 +225,12 @@ pub enum ActionKind {
     Log {
         value: Expr,
     },
+    Table {
+        rows: Expr,
+    },
+    Progress {
+        value: Expr,
+    },
 }
 
 impl Expr {
@@ -156,3 +238,228 @@ impl Expr {
         Self { kind, span }
     }
 }
+
+/// Entry-point convention: when the program defines a top-level `~ fn main()` without
+/// parameters, append `^ main()` so it runs after every top-level statement and its return
+/// value becomes the program's value. A top-level `^` reached earlier still ends the program.
+pub fn append_main_call(stmts: &mut Vec<Stmt>) {
+    let main_span = stmts.iter().find_map(|s| match s {
+        Stmt::FnDef { name, params, span, .. } if name == "main" && params.is_empty() => Some(span.clone()),
+        _ => None,
+    });
+    if let Some(span) = main_span {
+        let callee = Expr::new(ExprKind::Var("main".into()), span.clone());
+        let call = Expr::new(ExprKind::Call { callee: Box::new(callee), args: Vec::new() }, span.clone());
+        stmts.push(Stmt::Return { value: Some(call), span });
+    }
+}
+
+/// Serialize a parsed program to JSON for external tools; enums use serde's default
+/// externally tagged form (`{"Assign": {...}}`) and every node keeps its span.
+pub fn ast_to_json(stmts: &[Stmt]) -> String {
+    serde_json::to_string_pretty(stmts).expect("AST always serializes")
+}
+
+/// Pretty-print a parsed program as an indented tree (node kind, details, span).
+pub fn pretty_print_ast(stmts: &[Stmt]) -> String {
+    let mut out = String::new();
+    for stmt in stmts {
+        dump_stmt(&mut out, stmt, 0);
+    }
+    out
+}
+
+fn fmt_span(span: &Option<Span>) -> String {
+    match span {
+        Some(s) if s.end_line != s.line || s.end_column != s.column => {
+            format!(" @{}:{}-{}:{}", s.line, s.column, s.end_line, s.end_column)
+        }
+        Some(s) => format!(" @{}:{}", s.line, s.column),
+        None => String::new(),
+    }
+}
+
+fn line(out: &mut String, depth: usize, text: &str) {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(text);
+    out.push('\n');
+}
+
+fn dump_block(out: &mut String, label: &str, body: &[Stmt], depth: usize) {
+    line(out, depth, &format!("{}:", label));
+    for s in body {
+        dump_stmt(out, s, depth + 1);
+    }
+}
+
+fn dump_stmt(out: &mut String, stmt: &Stmt, depth: usize) {
+    match stmt {
+        Stmt::Rite { body, span } => {
+            line(out, depth, &format!("Rite{}", fmt_span(span)));
+            dump_block(out, "body", body, depth + 1);
+        }
+        Stmt::Unsafe { body, span } => {
+            line(out, depth, &format!("Unsafe{}", fmt_span(span)));
+            dump_block(out, "body", body, depth + 1);
+        }
+        Stmt::FnDef { name, params, body, memo, span } => {
+            let params: Vec<String> = params.iter().map(|p| format!("${}", p)).collect();
+            let memo = if *memo { " memo" } else { "" };
+            line(out, depth, &format!("FnDef {}({}){}{}", name, params.join(", "), memo, fmt_span(span)));
+            dump_block(out, "body", body, depth + 1);
+        }
+        Stmt::Assign { name, expr, span } => {
+            line(out, depth, &format!("Assign ${}{}", name, fmt_span(span)));
+            dump_expr(out, expr, depth + 1);
+        }
+        Stmt::If { cond, then_block, else_block, span } => {
+            line(out, depth, &format!("If{}", fmt_span(span)));
+            line(out, depth + 1, "cond:");
+            dump_expr(out, cond, depth + 2);
+            dump_block(out, "then", then_block, depth + 1);
+            if !else_block.is_empty() {
+                dump_block(out, "else", else_block, depth + 1);
+            }
+        }
+        Stmt::Loop { count, body, span } => {
+            line(out, depth, &format!("Loop{}", fmt_span(span)));
+            line(out, depth + 1, "count:");
+            dump_expr(out, count, depth + 2);
+            dump_block(out, "body", body, depth + 1);
+        }
+        Stmt::Each { index, var, iter, body, span } => {
+            let vars = match index {
+                Some(i) => format!("${}, ${}", i, var),
+                None => format!("${}", var),
+            };
+            line(out, depth, &format!("Each {}{}", vars, fmt_span(span)));
+            line(out, depth + 1, "iter:");
+            dump_expr(out, iter, depth + 2);
+            dump_block(out, "body", body, depth + 1);
+        }
+        Stmt::While { cond, body, span } => {
+            line(out, depth, &format!("While{}", fmt_span(span)));
+            line(out, depth + 1, "cond:");
+            dump_expr(out, cond, depth + 2);
+            dump_block(out, "body", body, depth + 1);
+        }
+        Stmt::Action { action, span } => {
+            let (name, values): (&str, Vec<&Expr>) = match action {
+                ActionKind::Say { value } => ("Say", vec![value]),
+                ActionKind::Ui { kind, props } => {
+                    line(out, depth, &format!("Action Ui {}{}", kind, fmt_span(span)));
+                    for (k, v) in props {
+                        line(out, depth + 1, &format!("{}:", k));
+                        dump_expr(out, v, depth + 2);
+                    }
+                    return;
+                }
+                ActionKind::Text { value } => ("Text", vec![value]),
+                ActionKind::Button { value } => ("Button", vec![value]),
+                ActionKind::Fetch { target } => ("Fetch", vec![target]),
+                ActionKind::Ask { prompt } => ("Ask", vec![prompt]),
+                ActionKind::Log { value } => ("Log", vec![value]),
+                ActionKind::Table { rows } => ("Table", vec![rows]),
+                ActionKind::Progress { value } => ("Progress", vec![value]),
+            };
+            line(out, depth, &format!("Action {}{}", name, fmt_span(span)));
+            for v in values {
+                dump_expr(out, v, depth + 1);
+            }
+        }
+        Stmt::Return { value, span } => {
+            line(out, depth, &format!("Return{}", fmt_span(span)));
+            if let Some(v) = value {
+                dump_expr(out, v, depth + 1);
+            }
+        }
+        Stmt::Break { span } => line(out, depth, &format!("Break{}", fmt_span(span))),
+        Stmt::Continue { span } => line(out, depth, &format!("Continue{}", fmt_span(span))),
+        Stmt::TryCatch { try_block, catch_var, catch_block, span } => {
+            line(out, depth, &format!("TryCatch{}", fmt_span(span)));
+            dump_block(out, "try", try_block, depth + 1);
+            dump_block(out, &format!("catch ${}", catch_var), catch_block, depth + 1);
+        }
+        Stmt::Import { module, span } => {
+            line(out, depth, &format!("Import \"{}\"{}", module, fmt_span(span)));
+        }
+    }
+}
+
+fn dump_expr(out: &mut String, expr: &Expr, depth: usize) {
+    let sp = fmt_span(&expr.span);
+    match &expr.kind {
+        ExprKind::Number(n) => line(out, depth, &format!("Number {}{}", n, sp)),
+        ExprKind::Bool(b) => line(out, depth, &format!("Bool {}{}", b, sp)),
+        ExprKind::Text(s) => line(out, depth, &format!("Text {:?}{}", s, sp)),
+        ExprKind::Var(name) => line(out, depth, &format!("Var {}{}", name, sp)),
+        ExprKind::List(items) => {
+            line(out, depth, &format!("List [{}]{}", items.len(), sp));
+            for item in items {
+                dump_expr(out, item, depth + 1);
+            }
+        }
+        ExprKind::Map(entries) => {
+            line(out, depth, &format!("Map {{{}}}{}", entries.len(), sp));
+            for (k, v) in entries {
+                line(out, depth + 1, &format!("{}:", k));
+                dump_expr(out, v, depth + 2);
+            }
+        }
+        ExprKind::Call { callee, args } => {
+            line(out, depth, &format!("Call{}", sp));
+            line(out, depth + 1, "callee:");
+            dump_expr(out, callee, depth + 2);
+            if !args.is_empty() {
+                line(out, depth + 1, "args:");
+                for a in args {
+                    dump_expr(out, a, depth + 2);
+                }
+            }
+        }
+        ExprKind::Binary { op, left, right } => {
+            line(out, depth, &format!("Binary {:?}{}", op, sp));
+            dump_expr(out, left, depth + 1);
+            dump_expr(out, right, depth + 1);
+        }
+        ExprKind::Unary { op, expr } => {
+            line(out, depth, &format!("Unary {:?}{}", op, sp));
+            dump_expr(out, expr, depth + 1);
+        }
+        ExprKind::Index { target, index } => {
+            line(out, depth, &format!("Index{}", sp));
+            dump_expr(out, target, depth + 1);
+            dump_expr(out, index, depth + 1);
+        }
+        ExprKind::Field { target, field } => {
+            line(out, depth, &format!("Field .{}{}", field, sp));
+            dump_expr(out, target, depth + 1);
+        }
+        ExprKind::Ternary { cond, then_expr, else_expr } => {
+            line(out, depth, &format!("Ternary{}", sp));
+            dump_expr(out, cond, depth + 1);
+            dump_expr(out, then_expr, depth + 1);
+            dump_expr(out, else_expr, depth + 1);
+        }
+        ExprKind::Comprehension { elem, var, iter, cond } => {
+            line(out, depth, &format!("Comprehension ${}{}", var, sp));
+            line(out, depth + 1, "elem:");
+            dump_expr(out, elem, depth + 2);
+            line(out, depth + 1, "iter:");
+            dump_expr(out, iter, depth + 2);
+            if let Some(c) = cond {
+                line(out, depth + 1, "if:");
+                dump_expr(out, c, depth + 2);
+            }
+        }
+        ExprKind::Interpolate(parts) => {
+            line(out, depth, &format!("Interpolate{}", sp));
+            for part in parts {
+                match part {
+                    InterpolPart::Text(t) => line(out, depth + 1, &format!("Text {:?}", t)),
+                    InterpolPart::Expr(e) => dump_expr(out, e, depth + 1),
+                }
+            }
+        }
+    }
+}
@@ -3,8 +3,10 @@ use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
+use crate::ast::{append_main_call, Stmt};
 use crate::cli::{util, DefaultEngine};
 use crate::renderer::{cli::render_cli_to_string, render_html};
+use crate::vm::compiler::compile_script;
 
 #[derive(Debug, Deserialize)]
 struct BuildToml {
@@ -115,9 +117,23 @@ pub fn handle_build() -> Result<(), String> {
     fs::write(&output_file, rendered)
         .map_err(|e| format!("Không ghi được {}: {}", output_file.display(), e))?;
     println!("Build thành công: {}", output_file.display());
+    let bytecode_file = write_bytecode(&ast, stem)?;
+    println!("Bytecode: {}", bytecode_file.display());
     Ok(())
 }
 
+/// Compile `ast` once more and persist it as `out/<stem>.nxb` for `naux run`.
+fn write_bytecode(ast: &[Stmt], stem: &str) -> Result<PathBuf, String> {
+    let out_dir = PathBuf::from("out");
+    fs::create_dir_all(&out_dir).map_err(|e| format!("Không tạo được thư mục {:?}: {}", out_dir, e))?;
+    let path = out_dir.join(format!("{}.nxb", stem));
+    let mut stmts = ast.to_vec();
+    append_main_call(&mut stmts);
+    fs::write(&path, compile_script(&stmts).to_bytes())
+        .map_err(|e| format!("Không ghi được {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
 fn load_build_config() -> Result<BuildOptions, String> {
     let path = Path::new("naux.toml");
     if !path.exists() {
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::ast::{Span, Stmt};
+use crate::cli::lint::{lint_recursion, lint_unused};
+use crate::cli::typecheck::type_errors;
+use crate::lexer;
+use crate::parser;
+use crate::runtime::env::Env;
+use crate::vm::compiler::{compile_ir, unresolved_calls};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Option<Span>) -> Self {
+        Self { severity: Severity::Error, message: message.into(), span }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Option<Span>) -> Self {
+        Self { severity: Severity::Warning, message: message.into(), span }
+    }
+
+    pub fn note(message: impl Into<String>, span: Option<Span>) -> Self {
+        Self { severity: Severity::Note, message: message.into(), span }
+    }
+}
+
+pub fn handle_check(path: Option<PathBuf>) -> Result<(), String> {
+    let target = path.unwrap_or_else(|| PathBuf::from("main.nx"));
+    let src = fs::read_to_string(&target).map_err(|e| format!("Không đọc được {}: {}", target.display(), e))?;
+    let filename = target.to_string_lossy();
+    let diags = check_source(&src);
+    for diag in &diags {
+        eprintln!("{}", format_diagnostic(&src, diag, &filename));
+    }
+    let errors = diags.iter().filter(|d| d.severity == Severity::Error).count();
+    if errors > 0 {
+        Err(format!("check thất bại: {} lỗi", errors))
+    } else {
+        println!("✅ {} OK", target.display());
+        Ok(())
+    }
+}
+
+/// Lex, parse and compile `src` without executing it, collecting every diagnostic found.
+pub fn check_source(src: &str) -> Vec<Diagnostic> {
+    let tokens = match lexer::lex(src) {
+        Ok(t) => t,
+        Err(e) => return vec![Diagnostic::error(format!("Lex error: {}", e.message), Some(e.span))],
+    };
+    let ast = match parser::Parser::from_tokens(&tokens) {
+        Ok(ast) => ast,
+        Err(e) => return vec![Diagnostic::error(format!("Parse error: {}", e.message), Some(e.span))],
+    };
+    let mut diags = Vec::new();
+    // Imported modules are only resolved at runtime, so their functions can't be checked here.
+    if !ast.iter().any(|s| matches!(s, Stmt::Import { .. })) {
+        let ir = compile_ir(&ast);
+        let mut env = Env::new();
+        crate::stdlib::register_all(&mut env);
+        let mut known: HashSet<String> = env.builtins().into_keys().collect();
+        known.insert("len".into()); // VM intrinsic
+        diags.extend(
+            unresolved_calls(&ir, &known)
+                .into_iter()
+                .map(|(name, span)| Diagnostic::error(format!("Unknown function: {}", name), span)),
+        );
+    }
+    diags.extend(type_errors(&ast));
+    diags.extend(lint_unused(&ast));
+    diags.extend(lint_recursion(&ast));
+    diags
+}
+
+pub fn format_diagnostic(src: &str, diag: &Diagnostic, filename: &str) -> String {
+    let label = match diag.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+    };
+    if let Some(span) = &diag.span {
+        let line_text = src.lines().nth(span.line.saturating_sub(1)).unwrap_or("");
+        let caret = span.caret();
+        format!(
+            "{}: {}\n --> {}:{}:{}\n {}\n {}",
+            label, diag.message, filename, span.line, span.column, line_text, caret
+        )
+    } else {
+        format!("{}: {}", label, diag.message)
+    }
+}
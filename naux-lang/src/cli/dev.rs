@@ -1,25 +1,27 @@
 use std::collections::BTreeSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+use crate::ast;
 use crate::cli::run;
-use crate::cli::{DefaultEngine, DefaultMode, DevCommand};
+use crate::cli::{DefaultEngine, DefaultMode, DevCommand, JsonStyle};
 use crate::cli::util;
 use crate::vm::{bytecode, compiler, ir};
 
 pub fn handle_dev(cmd: DevCommand) -> Result<(), String> {
     match cmd {
-        DevCommand::Run { path, engine, mode } => run_core(&path, &engine, &mode),
+        DevCommand::Run { path, engine, mode, json } => run_core(&path, &engine, &mode, json),
         DevCommand::Disasm { path } => disasm_core(&path),
+        DevCommand::Ast { path, json } => ast_core(&path, json),
         DevCommand::Ir { path } => ir_core(&path),
         DevCommand::Bench { path, engine, iters } => bench_core(&path, &engine, iters),
     }
 }
 
-pub fn run_core(path: &PathBuf, engine: &str, mode: &str) -> Result<(), String> {
+pub fn run_core(path: &PathBuf, engine: &str, mode: &str, json: JsonStyle) -> Result<(), String> {
     let engine = parse_engine(engine)?;
     let mode = parse_mode(mode)?;
-    run::handle_run(Some(path.clone()), mode, engine)
+    run::handle_run(Some(path.clone()), mode, engine, json, false)
 }
 
 pub fn disasm_core(path: &PathBuf) -> Result<(), String> {
@@ -43,6 +45,16 @@ pub fn disasm_core(path: &PathBuf) -> Result<(), String> {
     Ok(())
 }
 
+pub fn ast_core(path: &Path, json: bool) -> Result<(), String> {
+    let (_, ast) = util::load_ast(path)?;
+    if json {
+        println!("{}", ast::ast_to_json(&ast));
+    } else {
+        print!("{}", ast::pretty_print_ast(&ast));
+    }
+    Ok(())
+}
+
 pub fn ir_core(path: &PathBuf) -> Result<(), String> {
     let (_, ast) = util::load_ast(path)?;
     let ir_prog = compiler::compile_ir(&ast);
@@ -1,4 +1,4 @@
-use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, Stmt, UnaryOp};
+use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, InterpolPart, Stmt, UnaryOp};
 
 pub fn format_stmts(stmts: &[Stmt]) -> String {
     let mut formatter = Formatter::new();
@@ -62,13 +62,29 @@ impl Formatter {
                 self.indent -= 1;
                 self.write_line("~ end");
             }
-            Stmt::FnDef { name, params, body, .. } => {
+            Stmt::TryCatch { try_block, catch_var, catch_block, .. } => {
+                self.write_line("~ try");
+                self.indent += 1;
+                for stmt in try_block {
+                    self.format_stmt(stmt);
+                }
+                self.indent -= 1;
+                self.write_line(&format!("~ catch ${}", catch_var));
+                self.indent += 1;
+                for stmt in catch_block {
+                    self.format_stmt(stmt);
+                }
+                self.indent -= 1;
+                self.write_line("~ end");
+            }
+            Stmt::FnDef { name, params, body, memo, .. } => {
                 let params = params
                     .iter()
                     .map(|param| format!("${}", param))
                     .collect::<Vec<_>>()
                     .join(", ");
-                self.write_line(&format!("~ fn {}({})", name, params));
+                let memo = if *memo { " memo" } else { "" };
+                self.write_line(&format!("~ fn {}({}){}", name, params, memo));
                 self.indent += 1;
                 for stmt in body {
                     self.format_stmt(stmt);
@@ -110,8 +126,12 @@ impl Formatter {
                 self.indent -= 1;
                 self.write_line("~ end");
             }
-            Stmt::Each { var, iter, body, .. } => {
-                self.write_line(&format!("~ each ${} in {}", var, format_expr(iter)));
+            Stmt::Each { index, var, iter, body, .. } => {
+                let vars = match index {
+                    Some(i) => format!("${}, ${}", i, var),
+                    None => format!("${}", var),
+                };
+                self.write_line(&format!("~ each {} in {}", vars, format_expr(iter)));
                 self.indent += 1;
                 for stmt in body {
                     self.format_stmt(stmt);
@@ -138,6 +158,8 @@ impl Formatter {
                     self.write_line("^");
                 }
             }
+            Stmt::Break { .. } => self.write_line("~ break"),
+            Stmt::Continue { .. } => self.write_line("~ continue"),
             Stmt::Import { module, .. } => {
                 self.write_line(&format!("~ import \"{}\"", module));
             }
@@ -165,6 +187,8 @@ fn format_action(action: &ActionKind) -> String {
         ActionKind::Text { value } => format!("!text {}", format_expr(value)),
         ActionKind::Button { value } => format!("!button {}", format_expr(value)),
         ActionKind::Log { value } => format!("!log {}", format_expr(value)),
+        ActionKind::Table { rows } => format!("!table {}", format_expr(rows)),
+        ActionKind::Progress { value } => format!("!progress {}", format_expr(value)),
     }
 }
 
@@ -173,6 +197,16 @@ fn format_expr(expr: &Expr) -> String {
         ExprKind::Number(n) => format_number(*n),
         ExprKind::Bool(b) => format!("{}", b),
         ExprKind::Text(text) => format!("\"{}\"", escape_string(text)),
+        ExprKind::Interpolate(parts) => {
+            let body: String = parts
+                .iter()
+                .map(|part| match part {
+                    InterpolPart::Text(t) => escape_string(t),
+                    InterpolPart::Expr(e) => format!("{{{}}}", format_expr(e)),
+                })
+                .collect();
+            format!("\"{}\"", body)
+        }
         ExprKind::List(items) => {
             let inner = items.iter().map(format_expr).collect::<Vec<_>>().join(", ");
             format!("[{}]", inner)
@@ -209,6 +243,13 @@ fn format_expr(expr: &Expr) -> String {
         ExprKind::Field { target, field } => {
             format!("{}.{}", format_expr(target), field)
         }
+        ExprKind::Ternary { cond, then_expr, else_expr } => {
+            format!("{} ? {} : {}", format_expr(cond), format_expr(then_expr), format_expr(else_expr))
+        }
+        ExprKind::Comprehension { elem, var, iter, cond } => {
+            let guard = cond.as_ref().map(|c| format!(" if {}", format_expr(c))).unwrap_or_default();
+            format!("[{} for ${} in {}{}]", format_expr(elem), var, format_expr(iter), guard)
+        }
     }
 }
 
@@ -247,6 +288,9 @@ fn escape_string(src: &str) -> String {
             '\n' => out.push_str("\\n"),
             '\r' => out.push_str("\\r"),
             '\t' => out.push_str("\\t"),
+            // a bare brace would start an interpolation
+            '{' => out.push_str("\\{"),
+            '}' => out.push_str("\\}"),
             _ => out.push(ch),
         }
     }
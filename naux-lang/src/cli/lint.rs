@@ -0,0 +1,305 @@
+use std::collections::HashSet;
+
+use crate::ast::{ActionKind, Expr, ExprKind, InterpolPart, Span, Stmt};
+use crate::cli::check::Diagnostic;
+
+/// Warn about variables assigned but never read and functions defined but never called.
+/// Names starting with `_` are exempt, and so is `main`, which `naux run` calls itself.
+pub fn lint_unused(stmts: &[Stmt]) -> Vec<Diagnostic> {
+    let mut uses = Uses::default();
+    for stmt in stmts {
+        uses.stmt(stmt, None);
+    }
+
+    let mut defs = Vec::new();
+    collect_defs(stmts, &mut defs);
+
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for def in defs {
+        if def.name.starts_with('_') || !seen.insert((def.is_fn, def.name.clone())) {
+            continue;
+        }
+        if def.is_fn && def.name != "main" && !uses.calls.contains(&def.name) {
+            out.push(Diagnostic::warning(format!("Unused function: {}", def.name), def.span));
+        } else if !def.is_fn && !uses.reads.contains(&def.name) {
+            out.push(Diagnostic::warning(format!("Unused variable: ${}", def.name), def.span));
+        }
+    }
+    out
+}
+
+struct Def {
+    name: String,
+    is_fn: bool,
+    span: Option<Span>,
+}
+
+fn collect_defs(stmts: &[Stmt], out: &mut Vec<Def>) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Assign { name, span, .. } => out.push(Def { name: name.clone(), is_fn: false, span: span.clone() }),
+            Stmt::Each { index, var, body, span, .. } => {
+                if let Some(i) = index {
+                    out.push(Def { name: i.clone(), is_fn: false, span: span.clone() });
+                }
+                out.push(Def { name: var.clone(), is_fn: false, span: span.clone() });
+                collect_defs(body, out);
+            }
+            Stmt::FnDef { name, body, span, .. } => {
+                out.push(Def { name: name.clone(), is_fn: true, span: span.clone() });
+                collect_defs(body, out);
+            }
+            Stmt::If { then_block, else_block, .. } => {
+                collect_defs(then_block, out);
+                collect_defs(else_block, out);
+            }
+            // the catch variable is bound by the runtime, so leaving it unread is fine
+            Stmt::TryCatch { try_block, catch_block, .. } => {
+                collect_defs(try_block, out);
+                collect_defs(catch_block, out);
+            }
+            Stmt::Loop { body, .. }
+            | Stmt::While { body, .. }
+            | Stmt::Rite { body, .. }
+            | Stmt::Unsafe { body, .. } => collect_defs(body, out),
+            Stmt::Action { .. }
+            | Stmt::Return { .. }
+            | Stmt::Break { .. }
+            | Stmt::Continue { .. }
+            | Stmt::Import { .. } => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct Uses {
+    reads: HashSet<String>,
+    calls: HashSet<String>,
+}
+
+impl Uses {
+    /// `owner` is the enclosing function, so recursion alone doesn't count as a use.
+    fn stmt(&mut self, stmt: &Stmt, owner: Option<&str>) {
+        match stmt {
+            Stmt::Assign { expr, .. } => self.expr(expr, owner),
+            Stmt::If { cond, then_block, else_block, .. } => {
+                self.expr(cond, owner);
+                self.block(then_block, owner);
+                self.block(else_block, owner);
+            }
+            Stmt::Loop { count: e, body, .. } | Stmt::While { cond: e, body, .. } | Stmt::Each { iter: e, body, .. } => {
+                self.expr(e, owner);
+                self.block(body, owner);
+            }
+            Stmt::FnDef { name, body, .. } => self.block(body, Some(name)),
+            Stmt::Rite { body, .. } | Stmt::Unsafe { body, .. } => self.block(body, owner),
+            Stmt::TryCatch { try_block, catch_block, .. } => {
+                self.block(try_block, owner);
+                self.block(catch_block, owner);
+            }
+            Stmt::Action { action, .. } => {
+                for e in action_exprs(action) {
+                    self.expr(e, owner);
+                }
+            }
+            Stmt::Return { value, .. } => {
+                if let Some(v) = value {
+                    self.expr(v, owner);
+                }
+            }
+            Stmt::Break { .. } | Stmt::Continue { .. } | Stmt::Import { .. } => {}
+        }
+    }
+
+    fn block(&mut self, stmts: &[Stmt], owner: Option<&str>) {
+        for s in stmts {
+            self.stmt(s, owner);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, owner: Option<&str>) {
+        match &expr.kind {
+            ExprKind::Var(name) => {
+                self.reads.insert(name.clone());
+                self.call(name, owner);
+            }
+            ExprKind::Call { callee, args } => {
+                match &callee.kind {
+                    ExprKind::Var(name) => self.call(name, owner),
+                    _ => self.expr(callee, owner),
+                }
+                for a in args {
+                    self.expr(a, owner);
+                }
+            }
+            ExprKind::List(items) => {
+                for i in items {
+                    self.expr(i, owner);
+                }
+            }
+            ExprKind::Map(entries) => {
+                for (_, v) in entries {
+                    self.expr(v, owner);
+                }
+            }
+            ExprKind::Binary { left, right, .. } => {
+                self.expr(left, owner);
+                self.expr(right, owner);
+            }
+            ExprKind::Unary { expr, .. } => self.expr(expr, owner),
+            ExprKind::Index { target, index } => {
+                self.expr(target, owner);
+                self.expr(index, owner);
+            }
+            ExprKind::Field { target, .. } => self.expr(target, owner),
+            ExprKind::Ternary { cond, then_expr, else_expr } => {
+                self.expr(cond, owner);
+                self.expr(then_expr, owner);
+                self.expr(else_expr, owner);
+            }
+            ExprKind::Comprehension { elem, iter, cond, .. } => {
+                self.expr(elem, owner);
+                self.expr(iter, owner);
+                if let Some(c) = cond {
+                    self.expr(c, owner);
+                }
+            }
+            ExprKind::Interpolate(parts) => {
+                for part in parts {
+                    if let InterpolPart::Expr(e) = part {
+                        self.expr(e, owner);
+                    }
+                }
+            }
+            ExprKind::Number(_) | ExprKind::Bool(_) | ExprKind::Text(_) => {}
+        }
+    }
+
+    fn call(&mut self, name: &str, owner: Option<&str>) {
+        if owner != Some(name) {
+            self.calls.insert(name.to_string());
+        }
+    }
+}
+
+/// Report self-recursive calls: a note when the call is in tail position (eligible for TCO),
+/// a warning when its result feeds further computation and deep inputs may overflow the stack.
+pub fn lint_recursion(stmts: &[Stmt]) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    for stmt in stmts {
+        if let Stmt::FnDef { name, body, .. } = stmt {
+            for s in body {
+                recursion_in_stmt(name, s, &mut out);
+            }
+        }
+    }
+    out
+}
+
+fn recursion_in_stmt(name: &str, stmt: &Stmt, out: &mut Vec<Diagnostic>) {
+    match stmt {
+        Stmt::Return { value: Some(expr), .. } => recursion_in_tail(name, expr, out),
+        Stmt::Assign { expr, .. } => self_calls_in_expr(name, expr, out),
+        Stmt::If { cond, then_block, else_block, .. } => {
+            self_calls_in_expr(name, cond, out);
+            for s in then_block.iter().chain(else_block) {
+                recursion_in_stmt(name, s, out);
+            }
+        }
+        Stmt::Loop { count: e, body, .. } | Stmt::While { cond: e, body, .. } | Stmt::Each { iter: e, body, .. } => {
+            self_calls_in_expr(name, e, out);
+            for s in body {
+                recursion_in_stmt(name, s, out);
+            }
+        }
+        Stmt::Rite { body, .. } | Stmt::Unsafe { body, .. } => {
+            for s in body {
+                recursion_in_stmt(name, s, out);
+            }
+        }
+        Stmt::TryCatch { try_block, catch_block, .. } => {
+            for s in try_block.iter().chain(catch_block) {
+                recursion_in_stmt(name, s, out);
+            }
+        }
+        Stmt::Action { action, .. } => {
+            for e in action_exprs(action) {
+                self_calls_in_expr(name, e, out);
+            }
+        }
+        // a nested function is checked against its own name
+        Stmt::FnDef { .. } => out.extend(lint_recursion(std::slice::from_ref(stmt))),
+        Stmt::Return { value: None, .. } | Stmt::Break { .. } | Stmt::Continue { .. } | Stmt::Import { .. } => {}
+    }
+}
+
+/// `expr` is returned directly, so a self-call here (or in either ternary branch) is a tail call.
+fn recursion_in_tail(name: &str, expr: &Expr, out: &mut Vec<Diagnostic>) {
+    match &expr.kind {
+        ExprKind::Call { args, .. } if is_call_to(expr, name) => {
+            out.push(Diagnostic::note(format!("Tail call to {} (eligible for TCO)", name), expr.span.clone()));
+            for a in args {
+                self_calls_in_expr(name, a, out);
+            }
+        }
+        ExprKind::Ternary { cond, then_expr, else_expr } => {
+            self_calls_in_expr(name, cond, out);
+            recursion_in_tail(name, then_expr, out);
+            recursion_in_tail(name, else_expr, out);
+        }
+        _ => self_calls_in_expr(name, expr, out),
+    }
+}
+
+fn self_calls_in_expr(name: &str, expr: &Expr, out: &mut Vec<Diagnostic>) {
+    if is_call_to(expr, name) {
+        out.push(Diagnostic::warning(
+            format!("Non-tail recursive call to {} may overflow the stack on large inputs", name),
+            expr.span.clone(),
+        ));
+    }
+    for e in subexprs(expr) {
+        self_calls_in_expr(name, e, out);
+    }
+}
+
+fn is_call_to(expr: &Expr, name: &str) -> bool {
+    matches!(&expr.kind, ExprKind::Call { callee, .. } if matches!(&callee.kind, ExprKind::Var(n) if n == name))
+}
+
+pub(crate) fn action_exprs(action: &ActionKind) -> Vec<&Expr> {
+    match action {
+        ActionKind::Say { value }
+        | ActionKind::Text { value }
+        | ActionKind::Button { value }
+        | ActionKind::Log { value }
+        | ActionKind::Progress { value } => vec![value],
+        ActionKind::Fetch { target } => vec![target],
+        ActionKind::Table { rows } => vec![rows],
+        ActionKind::Ask { prompt } => vec![prompt],
+        ActionKind::Ui { props, .. } => props.iter().map(|(_, v)| v).collect(),
+    }
+}
+
+pub(crate) fn subexprs(expr: &Expr) -> Vec<&Expr> {
+    match &expr.kind {
+        ExprKind::Call { callee, args } => std::iter::once(callee.as_ref()).chain(args).collect(),
+        ExprKind::List(items) => items.iter().collect(),
+        ExprKind::Map(entries) => entries.iter().map(|(_, v)| v).collect(),
+        ExprKind::Binary { left, right, .. } => vec![left, right],
+        ExprKind::Unary { expr, .. } => vec![expr],
+        ExprKind::Index { target, index } => vec![target, index],
+        ExprKind::Field { target, .. } => vec![target],
+        ExprKind::Ternary { cond, then_expr, else_expr } => vec![cond, then_expr, else_expr],
+        ExprKind::Comprehension { elem, iter, cond, .. } => [Some(elem), Some(iter), cond.as_ref()].into_iter().flatten().map(|e| e.as_ref()).collect(),
+        ExprKind::Interpolate(parts) => parts
+            .iter()
+            .filter_map(|p| match p {
+                InterpolPart::Expr(e) => Some(e),
+                InterpolPart::Text(_) => None,
+            })
+            .collect(),
+        ExprKind::Var(_) | ExprKind::Number(_) | ExprKind::Bool(_) | ExprKind::Text(_) => Vec::new(),
+    }
+}
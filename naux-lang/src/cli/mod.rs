@@ -3,13 +3,18 @@ use std::path::PathBuf;
 use clap::{Parser, Subcommand, ValueEnum};
 
 pub mod build;
+pub mod check;
 pub mod dev;
 pub mod fmt;
 pub mod format;
 pub mod init;
+pub mod lint;
 pub mod new;
+pub mod repl;
 pub mod run;
 pub mod test;
+pub mod transpile;
+pub mod typecheck;
 pub mod util;
 
 const NAUX_VERSION: &str = "0.2.0-dev";
@@ -23,6 +28,9 @@ const NAUX_VERSION: &str = "0.2.0-dev";
     disable_help_subcommand = true
 )]
 pub struct Cli {
+    /// Disable ANSI colors in error output
+    #[arg(long, global = true)]
+    pub no_color: bool,
     #[command(subcommand)]
     pub command: Command,
 }
@@ -42,6 +50,12 @@ pub enum DefaultMode {
     Json,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonStyle {
+    Pretty,
+    Compact,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Command {
     New { name: String },
@@ -51,8 +65,25 @@ pub enum Command {
         mode: DefaultMode,
         #[arg(long, default_value = "vm")]
         engine: DefaultEngine,
+        #[arg(long, default_value = "pretty")]
+        json: JsonStyle,
+        /// Drop informational events (variable sets, `!log`, `!fetch` traces); errors still print.
+        #[arg(long)]
+        quiet: bool,
+        /// Let the script read host environment variables via `env_get`.
+        #[arg(long)]
+        allow_env: bool,
+        /// Arguments passed to the script, readable via `args()`.
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Interactive prompt; variables and functions persist between inputs.
+    Repl {
+        #[arg(long, default_value = "vm")]
+        engine: DefaultEngine,
     },
     Build,
+    Check { path: Option<PathBuf> },
     Fmt {
         path: Option<PathBuf>,
         #[arg(long)]
@@ -61,7 +92,21 @@ pub enum Command {
     Test {
         #[arg(value_name = "PATTERN")]
         pattern: Option<String>,
+        /// Compare each test's events with its sibling `.snap` file.
+        #[arg(long)]
+        snapshot: bool,
+        /// Rewrite `.snap` files with the current events (implies --snapshot).
+        #[arg(long)]
+        update_snapshots: bool,
+    },
+    /// Translate a script to another language (supported subset only).
+    Transpile {
+        path: PathBuf,
+        #[arg(long, value_parser = ["python"], default_value = "python")]
+        to: String,
     },
+    /// Language server over stdin/stdout (diagnostics, hover, completion, go to definition).
+    Lsp,
     Dev {
         #[command(subcommand)]
         cmd: DevCommand,
@@ -76,8 +121,16 @@ pub enum DevCommand {
         engine: String,
         #[arg(long, value_parser = ["cli", "html", "json"], default_value = "cli")]
         mode: String,
+        #[arg(long, default_value = "pretty")]
+        json: JsonStyle,
     },
     Disasm { path: PathBuf },
+    Ast {
+        path: PathBuf,
+        /// Emit the syntax tree as JSON instead of the indented dump.
+        #[arg(long)]
+        json: bool,
+    },
     Ir { path: PathBuf },
     Bench {
         path: PathBuf,
@@ -89,12 +142,30 @@ pub enum DevCommand {
 }
 
 pub fn run(cli: Cli) -> Result<(), String> {
+    crate::renderer::color::init(cli.no_color);
     match cli.command {
         Command::New { name } => new::handle_new(name),
-        Command::Run { path, mode, engine } => run::handle_run(path, mode, engine),
+        Command::Run { path, mode, engine, json, quiet, allow_env, args } => {
+            crate::stdlib::sys::allow_env(allow_env);
+            crate::stdlib::sys::set_script_args(args);
+            run::handle_run(path, mode, engine, json, quiet)
+        }
+        Command::Repl { engine } => repl::handle_repl(engine),
         Command::Build => build::handle_build(),
+        Command::Check { path } => check::handle_check(path),
         Command::Fmt { path, check } => fmt::handle_fmt(path, check),
-        Command::Test { pattern } => test::handle_test(pattern),
+        Command::Test { pattern, snapshot, update_snapshots } => {
+            let mode = if update_snapshots {
+                test::SnapshotMode::Update
+            } else if snapshot {
+                test::SnapshotMode::Compare
+            } else {
+                test::SnapshotMode::Off
+            };
+            test::handle_test(pattern, mode)
+        }
+        Command::Transpile { path, to } => transpile::handle_transpile(&path, &to),
+        Command::Lsp => crate::lsp::serve_stdio(),
         Command::Dev { cmd } => dev::handle_dev(cmd),
     }
 }
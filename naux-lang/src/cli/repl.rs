@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::ast::Stmt;
+use crate::cli::DefaultEngine;
+use crate::lexer;
+use crate::parser::error::format_parse_error;
+use crate::parser::parser::Parser;
+use crate::renderer::cli::render_cli_to_string;
+use crate::runtime::env::Env;
+use crate::runtime::error::format_runtime_error_with_file;
+use crate::runtime::eval::eval_in_env;
+use crate::runtime::events::RuntimeEvent;
+use crate::runtime::value::{repr, Value};
+use crate::stdlib::register_all;
+use crate::token::TokenKind;
+use crate::vm::bytecode::FunctionBytecode;
+use crate::vm::compiler::compile_script;
+use crate::vm::interpreter::run_program_with_globals;
+
+const REPL_FILE: &str = "<repl>";
+const PROMPT: &str = "naux> ";
+const CONTINUATION_PROMPT: &str = "... ";
+
+/// State kept between REPL inputs.
+///
+/// The interpreter evaluates each input in one persistent `Env`. The VM compiles each input on
+/// its own and runs it with the top-level variables and functions left by earlier inputs.
+pub struct ReplSession {
+    engine: DefaultEngine,
+    env: Env,
+    events: Vec<RuntimeEvent>,
+    vm_globals: HashMap<String, Value>,
+    vm_functions: HashMap<String, FunctionBytecode>,
+}
+
+/// Result of one REPL input: the events it emitted and its `^` value.
+#[derive(Debug)]
+pub struct ReplOutput {
+    pub events: Vec<RuntimeEvent>,
+    pub value: Value,
+}
+
+impl ReplSession {
+    pub fn new(engine: DefaultEngine) -> Result<Self, String> {
+        if !matches!(engine, DefaultEngine::Vm | DefaultEngine::Interp) {
+            return Err("naux repl chỉ hỗ trợ --engine vm hoặc interp".into());
+        }
+        let mut env = Env::new();
+        register_all(&mut env);
+        Ok(Self { engine, env, events: Vec::new(), vm_globals: HashMap::new(), vm_functions: HashMap::new() })
+    }
+
+    /// Every event emitted so far in this session.
+    pub fn events(&self) -> &[RuntimeEvent] {
+        &self.events
+    }
+
+    /// Parse and run one complete input. Errors are rendered and leave the session usable.
+    pub fn eval(&mut self, src: &str) -> Result<ReplOutput, String> {
+        let stmts = parse_input(src)?;
+        let seen = self.events.len();
+        let value = match self.engine {
+            DefaultEngine::Interp => {
+                let mut errors = Vec::new();
+                let value = eval_in_env(&stmts, &mut self.env, &mut self.events, &mut errors);
+                if !errors.is_empty() {
+                    let rendered: Vec<String> =
+                        errors.iter().map(|e| format_runtime_error_with_file(src, e, REPL_FILE)).collect();
+                    return Err(rendered.join("\n"));
+                }
+                value
+            }
+            _ => {
+                let mut prog = compile_script(&stmts);
+                self.vm_functions.extend(prog.functions.drain());
+                prog.functions = self.vm_functions.clone();
+                let builtins = self.env.builtins();
+                let (value, events) = run_program_with_globals(&prog, &builtins, src, REPL_FILE, &mut self.vm_globals)?;
+                self.events.extend(events);
+                value
+            }
+        };
+        Ok(ReplOutput { events: self.events[seen..].to_vec(), value })
+    }
+}
+
+/// Parse `src`; a line that is not a statement is retried as `^ <line>` so bare
+/// expressions like `1 + 2` print their value.
+fn parse_input(src: &str) -> Result<Vec<Stmt>, String> {
+    let tokens = lexer::lex(src).map_err(|e| {
+        format!("Lex error at {}:{}:{}: {}", REPL_FILE, e.span.line, e.span.column, e.message)
+    })?;
+    match Parser::from_tokens(&tokens) {
+        Ok(stmts) => Ok(stmts),
+        Err(err) => {
+            if !src.trim().contains('\n') {
+                let wrapped = format!("^ {}", src.trim());
+                if let Ok(stmts) = lexer::lex(&wrapped).map_err(|_| ()).and_then(|t| Parser::from_tokens(&t).map_err(|_| ())) {
+                    return Ok(stmts);
+                }
+            }
+            Err(format_parse_error(src, &err, REPL_FILE))
+        }
+    }
+}
+
+/// Number of `~`-opened blocks (`fn`, `if`, `loop`, `each`, `while`, `try`, `rite`, `unsafe`)
+/// still waiting for their `~ end`. Input that does not lex counts as complete so the error
+/// is reported instead of prompting for more.
+pub fn open_blocks(src: &str) -> usize {
+    let Ok(tokens) = lexer::lex(src) else {
+        return 0;
+    };
+    let mut depth: usize = 0;
+    for pair in tokens.windows(2) {
+        if pair[0].kind != TokenKind::Tilde {
+            continue;
+        }
+        match pair[1].kind {
+            TokenKind::Fn
+            | TokenKind::If
+            | TokenKind::Loop
+            | TokenKind::Each
+            | TokenKind::While
+            | TokenKind::Try
+            | TokenKind::Rite
+            | TokenKind::Unsafe => depth += 1,
+            TokenKind::End => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Read inputs from `input` until EOF, writing prompts, rendered events, values and errors to `out`.
+pub fn run_repl<R: BufRead, W: Write>(
+    input: R,
+    out: &mut W,
+    engine: DefaultEngine,
+    mut on_input: impl FnMut(&str),
+) -> Result<(), String> {
+    let mut session = ReplSession::new(engine)?;
+    let mut buffer = String::new();
+    let io_err = |e: io::Error| format!("Lỗi I/O: {}", e);
+    write!(out, "{}", PROMPT).map_err(io_err)?;
+    out.flush().map_err(io_err)?;
+    for line in input.lines() {
+        let line = line.map_err(io_err)?;
+        buffer.push_str(&line);
+        buffer.push('\n');
+        if open_blocks(&buffer) > 0 {
+            write!(out, "{}", CONTINUATION_PROMPT).map_err(io_err)?;
+            out.flush().map_err(io_err)?;
+            continue;
+        }
+        let src = std::mem::take(&mut buffer);
+        if !src.trim().is_empty() {
+            on_input(src.trim_end());
+            match session.eval(&src) {
+                Ok(res) => {
+                    write!(out, "{}", render_cli_to_string(&res.events)).map_err(io_err)?;
+                    if res.value != Value::Null {
+                        writeln!(out, "{}", repr(&res.value)).map_err(io_err)?;
+                    }
+                }
+                Err(msg) => writeln!(out, "{}", msg).map_err(io_err)?,
+            }
+        }
+        write!(out, "{}", PROMPT).map_err(io_err)?;
+        out.flush().map_err(io_err)?;
+    }
+    writeln!(out).map_err(io_err)?;
+    Ok(())
+}
+
+pub fn handle_repl(engine: DefaultEngine) -> Result<(), String> {
+    println!("NAUX REPL — nhập `~ fn`/`~ if`... nhiều dòng, kết thúc bằng `~ end`; Ctrl-D để thoát");
+    let history = history_path();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    run_repl(stdin.lock(), &mut stdout, engine, |src| {
+        if let Some(path) = &history {
+            // history is best-effort; a read-only home must not stop the session
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "{}", src);
+            }
+        }
+    })
+}
+
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".naux_history"))
+}
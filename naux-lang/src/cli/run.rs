@@ -1,27 +1,100 @@
 use std::fs;
 use std::path::PathBuf;
 
-use naux::lexer;
-use naux::parser;
-use naux::runtime;
-use naux::vm;
-use crate::cli::{DefaultEngine, DefaultMode};
+use crate::cli::{DefaultEngine, DefaultMode, JsonStyle};
+use crate::parser::error::format_parse_error;
+use crate::renderer::{render_cli, render_html, render_json};
+use crate::runtime::error::format_runtime_error_with_file;
+use crate::runtime::events::{quiet_events, RuntimeEvent};
+use crate::runtime::value::Value;
+use crate::vm::bytecode::is_bytecode;
+use crate::vm::run::run_bytecode;
+use crate::{run_source_named, Engine, NauxError};
 
-pub fn handle_run(path: Option<PathBuf>, _mode: DefaultMode, engine: DefaultEngine) -> Result<(), String> {
+/// Outcome of executing a script: emitted events, rendered diagnostics and the top-level value.
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub events: Vec<RuntimeEvent>,
+    pub errors: Vec<String>,
+    pub value: Value,
+}
+
+impl RunResult {
+    fn failed(msg: String) -> Self {
+        Self { events: Vec::new(), errors: vec![msg], value: Value::Null }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+pub fn handle_run(
+    path: Option<PathBuf>,
+    mode: DefaultMode,
+    engine: DefaultEngine,
+    json: JsonStyle,
+    quiet: bool,
+) -> Result<(), String> {
     let target = path.unwrap_or_else(|| PathBuf::from("main.nx"));
     if !target.exists() {
         return Err(format!("Không tìm thấy file `{}`", target.display()));
     }
-    let src = fs::read_to_string(&target).map_err(|e| format!("Không đọc được {}: {}", target.display(), e))?;
-    let tokens = lexer::lex(&src).map_err(|e| format!("Lex error: {}", e.message))?;
-    let ast = parser::parser::Parser::from_tokens(&tokens).map_err(|e| format!("Parse error: {}", e.message))?;
-    match engine {
-        DefaultEngine::Vm => vm::run::run_vm(&ast, &src, &target.to_string_lossy()).map(|_| ()),
-        DefaultEngine::Interp => {
-            runtime::eval_script(&ast);
-            Ok(())
+    let data = fs::read(&target).map_err(|e| format!("Không đọc được {}: {}", target.display(), e))?;
+    let filename = target.to_string_lossy();
+    let mut result = if is_bytecode(&data) {
+        execute_bytecode(&data, &filename)
+    } else {
+        let src = String::from_utf8(data).map_err(|e| format!("Không đọc được {}: {}", target.display(), e))?;
+        execute(&src, &filename, engine)
+    };
+    if quiet {
+        result.events = quiet_events(result.events);
+    }
+    match mode {
+        DefaultMode::Cli => render_cli(&result.events),
+        DefaultMode::Html => println!("{}", render_html(&result.events, &[])),
+        DefaultMode::Json => println!("{}", render_json(&result.events, json == JsonStyle::Compact)),
+    }
+    if result.is_ok() {
+        Ok(())
+    } else {
+        Err(result.errors.join("\n"))
+    }
+}
+
+/// Run `src` on `engine` without touching stdout; all I/O is left to the caller.
+pub fn execute(src: &str, filename: &str, engine: DefaultEngine) -> RunResult {
+    let engine = match engine {
+        DefaultEngine::Vm => Engine::Vm,
+        DefaultEngine::Interp => Engine::Interp,
+        DefaultEngine::Jit => Engine::Jit,
+        DefaultEngine::Llvm => return RunResult::failed("LLVM not supported yet".into()),
+    };
+    match run_source_named(src, filename, engine) {
+        Ok(out) => {
+            let errors = out
+                .errors
+                .iter()
+                .map(|err| match engine {
+                    // VM errors arrive already rendered with source context
+                    Engine::Vm | Engine::Jit => err.message.clone(),
+                    Engine::Interp => format_runtime_error_with_file(src, err, filename),
+                })
+                .collect();
+            RunResult { events: out.events, errors, value: out.value }
         }
-        DefaultEngine::Jit => vm::run::run_jit(&ast, &src, &target.to_string_lossy()).map(|_| ()),
-        DefaultEngine::Llvm => Err("LLVM not supported yet".into()),
+        Err(NauxError::Lex(e)) => {
+            RunResult::failed(format!("Lex error at {}:{}:{}: {}", filename, e.span.line, e.span.column, e.message))
+        }
+        Err(NauxError::Parse(e)) => RunResult::failed(format_parse_error(src, &e, filename)),
+    }
+}
+
+/// Run a serialized `.nxb` program (as written by `naux build`) on the VM.
+pub fn execute_bytecode(data: &[u8], filename: &str) -> RunResult {
+    match run_bytecode(data, filename) {
+        Ok((events, value)) => RunResult { events, errors: Vec::new(), value },
+        Err(msg) => RunResult::failed(msg),
     }
 }
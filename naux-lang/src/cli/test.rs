@@ -2,12 +2,15 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::cli::DefaultEngine;
-use naux::lexer;
-use naux::parser;
-use naux::runtime;
-use naux::runtime::events::RuntimeEvent;
-use naux::runtime::error::format_runtime_error_with_file;
-use naux::vm;
+use crate::renderer::color;
+use crate::lexer;
+use crate::parser;
+use crate::renderer::render_json;
+use crate::runtime;
+use crate::runtime::events::RuntimeEvent;
+use crate::runtime::error::format_runtime_error_with_file;
+use crate::stdlib::time;
+use crate::vm;
 
 pub struct TestResult {
     pub path: PathBuf,
@@ -15,6 +18,16 @@ pub struct TestResult {
     pub message: Option<String>,
 }
 
+/// How `naux test` treats the `.snap` file next to each test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotMode {
+    Off,
+    /// Fail when the emitted events differ from the stored snapshot.
+    Compare,
+    /// Rewrite every snapshot with the events of the current run.
+    Update,
+}
+
 pub struct TestSummary {
     pub results: Vec<TestResult>,
 }
@@ -37,19 +50,19 @@ impl TestSummary {
     }
 }
 
-pub fn handle_test(pattern: Option<String>) -> Result<(), String> {
+pub fn handle_test(pattern: Option<String>, snapshot: SnapshotMode) -> Result<(), String> {
     let mut paths = vec![PathBuf::from("tests")];
     if let Some(pat) = pattern {
         paths = vec![PathBuf::from(pat)];
     }
-    let summary = run_tests(&paths, DefaultEngine::Vm);
+    let summary = run_tests(&paths, DefaultEngine::Vm, snapshot);
     for result in &summary.results {
         if result.passed {
             println!("[PASS] {}", result.path.display());
         } else {
             println!("[FAIL] {}", result.path.display());
             if let Some(msg) = &result.message {
-                println!("  {}", msg.replace('\n', "\n  "));
+                println!("  {}", color::diagnostic(&msg.replace('\n', "\n  ")));
             }
         }
     }
@@ -102,15 +115,60 @@ fn matches_test_file(path: &Path) -> bool {
     }
 }
 
-fn run_tests(paths: &[PathBuf], engine: DefaultEngine) -> TestSummary {
+pub fn run_tests(paths: &[PathBuf], engine: DefaultEngine, snapshot: SnapshotMode) -> TestSummary {
     let mut summary = TestSummary::new();
     for path in discover_tests(paths) {
-        summary.add(run_test_file(&path, engine));
+        // Each file sees the same clock readings regardless of run order.
+        time::set_deterministic(true);
+        summary.add(run_test_file(&path, engine, snapshot));
     }
+    time::set_deterministic(false);
     summary
 }
 
-fn run_test_file(path: &Path, engine: DefaultEngine) -> TestResult {
+/// Sibling snapshot of a test file: `foo_test.nx` -> `foo_test.snap`.
+pub fn snapshot_path(path: &Path) -> PathBuf {
+    path.with_extension("snap")
+}
+
+/// Compare `events` against the snapshot stored next to `path`, or rewrite it in `Update` mode.
+pub fn check_snapshot(path: &Path, events: &[RuntimeEvent], mode: SnapshotMode) -> Result<(), String> {
+    let snap = snapshot_path(path);
+    let actual = format!("{}\n", render_json(events, false));
+    match mode {
+        SnapshotMode::Off => Ok(()),
+        SnapshotMode::Update => {
+            fs::write(&snap, actual).map_err(|e| format!("Failed to write {}: {}", snap.display(), e))
+        }
+        SnapshotMode::Compare => {
+            let expected = fs::read_to_string(&snap).map_err(|_| {
+                format!("Missing snapshot {} (run with --update-snapshots)", snap.display())
+            })?;
+            if expected == actual {
+                return Ok(());
+            }
+            let mut expected_lines = expected.lines();
+            let mut actual_lines = actual.lines();
+            let mut line = 1;
+            loop {
+                match (expected_lines.next(), actual_lines.next()) {
+                    (Some(e), Some(a)) if e == a => line += 1,
+                    (e, a) => {
+                        return Err(format!(
+                            "Snapshot mismatch in {} at line {}\n- {}\n+ {}",
+                            snap.display(),
+                            line,
+                            e.unwrap_or("<eof>"),
+                            a.unwrap_or("<eof>")
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn run_test_file(path: &Path, engine: DefaultEngine, snapshot: SnapshotMode) -> TestResult {
     let mut passed = true;
     let mut message = None;
 
@@ -186,6 +244,9 @@ fn run_test_file(path: &Path, engine: DefaultEngine) -> TestResult {
         }
     } else if !passed {
         message = message.or_else(|| fail_log);
+    } else if let Err(msg) = check_snapshot(path, &events, snapshot) {
+        passed = false;
+        message = Some(msg);
     }
 
     TestResult { path: path.to_path_buf(), passed, message }
@@ -0,0 +1,308 @@
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, InterpolPart, Span, Stmt, UnaryOp};
+use crate::cli::util;
+
+/// Python names that NAUX identifiers must not shadow; clashing names get a `_` suffix.
+const PY_RESERVED: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield", "print", "len", "str", "range",
+    "enumerate", "bool", "int", "float", "math", "functools",
+];
+
+const MOD_HELPER: &str = "def _naux_mod(a, b):
+    # NAUX `%` truncates toward zero like Rust, unlike Python's floored `%`
+    r = math.fmod(a, b)
+    return int(r) if isinstance(a, int) and isinstance(b, int) else r
+";
+
+pub fn handle_transpile(path: &Path, to: &str) -> Result<(), String> {
+    let (_, ast) = util::load_ast(path)?;
+    let out = match to {
+        "python" => transpile_python(&ast)?,
+        other => return Err(format!("Unsupported transpile target: {}", other)),
+    };
+    print!("{}", out);
+    Ok(())
+}
+
+/// Translate a program to Python 3. Supported: assignments, if/while/loop/each, functions
+/// (including `memo`), `^`, `!say`, `rite` blocks, arithmetic/comparison/logic, lists, maps,
+/// indexing, ternaries, comprehensions and calls to user functions, `len` and `to_text`.
+/// Anything else is reported as an error instead of being translated approximately.
+pub fn transpile_python(stmts: &[Stmt]) -> Result<String, String> {
+    let mut tr = PyTranspiler::new(stmts);
+    for stmt in stmts {
+        tr.stmt(stmt, true)?;
+    }
+    let mut out = String::new();
+    for module in &tr.imports {
+        out.push_str(&format!("import {}\n", module));
+    }
+    if !tr.imports.is_empty() {
+        out.push('\n');
+    }
+    if tr.needs_mod_helper {
+        out.push_str(MOD_HELPER);
+        out.push('\n');
+    }
+    out.push_str(&tr.body);
+    Ok(out)
+}
+
+struct PyTranspiler {
+    body: String,
+    indent: usize,
+    functions: HashSet<String>,
+    imports: BTreeSet<&'static str>,
+    needs_mod_helper: bool,
+}
+
+fn unsupported(what: &str, span: &Option<Span>) -> String {
+    match span {
+        Some(s) => format!("line {}: {} is not supported by the Python transpiler", s.line, what),
+        None => format!("{} is not supported by the Python transpiler", what),
+    }
+}
+
+fn py_name(name: &str) -> String {
+    if PY_RESERVED.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn py_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl PyTranspiler {
+    fn new(stmts: &[Stmt]) -> Self {
+        let mut functions = HashSet::new();
+        collect_functions(stmts, &mut functions);
+        Self { body: String::new(), indent: 0, functions, imports: BTreeSet::new(), needs_mod_helper: false }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.body.push_str("    ");
+        }
+        self.body.push_str(text);
+        self.body.push('\n');
+    }
+
+    fn block(&mut self, stmts: &[Stmt], top_level: bool) -> Result<(), String> {
+        self.indent += 1;
+        if stmts.is_empty() {
+            self.line("pass");
+        }
+        for stmt in stmts {
+            self.stmt(stmt, top_level)?;
+        }
+        self.indent -= 1;
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: &Stmt, top_level: bool) -> Result<(), String> {
+        match stmt {
+            // a rite only opens a scope; its statements run in place
+            Stmt::Rite { body, .. } => {
+                for stmt in body {
+                    self.stmt(stmt, top_level)?;
+                }
+            }
+            Stmt::FnDef { name, params, body, memo, .. } => {
+                if *memo {
+                    self.imports.insert("functools");
+                    self.line("@functools.lru_cache(maxsize=None)");
+                }
+                let params: Vec<String> = params.iter().map(|p| py_name(p)).collect();
+                self.line(&format!("def {}({}):", py_name(name), params.join(", ")));
+                self.block(body, false)?;
+                if self.indent == 0 {
+                    self.body.push('\n');
+                }
+            }
+            Stmt::Assign { name, expr, .. } => {
+                let value = self.expr(expr)?;
+                self.line(&format!("{} = {}", py_name(name), value));
+            }
+            Stmt::If { cond, then_block, else_block, .. } => {
+                let cond = self.expr(cond)?;
+                self.line(&format!("if {}:", cond));
+                self.block(then_block, top_level)?;
+                if !else_block.is_empty() {
+                    self.line("else:");
+                    self.block(else_block, top_level)?;
+                }
+            }
+            Stmt::Loop { count, body, .. } => {
+                // non-positive counts run zero times, as in NAUX
+                let count = self.expr(count)?;
+                self.line(&format!("for _ in range(int({})):", count));
+                self.block(body, top_level)?;
+            }
+            Stmt::Each { index, var, iter, body, .. } => {
+                let iter = self.expr(iter)?;
+                match index {
+                    Some(index) => self.line(&format!("for {}, {} in enumerate({}):", py_name(index), py_name(var), iter)),
+                    None => self.line(&format!("for {} in {}:", py_name(var), iter)),
+                }
+                self.block(body, top_level)?;
+            }
+            Stmt::While { cond, body, .. } => {
+                let cond = self.expr(cond)?;
+                self.line(&format!("while {}:", cond));
+                self.block(body, top_level)?;
+            }
+            Stmt::Action { action: ActionKind::Say { value }, .. } => {
+                let value = self.expr(value)?;
+                self.line(&format!("print({})", value));
+            }
+            Stmt::Action { span, .. } => return Err(unsupported("actions other than `!say`", span)),
+            Stmt::Return { span, .. } if top_level => return Err(unsupported("top-level `^`", span)),
+            Stmt::Return { value: Some(value), .. } => {
+                let value = self.expr(value)?;
+                self.line(&format!("return {}", value));
+            }
+            Stmt::Return { value: None, .. } => self.line("return None"),
+            Stmt::Break { .. } => self.line("break"),
+            Stmt::Continue { .. } => self.line("continue"),
+            Stmt::Unsafe { span, .. } => return Err(unsupported("`~ unsafe`", span)),
+            Stmt::TryCatch { span, .. } => return Err(unsupported("`~ try`", span)),
+            Stmt::Import { span, .. } => return Err(unsupported("`import`", span)),
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<String, String> {
+        Ok(match &expr.kind {
+            ExprKind::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            ExprKind::Number(n) => format!("{:?}", n),
+            ExprKind::Bool(b) => if *b { "True" } else { "False" }.to_string(),
+            ExprKind::Text(s) => py_string(s),
+            ExprKind::Interpolate(parts) => {
+                let mut pieces = Vec::new();
+                for part in parts {
+                    pieces.push(match part {
+                        InterpolPart::Text(t) => py_string(t),
+                        InterpolPart::Expr(e) => format!("str({})", self.expr(e)?),
+                    });
+                }
+                format!("\"\".join([{}])", pieces.join(", "))
+            }
+            ExprKind::List(items) => {
+                let items = items.iter().map(|i| self.expr(i)).collect::<Result<Vec<_>, _>>()?;
+                format!("[{}]", items.join(", "))
+            }
+            ExprKind::Map(entries) => {
+                let mut parts = Vec::new();
+                for (k, v) in entries {
+                    parts.push(format!("{}: {}", py_string(k), self.expr(v)?));
+                }
+                format!("{{{}}}", parts.join(", "))
+            }
+            ExprKind::Var(name) => py_name(name),
+            ExprKind::Call { callee, args } => {
+                let name = match &callee.kind {
+                    ExprKind::Var(name) => name,
+                    _ => return Err(unsupported("calls through expressions", &expr.span)),
+                };
+                let target = if self.functions.contains(name) {
+                    py_name(name)
+                } else {
+                    match name.as_str() {
+                        "len" => "len".to_string(),
+                        "to_text" => "str".to_string(),
+                        other => return Err(unsupported(&format!("builtin `{}`", other), &expr.span)),
+                    }
+                };
+                let args = args.iter().map(|a| self.expr(a)).collect::<Result<Vec<_>, _>>()?;
+                format!("{}({})", target, args.join(", "))
+            }
+            ExprKind::Binary { op: BinaryOp::Mod, left, right } => {
+                self.imports.insert("math");
+                self.needs_mod_helper = true;
+                format!("_naux_mod({}, {})", self.expr(left)?, self.expr(right)?)
+            }
+            ExprKind::Binary { op, left, right } => {
+                let (l, r) = (self.expr(left)?, self.expr(right)?);
+                match op {
+                    // NAUX logic operators always yield a Bool
+                    BinaryOp::And => format!("bool({} and {})", l, r),
+                    BinaryOp::Or => format!("bool({} or {})", l, r),
+                    _ => {
+                        let sym = match op {
+                            BinaryOp::Add => "+",
+                            BinaryOp::Sub => "-",
+                            BinaryOp::Mul => "*",
+                            BinaryOp::Div => "/",
+                            BinaryOp::Eq => "==",
+                            BinaryOp::Ne => "!=",
+                            BinaryOp::Gt => ">",
+                            BinaryOp::Ge => ">=",
+                            BinaryOp::Lt => "<",
+                            BinaryOp::Le => "<=",
+                            BinaryOp::Mod | BinaryOp::And | BinaryOp::Or => unreachable!("handled above"),
+                        };
+                        format!("({} {} {})", l, sym, r)
+                    }
+                }
+            }
+            ExprKind::Unary { op: UnaryOp::Neg, expr: inner } => format!("(-{})", self.expr(inner)?),
+            ExprKind::Unary { op: UnaryOp::Not, expr: inner } => format!("(not {})", self.expr(inner)?),
+            ExprKind::Index { target, index } => format!("{}[{}]", self.expr(target)?, self.expr(index)?),
+            ExprKind::Field { target, field } => format!("{}[{}]", self.expr(target)?, py_string(field)),
+            ExprKind::Ternary { cond, then_expr, else_expr } => {
+                format!("({} if {} else {})", self.expr(then_expr)?, self.expr(cond)?, self.expr(else_expr)?)
+            }
+            ExprKind::Comprehension { elem, var, iter, cond } => {
+                let elem = self.expr(elem)?;
+                let iter = self.expr(iter)?;
+                match cond {
+                    Some(cond) => format!("[{} for {} in {} if {}]", elem, py_name(var), iter, self.expr(cond)?),
+                    None => format!("[{} for {} in {}]", elem, py_name(var), iter),
+                }
+            }
+        })
+    }
+}
+
+fn collect_functions(stmts: &[Stmt], out: &mut HashSet<String>) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::FnDef { name, body, .. } => {
+                out.insert(name.clone());
+                collect_functions(body, out);
+            }
+            Stmt::Rite { body, .. } | Stmt::Unsafe { body, .. } | Stmt::Loop { body, .. } | Stmt::While { body, .. } => {
+                collect_functions(body, out)
+            }
+            Stmt::Each { body, .. } => collect_functions(body, out),
+            Stmt::If { then_block, else_block, .. } => {
+                collect_functions(then_block, out);
+                collect_functions(else_block, out);
+            }
+            Stmt::TryCatch { try_block, catch_block, .. } => {
+                collect_functions(try_block, out);
+                collect_functions(catch_block, out);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use crate::ast::{BinaryOp, Expr, ExprKind, Stmt, UnaryOp};
+use crate::cli::check::Diagnostic;
+use crate::cli::lint::{action_exprs, subexprs};
+
+/// Static type of an expression as far as literals and earlier assignments reveal it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ty {
+    Number,
+    Text,
+    Bool,
+    List,
+    Map,
+    Unknown,
+}
+
+impl Ty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Ty::Number => "Number",
+            Ty::Text => "Text",
+            Ty::Bool => "Bool",
+            Ty::List => "List",
+            Ty::Map => "Map",
+            Ty::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Report operations that fail at runtime for every input, e.g. `"a" - 1` or `-[1]`.
+/// Variable types follow assignments in order; a variable whose type differs between branches,
+/// or that a loop body reassigns, becomes `Unknown` and is never reported.
+pub fn type_errors(stmts: &[Stmt]) -> Vec<Diagnostic> {
+    let mut checker = Checker::default();
+    checker.block(stmts);
+    checker.out
+}
+
+type Scope = HashMap<String, Ty>;
+
+#[derive(Default)]
+struct Checker {
+    vars: Scope,
+    out: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn block(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    /// Check `stmts` starting from the current scope and return the scope they end with,
+    /// leaving the current one untouched.
+    fn branch(&mut self, stmts: &[Stmt], bind: &[(&str, Ty)]) -> Scope {
+        let saved = self.vars.clone();
+        for (name, ty) in bind {
+            self.vars.insert(name.to_string(), *ty);
+        }
+        self.block(stmts);
+        std::mem::replace(&mut self.vars, saved)
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assign { name, expr, .. } => {
+                let ty = self.expr(expr);
+                self.vars.insert(name.clone(), ty);
+            }
+            Stmt::FnDef { params, body, .. } => {
+                let saved = std::mem::take(&mut self.vars);
+                for p in params {
+                    self.vars.insert(p.clone(), Ty::Unknown);
+                }
+                self.block(body);
+                self.vars = saved;
+            }
+            Stmt::If { cond, then_block, else_block, .. } => {
+                self.expr(cond);
+                let then_scope = self.branch(then_block, &[]);
+                let else_scope = self.branch(else_block, &[]);
+                self.vars = merge(&[then_scope, else_scope]);
+            }
+            Stmt::Loop { count, body, .. } => {
+                self.expr(count);
+                self.loop_body(body, &[]);
+            }
+            Stmt::While { cond, body, .. } => {
+                self.forget_assigned(body);
+                self.expr(cond);
+                self.loop_body(body, &[]);
+            }
+            Stmt::Each { index, var, iter, body, .. } => {
+                self.expr(iter);
+                let mut bind = vec![(var.as_str(), Ty::Unknown)];
+                if let Some(i) = index {
+                    bind.push((i.as_str(), Ty::Number));
+                }
+                self.loop_body(body, &bind);
+            }
+            Stmt::TryCatch { try_block, catch_var, catch_block, .. } => {
+                // the body may stop at any statement, so the scope before it stays possible
+                let before = self.vars.clone();
+                let try_scope = self.branch(try_block, &[]);
+                let mut catch_from = merge(&[before.clone(), try_scope.clone()]);
+                std::mem::swap(&mut self.vars, &mut catch_from);
+                let catch_scope = self.branch(catch_block, &[(catch_var.as_str(), Ty::Map)]);
+                self.vars = merge(&[try_scope, catch_scope]);
+            }
+            Stmt::Rite { body, .. } | Stmt::Unsafe { body, .. } => self.block(body),
+            Stmt::Action { action, .. } => {
+                for e in action_exprs(action) {
+                    self.expr(e);
+                }
+            }
+            Stmt::Return { value, .. } => {
+                if let Some(v) = value {
+                    self.expr(v);
+                }
+            }
+            Stmt::Break { .. } | Stmt::Continue { .. } | Stmt::Import { .. } => {}
+        }
+    }
+
+    /// Loop bodies run zero or more times: anything they assign is `Unknown` inside the body
+    /// (a later iteration sees the previous one's value) and merged with the entry scope after.
+    fn loop_body(&mut self, body: &[Stmt], bind: &[(&str, Ty)]) {
+        self.forget_assigned(body);
+        let before = self.vars.clone();
+        let after = self.branch(body, bind);
+        self.vars = merge(&[before, after]);
+    }
+
+    fn forget_assigned(&mut self, body: &[Stmt]) {
+        let mut names = Vec::new();
+        assigned_names(body, &mut names);
+        for name in names {
+            self.vars.insert(name, Ty::Unknown);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Ty {
+        match &expr.kind {
+            ExprKind::Number(_) => Ty::Number,
+            ExprKind::Bool(_) => Ty::Bool,
+            ExprKind::Text(_) => Ty::Text,
+            ExprKind::Interpolate(_) => {
+                self.subexprs(expr);
+                Ty::Text
+            }
+            ExprKind::List(_) => {
+                self.subexprs(expr);
+                Ty::List
+            }
+            ExprKind::Map(_) => {
+                self.subexprs(expr);
+                Ty::Map
+            }
+            ExprKind::Var(name) => self.vars.get(name).copied().unwrap_or(Ty::Unknown),
+            ExprKind::Binary { op, left, right } => {
+                let l = self.expr(left);
+                let r = self.expr(right);
+                self.binary(op, l, r, expr)
+            }
+            ExprKind::Unary { op, expr: inner } => {
+                let ty = self.expr(inner);
+                match op {
+                    UnaryOp::Not => Ty::Bool,
+                    UnaryOp::Neg if ty == Ty::Number || ty == Ty::Unknown => ty,
+                    UnaryOp::Neg => {
+                        self.error(format!("Type error: cannot negate {}", ty.name()), expr);
+                        Ty::Unknown
+                    }
+                }
+            }
+            ExprKind::Ternary { cond, then_expr, else_expr } => {
+                self.expr(cond);
+                let a = self.expr(then_expr);
+                let b = self.expr(else_expr);
+                if a == b {
+                    a
+                } else {
+                    Ty::Unknown
+                }
+            }
+            ExprKind::Comprehension { elem, var, iter, cond } => {
+                self.expr(iter);
+                let shadowed = self.vars.insert(var.clone(), Ty::Unknown);
+                if let Some(c) = cond {
+                    self.expr(c);
+                }
+                self.expr(elem);
+                match shadowed {
+                    Some(ty) => self.vars.insert(var.clone(), ty),
+                    None => self.vars.remove(var),
+                };
+                Ty::List
+            }
+            ExprKind::Call { .. } | ExprKind::Index { .. } | ExprKind::Field { .. } => {
+                self.subexprs(expr);
+                Ty::Unknown
+            }
+        }
+    }
+
+    fn subexprs(&mut self, expr: &Expr) {
+        for e in subexprs(expr) {
+            self.expr(e);
+        }
+    }
+
+    fn binary(&mut self, op: &BinaryOp, l: Ty, r: Ty, expr: &Expr) -> Ty {
+        let known = l != Ty::Unknown && r != Ty::Unknown;
+        match op {
+            BinaryOp::Add => {
+                if !known {
+                    return Ty::Unknown;
+                }
+                match (l, r) {
+                    (Ty::Number, Ty::Number) => Ty::Number,
+                    (Ty::Text, Ty::Text) => Ty::Text,
+                    _ => {
+                        self.error(format!("Type error: cannot add {} and {}", l.name(), r.name()), expr);
+                        Ty::Unknown
+                    }
+                }
+            }
+            BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
+                let bad = [l, r].into_iter().find(|t| *t != Ty::Number && *t != Ty::Unknown);
+                if let Some(bad) = bad {
+                    let sym = match op {
+                        BinaryOp::Sub => "-",
+                        BinaryOp::Mul => "*",
+                        BinaryOp::Div => "/",
+                        _ => "%",
+                    };
+                    self.error(format!("Type error: `{}` needs numbers, got {}", sym, bad.name()), expr);
+                    return Ty::Unknown;
+                }
+                Ty::Number
+            }
+            BinaryOp::Gt | BinaryOp::Ge | BinaryOp::Lt | BinaryOp::Le => {
+                let ordered = matches!((l, r), (Ty::Number, Ty::Number) | (Ty::Text, Ty::Text));
+                if known && !ordered {
+                    self.error(format!("Type error: cannot compare {} with {}", l.name(), r.name()), expr);
+                }
+                Ty::Bool
+            }
+            BinaryOp::Eq | BinaryOp::Ne => Ty::Bool,
+            BinaryOp::And | BinaryOp::Or => Ty::Unknown,
+        }
+    }
+
+    fn error(&mut self, message: String, expr: &Expr) {
+        self.out.push(Diagnostic::error(message, expr.span.clone()));
+    }
+}
+
+/// Variables keep a type only when every scope agrees on it.
+fn merge(scopes: &[Scope]) -> Scope {
+    let mut out = Scope::new();
+    for scope in scopes {
+        for name in scope.keys() {
+            let first = scopes[0].get(name);
+            let same = scopes.iter().all(|s| s.get(name) == first);
+            out.insert(name.clone(), if same { *first.unwrap() } else { Ty::Unknown });
+        }
+    }
+    out
+}
+
+fn assigned_names(stmts: &[Stmt], out: &mut Vec<String>) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Assign { name, .. } => out.push(name.clone()),
+            Stmt::If { then_block, else_block, .. } => {
+                assigned_names(then_block, out);
+                assigned_names(else_block, out);
+            }
+            Stmt::TryCatch { try_block, catch_block, .. } => {
+                assigned_names(try_block, out);
+                assigned_names(catch_block, out);
+            }
+            Stmt::Loop { body, .. }
+            | Stmt::While { body, .. }
+            | Stmt::Each { body, .. }
+            | Stmt::Rite { body, .. }
+            | Stmt::Unsafe { body, .. } => assigned_names(body, out),
+            _ => {}
+        }
+    }
+}
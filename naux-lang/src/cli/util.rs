@@ -1,7 +1,7 @@
 use std::fs;
 use std::path::Path;
 
-use crate::ast::Stmt;
+use crate::ast::{append_main_call, Stmt};
 use crate::cli::DefaultEngine;
 use crate::lexer;
 use crate::parser;
@@ -18,7 +18,11 @@ pub fn load_ast(path: &Path) -> Result<(String, Vec<Stmt>), String> {
     Ok((src, stmts))
 }
 
+/// Runs a parsed program on `engine`, calling a parameterless `main` the way `naux run` does.
 pub fn execute_ast(engine: DefaultEngine, ast: &[Stmt], src: &str, path: &Path) -> Result<Vec<runtime::events::RuntimeEvent>, String> {
+    let mut stmts = ast.to_vec();
+    append_main_call(&mut stmts);
+    let ast = stmts.as_slice();
     match engine {
         DefaultEngine::Interp => {
             let (_env, events, errors) = runtime::eval_script(ast);
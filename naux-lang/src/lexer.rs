@@ -1,5 +1,8 @@
 use crate::ast::Span;
-use crate::token::{LexError, Token, TokenKind};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::token::{LexError, StringPart, Token, TokenKind};
 
 pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
     let mut tokens = Vec::new();
@@ -7,14 +10,14 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
     let mut line: usize = 1;
     let mut col: usize = 1;
 
-    while let Some((_, ch)) = chars.next() {
+    while let Some((pos, ch)) = chars.next() {
         // Update line/col for current char
         if ch == '\n' {
             line += 1;
             col = 1;
             tokens.push(Token {
                 kind: TokenKind::Newline,
-                span: Span { line, column: col },
+                span: Span::new(line, col),
             });
             continue;
         }
@@ -24,7 +27,24 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
             continue;
         }
 
-        let span = Span { line, column: col };
+        let span = Span::new(line, col);
+
+        // Comparison ops (checked before `=`/`!` so `==` and `!=` are not split)
+        if input[pos..].starts_with("==")
+            || input[pos..].starts_with("!=")
+            || input[pos..].starts_with(">=")
+            || input[pos..].starts_with("<=")
+        {
+            let op = &input[pos..pos + 2];
+            tokens.push(Token {
+                kind: TokenKind::Op(op.into()),
+                span: span.with_len(2),
+            });
+            // advance two chars
+            chars.next();
+            col += 2;
+            continue;
+        }
 
         // Symbols
         match ch {
@@ -159,7 +179,7 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
                     chars.next();
                     tokens.push(Token {
                         kind: TokenKind::Arrow,
-                        span,
+                        span: span.with_len(2),
                     });
                     col += 2;
                     continue;
@@ -173,11 +193,17 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
                 col += 1;
                 continue;
             }
+            '?' => {
+                tokens.push(Token { kind: TokenKind::Question, span });
+                col += 1;
+                continue;
+            }
             _ => {}
         }
 
-        // String literal
+        // String literal; `{expr}` inside it is interpolated (`\{` / `\}` are literal braces)
         if ch == '"' {
+            let mut parts = Vec::new();
             let mut content = String::new();
             let mut esc = false;
             let mut cur_col = col + 1;
@@ -195,6 +221,15 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
                     esc = true;
                 } else if ch2 == '"' {
                     break;
+                } else if ch2 == '{' {
+                    if !content.is_empty() {
+                        parts.push(StringPart::Text(std::mem::take(&mut content)));
+                    }
+                    let (inner_line, inner_col) = (line, cur_col + 1);
+                    let inner = scan_interpolation(&mut chars, &mut line, &mut cur_col)
+                        .ok_or_else(|| LexError::new("Unterminated interpolation in string literal", span.clone()))?;
+                    parts.push(StringPart::Expr(lex_interpolation(&inner, inner_line, inner_col)?));
+                    continue;
                 } else {
                     content.push(ch2);
                 }
@@ -205,9 +240,17 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
                     cur_col += 1;
                 }
             }
+            let kind = if parts.is_empty() {
+                TokenKind::StringLit(content)
+            } else {
+                if !content.is_empty() {
+                    parts.push(StringPart::Text(content));
+                }
+                TokenKind::StringInterp(parts)
+            };
             tokens.push(Token {
-                kind: TokenKind::StringLit(content),
-                span,
+                kind,
+                span: Span { end_line: line, end_column: cur_col, ..span },
             });
             col = cur_col + 1;
             continue;
@@ -217,7 +260,7 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
         if ch == '&' {
             if let Some((_, '&')) = chars.peek() {
                 chars.next();
-                tokens.push(Token { kind: TokenKind::AndAnd, span });
+                tokens.push(Token { kind: TokenKind::AndAnd, span: span.with_len(2) });
                 col += 2;
                 continue;
             }
@@ -225,29 +268,12 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
         if ch == '|' {
             if let Some((_, '|')) = chars.peek() {
                 chars.next();
-                tokens.push(Token { kind: TokenKind::OrOr, span });
+                tokens.push(Token { kind: TokenKind::OrOr, span: span.with_len(2) });
                 col += 2;
                 continue;
             }
         }
 
-        // Comparison ops
-        if input[col.saturating_sub(1)..].starts_with("==")
-            || input[col.saturating_sub(1)..].starts_with("!=")
-            || input[col.saturating_sub(1)..].starts_with(">=")
-            || input[col.saturating_sub(1)..].starts_with("<=")
-        {
-            let start = col.saturating_sub(1);
-            let op = &input[start..start + 2];
-            tokens.push(Token {
-                kind: TokenKind::Op(op.into()),
-                span,
-            });
-            // advance two chars
-            chars.next();
-            col += 2;
-            continue;
-        }
         if ch == '>' || ch == '<' {
             tokens.push(Token {
                 kind: TokenKind::Op(ch.to_string()),
@@ -274,7 +300,7 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
             let val: f64 = s.parse().map_err(|_| LexError::new("Invalid number", span.clone()))?;
             tokens.push(Token {
                 kind: TokenKind::Number(val),
-                span,
+                span: span.with_len(cur_col - col),
             });
             col = cur_col;
             continue;
@@ -295,7 +321,7 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
                 }
             }
             let kind = keyword_or_ident(&ident);
-            tokens.push(Token { kind, span });
+            tokens.push(Token { kind, span: span.with_len(cur_col - col) });
             col = cur_col;
             continue;
         }
@@ -305,7 +331,7 @@ pub fn lex(input: &str) -> Result<Vec<Token>, LexError> {
 
     tokens.push(Token {
         kind: TokenKind::Eof,
-        span: Span { line, column: col },
+        span: Span::new(line, col),
     });
     Ok(tokens)
 }
@@ -321,6 +347,10 @@ fn keyword_or_ident(s: &str) -> TokenKind {
         "loop" => TokenKind::Loop,
         "each" => TokenKind::Each,
         "while" => TokenKind::While,
+        "break" => TokenKind::Break,
+        "continue" => TokenKind::Continue,
+        "try" => TokenKind::Try,
+        "catch" => TokenKind::Catch,
         "end" => TokenKind::End,
         "in" => TokenKind::In,
         _ => TokenKind::Ident(s.to_string()),
@@ -338,3 +368,64 @@ fn is_ident_part(c: char) -> bool {
 fn peek_is_digit(iter: &mut std::iter::Peekable<std::str::CharIndices<'_>>) -> bool {
     iter.peek().map(|(_, ch)| ch.is_ascii_digit()).unwrap_or(false)
 }
+
+/// Consume an interpolation body after its opening `{` up to the matching `}` (which is
+/// consumed too), keeping `line`/`col` in step. Nested braces and string literals inside
+/// the expression are skipped over. Returns `None` if the input ends first.
+fn scan_interpolation(chars: &mut Peekable<CharIndices>, line: &mut usize, col: &mut usize) -> Option<String> {
+    let mut inner = String::new();
+    let mut depth = 1;
+    let mut in_string = false;
+    let mut esc = false;
+    *col += 1; // the opening `{`
+    for (_, ch) in chars.by_ref() {
+        if ch == '\n' {
+            *line += 1;
+            *col = 1;
+        } else {
+            *col += 1;
+        }
+        if in_string {
+            if esc {
+                esc = false;
+            } else if ch == '\\' {
+                esc = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == '{' {
+            depth += 1;
+        } else if ch == '}' {
+            depth -= 1;
+            if depth == 0 {
+                return Some(inner);
+            }
+        }
+        inner.push(ch);
+    }
+    None
+}
+
+/// Lex an interpolated expression, shifting spans so they point into the enclosing source.
+fn lex_interpolation(src: &str, line: usize, col: usize) -> Result<Vec<Token>, LexError> {
+    let shift = |span: &mut Span| {
+        if span.line == 1 {
+            span.column += col - 1;
+        }
+        if span.end_line == 1 {
+            span.end_column += col - 1;
+        }
+        span.line += line - 1;
+        span.end_line += line - 1;
+    };
+    let mut tokens = lex(src).map_err(|mut e| {
+        shift(&mut e.span);
+        e
+    })?;
+    for tok in &mut tokens {
+        shift(&mut tok.span);
+    }
+    Ok(tokens)
+}
@@ -8,4 +8,100 @@ pub mod renderer;
 pub mod stdlib;
 pub mod vm;
 pub mod cli;
+pub mod lsp;
 pub mod llvm;
+
+use std::fmt;
+
+use crate::parser::error::ParseError;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::events::RuntimeEvent;
+use crate::runtime::value::Value;
+use crate::token::LexError;
+
+/// Execution engine selectable through the embedding API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Interp,
+    Vm,
+    Jit,
+}
+
+/// Everything a script produced: emitted events, the top-level `^` value and runtime errors.
+#[derive(Debug, Clone)]
+pub struct RunOutput {
+    pub events: Vec<RuntimeEvent>,
+    pub value: Value,
+    pub errors: Vec<RuntimeError>,
+}
+
+impl RunOutput {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Errors that stop a script before it can run.
+#[derive(Debug, Clone)]
+pub enum NauxError {
+    Lex(LexError),
+    Parse(ParseError),
+}
+
+impl fmt::Display for NauxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NauxError::Lex(e) => write!(f, "Lex error at {}:{}: {}", e.span.line, e.span.column, e.message),
+            NauxError::Parse(e) => write!(f, "Parse error at {}:{}: {}", e.span.line, e.span.column, e.message),
+        }
+    }
+}
+
+impl std::error::Error for NauxError {}
+
+impl From<LexError> for NauxError {
+    fn from(err: LexError) -> Self {
+        NauxError::Lex(err)
+    }
+}
+
+impl From<ParseError> for NauxError {
+    fn from(err: ParseError) -> Self {
+        NauxError::Parse(err)
+    }
+}
+
+/// Lex, parse and run `src` on the chosen engine with the full stdlib registered.
+/// A parameterless top-level `~ fn main()` is called after the top-level statements.
+pub fn run_source(src: &str, engine: Engine) -> Result<RunOutput, NauxError> {
+    run_source_named(src, "<source>", engine)
+}
+
+/// Same as [`run_source`], but `filename` is used when rendering VM diagnostics.
+pub fn run_source_named(src: &str, filename: &str, engine: Engine) -> Result<RunOutput, NauxError> {
+    let tokens = lexer::lex(src)?;
+    let mut ast = parser::parser::Parser::from_tokens(&tokens)?;
+    ast::append_main_call(&mut ast);
+    let output = match engine {
+        Engine::Interp => {
+            let (_env, events, errors, value) = runtime::eval::eval_script_value(&ast);
+            RunOutput { events, value, errors }
+        }
+        Engine::Vm | Engine::Jit => {
+            let res = if engine == Engine::Vm {
+                vm::run::run_vm(&ast, src, filename)
+            } else {
+                vm::run::run_jit(&ast, src, filename)
+            };
+            match res {
+                Ok((events, value)) => RunOutput { events, value, errors: Vec::new() },
+                Err(msg) => RunOutput {
+                    events: Vec::new(),
+                    value: Value::Null,
+                    errors: vec![RuntimeError::new(msg, None)],
+                },
+            }
+        }
+    };
+    Ok(output)
+}
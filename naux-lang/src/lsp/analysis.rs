@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use crate::ast::{Expr, ExprKind, Span, Stmt};
+use crate::cli::check::{check_source, Diagnostic};
+use crate::cli::lint::{action_exprs, subexprs};
+use crate::lexer;
+use crate::parser;
+use crate::runtime::env::Env;
+
+/// Keywords written after `~`, offered by completion.
+pub const KEYWORDS: &[&str] = &[
+    "fn", "if", "else", "loop", "each", "in", "while", "break", "continue", "try", "catch", "end", "rite", "unsafe",
+    "import",
+];
+
+/// Actions written after `!`, offered by completion.
+pub const ACTIONS: &[&str] = &["say", "ask", "fetch", "table", "progress"];
+
+const SPEC: &str = include_str!("../../SPEC.md");
+
+#[derive(Debug, Clone)]
+pub struct FnSymbol {
+    pub name: String,
+    pub params: Vec<String>,
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VarSymbol {
+    pub name: String,
+    pub span: Option<Span>,
+}
+
+/// An identifier use: `$x` or a call/reference to a function `f`.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub name: String,
+    pub span: Span,
+    pub is_call: bool,
+}
+
+/// Definitions and uses of every identifier in one document, keyed by source span.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    pub functions: Vec<FnSymbol>,
+    /// First assignment (or loop/catch binding) of each variable.
+    pub variables: Vec<VarSymbol>,
+    pub references: Vec<Reference>,
+}
+
+impl SymbolTable {
+    pub fn build(stmts: &[Stmt]) -> Self {
+        let mut table = SymbolTable::default();
+        table.block(stmts);
+        table
+    }
+
+    pub fn function(&self, name: &str) -> Option<&FnSymbol> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&VarSymbol> {
+        self.variables.iter().find(|v| v.name == name)
+    }
+
+    /// The reference covering 1-based `line`/`column`, if any.
+    pub fn reference_at(&self, line: usize, column: usize) -> Option<&Reference> {
+        self.references
+            .iter()
+            .find(|r| r.span.line == line && r.span.column <= column && column < r.span.column + r.span.width())
+    }
+
+    /// Span of the definition `reference` resolves to: a function for calls (or names that
+    /// are not variables), otherwise the variable's first binding.
+    pub fn definition(&self, reference: &Reference) -> Option<Span> {
+        let var = if reference.is_call { None } else { self.variable(&reference.name) };
+        match var {
+            Some(v) => v.span.clone(),
+            None => self.function(&reference.name).and_then(|f| f.span.clone()),
+        }
+    }
+
+    fn bind(&mut self, name: &str, span: &Option<Span>) {
+        if self.variable(name).is_none() {
+            self.variables.push(VarSymbol { name: name.to_string(), span: span.clone() });
+        }
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::FnDef { name, params, body, span, .. } => {
+                self.functions.push(FnSymbol { name: name.clone(), params: params.clone(), span: span.clone() });
+                for p in params {
+                    self.bind(p, span);
+                }
+                self.block(body);
+            }
+            Stmt::Assign { name, expr, span } => {
+                self.bind(name, span);
+                self.expr(expr);
+            }
+            Stmt::If { cond, then_block, else_block, .. } => {
+                self.expr(cond);
+                self.block(then_block);
+                self.block(else_block);
+            }
+            Stmt::Loop { count, body, .. } => {
+                self.expr(count);
+                self.block(body);
+            }
+            Stmt::While { cond, body, .. } => {
+                self.expr(cond);
+                self.block(body);
+            }
+            Stmt::Each { index, var, iter, body, span } => {
+                if let Some(i) = index {
+                    self.bind(i, span);
+                }
+                self.bind(var, span);
+                self.expr(iter);
+                self.block(body);
+            }
+            Stmt::TryCatch { try_block, catch_var, catch_block, span } => {
+                self.block(try_block);
+                self.bind(catch_var, span);
+                self.block(catch_block);
+            }
+            Stmt::Rite { body, .. } | Stmt::Unsafe { body, .. } => self.block(body),
+            Stmt::Action { action, .. } => {
+                for e in action_exprs(action) {
+                    self.expr(e);
+                }
+            }
+            Stmt::Return { value: Some(v), .. } => self.expr(v),
+            Stmt::Return { value: None, .. } | Stmt::Break { .. } | Stmt::Continue { .. } | Stmt::Import { .. } => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Var(name) => self.reference(name, &expr.span, false),
+            ExprKind::Call { callee, args } => {
+                match &callee.kind {
+                    ExprKind::Var(name) => self.reference(name, &callee.span, true),
+                    _ => self.expr(callee),
+                }
+                for a in args {
+                    self.expr(a);
+                }
+            }
+            ExprKind::Comprehension { var, .. } => {
+                self.bind(var, &expr.span);
+                for e in subexprs(expr) {
+                    self.expr(e);
+                }
+            }
+            _ => {
+                for e in subexprs(expr) {
+                    self.expr(e);
+                }
+            }
+        }
+    }
+
+    fn reference(&mut self, name: &str, span: &Option<Span>, is_call: bool) {
+        if let Some(span) = span {
+            self.references.push(Reference { name: name.to_string(), span: span.clone(), is_call });
+        }
+    }
+}
+
+/// What completion offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Builtin,
+    Function,
+    Variable,
+    Keyword,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: Option<String>,
+}
+
+/// Parsed view of one open document.
+pub struct Analysis {
+    pub diagnostics: Vec<Diagnostic>,
+    pub symbols: SymbolTable,
+}
+
+impl Analysis {
+    /// Diagnostics come from `check_source` (lex/parse errors, unknown functions, type errors,
+    /// lints). A document that does not parse has an empty symbol table.
+    pub fn new(src: &str) -> Self {
+        let diagnostics = check_source(src);
+        let symbols = lexer::lex(src)
+            .ok()
+            .and_then(|tokens| parser::parser::Parser::from_tokens(&tokens).ok())
+            .map(|ast| SymbolTable::build(&ast))
+            .unwrap_or_default();
+        Self { diagnostics, symbols }
+    }
+
+    /// Markdown hover for the identifier at 1-based `line`/`column`.
+    pub fn hover(&self, line: usize, column: usize) -> Option<String> {
+        let r = self.symbols.reference_at(line, column)?;
+        if !r.is_call {
+            if let Some(v) = self.symbols.variable(&r.name) {
+                let at = v.span.as_ref().map(|s| format!(" (bound at line {})", s.line)).unwrap_or_default();
+                return Some(format!("variable `${}`{}", v.name, at));
+            }
+        }
+        if let Some(f) = self.symbols.function(&r.name) {
+            return Some(format!("```naux\n{}\n```", fn_signature(f)));
+        }
+        builtin_names().contains(&r.name).then(|| builtin_doc(&r.name))
+    }
+
+    /// Definition span for the identifier at 1-based `line`/`column`.
+    pub fn definition(&self, line: usize, column: usize) -> Option<Span> {
+        let r = self.symbols.reference_at(line, column)?;
+        self.symbols.definition(r)
+    }
+
+    pub fn completions(&self) -> Vec<Completion> {
+        let mut out = Vec::new();
+        let mut builtins: Vec<String> = builtin_names().into_iter().collect();
+        builtins.sort();
+        for name in builtins {
+            out.push(Completion { label: name, kind: CompletionKind::Builtin, detail: None });
+        }
+        for f in &self.symbols.functions {
+            out.push(Completion { label: f.name.clone(), kind: CompletionKind::Function, detail: Some(fn_signature(f)) });
+        }
+        for v in &self.symbols.variables {
+            out.push(Completion { label: format!("${}", v.name), kind: CompletionKind::Variable, detail: None });
+        }
+        for kw in KEYWORDS.iter().chain(ACTIONS) {
+            out.push(Completion { label: kw.to_string(), kind: CompletionKind::Keyword, detail: None });
+        }
+        out
+    }
+}
+
+fn fn_signature(f: &FnSymbol) -> String {
+    let params: Vec<String> = f.params.iter().map(|p| format!("${}", p)).collect();
+    format!("~ fn {}({})", f.name, params.join(", "))
+}
+
+/// Every builtin registered by the stdlib, plus the VM's `len` intrinsic.
+pub fn builtin_names() -> HashSet<String> {
+    let mut env = Env::new();
+    crate::stdlib::register_all(&mut env);
+    let mut names: HashSet<String> = env.builtins().into_keys().collect();
+    names.insert("len".into());
+    names
+}
+
+/// Builtins carry no doc strings; the SPEC bullet describing `name(...)` is the documentation.
+pub fn builtin_doc(name: &str) -> String {
+    let needle = format!("`{}(", name);
+    SPEC.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("- ") && line.contains(&needle))
+        .map(|line| format!("builtin `{}`\n\n{}", name, line.trim_start_matches("- ")))
+        .unwrap_or_else(|| format!("builtin `{}`", name))
+}
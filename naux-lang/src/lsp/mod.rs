@@ -0,0 +1,207 @@
+//! `naux lsp`: a Language Server Protocol (3.17) server speaking JSON-RPC over stdin/stdout.
+//! Documents are synced in full on every change and re-analysed from scratch.
+
+pub mod analysis;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value as Json};
+
+use crate::ast::Span;
+use crate::cli::check::{Diagnostic, Severity};
+use crate::lsp::analysis::{Analysis, CompletionKind};
+
+// JSON-RPC error codes used by LSP
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub fn serve_stdio() -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    serve(stdin.lock(), &mut stdout)
+}
+
+/// Handle messages from `input` until `exit` or end of input, writing responses and
+/// notifications to `out`.
+pub fn serve<R: BufRead, W: Write>(mut input: R, out: &mut W) -> Result<(), String> {
+    let mut server = Server::default();
+    while let Some(msg) = read_message(&mut input)? {
+        let outgoing = server.handle(&msg);
+        for reply in outgoing {
+            write_message(out, &reply)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Read one `Content-Length` framed message; `None` at end of input.
+pub fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Json>, String> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        let n = input.read_line(&mut line).map_err(|e| format!("LSP read error: {}", e))?;
+        if n == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(v) = line.strip_prefix("Content-Length:") {
+            length = Some(v.trim().parse::<usize>().map_err(|_| format!("bad Content-Length: {}", v.trim()))?);
+        }
+    }
+    let length = length.ok_or("LSP message without Content-Length")?;
+    let mut body = vec![0u8; length];
+    input.read_exact(&mut body).map_err(|e| format!("LSP read error: {}", e))?;
+    serde_json::from_slice(&body).map(Some).map_err(|e| format!("LSP message is not JSON: {}", e))
+}
+
+pub fn write_message<W: Write>(out: &mut W, msg: &Json) -> Result<(), String> {
+    let body = msg.to_string();
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body).map_err(|e| format!("LSP write error: {}", e))?;
+    out.flush().map_err(|e| format!("LSP write error: {}", e))
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, String>,
+    exited: bool,
+}
+
+impl Server {
+    fn handle(&mut self, msg: &Json) -> Vec<Json> {
+        let method = msg["method"].as_str().unwrap_or("");
+        let params = &msg["params"];
+        let id = msg.get("id").cloned();
+        let result = match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": ["$", "~", "!"] },
+                    "definitionProvider": true
+                },
+                "serverInfo": { "name": "naux-lsp", "version": env!("CARGO_PKG_VERSION") }
+            })),
+            "shutdown" => Ok(Json::Null),
+            "exit" => {
+                self.exited = true;
+                return Vec::new();
+            }
+            "textDocument/didOpen" => {
+                let doc = &params["textDocument"];
+                return self.update(doc["uri"].as_str(), doc["text"].as_str());
+            }
+            "textDocument/didChange" => {
+                // full sync: the last change holds the whole document
+                let text = params["contentChanges"].as_array().and_then(|c| c.last()).and_then(|c| c["text"].as_str());
+                return self.update(params["textDocument"]["uri"].as_str(), text);
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+                    return vec![publish(uri, &[])];
+                }
+                return Vec::new();
+            }
+            "textDocument/hover" => self.at_position(params, |analysis, line, col| {
+                analysis
+                    .hover(line, col)
+                    .map(|text| json!({ "contents": { "kind": "markdown", "value": text } }))
+                    .unwrap_or(Json::Null)
+            }),
+            "textDocument/definition" => {
+                let uri = params["textDocument"]["uri"].clone();
+                self.at_position(params, |analysis, line, col| {
+                    analysis
+                        .definition(line, col)
+                        .map(|span| json!({ "uri": uri, "range": range(&span) }))
+                        .unwrap_or(Json::Null)
+                })
+            }
+            "textDocument/completion" => self.at_position(params, |analysis, _, _| {
+                let items: Vec<Json> = analysis
+                    .completions()
+                    .into_iter()
+                    .map(|c| {
+                        let kind = match c.kind {
+                            CompletionKind::Builtin | CompletionKind::Function => 3,
+                            CompletionKind::Variable => 6,
+                            CompletionKind::Keyword => 14,
+                        };
+                        let mut item = json!({ "label": c.label, "kind": kind });
+                        if let Some(detail) = c.detail {
+                            item["detail"] = json!(detail);
+                        }
+                        item
+                    })
+                    .collect();
+                json!(items)
+            }),
+            _ if id.is_none() => return Vec::new(), // unknown notifications are ignored
+            _ => Err((METHOD_NOT_FOUND, format!("method not supported: {}", method))),
+        };
+        let Some(id) = id else {
+            return Vec::new();
+        };
+        vec![match result {
+            Ok(value) => json!({ "jsonrpc": "2.0", "id": id, "result": value }),
+            Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+        }]
+    }
+
+    fn update(&mut self, uri: Option<&str>, text: Option<&str>) -> Vec<Json> {
+        let (Some(uri), Some(text)) = (uri, text) else {
+            return Vec::new();
+        };
+        self.documents.insert(uri.to_string(), text.to_string());
+        vec![publish(uri, &Analysis::new(text).diagnostics)]
+    }
+
+    /// Run `f` on the analysed document at `params.position` (converted to 1-based line/column).
+    fn at_position(
+        &self,
+        params: &Json,
+        f: impl FnOnce(&Analysis, usize, usize) -> Json,
+    ) -> Result<Json, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().ok_or((INVALID_PARAMS, "missing textDocument.uri".to_string()))?;
+        let text = self.documents.get(uri).ok_or((INVALID_PARAMS, format!("document not open: {}", uri)))?;
+        let line = params["position"]["line"].as_u64().unwrap_or(0) as usize + 1;
+        let col = params["position"]["character"].as_u64().unwrap_or(0) as usize + 1;
+        Ok(f(&Analysis::new(text), line, col))
+    }
+}
+
+/// LSP range for a span: 0-based, end exclusive.
+fn range(span: &Span) -> Json {
+    let end_col = if span.end_line == span.line { span.column + span.width() - 1 } else { span.end_column };
+    json!({
+        "start": { "line": span.line.saturating_sub(1), "character": span.column.saturating_sub(1) },
+        "end": { "line": span.end_line.saturating_sub(1), "character": end_col }
+    })
+}
+
+fn publish(uri: &str, diags: &[Diagnostic]) -> Json {
+    let items: Vec<Json> = diags
+        .iter()
+        .map(|d| {
+            let severity = match d.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+                Severity::Note => 3,
+            };
+            let span = d.span.clone().unwrap_or_else(|| Span::new(1, 1));
+            json!({ "range": range(&span), "severity": severity, "source": "naux", "message": d.message })
+        })
+        .collect();
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": items }
+    })
+}
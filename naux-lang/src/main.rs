@@ -1,20 +1,11 @@
 use clap::Parser;
-mod ast;
-mod cli;
-mod lexer;
-mod parser;
-mod renderer;
-mod runtime;
-mod stdlib;
-mod token;
-mod vm;
-use executor::Executor;
-use cli::Cli;
+use naux::cli::{self, Cli};
+use naux::renderer::color;
 
 fn main() {
     let cli = Cli::parse();
     if let Err(err) = cli::run(cli) {
-        eprintln!("❌ {}", err);
+        eprintln!("❌ {}", color::diagnostic(&err));
         std::process::exit(1);
     }
 }
@@ -1,3 +1,5 @@
+use thiserror::Error;
+
 use crate::ast::Span;
 use crate::token::TokenKind;
 
@@ -9,7 +11,8 @@ pub enum ParseErrorKind {
     BlockMismatch(&'static str),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Error)]
+#[error("{message} at line {}, col {}", .span.line, .span.column)]
 pub struct ParseError {
     pub kind: ParseErrorKind,
     pub span: Span,
@@ -29,9 +32,14 @@ impl ParseError {
 pub fn format_parse_error(src: &str, err: &ParseError, filename: &str) -> String {
     let line_idx = err.span.line.saturating_sub(1);
     let line_text = src.lines().nth(line_idx).unwrap_or("");
-    let caret = format!("{}^", " ".repeat(err.span.column.saturating_sub(1)));
+    let caret = err.span.caret();
     format!(
         "Parse error: {}\n --> {}:{}:{}\n {}\n {}",
-        err.message, filename, err.span.line, err.span.column, line_text, caret
+        err.message,
+        filename,
+        err.span.line,
+        err.span.column,
+        line_text,
+        caret
     )
 }
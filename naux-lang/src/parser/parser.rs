@@ -1,15 +1,17 @@
-use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, Span, Stmt, UnaryOp};
+use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, InterpolPart, Span, Stmt, UnaryOp};
 use crate::parser::error::{ParseError, ParseErrorKind};
-use crate::token::{Token, TokenKind};
+use crate::token::{StringPart, Token, TokenKind};
 
 pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
+    /// Loops enclosing the current statement within its function; `~ break`/`~ continue` need one.
+    loop_depth: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, pos: 0 }
+        Self { tokens, pos: 0, loop_depth: 0 }
     }
 
     pub fn from_tokens(tokens: &[Token]) -> Result<Vec<Stmt>, ParseError> {
@@ -50,6 +52,8 @@ impl Parser {
             TokenKind::Loop => self.parse_loop_block(),
             TokenKind::Each => self.parse_each_block(),
             TokenKind::While => self.parse_while_block(),
+            TokenKind::Try => self.parse_try_block(),
+            TokenKind::Break | TokenKind::Continue => self.parse_loop_jump(),
             _ => Err(self.error_unexpected()),
         }
     }
@@ -88,6 +92,35 @@ impl Parser {
         Ok(Stmt::Unsafe { body, span })
     }
 
+    fn parse_try_block(&mut self) -> Result<Stmt, ParseError> {
+        let span = Some(self.current().span.clone());
+        self.expect(TokenKind::Try)?;
+        self.optional_newlines();
+        let mut try_block = Vec::new();
+        while !(self.current().kind == TokenKind::Tilde && self.peek_kind() == Some(&TokenKind::Catch)) {
+            if self.is_eof() {
+                return Err(self.error_expected("~ catch"));
+            }
+            try_block.push(self.parse_stmt()?);
+            self.optional_newlines();
+        }
+        self.expect(TokenKind::Tilde)?;
+        self.expect(TokenKind::Catch)?;
+        let catch_var = self.parse_var_name()?;
+        self.optional_newlines();
+        let mut catch_block = Vec::new();
+        while !(self.current().kind == TokenKind::Tilde && self.peek_kind() == Some(&TokenKind::End)) {
+            if self.is_eof() {
+                return Err(self.error_expected("~ end"));
+            }
+            catch_block.push(self.parse_stmt()?);
+            self.optional_newlines();
+        }
+        self.expect(TokenKind::Tilde)?;
+        self.expect(TokenKind::End)?;
+        Ok(Stmt::TryCatch { try_block, catch_var, catch_block, span })
+    }
+
     fn parse_fn_block(&mut self) -> Result<Stmt, ParseError> {
         let span = Some(self.current().span.clone());
         self.expect(TokenKind::Fn)?;
@@ -109,15 +142,22 @@ impl Parser {
             }
         }
         self.expect(TokenKind::RParen)?;
+        let memo = matches!(&self.current().kind, TokenKind::Ident(s) if s == "memo");
+        if memo {
+            self.advance();
+        }
         self.optional_newlines();
+        // loops around a function definition don't extend into its body
+        let outer_loops = std::mem::take(&mut self.loop_depth);
         let mut body = Vec::new();
         while !(self.current().kind == TokenKind::Tilde && self.peek_kind() == Some(&TokenKind::End)) {
             body.push(self.parse_stmt()?);
             self.optional_newlines();
         }
+        self.loop_depth = outer_loops;
         self.expect(TokenKind::Tilde)?;
         self.expect(TokenKind::End)?;
-        Ok(Stmt::FnDef { name, params, body, span })
+        Ok(Stmt::FnDef { name, params, body, memo, span })
     }
 
     fn parse_if_block(&mut self) -> Result<Stmt, ParseError> {
@@ -150,31 +190,25 @@ impl Parser {
         self.expect(TokenKind::Loop)?;
         let count = self.parse_expr()?;
         self.optional_newlines();
-        let mut body = Vec::new();
-        while !(self.current().kind == TokenKind::Tilde && self.peek_kind() == Some(&TokenKind::End)) {
-            body.push(self.parse_stmt()?);
-            self.optional_newlines();
-        }
-        self.expect(TokenKind::Tilde)?;
-        self.expect(TokenKind::End)?;
+        let body = self.parse_loop_body()?;
         Ok(Stmt::Loop { count, body, span })
     }
 
     fn parse_each_block(&mut self) -> Result<Stmt, ParseError> {
         let span = Some(self.current().span.clone());
         self.expect(TokenKind::Each)?;
-        let var = self.parse_ident_string()?;
+        let first = self.parse_var_name()?;
+        let (index, var) = if self.current().kind == TokenKind::Comma {
+            self.advance();
+            (Some(first), self.parse_var_name()?)
+        } else {
+            (None, first)
+        };
         self.expect(TokenKind::In)?;
         let iter = self.parse_expr()?;
         self.optional_newlines();
-        let mut body = Vec::new();
-        while !(self.current().kind == TokenKind::Tilde && self.peek_kind() == Some(&TokenKind::End)) {
-            body.push(self.parse_stmt()?);
-            self.optional_newlines();
-        }
-        self.expect(TokenKind::Tilde)?;
-        self.expect(TokenKind::End)?;
-        Ok(Stmt::Each { var, iter, body, span })
+        let body = self.parse_loop_body()?;
+        Ok(Stmt::Each { index, var, iter, body, span })
     }
 
     fn parse_while_block(&mut self) -> Result<Stmt, ParseError> {
@@ -182,14 +216,38 @@ impl Parser {
         self.expect(TokenKind::While)?;
         let cond = self.parse_expr()?;
         self.optional_newlines();
+        let body = self.parse_loop_body()?;
+        Ok(Stmt::While { cond, body, span })
+    }
+
+    /// Statements up to `~ end` of a `loop`/`each`/`while`, with the loop counted as enclosing them.
+    fn parse_loop_body(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.loop_depth += 1;
         let mut body = Vec::new();
         while !(self.current().kind == TokenKind::Tilde && self.peek_kind() == Some(&TokenKind::End)) {
             body.push(self.parse_stmt()?);
             self.optional_newlines();
         }
+        self.loop_depth -= 1;
         self.expect(TokenKind::Tilde)?;
         self.expect(TokenKind::End)?;
-        Ok(Stmt::While { cond, body, span })
+        Ok(body)
+    }
+
+    fn parse_loop_jump(&mut self) -> Result<Stmt, ParseError> {
+        let tok = self.current().clone();
+        let span = Some(tok.span.clone());
+        let is_break = tok.kind == TokenKind::Break;
+        if self.loop_depth == 0 {
+            let keyword = if is_break { "break" } else { "continue" };
+            return Err(ParseError::new(
+                ParseErrorKind::UnexpectedToken(tok.kind),
+                tok.span,
+                format!("`~ {}` outside of a loop", keyword),
+            ));
+        }
+        self.advance();
+        Ok(if is_break { Stmt::Break { span } } else { Stmt::Continue { span } })
     }
 
     fn parse_assign(&mut self) -> Result<Stmt, ParseError> {
@@ -249,6 +307,14 @@ impl Parser {
                         let target = self.parse_expr()?;
                         ActionKind::Fetch { target }
                     }
+                    "table" => {
+                        let rows = self.parse_expr()?;
+                        ActionKind::Table { rows }
+                    }
+                    "progress" => {
+                        let value = self.parse_expr()?;
+                        ActionKind::Progress { value }
+                    }
                     other => return Err(self.error_custom(format!("Unknown action '!{}'", other))),
                 }
             }
@@ -264,7 +330,28 @@ impl Parser {
     }
 
     fn parse_expr(&mut self) -> Result<Expr, ParseError> {
-        self.parse_binary_expr(0)
+        self.parse_ternary()
+    }
+
+    // `c ? a : b` binds looser than `||` and nests to the right: `a ? b : c ? d : e`.
+    fn parse_ternary(&mut self) -> Result<Expr, ParseError> {
+        let cond = self.parse_binary_expr(0)?;
+        if self.current().kind != TokenKind::Question {
+            return Ok(cond);
+        }
+        self.advance();
+        let then_expr = self.parse_ternary()?;
+        self.expect(TokenKind::Colon)?;
+        let else_expr = self.parse_ternary()?;
+        let span = cover(cond.span.as_ref(), else_expr.span.as_ref());
+        Ok(Expr::new(
+            ExprKind::Ternary {
+                cond: Box::new(cond),
+                then_expr: Box::new(then_expr),
+                else_expr: Box::new(else_expr),
+            },
+            span,
+        ))
     }
 
     // Pratt parser for expressions
@@ -278,13 +365,14 @@ impl Parser {
             self.advance(); // consume op
             let next_min_prec = if right_assoc { prec } else { prec + 1 };
             let right = self.parse_binary_expr(next_min_prec)?;
+            let span = cover(left.span.as_ref().or(op_span.as_ref()), right.span.as_ref());
             left = Expr::new(
                 ExprKind::Binary {
                     op,
                     left: Box::new(left),
                     right: Box::new(right),
                 },
-                op_span,
+                span,
             );
         }
         Ok(left)
@@ -296,6 +384,7 @@ impl Parser {
                 let span = Some(self.current().span.clone());
                 self.advance();
                 let expr = self.parse_unary_expr()?;
+                let span = cover(span.as_ref(), expr.span.as_ref());
                 Ok(Expr::new(
                     ExprKind::Unary {
                         op: UnaryOp::Not,
@@ -308,6 +397,7 @@ impl Parser {
                 let span = Some(self.current().span.clone());
                 self.advance();
                 let expr = self.parse_unary_expr()?;
+                let span = cover(span.as_ref(), expr.span.as_ref());
                 Ok(Expr::new(
                     ExprKind::Unary {
                         op: UnaryOp::Neg,
@@ -340,6 +430,7 @@ impl Parser {
                         }
                     }
                     self.expect(TokenKind::RParen)?;
+                    let span = cover(expr.span.as_ref().or(span.as_ref()), Some(&self.prev_span()));
                     expr = Expr::new(
                         ExprKind::Call {
                             callee: Box::new(expr),
@@ -353,6 +444,7 @@ impl Parser {
                     self.advance();
                     let idx = self.parse_expr()?;
                     self.expect(TokenKind::RBracket)?;
+                    let span = cover(expr.span.as_ref().or(span.as_ref()), Some(&self.prev_span()));
                     expr = Expr::new(
                         ExprKind::Index {
                             target: Box::new(expr),
@@ -365,6 +457,7 @@ impl Parser {
                     let span = Some(self.current().span.clone());
                     self.advance();
                     let field = self.parse_ident_string()?;
+                    let span = cover(expr.span.as_ref().or(span.as_ref()), Some(&self.prev_span()));
                     expr = Expr::new(
                         ExprKind::Field {
                             target: Box::new(expr),
@@ -383,9 +476,10 @@ impl Parser {
         let tok = self.current().clone();
         match tok.kind {
             TokenKind::Dollar => {
-                let span = Some(tok.span.clone());
                 self.advance();
                 let name = self.parse_ident_string()?;
+                // `$name` is underlined as one unit.
+                let span = Some(tok.span.to(&self.prev_span()));
                 Ok(Expr::new(ExprKind::Var(name), span))
             }
             TokenKind::Number(n) => {
@@ -398,6 +492,13 @@ impl Parser {
                 self.advance();
                 Ok(Expr::new(ExprKind::Text(s), span))
             }
+            TokenKind::StringInterp(parts) => {
+                let span = Some(tok.span.clone());
+                self.advance();
+                let parts = parts.into_iter().map(parse_interpolation).collect::<Result<Vec<_>, _>>()?;
+                // an empty `{}` splices nothing
+                Ok(Expr::new(ExprKind::Interpolate(parts.into_iter().flatten().collect()), span))
+            }
             TokenKind::Ident(name) => {
                 let span = Some(tok.span.clone());
                 self.advance();
@@ -422,6 +523,9 @@ impl Parser {
                 if self.current().kind != TokenKind::RBracket {
                     loop {
                         let item = self.parse_expr()?;
+                        if items.is_empty() && matches!(&self.current().kind, TokenKind::Ident(s) if s == "for") {
+                            return self.parse_comprehension(item, span);
+                        }
                         items.push(item);
                         if self.current().kind == TokenKind::Comma {
                             self.advance();
@@ -431,6 +535,7 @@ impl Parser {
                     }
                 }
                 self.expect(TokenKind::RBracket)?;
+                let span = cover(span.as_ref(), Some(&self.prev_span()));
                 Ok(Expr::new(ExprKind::List(items), span))
             }
             TokenKind::LBrace => {
@@ -451,12 +556,41 @@ impl Parser {
                     }
                 }
                 self.expect(TokenKind::RBrace)?;
+                let span = cover(span.as_ref(), Some(&self.prev_span()));
                 Ok(Expr::new(ExprKind::Map(entries), span))
             }
             _ => Err(self.error_custom("Expected expression")),
         }
     }
 
+    /// Rest of `[elem for $var in iter if cond]` once `elem` has been parsed; `[` is already consumed.
+    fn parse_comprehension(&mut self, elem: Expr, open: Option<Span>) -> Result<Expr, ParseError> {
+        self.advance(); // consume `for`
+        if self.current().kind == TokenKind::Dollar {
+            self.advance();
+        }
+        let var = self.parse_ident_string()?;
+        self.expect(TokenKind::In)?;
+        let iter = self.parse_expr()?;
+        let cond = if self.current().kind == TokenKind::If {
+            self.advance();
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        self.expect(TokenKind::RBracket)?;
+        let span = cover(open.as_ref(), Some(&self.prev_span()));
+        Ok(Expr::new(
+            ExprKind::Comprehension {
+                elem: Box::new(elem),
+                var,
+                iter: Box::new(iter),
+                cond,
+            },
+            span,
+        ))
+    }
+
     fn peek_binary_op(&self) -> Option<(BinaryOp, u8, bool)> {
         match self.current().kind {
             TokenKind::Plus => Some((BinaryOp::Add, 10, false)),
@@ -490,6 +624,14 @@ impl Parser {
         }
     }
 
+    /// Loop variable name, with or without its leading `$`.
+    fn parse_var_name(&mut self) -> Result<String, ParseError> {
+        if self.current().kind == TokenKind::Dollar {
+            self.advance();
+        }
+        self.parse_ident_string()
+    }
+
     fn optional_newlines(&mut self) {
         while self.current().kind == TokenKind::Newline {
             self.advance();
@@ -516,6 +658,10 @@ impl Parser {
         }
     }
 
+    fn prev_span(&self) -> Span {
+        self.tokens[self.pos.saturating_sub(1)].span.clone()
+    }
+
     fn current(&self) -> &Token {
         self.tokens.get(self.pos).unwrap_or_else(|| self.tokens.last().unwrap())
     }
@@ -555,3 +701,33 @@ impl Parser {
         }
     }
 }
+
+fn cover(start: Option<&Span>, end: Option<&Span>) -> Option<Span> {
+    match (start, end) {
+        (Some(s), Some(e)) => Some(s.to(e)),
+        (Some(s), None) => Some(s.clone()),
+        (None, e) => e.cloned(),
+    }
+}
+
+/// Parse one piece of an interpolated string; `None` for an empty `{}`.
+fn parse_interpolation(part: StringPart) -> Result<Option<InterpolPart>, ParseError> {
+    let tokens = match part {
+        StringPart::Text(t) => return Ok(Some(InterpolPart::Text(t))),
+        StringPart::Expr(tokens) => tokens,
+    };
+    if tokens.iter().all(|t| matches!(t.kind, TokenKind::Newline | TokenKind::Eof)) {
+        return Ok(None);
+    }
+    let mut p = Parser::new(tokens);
+    let expr = p.parse_expr()?;
+    let rest = p.current().clone();
+    if rest.kind != TokenKind::Eof {
+        return Err(ParseError::new(
+            ParseErrorKind::UnexpectedToken(rest.kind),
+            rest.span,
+            "Unexpected token in string interpolation",
+        ));
+    }
+    Ok(Some(InterpolPart::Expr(expr)))
+}
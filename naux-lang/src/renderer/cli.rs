@@ -4,13 +4,21 @@ use crate::runtime::error::{Frame, RuntimeError};
 use crate::runtime::events::RuntimeEvent;
 use crate::token::LexError;
 use std::fmt::Write;
+use std::io::Write as _;
 
 /// Render runtime events in an ASCII-friendly, ritual-ish style.
 pub fn render_cli(events: &[RuntimeEvent]) {
     let mut ui_active = false;
+    let mut progress_active = false;
     for ev in events {
+        // A bar is redrawn in place until something else needs the line.
+        if progress_active && !matches!(ev, RuntimeEvent::Progress(_)) {
+            println!();
+            progress_active = false;
+        }
         match ev {
             RuntimeEvent::Say(msg) => println!("> {}", msg),
+            RuntimeEvent::SetVar { name, value } => eprintln!("log: set {} = {}", name, value),
             RuntimeEvent::Ask { prompt, answer } => {
                 println!("? ASK: {}", prompt);
                 println!("= ORACLE: {}", answer);
@@ -24,6 +32,12 @@ pub fn render_cli(events: &[RuntimeEvent]) {
                 println!("│ UI: {:<22} │", kind);
                 ui_active = true;
             }
+            RuntimeEvent::UiEnd => {
+                if ui_active {
+                    println!("└──────────────────────────┘");
+                    ui_active = false;
+                }
+            }
             RuntimeEvent::Text(text) => {
                 if !ui_active {
                     println!("┌──────────────────────────┐");
@@ -39,13 +53,53 @@ pub fn render_cli(events: &[RuntimeEvent]) {
                 println!("│   [ {} ]", label);
             }
             RuntimeEvent::Log(msg) => eprintln!("log: {}", msg),
+            RuntimeEvent::Table { columns, rows } => print!("{}", format_table(columns, rows)),
+            RuntimeEvent::Progress(f) => {
+                print!("\r{}", progress_bar(*f));
+                std::io::stdout().flush().ok();
+                progress_active = true;
+            }
         }
     }
+    if progress_active {
+        println!();
+    }
     if ui_active {
         println!("└──────────────────────────┘");
     }
 }
 
+/// 20-cell bar with percentage, e.g. `[##########          ]  50%`.
+pub fn progress_bar(fraction: f64) -> String {
+    let filled = (fraction.clamp(0.0, 1.0) * 20.0).round() as usize;
+    format!("[{}{}] {:>3}%", "#".repeat(filled), " ".repeat(20 - filled), (fraction * 100.0).round() as i64)
+}
+
+/// Render a table with columns padded to their widest cell.
+pub fn format_table(columns: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!("{}{}", c, " ".repeat(w - c.chars().count())))
+            .collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+    let mut out = line(columns);
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    out.push_str(&format!("|-{}-|\n", rule.join("-|-")));
+    for row in rows {
+        out.push_str(&line(row));
+    }
+    out
+}
+
 pub fn print_lex_error(src: &str, err: &LexError, path: &str) {
     eprintln!("❌ LexError: {} at {}:{}:{}", err.message, path, err.span.line, err.span.column);
     print_snippet(src, err.span.clone());
@@ -84,18 +138,26 @@ fn print_snippet(src: &str, span: Span) {
     if let Some(line_str) = src.lines().nth(line_idx) {
         let gutter = span.line.to_string();
         eprintln!("{} | {}", gutter, line_str);
-        eprintln!("{}   {}^", " ".repeat(gutter.len()), " ".repeat(span.column.saturating_sub(1)));
+        eprintln!("{}   {}", " ".repeat(gutter.len()), span.caret());
     }
 }
 
 pub fn render_cli_to_string(events: &[RuntimeEvent]) -> String {
     let mut out = String::new();
     let mut ui_active = false;
+    let mut progress_active = false;
     for ev in events {
+        if progress_active && !matches!(ev, RuntimeEvent::Progress(_)) {
+            out.push('\n');
+            progress_active = false;
+        }
         match ev {
             RuntimeEvent::Say(msg) => {
                 writeln!(&mut out, "> {}", msg).ok();
             }
+            RuntimeEvent::SetVar { name, value } => {
+                writeln!(&mut out, "log: set {} = {}", name, value).ok();
+            }
             RuntimeEvent::Ask { prompt, answer } => {
                 writeln!(&mut out, "? ASK: {}", prompt).ok();
                 writeln!(&mut out, "= ORACLE: {}", answer).ok();
@@ -111,6 +173,12 @@ pub fn render_cli_to_string(events: &[RuntimeEvent]) -> String {
                 writeln!(&mut out, "│ UI: {:<22} │", kind).ok();
                 ui_active = true;
             }
+            RuntimeEvent::UiEnd => {
+                if ui_active {
+                    writeln!(&mut out, "└──────────────────────────┘").ok();
+                    ui_active = false;
+                }
+            }
             RuntimeEvent::Text(text) => {
                 if !ui_active {
                     writeln!(&mut out, "┌──────────────────────────┐").ok();
@@ -128,8 +196,16 @@ pub fn render_cli_to_string(events: &[RuntimeEvent]) -> String {
             RuntimeEvent::Log(msg) => {
                 writeln!(&mut out, "log: {}", msg).ok();
             }
+            RuntimeEvent::Table { columns, rows } => out.push_str(&format_table(columns, rows)),
+            RuntimeEvent::Progress(f) => {
+                write!(&mut out, "\r{}", progress_bar(*f)).ok();
+                progress_active = true;
+            }
         }
     }
+    if progress_active {
+        out.push('\n');
+    }
     if ui_active {
         writeln!(&mut out, "└──────────────────────────┘").ok();
     }
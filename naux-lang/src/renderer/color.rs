@@ -0,0 +1,69 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Off by default so library callers and tests get plain text.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enable ANSI colors when stdout is a terminal, unless `--no-color` was passed.
+pub fn init(no_color: bool) {
+    set_enabled(!no_color && std::io::stdout().is_terminal());
+}
+
+pub fn set_enabled(on: bool) {
+    ENABLED.store(on, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn paint(code: &str, text: &str) -> String {
+    if enabled() {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn red(text: &str) -> String {
+    paint("31", text)
+}
+
+pub fn cyan(text: &str) -> String {
+    paint("36", text)
+}
+
+pub fn dim(text: &str) -> String {
+    paint("2", text)
+}
+
+/// Color a rendered error for printing: the `Parse error:`/`VM error:` headline red, caret lines
+/// cyan and the source line above each caret dim. The formatters themselves stay plain so
+/// messages handed to library callers never carry escape codes.
+pub fn diagnostic(text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    let is_caret = |line: &str| {
+        let t = line.trim();
+        !t.is_empty() && t.chars().all(|c| c == '^')
+    };
+    let lines: Vec<&str> = text.split('\n').collect();
+    let painted: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let (indent, body) = line.split_at(line.len() - line.trim_start().len());
+            if body.starts_with("Parse error:") || body.starts_with("VM error:") {
+                format!("{}{}", indent, red(body))
+            } else if is_caret(line) {
+                format!("{}{}", indent, cyan(body))
+            } else if lines.get(i + 1).is_some_and(|next| is_caret(next)) {
+                format!("{}{}", indent, dim(body))
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    painted.join("\n")
+}
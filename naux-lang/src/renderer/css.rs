@@ -37,6 +37,8 @@ section.log { max-width: 900px; margin: 0 auto; }
 .fetch, .ui, .log { color: var(--muted); font-size: 14px; margin: 4px 0; }
 .button { display: inline-block; margin: 6px 6px 6px 0; padding: 8px 14px; border-radius: 999px; border: 1px solid var(--accent); color: var(--accent); background: transparent; font-weight: 600; letter-spacing: 0.02em; }
 .text { margin: 6px 0; }
+.table { border-collapse: collapse; margin: 6px 0; }
+.table th, .table td { border: 1px solid rgba(255,255,255,0.15); padding: 4px 10px; text-align: left; }
 .error { color: var(--accent); font-weight: 700; margin: 10px 0; }
 code, pre { font-family: var(--mono); }
 pre.snippet { background: #0b0d18; padding: 12px; border-radius: 10px; border: 1px solid rgba(255,92,138,0.35); overflow-x: auto; }
@@ -26,6 +26,9 @@ pub fn render_html(events: &[RuntimeEvent], errors: &[RuntimeError]) -> String {
                 ensure_card(&mut out, &mut open_card, "SAY");
                 out.push_str(&format!("<p class=\"say\">{}</p>\n", html_escape(msg)));
             }
+            RuntimeEvent::SetVar { name, value } => {
+                out.push_str(&format!("<!-- set {} = {} -->\n", html_escape(name), html_escape(value)));
+            }
             RuntimeEvent::Ask { prompt, answer } => {
                 ensure_card(&mut out, &mut open_card, "ORACLE");
                 out.push_str(&format!("<p class=\"ask\">? {}</p>\n", html_escape(prompt)));
@@ -39,6 +42,12 @@ pub fn render_html(events: &[RuntimeEvent], errors: &[RuntimeError]) -> String {
                 ensure_card(&mut out, &mut open_card, "UI");
                 out.push_str(&format!("<p class=\"ui\">ui: {}</p>\n", html_escape(kind)));
             }
+            RuntimeEvent::UiEnd => {
+                if open_card {
+                    out.push_str("</div>\n");
+                    open_card = false;
+                }
+            }
             RuntimeEvent::Text(txt) => {
                 ensure_card(&mut out, &mut open_card, "TEXT");
                 out.push_str(&format!("<p class=\"text\">{}</p>\n", html_escape(txt)));
@@ -51,6 +60,26 @@ pub fn render_html(events: &[RuntimeEvent], errors: &[RuntimeError]) -> String {
                 ensure_card(&mut out, &mut open_card, "LOG");
                 out.push_str(&format!("<p class=\"log\">{}</p>\n", html_escape(msg)));
             }
+            RuntimeEvent::Progress(f) => {
+                ensure_card(&mut out, &mut open_card, "PROGRESS");
+                out.push_str(&format!("<progress class=\"progress\" value=\"{}\" max=\"1\"></progress>\n", f));
+            }
+            RuntimeEvent::Table { columns, rows } => {
+                ensure_card(&mut out, &mut open_card, "TABLE");
+                out.push_str("<table class=\"table\"><tr>");
+                for c in columns {
+                    out.push_str(&format!("<th>{}</th>", html_escape(c)));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        out.push_str(&format!("<td>{}</td>", html_escape(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
         }
     }
     if open_card {
@@ -0,0 +1,9 @@
+use crate::runtime::events::{EventStream, RuntimeEvent};
+
+/// Render events as the versioned JSON envelope consumed by `--mode json`.
+/// Compact output is a single line for machine consumers.
+pub fn render_json(events: &[RuntimeEvent], compact: bool) -> String {
+    let stream = EventStream::new(events.to_vec());
+    let out = if compact { serde_json::to_string(&stream) } else { serde_json::to_string_pretty(&stream) };
+    out.expect("events always serialize")
+}
@@ -1,8 +1,11 @@
 #![allow(dead_code, unused_imports)]
 
 pub mod cli;
+pub mod color;
 pub mod html;
 pub mod css;
+pub mod json;
 
 pub use cli::render_cli;
 pub use html::render_html;
+pub use json::render_json;
@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
-use crate::runtime::value::{NauxObj, Value};
+use crate::runtime::value::{format_value, NauxObj, Value};
 use crate::runtime::error::RuntimeError;
+use crate::runtime::memo::MemoCache;
 use crate::ast::Stmt;
 
 pub type BuiltinFn = fn(Vec<Value>) -> Result<Value, RuntimeError>;
@@ -24,13 +25,22 @@ pub struct Env {
     stack: Vec<Scope>,
     builtins: HashMap<String, BuiltinFn>,
     unsafe_stack: Vec<bool>,
+    /// Error counts on entry to each enclosing `~ try`; more errors than the innermost mark
+    /// means the try body must stop.
+    try_marks: Vec<usize>,
+    /// Loop bodies enclosing the running statement within the current function or top level.
+    loop_depth: usize,
     functions: HashMap<String, FnDef>,
+    pub memo: MemoCache,
 }
 
 #[derive(Debug, Clone)]
 pub struct FnDef {
     pub params: Vec<String>,
     pub body: Vec<Stmt>,
+    pub memo: bool,
+    /// Closure bindings, see `Env::closure_snapshot`.
+    pub captured: HashMap<String, Value>,
     pub span: Option<crate::ast::Span>,
 }
 
@@ -40,7 +50,10 @@ impl Env {
             stack: vec![Scope::new()],
             builtins: HashMap::new(),
             unsafe_stack: vec![false],
+            try_marks: Vec::new(),
+            loop_depth: 0,
             functions: HashMap::new(),
+            memo: MemoCache::default(),
         };
         register_builtins(&mut env);
         env
@@ -102,8 +115,59 @@ impl Env {
         *self.unsafe_stack.last().unwrap_or(&false)
     }
 
-    pub fn define_fn(&mut self, name: &str, params: Vec<String>, body: Vec<Stmt>, span: Option<crate::ast::Span>) {
-        self.functions.insert(name.to_string(), FnDef { params, body, span });
+    pub fn enter_try(&mut self, errors_len: usize) {
+        self.try_marks.push(errors_len);
+    }
+
+    pub fn leave_try(&mut self) {
+        self.try_marks.pop();
+    }
+
+    /// True once an error was raised inside the innermost `~ try` body.
+    pub fn raising(&self, errors_len: usize) -> bool {
+        self.try_marks.last().is_some_and(|&mark| errors_len > mark)
+    }
+
+    pub fn define_fn(
+        &mut self,
+        name: &str,
+        params: Vec<String>,
+        body: Vec<Stmt>,
+        memo: bool,
+        span: Option<crate::ast::Span>,
+        captured: HashMap<String, Value>,
+    ) {
+        self.functions.insert(name.to_string(), FnDef { params, body, memo, captured, span });
+    }
+
+    pub fn enter_loop(&mut self) {
+        self.loop_depth += 1;
+    }
+
+    pub fn leave_loop(&mut self) {
+        self.loop_depth -= 1;
+    }
+
+    /// A function body starts outside any loop; returns the caller's depth for `leave_fn_body`.
+    pub fn enter_fn_body(&mut self) -> usize {
+        std::mem::take(&mut self.loop_depth)
+    }
+
+    pub fn leave_fn_body(&mut self, loop_depth: usize) {
+        self.loop_depth = loop_depth;
+    }
+
+    /// Every binding visible from the current scope, innermost winning. Outside loops globals
+    /// are left out, so top-level functions keep seeing their current values; inside a loop
+    /// body they are included, so each iteration's function keeps that iteration's values.
+    /// Containers are captured by handle, like an assignment would share them.
+    pub fn closure_snapshot(&self) -> HashMap<String, Value> {
+        let mut captured = HashMap::new();
+        let skip = if self.loop_depth > 0 { 0 } else { 1 };
+        for scope in self.stack.iter().skip(skip) {
+            captured.extend(scope.map.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        captured
     }
 
     pub fn get_fn(&self, name: &str) -> Option<FnDef> {
@@ -114,6 +178,9 @@ impl Env {
 fn register_builtins(env: &mut Env) {
     env.builtins.insert("len".into(), builtin_len);
     env.builtins.insert("to_text".into(), builtin_to_text);
+    env.builtins.insert("bool".into(), builtin_bool);
+    env.builtins.insert("int".into(), builtin_int);
+    env.builtins.insert("float".into(), builtin_float);
     env.builtins.insert("__index".into(), builtin_index);
 }
 
@@ -133,7 +200,63 @@ fn builtin_len(args: Vec<Value>) -> Result<Value, RuntimeError> {
 
 fn builtin_to_text(args: Vec<Value>) -> Result<Value, RuntimeError> {
     let arg = args.get(0).cloned().unwrap_or(Value::Null);
-    Ok(Value::make_text(format!("{:?}", arg)))
+    Ok(Value::make_text(format_value(&arg)))
+}
+
+fn builtin_bool(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("bool(value)", None));
+    }
+    Ok(Value::Bool(args[0].truthy()))
+}
+
+/// Numeric value of `v` for `int`/`float`: bools are 1/0, text is parsed after trimming.
+/// NaN and infinity are rejected, including the texts `"nan"` and `"inf"`.
+fn coerce_number(v: &Value, usage: &str) -> Result<f64, RuntimeError> {
+    let fail = |what: String| RuntimeError::new(format!("{}: cannot convert {} to a number", usage, what), None);
+    let n = match v {
+        Value::SmallInt(n) => *n as f64,
+        Value::Float(f) => *f,
+        Value::Bool(b) => if *b { 1.0 } else { 0.0 },
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::Text(s) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()).ok_or_else(|| fail(format!("{:?}", s)))?,
+            _ => return Err(fail(format_value(v))),
+        },
+        Value::Null => return Err(fail("null".into())),
+    };
+    if !n.is_finite() {
+        return Err(fail(n.to_string()));
+    }
+    Ok(n)
+}
+
+fn builtin_int(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("int(value)", None));
+    }
+    if let Value::SmallInt(n) = args[0] {
+        return Ok(Value::SmallInt(n));
+    }
+    // integral text goes straight to i64 so large values keep full precision
+    if let Value::RcObj(rc) = &args[0] {
+        if let NauxObj::Text(s) = rc.as_ref() {
+            if let Ok(n) = s.trim().parse::<i64>() {
+                return Ok(Value::SmallInt(n));
+            }
+        }
+    }
+    let f = coerce_number(&args[0], "int(value)")?;
+    if !f.is_finite() || f.abs() >= i64::MAX as f64 {
+        return Err(RuntimeError::new(format!("int(value): cannot convert {} to an integer", f), None));
+    }
+    Ok(Value::SmallInt(f.trunc() as i64))
+}
+
+fn builtin_float(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("float(value)", None));
+    }
+    Ok(Value::Float(coerce_number(&args[0], "float(value)")?))
 }
 
 fn builtin_index(args: Vec<Value>) -> Result<Value, RuntimeError> {
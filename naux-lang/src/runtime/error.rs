@@ -1,4 +1,10 @@
+use std::collections::HashMap;
+
 use crate::ast::Span;
+use crate::runtime::value::Value;
+
+/// Message prefix marking an error raised by `throw(message)` rather than by the runtime.
+pub const THROWN_PREFIX: &str = "Thrown: ";
 
 #[derive(Debug, Clone)]
 pub struct Frame {
@@ -31,12 +37,27 @@ impl RuntimeError {
     }
 }
 
+/// The map a `~ catch $err` binds: `message`, `code` (`"thrown"` for `throw(...)`, otherwise
+/// `"runtime"`) and `trace`, the names of the calls active when the error was raised, outermost first.
+pub fn caught_error_value(message: &str, trace: &[Frame]) -> Value {
+    let (message, code) = match message.strip_prefix(THROWN_PREFIX) {
+        Some(thrown) => (thrown, "thrown"),
+        None => (message, "runtime"),
+    };
+    let trace = trace.iter().map(|f| Value::make_text(f.name.clone())).collect();
+    Value::make_map(HashMap::from([
+        ("message".to_string(), Value::make_text(message.to_string())),
+        ("code".to_string(), Value::make_text(code.to_string())),
+        ("trace".to_string(), Value::make_list(trace)),
+    ]))
+}
+
 pub fn format_runtime_error(src: &str, err: &RuntimeError) -> String {
     let trace_rendered = format_trace(src, err, None);
     if let Some(span) = &err.span {
         let line_idx = span.line.saturating_sub(1);
         let line_text = src.lines().nth(line_idx).unwrap_or("");
-        let caret = format!("{}^", " ".repeat(span.column.saturating_sub(1)));
+        let caret = span.caret();
         format!(
             "Runtime error: {}\n --> line {}, col {}\n {}\n {}{}",
             err.message, span.line, span.column, line_text, caret, trace_rendered
@@ -51,7 +72,7 @@ pub fn format_runtime_error_with_file(src: &str, err: &RuntimeError, filename: &
     if let Some(span) = &err.span {
         let line_idx = span.line.saturating_sub(1);
         let line_text = src.lines().nth(line_idx).unwrap_or("");
-        let caret = format!("{}^", " ".repeat(span.column.saturating_sub(1)));
+        let caret = span.caret();
         format!(
             "Runtime error: {}\n --> {}:{}:{}\n {}\n {}{}",
             err.message, filename, span.line, span.column, line_text, caret, trace_rendered
@@ -66,7 +87,7 @@ pub fn format_runtime_error_html(src: &str, err: &RuntimeError, filename: &str)
     if let Some(span) = &err.span {
         let line_idx = span.line.saturating_sub(1);
         let line_text = src.lines().nth(line_idx).unwrap_or("");
-        let caret = "&nbsp;".repeat(span.column.saturating_sub(1)) + "^";
+        let caret = "&nbsp;".repeat(span.column.saturating_sub(1)) + &"^".repeat(span.width());
         format!(
             "<div class=\"error\"><strong>Runtime error:</strong> {}<br/>{}:{}:{}<pre>{}</pre><pre>{}</pre>{}</div>",
             html_escape(&err.message),
@@ -95,7 +116,7 @@ fn format_trace(src: &str, err: &RuntimeError, file: Option<&str>) -> String {
             // optional snippet caret for last frame? keep concise
             let line_idx = sp.line.saturating_sub(1);
             if let Some(line_text) = src.lines().nth(line_idx) {
-                lines.push_str(&format!("\n    {}\n    {}^", line_text, " ".repeat(sp.column.saturating_sub(1))));
+                lines.push_str(&format!("\n    {}\n    {}", line_text, sp.caret()));
             }
         } else {
             lines.push_str(&format!("\n  at {}", frame.name));
@@ -114,7 +135,7 @@ fn html_trace(src: &str, err: &RuntimeError, file: Option<&str>) -> String {
             let fname = file.unwrap_or("<unknown>");
             let mut snippet = String::new();
             if let Some(line_text) = src.lines().nth(sp.line.saturating_sub(1)) {
-                snippet = format!("<pre>{}</pre><pre>{}</pre>", html_escape(line_text), "&nbsp;".repeat(sp.column.saturating_sub(1)) + "^");
+                snippet = format!("<pre>{}</pre><pre>{}</pre>", html_escape(line_text), "&nbsp;".repeat(sp.column.saturating_sub(1)) + &"^".repeat(sp.width()));
             }
             items.push_str(&format!("<li>{} ({}:{}:{}){}</li>", html_escape(&frame.name), html_escape(fname), sp.line, sp.column, snippet));
         } else {
@@ -1,15 +1,17 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs;
 
-use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, Stmt, UnaryOp};
+use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, InterpolPart, Stmt, UnaryOp};
 use crate::lexer::lex;
 use crate::oracle::query_oracle;
 use crate::parser::error::format_parse_error;
 use crate::parser::parser::Parser;
 use crate::runtime::env::{Env, FnDef};
-use crate::runtime::error::{Frame, RuntimeError};
-use crate::runtime::events::RuntimeEvent;
-use crate::runtime::value::{NauxObj, Value};
+use crate::runtime::error::{caught_error_value, Frame, RuntimeError, THROWN_PREFIX};
+use crate::runtime::events::{progress_event, table_event, RuntimeEvent};
+use crate::runtime::value::{format_value, Function, NauxObj, Value};
+use crate::stdlib::list::{call_higher_order, is_higher_order};
 use crate::stdlib::register_all;
 
 pub fn eval_script(stmts: &[Stmt]) -> (Env, Vec<RuntimeEvent>, Vec<RuntimeError>) {
@@ -19,26 +21,92 @@ pub fn eval_script(stmts: &[Stmt]) -> (Env, Vec<RuntimeEvent>, Vec<RuntimeError>
     let mut errors = Vec::new();
     let mut call_stack: Vec<Frame> = Vec::new();
     for stmt in stmts {
-        if eval_stmt(stmt, &mut env, &mut events, &mut errors, &mut call_stack).is_some() {
-            // ignore top-level returns
-        }
+        // top-level returns are ignored; the parser keeps break/continue inside loops
+        eval_stmt(stmt, &mut env, &mut events, &mut errors, &mut call_stack);
     }
     (env, events, errors)
 }
 
+/// Like `eval_script`, but stops at the first top-level `^` and returns its value (mirrors the VM).
+pub fn eval_script_value(stmts: &[Stmt]) -> (Env, Vec<RuntimeEvent>, Vec<RuntimeError>, Value) {
+    let mut env = Env::new();
+    register_all(&mut env);
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    let mut call_stack: Vec<Frame> = Vec::new();
+    let value = eval_block(stmts, &mut env, &mut events, &mut errors, &mut call_stack).returned();
+    (env, events, errors, value)
+}
+
+/// Run `stmts` against an existing `env` (REPL sessions); events and errors are appended.
+/// Returns the value of a top-level `^`, otherwise null.
+pub fn eval_in_env(
+    stmts: &[Stmt],
+    env: &mut Env,
+    events: &mut Vec<RuntimeEvent>,
+    errors: &mut Vec<RuntimeError>,
+) -> Value {
+    let mut call_stack: Vec<Frame> = Vec::new();
+    eval_block(stmts, env, events, errors, &mut call_stack).returned()
+}
+
+/// How a statement finished: normally, or by a `^`/`~ break`/`~ continue` that unwinds
+/// enclosing blocks until a function call (return) or the innermost loop (break/continue).
+/// `Raise` unwinds to the innermost `~ try` after an error inside its body.
+enum ControlFlow {
+    None,
+    Return(Value),
+    Break,
+    Continue,
+    Raise,
+}
+
+impl ControlFlow {
+    /// The value of a function body or script: its `^` value, otherwise null.
+    fn returned(self) -> Value {
+        match self {
+            ControlFlow::Return(v) => v,
+            _ => Value::Null,
+        }
+    }
+}
+
 fn eval_block(
     block: &[Stmt],
     env: &mut Env,
     events: &mut Vec<RuntimeEvent>,
     errors: &mut Vec<RuntimeError>,
     call_stack: &mut Vec<Frame>,
-) -> Option<Value> {
+) -> ControlFlow {
     for stmt in block {
-        if let Some(rv) = eval_stmt(stmt, env, events, errors, call_stack) {
-            return Some(rv);
+        let flow = eval_stmt(stmt, env, events, errors, call_stack);
+        if !matches!(flow, ControlFlow::None) {
+            return flow;
         }
+        if env.raising(errors.len()) {
+            return ControlFlow::Raise;
+        }
+    }
+    ControlFlow::None
+}
+
+/// Run one iteration of a loop body; `Some` means the loop must stop and yield that flow
+/// to its caller (a `^` propagates, a `~ break` ends just this loop).
+fn eval_loop_body(
+    body: &[Stmt],
+    env: &mut Env,
+    events: &mut Vec<RuntimeEvent>,
+    errors: &mut Vec<RuntimeError>,
+    call_stack: &mut Vec<Frame>,
+) -> Option<ControlFlow> {
+    env.enter_loop();
+    let flow = eval_block(body, env, events, errors, call_stack);
+    env.leave_loop();
+    match flow {
+        ControlFlow::None | ControlFlow::Continue => None,
+        ControlFlow::Break => Some(ControlFlow::None),
+        flow @ (ControlFlow::Return(_) | ControlFlow::Raise) => Some(flow),
     }
-    None
 }
 
 fn eval_stmt(
@@ -47,7 +115,7 @@ fn eval_stmt(
     events: &mut Vec<RuntimeEvent>,
     errors: &mut Vec<RuntimeError>,
     call_stack: &mut Vec<Frame>,
-) -> Option<Value> {
+) -> ControlFlow {
     match stmt {
         Stmt::Rite { body, span } => {
             env.push_scope();
@@ -63,19 +131,24 @@ fn eval_stmt(
             env.pop_unsafe();
             rv
         }
-        Stmt::FnDef { name, params, body, span } => {
-            env.define_fn(name, params.clone(), body.clone(), span.clone());
-            None
+        Stmt::FnDef { name, params, body, memo, span } => {
+            let captured = env.closure_snapshot();
+            env.define_fn(name, params.clone(), body.clone(), *memo, span.clone(), captured);
+            ControlFlow::None
         }
         Stmt::Assign { name, expr, .. } => {
             let val = eval_expr(expr, env, events, errors, call_stack);
+            if env.raising(errors.len()) {
+                return ControlFlow::Raise;
+            }
             env.set(name, val);
-            events.push(RuntimeEvent::Log(format!("set {}", name)));
-            None
+            ControlFlow::None
         }
         Stmt::If { cond, then_block, else_block, .. } => {
             let c = eval_expr(cond, env, events, errors, call_stack);
-            if c.truthy() {
+            if env.raising(errors.len()) {
+                ControlFlow::Raise
+            } else if c.truthy() {
                 eval_block(then_block, env, events, errors, call_stack)
             } else {
                 eval_block(else_block, env, events, errors, call_stack)
@@ -85,30 +158,33 @@ fn eval_stmt(
             let n = eval_expr(count, env, events, errors, call_stack);
             let times = n.as_f64().filter(|x| *x > 0.0).unwrap_or(0.0) as i64;
             for _ in 0..times {
-                if let Some(rv) = eval_block(body, env, events, errors, call_stack) {
-                    return Some(rv);
+                if let Some(flow) = eval_loop_body(body, env, events, errors, call_stack) {
+                    return flow;
                 }
             }
-            None
+            ControlFlow::None
         }
-        Stmt::Each { var, iter, body, span } => {
+        Stmt::Each { index, var, iter, body, span } => {
             let it = eval_expr(iter, env, events, errors, call_stack);
             if let Value::RcObj(rc) = it {
                 if let NauxObj::List(items) = rc.as_ref() {
-                    for v in items.borrow().iter() {
+                    for (i, v) in items.borrow().iter().enumerate() {
                         env.push_scope();
-                        env.set(var, v.clone());
-                        if let Some(rv) = eval_block(body, env, events, errors, call_stack) {
-                            env.pop_scope();
-                            return Some(rv);
+                        if let Some(index) = index {
+                            env.set(index, Value::SmallInt(i as i64));
                         }
+                        env.set(var, v.clone());
+                        let flow = eval_loop_body(body, env, events, errors, call_stack);
                         env.pop_scope();
+                        if let Some(flow) = flow {
+                            return flow;
+                        }
                     }
-                    return None;
+                    return ControlFlow::None;
                 }
             }
             push_error(errors, "Each expects a list to iterate", span.clone(), call_stack);
-            None
+            ControlFlow::None
         }
         Stmt::While { cond, body, .. } => {
             loop {
@@ -116,26 +192,41 @@ fn eval_stmt(
                 if !c.truthy() {
                     break;
                 }
-                if let Some(rv) = eval_block(body, env, events, errors, call_stack) {
-                    return Some(rv);
+                if let Some(flow) = eval_loop_body(body, env, events, errors, call_stack) {
+                    return flow;
                 }
             }
-            None
+            ControlFlow::None
         }
         Stmt::Action { action, .. } => {
             dispatch_action(action, env, events, errors, call_stack);
-            None
+            ControlFlow::None
         }
         Stmt::Return { value, .. } => {
             let v = value
                 .as_ref()
                 .map(|e| eval_expr(e, env, events, errors, call_stack))
                 .unwrap_or(Value::Null);
-            Some(v)
+            ControlFlow::Return(v)
         }
+        Stmt::TryCatch { try_block, catch_var, catch_block, .. } => {
+            let mark = errors.len();
+            env.enter_try(mark);
+            let flow = eval_block(try_block, env, events, errors, call_stack);
+            env.leave_try();
+            if errors.len() == mark {
+                return flow;
+            }
+            // the first error is the one that stopped the body; its follow-ups are dropped too
+            let caught = errors.drain(mark..).next().expect("errors grew past the mark");
+            env.set(catch_var, caught_error_value(&caught.message, &caught.trace));
+            eval_block(catch_block, env, events, errors, call_stack)
+        }
+        Stmt::Break { .. } => ControlFlow::Break,
+        Stmt::Continue { .. } => ControlFlow::Continue,
         Stmt::Import { module, span } => {
             eval_import(module, env, events, errors, call_stack, span.clone());
-            None
+            ControlFlow::None
         }
     }
 }
@@ -167,26 +258,48 @@ fn eval_expr(
         }
         ExprKind::Var(name) => match env.get(name) {
             Some(v) => v,
-            None => {
-                push_error(errors, format!("Variable not found: {}", name), expr.span.clone(), call_stack);
-                Value::Null
-            }
+            // a bare function name is a value carrying its captured bindings
+            None => match env.get_fn(name) {
+                Some(def) => Value::make_function(Function { params: def.params, body: def.body, captured: def.captured }),
+                None => {
+                    push_error(errors, format!("Variable not found: {}", name), expr.span.clone(), call_stack);
+                    Value::Null
+                }
+            },
         },
         ExprKind::Call { callee, args } => {
             let name_opt = if let ExprKind::Var(n) = &callee.kind { Some(n.clone()) } else { None };
+            if name_opt.as_deref() == Some("assert_throws") && env.get_fn("assert_throws").is_none() {
+                return eval_assert_throws(args, expr, env, events, errors, call_stack);
+            }
+            if name_opt.as_deref() == Some("throw") && args.len() == 1 {
+                let v = eval_expr(&args[0], env, events, errors, call_stack);
+                push_error(errors, format!("{}{}", THROWN_PREFIX, format_value(&v)), expr.span.clone(), call_stack);
+                return Value::Null;
+            }
+            if let Some(name) = name_opt.as_deref().filter(|n| is_higher_order(n) && env.get_fn(n).is_none()) {
+                return eval_higher_order(name, args, expr, env, events, errors, call_stack);
+            }
             let evaled_args: Vec<Value> = args.iter().map(|a| eval_expr(a, env, events, errors, call_stack)).collect();
             if let Some(name) = name_opt {
                 if let Some(fn_def) = env.get_fn(&name) {
-                    call_stack.push(Frame { name: name.clone(), span: expr.span.clone() });
-                    env.push_scope();
-                    for (i, param) in fn_def.params.iter().enumerate() {
-                        let v = evaled_args.get(i).cloned().unwrap_or(Value::Null);
-                        env.set(param, v);
+                    if let Some(hit) = fn_def.memo.then(|| env.memo.get(&name, &evaled_args)).flatten() {
+                        return hit;
+                    }
+                    let errors_before = errors.len();
+                    let frame = Frame { name: name.clone(), span: expr.span.clone() };
+                    let rv = call_fn_def(frame, &fn_def, &evaled_args, env, events, errors, call_stack);
+                    // failed calls aren't cached so the error shows up again on retry
+                    if fn_def.memo && errors.len() == errors_before {
+                        env.memo.insert(&name, evaled_args, rv.clone());
                     }
-                    let rv = eval_block(&fn_def.body, env, events, errors, call_stack).unwrap_or(Value::Null);
-                    env.pop_scope();
-                    call_stack.pop();
                     rv
+                } else if let Some(def) = env.get(&name).as_ref().and_then(closure_def) {
+                    let frame = Frame { name: name.clone(), span: expr.span.clone() };
+                    call_fn_def(frame, &def, &evaled_args, env, events, errors, call_stack)
+                } else if crate::stdlib::fs::requires_unsafe(&name) && !env.is_unsafe() {
+                    push_error(errors, crate::stdlib::fs::UNSAFE_REQUIRED, expr.span.clone(), call_stack);
+                    Value::Null
                 } else if let Some(res) = env.call_builtin(&name, evaled_args.clone()) {
                     match res {
                         Ok(v) => v,
@@ -201,8 +314,17 @@ fn eval_expr(
                     Value::Null
                 }
             } else {
-                push_error(errors, "Invalid call target", expr.span.clone(), call_stack);
-                Value::Null
+                let target = eval_expr(callee, env, events, errors, call_stack);
+                match closure_def(&target) {
+                    Some(def) => {
+                        let frame = Frame { name: "<fn>".into(), span: expr.span.clone() };
+                        call_fn_def(frame, &def, &evaled_args, env, events, errors, call_stack)
+                    }
+                    None => {
+                        push_error(errors, "Invalid call target", expr.span.clone(), call_stack);
+                        Value::Null
+                    }
+                }
             }
         }
         ExprKind::Binary { op, left, right } => {
@@ -224,6 +346,10 @@ fn eval_expr(
                             push_error(errors, "Division by zero", expr.span.clone(), call_stack);
                             Value::Null
                         }
+                        (_, Some(0.0)) if matches!(op, BinaryOp::Mod) => {
+                            push_error(errors, "Modulo by zero", expr.span.clone(), call_stack);
+                            Value::Null
+                        }
                         (Some(x), Some(y)) => match op {
                             BinaryOp::Sub => Value::Float(x - y),
                             BinaryOp::Mul => Value::Float(x * y),
@@ -241,26 +367,19 @@ fn eval_expr(
                     let eq = l == r;
                     Value::Bool(if matches!(op, BinaryOp::Eq) { eq } else { !eq })
                 }
-                BinaryOp::Gt | BinaryOp::Ge | BinaryOp::Lt | BinaryOp::Le => {
-                    let a = l.as_f64();
-                    let b = r.as_f64();
-                    match (a, b) {
-                        (Some(x), Some(y)) => {
-                            let res = match op {
-                                BinaryOp::Gt => x > y,
-                                BinaryOp::Ge => x >= y,
-                                BinaryOp::Lt => x < y,
-                                BinaryOp::Le => x <= y,
-                                _ => false,
-                            };
-                            Value::Bool(res)
-                        }
-                        _ => {
-                            push_error(errors, "Type error in binary expression", expr.span.clone(), call_stack);
-                            Value::Null
-                        }
+                BinaryOp::Gt | BinaryOp::Ge | BinaryOp::Lt | BinaryOp::Le => match l.compare(&r) {
+                    Ok(ord) => Value::Bool(match op {
+                        BinaryOp::Gt => ord == Some(Ordering::Greater),
+                        BinaryOp::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+                        BinaryOp::Lt => ord == Some(Ordering::Less),
+                        BinaryOp::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+                        _ => false,
+                    }),
+                    Err(msg) => {
+                        push_error(errors, format!("Type error in binary expression: {}", msg), expr.span.clone(), call_stack);
+                        Value::Null
                     }
-                }
+                },
                 BinaryOp::And | BinaryOp::Or => match (l.truthy(), r.truthy()) {
                     (la, ra) => Value::Bool(if matches!(op, BinaryOp::And) { la && ra } else { la || ra }),
                 },
@@ -308,6 +427,50 @@ fn eval_expr(
                 }
             }
         }
+        ExprKind::Ternary { cond, then_expr, else_expr } => {
+            if eval_expr(cond, env, events, errors, call_stack).truthy() {
+                eval_expr(then_expr, env, events, errors, call_stack)
+            } else {
+                eval_expr(else_expr, env, events, errors, call_stack)
+            }
+        }
+        ExprKind::Comprehension { elem, var, iter, cond } => {
+            let it = eval_expr(iter, env, events, errors, call_stack);
+            let items = match &it {
+                Value::RcObj(rc) => match rc.as_ref() {
+                    NauxObj::List(items) => Some(items.borrow().clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some(items) = items else {
+                push_error(errors, "Comprehension expects a list to iterate", iter.span.clone(), call_stack);
+                return Value::Null;
+            };
+            let mut out = Vec::new();
+            env.push_scope();
+            for v in items {
+                env.set(var, v);
+                if let Some(c) = cond {
+                    if !eval_expr(c, env, events, errors, call_stack).truthy() {
+                        continue;
+                    }
+                }
+                out.push(eval_expr(elem, env, events, errors, call_stack));
+            }
+            env.pop_scope();
+            Value::make_list(out)
+        }
+        ExprKind::Interpolate(parts) => {
+            let mut out = String::new();
+            for part in parts {
+                match part {
+                    InterpolPart::Text(t) => out.push_str(t),
+                    InterpolPart::Expr(e) => out.push_str(&format_value(&eval_expr(e, env, events, errors, call_stack))),
+                }
+            }
+            Value::make_text(out)
+        }
         ExprKind::Field { target, field } => {
             let t = eval_expr(target, env, events, errors, call_stack);
             match t {
@@ -327,41 +490,95 @@ fn eval_expr(
     }
 }
 
+/// Run a user function body in a new scope holding its captured bindings, then the arguments
+/// (missing ones are null).
+fn call_fn_def(
+    frame: Frame,
+    def: &FnDef,
+    args: &[Value],
+    env: &mut Env,
+    events: &mut Vec<RuntimeEvent>,
+    errors: &mut Vec<RuntimeError>,
+    call_stack: &mut Vec<Frame>,
+) -> Value {
+    call_stack.push(frame);
+    env.push_scope();
+    for (name, v) in &def.captured {
+        env.set(name, v.clone());
+    }
+    for (i, param) in def.params.iter().enumerate() {
+        env.set(param, args.get(i).cloned().unwrap_or(Value::Null));
+    }
+    let loop_depth = env.enter_fn_body();
+    let rv = eval_block(&def.body, env, events, errors, call_stack).returned();
+    env.leave_fn_body(loop_depth);
+    env.pop_scope();
+    call_stack.pop();
+    rv
+}
+
+/// The callable behind a function value; anything else is not callable.
+fn closure_def(v: &Value) -> Option<FnDef> {
+    let Value::RcObj(rc) = v else { return None };
+    let NauxObj::Function(f) = rc.as_ref() else { return None };
+    Some(FnDef { params: f.params.clone(), body: f.body.clone(), memo: false, captured: f.captured.clone(), span: None })
+}
+
 fn dispatch_action(action: &ActionKind, env: &mut Env, events: &mut Vec<RuntimeEvent>, errors: &mut Vec<RuntimeError>, call_stack: &mut Vec<Frame>) {
     match action {
         ActionKind::Say { value } => {
             let v = eval_expr(value, env, events, errors, call_stack);
-            events.push(RuntimeEvent::Say(format_value(&v)));
+            emit(events, env, errors, RuntimeEvent::Say(format_value(&v)));
         }
         ActionKind::Ask { prompt } => {
             let p = eval_expr(prompt, env, events, errors, call_stack);
             let p_str = format_value(&p);
-            events.push(RuntimeEvent::Ask { prompt: p_str.clone(), answer: String::new() });
-            let ans = query_oracle(&p_str);
-            events.push(RuntimeEvent::Ask { prompt: p_str, answer: ans.clone() });
+            let answer = query_oracle(&p_str);
+            emit(events, env, errors, RuntimeEvent::Ask { prompt: p_str, answer });
         }
         ActionKind::Fetch { target } => {
             let t = eval_expr(target, env, events, errors, call_stack);
-            events.push(RuntimeEvent::Fetch { target: format_value(&t) });
+            emit(events, env, errors, RuntimeEvent::Fetch { target: format_value(&t) });
         }
         ActionKind::Ui { kind, .. } => {
-            events.push(RuntimeEvent::Ui { kind: kind.clone(), props: Vec::new() });
+            emit(events, env, errors, RuntimeEvent::Ui { kind: kind.clone(), props: Vec::new() });
         }
         ActionKind::Text { value } => {
             let v = eval_expr(value, env, events, errors, call_stack);
-            events.push(RuntimeEvent::Text(format_value(&v)));
+            emit(events, env, errors, RuntimeEvent::Text(format_value(&v)));
         }
         ActionKind::Button { value } => {
             let v = eval_expr(value, env, events, errors, call_stack);
-            events.push(RuntimeEvent::Button(format_value(&v)));
+            emit(events, env, errors, RuntimeEvent::Button(format_value(&v)));
         }
         ActionKind::Log { value } => {
             let v = eval_expr(value, env, events, errors, call_stack);
-            events.push(RuntimeEvent::Log(format_value(&v)));
+            emit(events, env, errors, RuntimeEvent::Log(format_value(&v)));
+        }
+        ActionKind::Progress { value } => {
+            let v = eval_expr(value, env, events, errors, call_stack);
+            match progress_event(&v) {
+                Ok(ev) => emit(events, env, errors, ev),
+                Err(msg) => push_error(errors, msg, value.span.clone(), call_stack),
+            }
+        }
+        ActionKind::Table { rows } => {
+            let v = eval_expr(rows, env, events, errors, call_stack);
+            match table_event(&v, format_value) {
+                Ok(ev) => emit(events, env, errors, ev),
+                Err(msg) => push_error(errors, msg, rows.span.clone(), call_stack),
+            }
         }
     }
 }
 
+/// Records an action's event unless an error inside a `~ try` body is unwinding it.
+fn emit(events: &mut Vec<RuntimeEvent>, env: &Env, errors: &[RuntimeError], event: RuntimeEvent) {
+    if !env.raising(errors.len()) {
+        events.push(event);
+    }
+}
+
 fn eval_import(module: &str, env: &mut Env, events: &mut Vec<RuntimeEvent>, errors: &mut Vec<RuntimeError>, call_stack: &mut Vec<Frame>, span: Option<crate::ast::Span>) {
     match fs::read_to_string(module) {
         Ok(src) => {
@@ -377,7 +594,7 @@ fn eval_import(module: &str, env: &mut Env, events: &mut Vec<RuntimeEvent>, erro
                 Ok(ast) => {
                     for stmt in ast {
                         match stmt {
-                            Stmt::FnDef { name, params, body, span } => env.define_fn(&name, params, body, span),
+                            Stmt::FnDef { name, params, body, memo, span } => env.define_fn(&name, params, body, memo, span, HashMap::new()),
                             Stmt::Assign { name, expr, .. } => {
                                 let v = eval_expr(&expr, env, events, errors, call_stack);
                                 env.set(&name, v);
@@ -401,16 +618,135 @@ fn eval_import(module: &str, env: &mut Env, events: &mut Vec<RuntimeEvent>, erro
     }
 }
 
-fn format_value(v: &Value) -> String {
-    match v {
-        Value::RcObj(rc) => match rc.as_ref() {
-            NauxObj::Text(s) => s.clone(),
-            _ => format!("{:?}", v),
+/// `assert_throws(f, substring, args...)`: calls `f` (a bare function name or a text naming one)
+/// and succeeds only if the call raises an error whose message contains `substring`.
+/// Errors raised by the call are swallowed; a missing or mismatched error becomes the failure.
+fn eval_assert_throws(
+    args: &[Expr],
+    expr: &Expr,
+    env: &mut Env,
+    events: &mut Vec<RuntimeEvent>,
+    errors: &mut Vec<RuntimeError>,
+    call_stack: &mut Vec<Frame>,
+) -> Value {
+    if args.len() < 2 {
+        push_error(errors, "assert_throws(fn, message_substring, args...)", expr.span.clone(), call_stack);
+        return Value::Null;
+    }
+    let target = match &args[0].kind {
+        // a bare function name; a variable holds the name instead
+        ExprKind::Var(n) if env.get(n).is_none() => Some(n.clone()),
+        _ => eval_expr(&args[0], env, events, errors, call_stack).as_text(),
+    };
+    let needle = eval_expr(&args[1], env, events, errors, call_stack).as_text();
+    let (Some(target), Some(needle)) = (target, needle) else {
+        push_error(errors, "assert_throws: expected a function name and a text substring", expr.span.clone(), call_stack);
+        return Value::Null;
+    };
+    // route the call through the normal call path, capturing its errors separately
+    let call = Expr::new(
+        ExprKind::Call {
+            callee: Box::new(Expr::new(ExprKind::Var(target.clone()), args[0].span.clone())),
+            args: args[2..].to_vec(),
         },
-        Value::SmallInt(n) => n.to_string(),
-        Value::Float(n) => n.to_string(),
-        Value::Bool(b) => b.to_string(),
-        other => format!("{:?}", other),
+        expr.span.clone(),
+    );
+    let mut captured = Vec::new();
+    eval_expr(&call, env, events, &mut captured, call_stack);
+    match captured.first() {
+        None => {
+            push_error(
+                errors,
+                format!("assert_throws: expected {} to fail with '{}', but it succeeded", target, needle),
+                expr.span.clone(),
+                call_stack,
+            );
+            Value::Null
+        }
+        Some(err) if err.message.contains(&needle) => Value::Bool(true),
+        Some(err) => {
+            push_error(
+                errors,
+                format!("assert_throws: expected an error containing '{}', got '{}'", needle, err.message),
+                expr.span.clone(),
+                call_stack,
+            );
+            Value::Null
+        }
+    }
+}
+
+/// `list_map(list, f)` and friends: `f` is a function value, a bare function name (user
+/// function or builtin) or a text naming one.
+fn eval_higher_order(
+    name: &str,
+    args: &[Expr],
+    expr: &Expr,
+    env: &mut Env,
+    events: &mut Vec<RuntimeEvent>,
+    errors: &mut Vec<RuntimeError>,
+    call_stack: &mut Vec<Frame>,
+) -> Value {
+    let evaled_args: Vec<Value> = args
+        .iter()
+        .enumerate()
+        .map(|(i, a)| match &a.kind {
+            // builtins have no value form, so a bare name that is no variable is passed by name
+            ExprKind::Var(n) if i == 1 && env.get(n).is_none() && env.get_fn(n).is_none() => Value::make_text(n.clone()),
+            _ => eval_expr(a, env, events, errors, call_stack),
+        })
+        .collect();
+    let mut call = |f: &Value, call_args: Vec<Value>| -> Result<Value, RuntimeError> {
+        let errors_before = errors.len();
+        let rv = call_value(f, call_args, expr, env, events, errors, call_stack);
+        if errors.len() > errors_before {
+            let mut raised = errors.split_off(errors_before);
+            return Err(raised.swap_remove(0));
+        }
+        rv.map_err(|msg| RuntimeError::with_trace(msg, expr.span.clone(), call_stack.clone()))
+    };
+    match call_higher_order(name, evaled_args, &mut call) {
+        Ok(v) => v,
+        Err(mut e) => {
+            if e.trace.is_empty() {
+                e.trace = call_stack.clone();
+            }
+            if e.span.is_none() {
+                e.span = expr.span.clone();
+            }
+            errors.push(e);
+            Value::Null
+        }
+    }
+}
+
+/// Call a function value, or the user function / builtin a text names.
+fn call_value(
+    f: &Value,
+    args: Vec<Value>,
+    expr: &Expr,
+    env: &mut Env,
+    events: &mut Vec<RuntimeEvent>,
+    errors: &mut Vec<RuntimeError>,
+    call_stack: &mut Vec<Frame>,
+) -> Result<Value, String> {
+    if let Some(def) = closure_def(f) {
+        let frame = Frame { name: "<fn>".into(), span: expr.span.clone() };
+        return Ok(call_fn_def(frame, &def, &args, env, events, errors, call_stack));
+    }
+    let Some(name) = f.as_text() else {
+        return Err(format!("expected a function, got {}", f.type_name()));
+    };
+    if let Some(def) = env.get_fn(&name) {
+        let frame = Frame { name: name.clone(), span: expr.span.clone() };
+        return Ok(call_fn_def(frame, &def, &args, env, events, errors, call_stack));
+    }
+    if crate::stdlib::fs::requires_unsafe(&name) && !env.is_unsafe() {
+        return Err(crate::stdlib::fs::UNSAFE_REQUIRED.into());
+    }
+    match env.call_builtin(&name, args) {
+        Some(res) => res.map_err(|e| e.message),
+        None => Err(format!("Function not found: {}", name)),
     }
 }
 
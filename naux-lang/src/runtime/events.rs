@@ -1,12 +1,46 @@
-use crate::runtime::value::Value;
-
-#[derive(Debug, Clone)]
-pub enum RuntimeEvent {
-    Say(String),
-    Ask { prompt: String, answer: String },
-    Fetch { target: String },
-    Ui { kind: String, props: Vec<(String, Value)> },
-    Text(String),
-    Button(String),
-    Log(String),
+use std::collections::BTreeSet;
+
+use crate::runtime::value::{NauxObj, Value};
+
+pub use naux_events::{EventStream, RuntimeEvent, EVENTS_VERSION};
+
+/// True for events that only trace execution (`SetVar`, `Log`, `Fetch`) rather than produce
+/// program output; `naux run --quiet` drops these and keeps `Say`, `Ask`, UI, table and progress.
+pub fn is_informational(event: &RuntimeEvent) -> bool {
+    matches!(event, RuntimeEvent::SetVar { .. } | RuntimeEvent::Log(_) | RuntimeEvent::Fetch { .. })
+}
+
+/// The event stream as `--quiet` renders it: informational events removed, order kept.
+pub fn quiet_events(events: Vec<RuntimeEvent>) -> Vec<RuntimeEvent> {
+    events.into_iter().filter(|ev| !is_informational(ev)).collect()
+}
+
+/// Build a `Progress` event, clamping the fraction to `[0, 1]` (NaN counts as 0).
+pub fn progress_event(value: &Value) -> Result<RuntimeEvent, String> {
+    let f = value.as_f64().ok_or("!progress expects a number")?;
+    Ok(RuntimeEvent::Progress(if f.is_nan() { 0.0 } else { f.clamp(0.0, 1.0) }))
+}
+
+/// Build a `Table` event from a list of maps. Columns are the first row's keys, sorted.
+pub fn table_event(rows: &Value, cell: impl Fn(&Value) -> String) -> Result<RuntimeEvent, String> {
+    let usage = "!table expects a list of maps";
+    let list = match rows {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().clone(),
+            _ => return Err(usage.into()),
+        },
+        _ => return Err(usage.into()),
+    };
+    let mut columns: Vec<String> = Vec::new();
+    let mut out = Vec::with_capacity(list.len());
+    for (i, row) in list.iter().enumerate() {
+        let Value::RcObj(rc) = row else { return Err(usage.into()) };
+        let NauxObj::Map(map) = rc.as_ref() else { return Err(usage.into()) };
+        let map = map.borrow();
+        if i == 0 {
+            columns = map.keys().cloned().collect::<BTreeSet<_>>().into_iter().collect();
+        }
+        out.push(columns.iter().map(|c| map.get(c).map(&cell).unwrap_or_default()).collect());
+    }
+    Ok(RuntimeEvent::Table { columns, rows: out })
 }
@@ -85,7 +85,7 @@ mod tests {
         let mut out = RawValue::null();
         unsafe {
             assert_eq!(jit_helper_len(&raw, &mut out), 0);
-            let res = RawValue::from_raw(&out);
+            let res = Value::from_raw(&out);
             assert!(matches!(res, Value::SmallInt(3)));
         }
     }
@@ -98,7 +98,7 @@ mod tests {
         let mut out = RawValue::null();
         unsafe {
             assert_eq!(jit_helper_index(&raw_list, &idx, &mut out), 0);
-            let res = RawValue::from_raw(&out);
+            let res = Value::from_raw(&out);
             assert!(matches!(res, Value::SmallInt(2)));
         }
     }
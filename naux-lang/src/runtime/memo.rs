@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use crate::runtime::value::Value;
+
+type Bucket = Vec<(Vec<Value>, Value)>;
+
+/// Results of `memo` functions keyed by name and argument values.
+///
+/// The cache lives for a single run and is never persisted, so there is nothing to invalidate:
+/// a fresh run starts empty. Hits skip the body entirely, including any events it would emit,
+/// so only mark functions `memo` when their result depends on their arguments alone.
+#[derive(Debug, Clone, Default)]
+pub struct MemoCache {
+    // buckets by `stable_hash`; arguments are compared for equality to rule out collisions
+    entries: HashMap<(String, i64), Bucket>,
+}
+
+impl MemoCache {
+    pub fn get(&self, name: &str, args: &[Value]) -> Option<Value> {
+        let bucket = self.entries.get(&(name.to_string(), args_hash(args)))?;
+        bucket.iter().find(|(a, _)| a.as_slice() == args).map(|(_, v)| v.clone())
+    }
+
+    pub fn insert(&mut self, name: &str, args: Vec<Value>, value: Value) {
+        let key = (name.to_string(), args_hash(&args));
+        self.entries.entry(key).or_default().push((args, value));
+    }
+}
+
+fn args_hash(args: &[Value]) -> i64 {
+    Value::make_list(args.to_vec()).stable_hash()
+}
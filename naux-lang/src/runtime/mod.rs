@@ -7,6 +7,7 @@ pub mod eval;
 pub mod events;
 pub mod error;
 pub mod run;
+pub mod memo;
 
 pub use eval::eval_script;
 pub use events::RuntimeEvent;
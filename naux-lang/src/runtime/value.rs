@@ -4,6 +4,7 @@ use std::collections::{BTreeSet, HashMap};
 use std::rc::Rc;
 
 use crate::ast::Stmt;
+use crate::stdlib::regex::Regex;
 
 /// Any runtime value for NAUX VM/interpreter.
 #[derive(Debug, Clone)]
@@ -25,6 +26,9 @@ pub enum NauxObj {
     Set(RefCell<BTreeSet<Value>>),
     PriorityQueue(RefCell<Vec<Value>>),
     Function(Function),
+    Regex(Regex),
+    /// Dense row-major matrix; every row has the same length.
+    Matrix(RefCell<Vec<Vec<f64>>>),
 }
 
 #[derive(Debug, Clone)]
@@ -37,8 +41,13 @@ pub struct Graph {
 pub struct Function {
     pub params: Vec<String>,
     pub body: Vec<Stmt>,
+    /// Bindings of the enclosing function/block scopes at definition time, bound again on
+    /// every call before the arguments.
+    pub captured: HashMap<String, Value>,
 }
 
+/// Copies one level: the new container holds the same element `Value`s, which are `Rc`
+/// handles, so a clone costs O(top-level length) and never recurses into nested values.
 impl Clone for NauxObj {
     fn clone(&self) -> Self {
         match self {
@@ -49,6 +58,36 @@ impl Clone for NauxObj {
             NauxObj::Set(s) => NauxObj::Set(RefCell::new(s.borrow().clone())),
             NauxObj::PriorityQueue(pq) => NauxObj::PriorityQueue(RefCell::new(pq.borrow().clone())),
             NauxObj::Function(f) => NauxObj::Function(f.clone()),
+            NauxObj::Regex(re) => NauxObj::Regex(re.clone()),
+            NauxObj::Matrix(m) => NauxObj::Matrix(RefCell::new(m.borrow().clone())),
+        }
+    }
+}
+
+impl NauxObj {
+    /// Move out the element values of a container, leaving it empty.
+    fn take_children(&mut self) -> Vec<Value> {
+        match self {
+            NauxObj::List(v) | NauxObj::PriorityQueue(v) => std::mem::take(v.get_mut()),
+            NauxObj::Map(m) => m.get_mut().drain().map(|(_, v)| v).collect(),
+            NauxObj::Set(s) => std::mem::take(s.get_mut()).into_iter().collect(),
+            NauxObj::Function(f) => f.captured.drain().map(|(_, v)| v).collect(),
+            NauxObj::Text(_) | NauxObj::Graph(_) | NauxObj::Regex(_) | NauxObj::Matrix(_) => Vec::new(),
+        }
+    }
+}
+
+/// Nested containers are released from a worklist rather than by recursive drops, so
+/// freeing a deeply nested value cannot overflow the stack.
+impl Drop for NauxObj {
+    fn drop(&mut self) {
+        let mut pending = self.take_children();
+        while let Some(v) = pending.pop() {
+            if let Value::RcObj(rc) = v {
+                if let Ok(mut obj) = Rc::try_unwrap(rc) {
+                    pending.extend(obj.take_children());
+                }
+            }
         }
     }
 }
@@ -66,7 +105,7 @@ impl Value {
                 NauxObj::Graph(_) => true,
                 NauxObj::Set(s) => !s.borrow().is_empty(),
                 NauxObj::PriorityQueue(pq) => !pq.borrow().is_empty(),
-                NauxObj::Function(_) => true,
+                NauxObj::Function(_) | NauxObj::Regex(_) | NauxObj::Matrix(_) => true,
             },
             Value::Null => false,
         }
@@ -97,6 +136,129 @@ impl Value {
         }
     }
 
+    /// FNV-1a over a canonical encoding of the content, so equal values hash equal on any run or machine.
+    pub fn stable_hash(&self) -> i64 {
+        let mut buf = Vec::new();
+        self.write_canonical(&mut buf);
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in buf {
+            h ^= b as u64;
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        h as i64
+    }
+
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Null => out.push(b'N'),
+            Value::Bool(b) => out.extend_from_slice(if *b { b"T" } else { b"F" }),
+            Value::SmallInt(n) => {
+                out.push(b'i');
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            // integral floats equal the matching SmallInt, so they must encode the same way
+            Value::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Value::SmallInt(*f as i64).write_canonical(out),
+            Value::Float(f) => {
+                out.push(b'f');
+                out.extend_from_slice(&f.to_bits().to_le_bytes());
+            }
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::Text(s) => {
+                    out.push(b's');
+                    out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+                    out.extend_from_slice(s.as_bytes());
+                }
+                NauxObj::List(items) => {
+                    let items = items.borrow();
+                    out.push(b'l');
+                    out.extend_from_slice(&(items.len() as u64).to_le_bytes());
+                    for v in items.iter() {
+                        v.write_canonical(out);
+                    }
+                }
+                NauxObj::PriorityQueue(items) => {
+                    let items = sorted_pq(items);
+                    out.push(b'q');
+                    out.extend_from_slice(&(items.len() as u64).to_le_bytes());
+                    for v in items {
+                        v.write_canonical(out);
+                    }
+                }
+                NauxObj::Set(items) => {
+                    let items = items.borrow();
+                    out.push(b'e');
+                    out.extend_from_slice(&(items.len() as u64).to_le_bytes());
+                    for v in items.iter() {
+                        v.write_canonical(out);
+                    }
+                }
+                NauxObj::Map(map) => {
+                    // HashMap order varies between runs; sort keys first
+                    let map = map.borrow();
+                    let mut keys: Vec<&String> = map.keys().collect();
+                    keys.sort();
+                    out.push(b'm');
+                    out.extend_from_slice(&(keys.len() as u64).to_le_bytes());
+                    for k in keys {
+                        Value::make_text(k.clone()).write_canonical(out);
+                        map[k].write_canonical(out);
+                    }
+                }
+                // graphs and functions compare by identity; only their kind is hashed
+                NauxObj::Graph(_) => out.push(b'g'),
+                NauxObj::Function(_) => out.push(b'p'),
+                // regexes are equal when their patterns are
+                NauxObj::Regex(re) => {
+                    out.push(b'r');
+                    Value::make_text(re.as_str()).write_canonical(out);
+                }
+                NauxObj::Matrix(m) => {
+                    let m = m.borrow();
+                    out.push(b'x');
+                    out.extend_from_slice(&(m.len() as u64).to_le_bytes());
+                    out.extend_from_slice(&(m.first().map_or(0, |r| r.len()) as u64).to_le_bytes());
+                    for x in m.iter().flatten() {
+                        Value::Float(*x).write_canonical(out);
+                    }
+                }
+            },
+        }
+    }
+
+    /// Short type label for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::SmallInt(_) | Value::Float(_) => "number",
+            Value::Bool(_) => "bool",
+            Value::Null => "null",
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::Text(_) => "text",
+                NauxObj::List(_) => "list",
+                NauxObj::Map(_) => "map",
+                NauxObj::Graph(_) => "graph",
+                NauxObj::Set(_) => "set",
+                NauxObj::PriorityQueue(_) => "priority queue",
+                NauxObj::Function(_) => "function",
+                NauxObj::Regex(_) => "regex",
+                NauxObj::Matrix(_) => "matrix",
+            },
+        }
+    }
+
+    /// Order used by `< <= > >=`: numbers numerically, texts lexicographically by code point.
+    /// `Ok(None)` when a NaN is involved; `Err` for operands with no order between them.
+    pub fn compare(&self, other: &Value) -> Result<Option<Ordering>, String> {
+        if let (Some(a), Some(b)) = (self.as_f64(), other.as_f64()) {
+            return Ok(a.partial_cmp(&b));
+        }
+        if let (Value::RcObj(a), Value::RcObj(b)) = (self, other) {
+            if let (NauxObj::Text(x), NauxObj::Text(y)) = (a.as_ref(), b.as_ref()) {
+                return Ok(Some(x.cmp(y)));
+            }
+        }
+        Err(format!("cannot compare {} with {}", self.type_name(), other.type_name()))
+    }
+
     pub fn make_text(s: impl Into<String>) -> Value {
         Value::RcObj(Rc::new(NauxObj::Text(s.into())))
     }
@@ -125,6 +287,10 @@ impl Value {
         Value::RcObj(Rc::new(NauxObj::Function(f)))
     }
 
+    pub fn make_matrix(rows: Vec<Vec<f64>>) -> Value {
+        Value::RcObj(Rc::new(NauxObj::Matrix(RefCell::new(rows))))
+    }
+
     pub fn add(a: &Value, b: &Value) -> Value {
         match (a, b) {
             (Value::SmallInt(x), Value::SmallInt(y)) => Value::SmallInt(x + y),
@@ -140,44 +306,216 @@ impl Value {
     }
 }
 
+/// Render a value for events and messages. Map keys are sorted so the output is
+/// deterministic, and a container that (through in-place mutation) contains itself
+/// prints `<cycle>` at the back-reference instead of recursing forever.
+pub fn format_value(v: &Value) -> String {
+    format_value_in(v, &mut Vec::new())
+}
+
+/// Like `format_value`, but text is quoted and escaped so `"1"` and `1` read differently.
+pub fn repr(v: &Value) -> String {
+    match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::Text(s) => format!("{:?}", s),
+            _ => format_value(v),
+        },
+        _ => format_value(v),
+    }
+}
+
+fn format_value_in(v: &Value, ancestors: &mut Vec<*const NauxObj>) -> String {
+    match v {
+        Value::RcObj(rc) => {
+            let ptr = Rc::as_ptr(rc);
+            if ancestors.contains(&ptr) {
+                return "<cycle>".into();
+            }
+            match rc.as_ref() {
+                NauxObj::Text(s) => s.clone(),
+                NauxObj::List(list) => {
+                    ancestors.push(ptr);
+                    let items: Vec<String> = list.borrow().iter().map(|v| format_value_in(v, ancestors)).collect();
+                    ancestors.pop();
+                    format!("List [{}]", items.join(", "))
+                }
+                NauxObj::Map(map) => {
+                    ancestors.push(ptr);
+                    let map = map.borrow();
+                    let mut keys: Vec<&String> = map.keys().collect();
+                    keys.sort();
+                    let entries: Vec<String> =
+                        keys.into_iter().map(|k| format!("{}:{}", k, format_value_in(&map[k], ancestors))).collect();
+                    ancestors.pop();
+                    format!("Map {{{}}}", entries.join(", "))
+                }
+                NauxObj::Graph(g) => {
+                    let gb = g.borrow();
+                    let edges: usize = gb.adj.values().map(|v| v.len()).sum();
+                    format!("Graph(nodes={}, edges={})", gb.adj.len(), edges)
+                }
+                NauxObj::Set(s) => format!("Set len={}", s.borrow().len()),
+                NauxObj::PriorityQueue(pq) => format!("PriorityQueue len={}", pq.borrow().len()),
+                NauxObj::Function(_) => "<fn>".into(),
+                NauxObj::Regex(re) => format!("Regex /{}/", re.as_str()),
+                NauxObj::Matrix(m) => {
+                    let rows: Vec<String> = m
+                        .borrow()
+                        .iter()
+                        .map(|r| format!("[{}]", r.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")))
+                        .collect();
+                    format!("Matrix [{}]", rows.join(", "))
+                }
+            }
+        }
+        Value::SmallInt(n) => n.to_string(),
+        Value::Float(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "Null".into(),
+    }
+}
+
+/// Contents of a priority queue in ascending order. The heap layout depends on the order of
+/// pushes, so equality, hashing and ordering look at the sorted elements instead.
+fn sorted_pq(items: &RefCell<Vec<Value>>) -> Vec<Value> {
+    let mut items = items.borrow().clone();
+    items.sort();
+    items
+}
+
+/// Absolute tolerance `==` / `!=` allow between numbers. Zero: numbers compare exactly, so
+/// `0.1 + 0.2 == 0.3` is false (the old `f64::EPSILON` tolerance hid that case but not larger
+/// accumulated error). Use `approx_eq(a, b, eps)` when round-off is expected.
+pub const EQ_EPSILON: f64 = 0.0;
+
+/// Number equality shared by scalars and matrix cells.
+fn nums_eq(a: f64, b: f64) -> bool {
+    a == b || (a - b).abs() <= EQ_EPSILON
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Value::SmallInt(a), Value::SmallInt(b)) => a == b,
-            (Value::SmallInt(a), Value::Float(b)) | (Value::Float(b), Value::SmallInt(a)) => {
-                (*a as f64 - *b).abs() < f64::EPSILON
+        values_eq(self, other, &mut Vec::new())
+    }
+}
+
+/// Structural equality that terminates on cyclic containers: a pair of objects already
+/// under comparison is assumed equal, so two lists that each contain themselves compare
+/// equal when their remaining elements do.
+fn values_eq(a: &Value, b: &Value, seen: &mut Vec<(*const NauxObj, *const NauxObj)>) -> bool {
+    match (a, b) {
+        (Value::SmallInt(a), Value::SmallInt(b)) => a == b,
+        (Value::SmallInt(a), Value::Float(b)) | (Value::Float(b), Value::SmallInt(a)) => nums_eq(*a as f64, *b),
+        (Value::Float(a), Value::Float(b)) => nums_eq(*a, *b),
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        (Value::RcObj(a), Value::RcObj(b)) => {
+            if Rc::ptr_eq(a, b) {
+                return true;
             }
-            (Value::Float(a), Value::Float(b)) => (*a - *b).abs() < f64::EPSILON,
-            (Value::Bool(a), Value::Bool(b)) => a == b,
-            (Value::RcObj(a), Value::RcObj(b)) => {
-                if Rc::ptr_eq(a, b) {
-                    return true;
+            let pair = (Rc::as_ptr(a), Rc::as_ptr(b));
+            if seen.contains(&pair) {
+                return true;
+            }
+            seen.push(pair);
+            let seq_eq = |xs: &[Value], ys: &[Value], seen: &mut Vec<_>| {
+                xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| values_eq(x, y, seen))
+            };
+            match (a.as_ref(), b.as_ref()) {
+                (NauxObj::Text(sa), NauxObj::Text(sb)) => sa == sb,
+                (NauxObj::List(la), NauxObj::List(lb)) => seq_eq(&la.borrow(), &lb.borrow(), seen),
+                (NauxObj::Map(ma), NauxObj::Map(mb)) => {
+                    let (ma, mb) = (ma.borrow(), mb.borrow());
+                    ma.len() == mb.len()
+                        && ma.iter().all(|(k, va)| mb.get(k).is_some_and(|vb| values_eq(va, vb, seen)))
+                }
+                (NauxObj::Set(sa), NauxObj::Set(sb)) => {
+                    let (sa, sb) = (sa.borrow(), sb.borrow());
+                    sa.len() == sb.len() && sa.iter().zip(sb.iter()).all(|(x, y)| values_eq(x, y, seen))
                 }
-                match (a.as_ref(), b.as_ref()) {
-                    (NauxObj::Text(sa), NauxObj::Text(sb)) => sa == sb,
-                    (NauxObj::List(la), NauxObj::List(lb)) => la.borrow().clone().eq(&lb.borrow().clone()),
-                    (NauxObj::Map(ma), NauxObj::Map(mb)) => ma.borrow().clone().eq(&mb.borrow().clone()),
-                    (NauxObj::Set(sa), NauxObj::Set(sb)) => sa.borrow().clone().eq(&sb.borrow().clone()),
-                    (NauxObj::PriorityQueue(aq), NauxObj::PriorityQueue(bq)) => aq.borrow().clone().eq(&bq.borrow().clone()),
-                    (NauxObj::Graph(_), NauxObj::Graph(_)) => false, // graphs compared by identity
-                    (NauxObj::Function(_), NauxObj::Function(_)) => false,
-                    _ => false,
+                (NauxObj::PriorityQueue(aq), NauxObj::PriorityQueue(bq)) => seq_eq(&sorted_pq(aq), &sorted_pq(bq), seen),
+                (NauxObj::Graph(_), NauxObj::Graph(_)) => false, // graphs compared by identity
+                (NauxObj::Function(_), NauxObj::Function(_)) => false,
+                (NauxObj::Regex(ra), NauxObj::Regex(rb)) => ra.as_str() == rb.as_str(),
+                (NauxObj::Matrix(ma), NauxObj::Matrix(mb)) => {
+                    let (ma, mb) = (ma.borrow(), mb.borrow());
+                    ma.len() == mb.len()
+                        && ma.iter().zip(mb.iter()).all(|(ra, rb)| ra.len() == rb.len() && ra.iter().zip(rb).all(|(x, y)| nums_eq(*x, *y)))
                 }
+                _ => false,
             }
-            (Value::Null, Value::Null) => true,
-            _ => false,
         }
+        (Value::Null, Value::Null) => true,
+        _ => false,
     }
 }
 
 impl Eq for Value {}
 
+/// Total order over all values, used for sorting, sets and priority queues:
+/// null < bool < number < text < list < map < set < priority queue < graph < function < regex < matrix.
+/// Like types compare naturally (false < true, numeric, lexicographic); containers compare
+/// element-wise, maps by their key-sorted entries, matrices row by row. NaN sorts after every
+/// other number. Graphs and functions are all equal.
+pub fn total_cmp(a: &Value, b: &Value) -> Ordering {
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::SmallInt(_) | Value::Float(_) => 2,
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::Text(_) => 3,
+                NauxObj::List(_) => 4,
+                NauxObj::Map(_) => 5,
+                NauxObj::Set(_) => 6,
+                NauxObj::PriorityQueue(_) => 7,
+                NauxObj::Graph(_) => 8,
+                NauxObj::Function(_) => 9,
+                NauxObj::Regex(_) => 10,
+                NauxObj::Matrix(_) => 11,
+            },
+        }
+    }
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::RcObj(x), Value::RcObj(y)) => match (x.as_ref(), y.as_ref()) {
+            (NauxObj::Text(s), NauxObj::Text(t)) => s.cmp(t),
+            (NauxObj::List(l), NauxObj::List(m)) => l.borrow().iter().cmp(m.borrow().iter()),
+            (NauxObj::PriorityQueue(l), NauxObj::PriorityQueue(m)) => sorted_pq(l).cmp(&sorted_pq(m)),
+            (NauxObj::Set(l), NauxObj::Set(m)) => l.borrow().iter().cmp(m.borrow().iter()),
+            (NauxObj::Map(l), NauxObj::Map(m)) => {
+                let (l, m) = (l.borrow(), m.borrow());
+                let mut le: Vec<(&String, &Value)> = l.iter().collect();
+                let mut me: Vec<(&String, &Value)> = m.iter().collect();
+                le.sort_by(|x, y| x.0.cmp(y.0));
+                me.sort_by(|x, y| x.0.cmp(y.0));
+                le.cmp(&me)
+            }
+            (NauxObj::Regex(l), NauxObj::Regex(m)) => l.as_str().cmp(m.as_str()),
+            (NauxObj::Matrix(l), NauxObj::Matrix(m)) => {
+                let (l, m) = (l.borrow(), m.borrow());
+                let row_cmp = |x: &Vec<f64>, y: &Vec<f64>| {
+                    x.iter().zip(y).map(|(a, b)| num_cmp(*a, *b)).find(|o| o.is_ne()).unwrap_or(x.len().cmp(&y.len()))
+                };
+                l.iter().zip(m.iter()).map(|(x, y)| row_cmp(x, y)).find(|o| o.is_ne()).unwrap_or(l.len().cmp(&m.len()))
+            }
+            _ => rank(a).cmp(&rank(b)),
+        },
+        _ => match (a.as_f64(), b.as_f64()) {
+            (Some(x), Some(y)) => num_cmp(x, y),
+            _ => rank(a).cmp(&rank(b)),
+        },
+    }
+}
+
+/// Numeric order with every NaN equal to each other and above all other numbers, so sorting
+/// never sees an inconsistent comparison.
+fn num_cmp(x: f64, y: f64) -> Ordering {
+    x.partial_cmp(&y).unwrap_or_else(|| x.is_nan().cmp(&y.is_nan()))
+}
+
 impl Ord for Value {
     fn cmp(&self, other: &Self) -> Ordering {
-        match (self.as_f64(), other.as_f64()) {
-            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
-            _ => format!("{:?}", self).cmp(&format!("{:?}", other)),
-        }
+        total_cmp(self, other)
     }
 }
 
@@ -2,7 +2,8 @@
 
 use crate::runtime::env::Env;
 use crate::runtime::error::RuntimeError;
-use crate::runtime::value::{NauxObj, Value};
+use crate::runtime::value::{total_cmp, NauxObj, Value};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 pub fn register_algo(env: &mut Env) {
@@ -10,21 +11,47 @@ pub fn register_algo(env: &mut Env) {
     env.set_builtin("knapsack_01", knapsack_01);
     env.set_builtin("lower_bound", lower_bound);
     env.set_builtin("upper_bound", upper_bound);
+    env.set_builtin("binary_search", binary_search);
+    env.set_builtin("binary_search_values", binary_search_values);
     env.set_builtin("kmp_search", kmp_search);
+    env.set_builtin("rabin_karp", rabin_karp);
+    env.set_builtin("aho_corasick", aho_corasick);
+    env.set_builtin("aho_corasick_new", aho_corasick_new);
+    env.set_builtin("aho_corasick_search", aho_corasick_search);
     env.set_builtin("z_function", z_function);
+    env.set_builtin("string_period", string_period);
+    env.set_builtin("is_rotation", is_rotation);
     env.set_builtin("suffix_array", suffix_array);
+    env.set_builtin("suffix_automaton", suffix_automaton);
+    env.set_builtin("sam_contains", sam_contains);
+    env.set_builtin("sam_distinct_substrings", sam_distinct_substrings);
+    env.set_builtin("edit_distance", edit_distance);
+    env.set_builtin("edit_distance_list", edit_distance_list);
+    env.set_builtin("longest_palindrome", longest_palindrome);
+    env.set_builtin("palindrome_lengths", palindrome_lengths);
+    env.set_builtin("palindrome_radius_array", palindrome_radius_array);
+    env.set_builtin("palindrome_radius_even", palindrome_radius_even);
+    env.set_builtin("all_palindromic_substrings_count", all_palindromic_substrings_count);
+    env.set_builtin("is_palindrome", is_palindrome);
     env.set_builtin("fft_convolve", fft_convolve);
     env.set_builtin("ntt_convolve", ntt_convolve);
+    env.set_builtin("ntt_convolve_big", ntt_convolve_big);
     env.set_builtin("pollard_rho", pollard_rho);
     env.set_builtin("lichao_new", lichao_new);
     env.set_builtin("lichao_add", lichao_add);
+    env.set_builtin("lichao_add_many", lichao_add_many);
     env.set_builtin("lichao_query", lichao_query);
-    env.set_builtin("dsu_new", dsu_new);
-    env.set_builtin("dsu_union", dsu_union);
-    env.set_builtin("dsu_find", dsu_find);
-    env.set_builtin("segtree_new", segtree_new);
-    env.set_builtin("segtree_query", segtree_query);
-    env.set_builtin("segtree_update", segtree_update);
+    env.set_builtin("bit_new", bit_new);
+    env.set_builtin("bit_update", bit_update);
+    env.set_builtin("bit_query", bit_query);
+    env.set_builtin("bit_range_query", bit_range_query);
+    env.set_builtin("bit2d_new", bit2d_new);
+    env.set_builtin("bit2d_update", bit2d_update);
+    env.set_builtin("bit2d_query", bit2d_query);
+    env.set_builtin("sparse_table_new", sparse_table_new);
+    env.set_builtin("sparse_table_query", sparse_table_query);
+    env.set_builtin("sparse_table_update", sparse_table_update);
+    env.set_builtin("sparse_table_from_segtree", sparse_table_from_segtree);
 }
 
 fn to_num_list(v: &Value) -> Result<Vec<f64>, RuntimeError> {
@@ -139,6 +166,44 @@ fn upper_bound(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::SmallInt(pos as i64))
 }
 
+/// Index of the first element equal to the target, or `-(insertion point) - 1` when absent,
+/// where the insertion point is the first index whose element is greater than the target.
+/// Decode a negative result `r` with `-r - 1`; the list must be sorted.
+fn encoded_search<T>(items: &[T], cmp_to_target: impl Fn(&T) -> Ordering) -> i64 {
+    let pos = items.partition_point(|item| cmp_to_target(item) == Ordering::Less);
+    match items.get(pos) {
+        Some(item) if cmp_to_target(item) == Ordering::Equal => pos as i64,
+        _ => -(pos as i64) - 1,
+    }
+}
+
+fn binary_search(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("binary_search(list, target)", None));
+    }
+    let arr = to_num_list(&args[0])?;
+    let x = args[1]
+        .as_f64()
+        .filter(|x| !x.is_nan())
+        .ok_or_else(|| RuntimeError::new("binary_search: target must be a number other than NaN", None))?;
+    Ok(Value::SmallInt(encoded_search(&arr, |v| v.partial_cmp(&x).unwrap_or(Ordering::Equal))))
+}
+
+/// `binary_search` over any list sorted by the value order of `list_sort`.
+fn binary_search_values(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("binary_search_values(list, target)", None));
+    }
+    let items = match &args[0] {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().clone(),
+            _ => return Err(RuntimeError::new("binary_search_values: first arg must be list", None)),
+        },
+        _ => return Err(RuntimeError::new("binary_search_values: first arg must be list", None)),
+    };
+    Ok(Value::SmallInt(encoded_search(&items, |v| total_cmp(v, &args[1]))))
+}
+
 // --- String Algorithms ---
 
 fn expect_text(v: &Value, msg: &str) -> Result<String, RuntimeError> {
@@ -187,20 +252,241 @@ fn kmp_search(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_list(res))
 }
 
+/// Same output as `kmp_search` (byte offsets of every match), via a rolling polynomial hash.
+/// Hash hits are verified byte-for-byte, so collisions never produce false matches.
+fn rabin_karp(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("rabin_karp(text, pattern)", None));
+    }
+    let text = expect_text(&args[0], "text must be string")?;
+    let pat = expect_text(&args[1], "pattern must be string")?;
+    let (t, p) = (text.as_bytes(), pat.as_bytes());
+    if p.is_empty() || p.len() > t.len() {
+        return Ok(Value::make_list(vec![]));
+    }
+    const BASE: u64 = 257;
+    const MOD: u64 = 1_000_000_007;
+    let hash = |bytes: &[u8]| bytes.iter().fold(0u64, |h, &b| (h * BASE + b as u64) % MOD);
+    // BASE^(m-1), the weight of the byte leaving the window
+    let high = (1..p.len()).fold(1u64, |acc, _| acc * BASE % MOD);
+    let target = hash(p);
+    let mut h = hash(&t[..p.len()]);
+    let mut res = Vec::new();
+    for i in 0..=t.len() - p.len() {
+        if h == target && &t[i..i + p.len()] == p {
+            res.push(Value::SmallInt(i as i64));
+        }
+        if i + p.len() < t.len() {
+            h = (h + MOD - t[i] as u64 * high % MOD) % MOD;
+            h = (h * BASE + t[i + p.len()] as u64) % MOD;
+        }
+    }
+    Ok(Value::make_list(res))
+}
+
+/// Aho–Corasick automaton over symbols `T` (bytes or chars): trie edges, failure links and,
+/// per state, the indices of every pattern ending there (including via failure links),
+/// longest first.
+struct AhoCorasick<T> {
+    next: Vec<HashMap<T, usize>>,
+    fail: Vec<usize>,
+    out: Vec<Vec<usize>>,
+}
+
+impl<T: Copy + Eq + std::hash::Hash> AhoCorasick<T> {
+    fn build(patterns: &[Vec<T>]) -> Self {
+        let mut next: Vec<HashMap<T, usize>> = vec![HashMap::new()];
+        let mut out: Vec<Vec<usize>> = vec![Vec::new()];
+        for (idx, pat) in patterns.iter().enumerate() {
+            if pat.is_empty() {
+                continue;
+            }
+            let mut state = 0;
+            for &b in pat {
+                state = match next[state].get(&b) {
+                    Some(&s) => s,
+                    None => {
+                        next.push(HashMap::new());
+                        out.push(Vec::new());
+                        let s = next.len() - 1;
+                        next[state].insert(b, s);
+                        s
+                    }
+                };
+            }
+            out[state].push(idx);
+        }
+        // BFS so every failure target is finished before its dependents
+        let mut fail = vec![0usize; next.len()];
+        let mut queue: std::collections::VecDeque<usize> = next[0].values().copied().collect();
+        while let Some(u) = queue.pop_front() {
+            let edges: Vec<(T, usize)> = next[u].iter().map(|(&b, &v)| (b, v)).collect();
+            for (b, v) in edges {
+                let mut f = fail[u];
+                while f != 0 && !next[f].contains_key(&b) {
+                    f = fail[f];
+                }
+                fail[v] = match next[f].get(&b) {
+                    Some(&t) if t != v => t,
+                    _ => 0,
+                };
+                let inherited = out[fail[v]].clone();
+                out[v].extend(inherited);
+                queue.push_back(v);
+            }
+        }
+        AhoCorasick { next, fail, out }
+    }
+
+    /// All `(pattern_index, start)` matches, overlaps included, as symbol offsets.
+    fn find_all(&self, text: &[T], patterns: &[Vec<T>]) -> Vec<(usize, usize)> {
+        let mut state = 0;
+        let mut hits = Vec::new();
+        for (i, &b) in text.iter().enumerate() {
+            while state != 0 && !self.next[state].contains_key(&b) {
+                state = self.fail[state];
+            }
+            state = self.next[state].get(&b).copied().unwrap_or(0);
+            for &idx in &self.out[state] {
+                hits.push((idx, i + 1 - patterns[idx].len()));
+            }
+        }
+        hits.sort_by_key(|&(idx, start)| (start, idx));
+        hits
+    }
+}
+
+fn expect_text_list(v: &Value, msg: &str) -> Result<Vec<String>, RuntimeError> {
+    match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().iter().map(|p| expect_text(p, msg)).collect(),
+            _ => Err(RuntimeError::new(msg, None)),
+        },
+        _ => Err(RuntimeError::new(msg, None)),
+    }
+}
+
+fn aho_corasick(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("aho_corasick(text, patterns)", None));
+    }
+    let text = expect_text(&args[0], "text must be string")?;
+    let patterns = expect_text_list(&args[1], "patterns must be a list of strings")?;
+    let patterns: Vec<Vec<u8>> = patterns.into_iter().map(String::into_bytes).collect();
+    let ac = AhoCorasick::build(&patterns);
+    let matches = ac
+        .find_all(text.as_bytes(), &patterns)
+        .into_iter()
+        .map(|(idx, start)| {
+            let mut m = HashMap::new();
+            m.insert("pattern_index".to_string(), Value::SmallInt(idx as i64));
+            m.insert("start".to_string(), Value::SmallInt(start as i64));
+            Value::make_map(m)
+        })
+        .collect();
+    Ok(Value::make_list(matches))
+}
+
+/// Char-level automaton as `{goto, fail, output, patterns}`: `goto[s]` maps a one-char text to
+/// the next state, `fail[s]` is the failure link and `output[s]` lists the indices of the
+/// patterns ending at `s`, longest first. Built in O(total pattern length).
+fn aho_corasick_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("aho_corasick_new(patterns)", None));
+    }
+    let patterns = expect_text_list(&args[0], "aho_corasick_new: patterns must be a list of strings")?;
+    let chars: Vec<Vec<char>> = patterns.iter().map(|p| p.chars().collect()).collect();
+    let ac = AhoCorasick::build(&chars);
+    let ints = |xs: &[usize]| Value::make_list(xs.iter().map(|&x| Value::SmallInt(x as i64)).collect());
+    let goto = ac
+        .next
+        .iter()
+        .map(|edges| Value::make_map(edges.iter().map(|(c, &t)| (c.to_string(), Value::SmallInt(t as i64))).collect()))
+        .collect();
+    let mut map = HashMap::new();
+    map.insert("goto".to_string(), Value::make_list(goto));
+    map.insert("fail".to_string(), ints(&ac.fail));
+    map.insert("output".to_string(), Value::make_list(ac.out.iter().map(|o| ints(o)).collect()));
+    map.insert("patterns".to_string(), Value::make_list(patterns.into_iter().map(Value::make_text).collect()));
+    Ok(Value::make_map(map))
+}
+
+/// Non-overlapping matches as `{pattern, start, end}` (char offsets, `end` exclusive), scanning
+/// left to right: the first match to end wins (the longest pattern ending there), and the
+/// search restarts after it. O(T + matches), reading the stored automaton in place.
+fn aho_corasick_search(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("aho_corasick_search(ac, text)", None));
+    }
+    let bad = || RuntimeError::new("aho_corasick_search: first arg must be an aho_corasick_new automaton", None);
+    let field = |key: &str| map_field(&args[0], key).ok_or_else(bad);
+    let (goto, fail, output, patterns) = (field("goto")?, field("fail")?, field("output")?, field("patterns")?);
+    let text = expect_text(&args[1], "aho_corasick_search: text must be string")?;
+    let states = match &fail {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().len(),
+            _ => return Err(bad()),
+        },
+        _ => return Err(bad()),
+    };
+    let state_at = |v: Option<Value>| match v {
+        Some(Value::SmallInt(t)) if t >= 0 && (t as usize) < states => Ok(t as usize),
+        _ => Err(bad()),
+    };
+    let mut matches = Vec::new();
+    let mut state = 0;
+    for (i, ch) in text.chars().enumerate() {
+        let key = ch.to_string();
+        // each failure link is shallower, so at most `states` hops
+        let mut hops = 0;
+        loop {
+            let edge = list_cell(&goto, state).and_then(|edges| map_field(&edges, &key));
+            if edge.is_some() {
+                state = state_at(edge)?;
+                break;
+            }
+            if state == 0 {
+                break;
+            }
+            hops += 1;
+            if hops > states {
+                return Err(bad());
+            }
+            state = state_at(list_cell(&fail, state))?;
+        }
+        if let Some(idx) = list_cell(&output, state).and_then(|out| list_cell(&out, 0)) {
+            let pattern = list_cell(&patterns, idx.as_i64().ok_or_else(bad)? as usize).ok_or_else(bad)?;
+            let len = pattern.as_text().ok_or_else(bad)?.chars().count();
+            let mut m = HashMap::new();
+            m.insert("pattern".to_string(), pattern);
+            m.insert("start".to_string(), Value::SmallInt((i + 1 - len) as i64));
+            m.insert("end".to_string(), Value::SmallInt(i as i64 + 1));
+            matches.push(Value::make_map(m));
+            state = 0;
+        }
+    }
+    Ok(Value::make_list(matches))
+}
+
 fn z_function(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::new("z_function(s)", None));
     }
     let s = expect_text(&args[0], "s must be string")?;
+    let z = z_array(s.as_bytes());
+    Ok(Value::make_list(z.into_iter().map(|v| Value::SmallInt(v as i64)).collect()))
+}
+
+/// `z[i]` is the length of the longest common prefix of `s` and `s[i..]` (`z[0]` is 0).
+fn z_array<T: PartialEq>(s: &[T]) -> Vec<usize> {
     let n = s.len();
     let mut z = vec![0usize; n];
-    let bytes = s.as_bytes();
     let (mut l, mut r) = (0usize, 0usize);
     for i in 1..n {
         if i <= r {
             z[i] = (r - i + 1).min(z[i - l]);
         }
-        while i + z[i] < n && bytes[z[i]] == bytes[i + z[i]] {
+        while i + z[i] < n && s[z[i]] == s[i + z[i]] {
             z[i] += 1;
         }
         if i + z[i] - 1 > r {
@@ -208,7 +494,39 @@ fn z_function(args: Vec<Value>) -> Result<Value, RuntimeError> {
             r = i + z[i] - 1;
         }
     }
-    Ok(Value::make_list(z.into_iter().map(|v| Value::SmallInt(v as i64)).collect()))
+    z
+}
+
+/// Smallest `p` such that `s` is a prefix of `s[..p]` repeated (`"abcab"` → 3), counted in chars.
+fn string_period(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("string_period(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    let z = z_array(&chars);
+    let n = chars.len();
+    let period = (1..n).find(|&p| p + z[p] == n).unwrap_or(n);
+    Ok(Value::SmallInt(period as i64))
+}
+
+/// Whether `b` is `a` rotated: equal lengths and `b` occurs in `a + a`.
+fn is_rotation(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("is_rotation(a, b)", None));
+    }
+    let a: Vec<char> = expect_text(&args[0], "a must be string")?.chars().collect();
+    let b: Vec<char> = expect_text(&args[1], "b must be string")?.chars().collect();
+    if a.len() != b.len() {
+        return Ok(Value::Bool(false));
+    }
+    if b.is_empty() {
+        return Ok(Value::Bool(true));
+    }
+    // `None` separates the pattern from the doubled text so no match runs across it
+    let joined: Vec<Option<char>> =
+        b.iter().copied().map(Some).chain(std::iter::once(None)).chain(a.iter().chain(&a).copied().map(Some)).collect();
+    let z = z_array(&joined);
+    Ok(Value::Bool(z[b.len() + 1..].iter().any(|&len| len >= b.len())))
 }
 
 fn suffix_array(args: Vec<Value>) -> Result<Value, RuntimeError> {
@@ -274,6 +592,298 @@ fn suffix_array(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_map(res_map))
 }
 
+// --- Suffix automaton ---
+
+struct SamState {
+    len: usize,
+    link: Option<usize>,
+    next: HashMap<char, usize>,
+}
+
+/// Online construction over chars; state 0 is the initial state.
+fn build_sam(s: &str) -> Vec<SamState> {
+    let mut st = vec![SamState { len: 0, link: None, next: HashMap::new() }];
+    let mut last = 0;
+    for c in s.chars() {
+        let cur = st.len();
+        st.push(SamState { len: st[last].len + 1, link: None, next: HashMap::new() });
+        let mut p = Some(last);
+        while let Some(q) = p {
+            if st[q].next.contains_key(&c) {
+                break;
+            }
+            st[q].next.insert(c, cur);
+            p = st[q].link;
+        }
+        match p {
+            None => st[cur].link = Some(0),
+            Some(p) => {
+                let q = st[p].next[&c];
+                if st[p].len + 1 == st[q].len {
+                    st[cur].link = Some(q);
+                } else {
+                    let clone = st.len();
+                    let state = SamState { len: st[p].len + 1, link: st[q].link, next: st[q].next.clone() };
+                    st.push(state);
+                    let mut p = Some(p);
+                    while let Some(x) = p {
+                        if st[x].next.get(&c) != Some(&q) {
+                            break;
+                        }
+                        st[x].next.insert(c, clone);
+                        p = st[x].link;
+                    }
+                    st[q].link = Some(clone);
+                    st[cur].link = Some(clone);
+                }
+            }
+        }
+        last = cur;
+    }
+    st
+}
+
+/// `{states: [{len, link, next: {char: state}}]}`; the initial state has `link` -1.
+fn suffix_automaton(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("suffix_automaton(s)", None));
+    }
+    let s = expect_text(&args[0], "suffix_automaton: s must be text")?;
+    let states = build_sam(&s)
+        .into_iter()
+        .map(|st| {
+            let next = st.next.into_iter().map(|(c, to)| (c.to_string(), Value::SmallInt(to as i64))).collect();
+            let mut m = HashMap::new();
+            m.insert("len".into(), Value::SmallInt(st.len as i64));
+            m.insert("link".into(), Value::SmallInt(st.link.map_or(-1, |l| l as i64)));
+            m.insert("next".into(), Value::make_map(next));
+            Value::make_map(m)
+        })
+        .collect();
+    let mut sam = HashMap::new();
+    sam.insert("states".into(), Value::make_list(states));
+    Ok(Value::make_map(sam))
+}
+
+fn map_field(v: &Value, key: &str) -> Option<Value> {
+    match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::Map(m) => m.borrow().get(key).cloned(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn sam_states(sam: &Value, func: &str) -> Result<Vec<Value>, RuntimeError> {
+    let bad = || RuntimeError::new(format!("{}: expected a suffix_automaton", func), None);
+    match map_field(sam, "states").ok_or_else(bad)? {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => Ok(items.borrow().clone()),
+            _ => Err(bad()),
+        },
+        _ => Err(bad()),
+    }
+}
+
+fn sam_contains(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("sam_contains(sam, pattern)", None));
+    }
+    let states = sam_states(&args[0], "sam_contains")?;
+    let pattern = expect_text(&args[1], "sam_contains: pattern must be text")?;
+    let mut cur = 0usize;
+    for c in pattern.chars() {
+        let next = states.get(cur).and_then(|st| map_field(st, "next"));
+        match next.and_then(|n| map_field(&n, &c.to_string())).and_then(|v| v.as_i64()) {
+            Some(to) => cur = to as usize,
+            None => return Ok(Value::Bool(false)),
+        }
+    }
+    Ok(Value::Bool(true))
+}
+
+/// Number of distinct non-empty substrings: the sum of `len - len(link)` over non-initial states.
+fn sam_distinct_substrings(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("sam_distinct_substrings(sam)", None));
+    }
+    let states = sam_states(&args[0], "sam_distinct_substrings")?;
+    let len_of = |i: i64| -> Result<i64, RuntimeError> {
+        states
+            .get(i as usize)
+            .and_then(|st| map_field(st, "len"))
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| RuntimeError::new("sam_distinct_substrings: malformed state", None))
+    };
+    let mut total = 0i64;
+    for (i, st) in states.iter().enumerate().skip(1) {
+        let link = map_field(st, "link").and_then(|v| v.as_i64()).unwrap_or(0);
+        total += len_of(i as i64)? - len_of(link)?;
+    }
+    Ok(Value::SmallInt(total))
+}
+
+// --- Edit distance (Levenshtein) ---
+
+fn levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // single rolling row: prev[j] = distance(a[..i], b[..j])
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0usize; b.len() + 1];
+    for i in 1..=a.len() {
+        cur[0] = i;
+        for j in 1..=b.len() {
+            let sub = prev[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = sub.min(prev[j] + 1).min(cur[j - 1] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+fn edit_distance(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("edit_distance(a, b)", None));
+    }
+    let a: Vec<char> = expect_text(&args[0], "a must be string")?.chars().collect();
+    let b: Vec<char> = expect_text(&args[1], "b must be string")?.chars().collect();
+    Ok(Value::SmallInt(levenshtein(&a, &b) as i64))
+}
+
+fn edit_distance_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("edit_distance_list(a, b)", None));
+    }
+    let as_list = |v: &Value| match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => Ok(items.borrow().clone()),
+            _ => Err(RuntimeError::new("expected list", None)),
+        },
+        _ => Err(RuntimeError::new("expected list", None)),
+    };
+    let a = as_list(&args[0])?;
+    let b = as_list(&args[1])?;
+    Ok(Value::SmallInt(levenshtein(&a, &b) as i64))
+}
+
+// --- Manacher ---
+
+/// Radii over the separator-interleaved string `#c0#c1#...#`; `p[i]` equals the length
+/// of the longest palindrome in the original string centred at transformed position `i`.
+fn manacher(chars: &[char]) -> Vec<usize> {
+    let m = 2 * chars.len() + 1;
+    // odd positions hold chars, even positions are separators
+    let at = |i: usize| if i % 2 == 1 { Some(chars[i / 2]) } else { None };
+    let mut p = vec![0usize; m];
+    let (mut center, mut right) = (0usize, 0usize);
+    for i in 0..m {
+        if i < right {
+            p[i] = p[2 * center - i].min(right - i);
+        }
+        while i > p[i] && i + p[i] + 1 < m && at(i - p[i] - 1) == at(i + p[i] + 1) {
+            p[i] += 1;
+        }
+        if i + p[i] > right {
+            center = i;
+            right = i + p[i];
+        }
+    }
+    p
+}
+
+fn longest_palindrome(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("longest_palindrome(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    let p = manacher(&chars);
+    // first maximum wins, so ties go to the leftmost palindrome
+    let (mut best_len, mut best_center) = (0usize, 0usize);
+    for (i, &len) in p.iter().enumerate() {
+        if len > best_len {
+            best_len = len;
+            best_center = i;
+        }
+    }
+    let start = (best_center - best_len) / 2;
+    Ok(Value::make_text(chars[start..start + best_len].iter().collect::<String>()))
+}
+
+fn palindrome_lengths(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("palindrome_lengths(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    Ok(Value::make_list(manacher(&chars).into_iter().map(|v| Value::SmallInt(v as i64)).collect()))
+}
+
+/// Radius `r` per char: `s[i-r..=i+r]` is the longest odd palindrome centred on char `i`.
+fn palindrome_radius_array(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("palindrome_radius_array(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    let p = manacher(&chars);
+    let radii = (0..chars.len()).map(|i| Value::SmallInt(((p[2 * i + 1] - 1) / 2) as i64)).collect();
+    Ok(Value::make_list(radii))
+}
+
+/// Radius `r` per char: `s[i-r..i+r]` is the longest even palindrome centred just before
+/// char `i` (so entry 0 is always 0).
+fn palindrome_radius_even(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("palindrome_radius_even(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    let p = manacher(&chars);
+    let radii = (0..chars.len()).map(|i| Value::SmallInt((p[2 * i] / 2) as i64)).collect();
+    Ok(Value::make_list(radii))
+}
+
+/// Number of distinct palindromic substrings, via a palindromic tree (eertree): each char adds
+/// at most one new palindrome, so the node count minus the two roots is the answer. O(n).
+fn all_palindromic_substrings_count(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("all_palindromic_substrings_count(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    // node 0: imaginary root of length -1, node 1: empty string; both suffix-link to node 0
+    let mut len: Vec<i64> = vec![-1, 0];
+    let mut link: Vec<usize> = vec![0, 0];
+    let mut next: Vec<HashMap<char, usize>> = vec![HashMap::new(), HashMap::new()];
+    let mut last = 1;
+    // the longest palindromic suffix `v` of s[..i] that `c` can extend on both sides
+    let extendable = |len: &[i64], link: &[usize], mut v: usize, i: usize| {
+        while len[v] != -1 && (i as i64 - len[v] - 1 < 0 || chars[i - len[v] as usize - 1] != chars[i]) {
+            v = link[v];
+        }
+        v
+    };
+    for (i, &c) in chars.iter().enumerate() {
+        let v = extendable(&len, &link, last, i);
+        if let Some(&node) = next[v].get(&c) {
+            last = node;
+            continue;
+        }
+        let node = len.len();
+        len.push(len[v] + 2);
+        link.push(if len[node] == 1 { 1 } else { next[extendable(&len, &link, link[v], i)][&c] });
+        next.push(HashMap::new());
+        next[v].insert(c, node);
+        last = node;
+    }
+    Ok(Value::SmallInt(len.len() as i64 - 2))
+}
+
+fn is_palindrome(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("is_palindrome(s)", None));
+    }
+    let chars: Vec<char> = expect_text(&args[0], "s must be string")?.chars().collect();
+    let n = chars.len();
+    Ok(Value::Bool((0..n / 2).all(|i| chars[i] == chars[n - 1 - i])))
+}
+
 // --- FFT / NTT convolution ---
 
 #[derive(Clone, Copy, Debug)]
@@ -364,9 +974,15 @@ fn fft_convolve(args: Vec<Value>) -> Result<Value, RuntimeError> {
         fa[i] = fa[i].mul(fb[i]);
     }
     fft(&mut fa, true);
+    // integer inputs have an exact integer convolution; round away the FFT noise like NTT would
+    let integral = a.iter().chain(&b).all(|x| x.fract() == 0.0);
     let mut res = Vec::new();
     for i in 0..(a.len() + b.len() - 1) {
-        res.push(Value::Float(fa[i].re));
+        if integral {
+            res.push(Value::SmallInt(fa[i].re.round() as i64));
+        } else {
+            res.push(Value::Float(fa[i].re));
+        }
     }
     Ok(Value::make_list(res))
 }
@@ -374,6 +990,10 @@ fn fft_convolve(args: Vec<Value>) -> Result<Value, RuntimeError> {
 // NTT helpers
 const MOD: i64 = 998_244_353;
 const PRIM_ROOT: i64 = 3;
+/// Primes of the form c·2^k + 1 with primitive root 3, combined by CRT in `ntt_convolve_big`.
+const BIG_MODS: [i64; 3] = [998_244_353, 167_772_161, 469_762_049];
+/// Largest transform length every modulus above supports (998244353 = 119·2^23 + 1).
+const NTT_MAX_LEN: usize = 1 << 23;
 
 fn mod_pow(mut a: i64, mut e: i64, m: i64) -> i64 {
     let mut res = 1i64;
@@ -387,7 +1007,7 @@ fn mod_pow(mut a: i64, mut e: i64, m: i64) -> i64 {
     res
 }
 
-fn ntt(a: &mut Vec<i64>, invert: bool) {
+fn ntt(a: &mut [i64], invert: bool, m: i64) {
     let n = a.len();
     let mut j = 0usize;
     for i in 1..n {
@@ -404,195 +1024,444 @@ fn ntt(a: &mut Vec<i64>, invert: bool) {
     let mut len = 2;
     while len <= n {
         let wlen = if invert {
-            mod_pow(PRIM_ROOT, MOD - 1 - (MOD - 1) / len as i64, MOD)
+            mod_pow(PRIM_ROOT, m - 1 - (m - 1) / len as i64, m)
         } else {
-            mod_pow(PRIM_ROOT, (MOD - 1) / len as i64, MOD)
+            mod_pow(PRIM_ROOT, (m - 1) / len as i64, m)
         };
         let mut i = 0;
         while i < n {
             let mut w = 1i64;
             for j in 0..len / 2 {
                 let u = a[i + j];
-                let v = a[i + j + len / 2] * w % MOD;
-                a[i + j] = (u + v) % MOD;
-                a[i + j + len / 2] = (u - v + MOD) % MOD;
-                w = w * wlen % MOD;
+                let v = a[i + j + len / 2] * w % m;
+                a[i + j] = (u + v) % m;
+                a[i + j + len / 2] = (u - v + m) % m;
+                w = w * wlen % m;
             }
             i += len;
         }
         len <<= 1;
     }
     if invert {
-        let inv_n = mod_pow(n as i64, MOD - 2, MOD);
+        let inv_n = mod_pow(n as i64, m - 2, m);
         for x in a.iter_mut() {
-            *x = *x * inv_n % MOD;
+            *x = *x * inv_n % m;
         }
     }
 }
 
-fn ntt_convolve(args: Vec<Value>) -> Result<Value, RuntimeError> {
-    if args.len() != 2 {
-        return Err(RuntimeError::new("ntt_convolve(a, b)", None));
-    }
-    let a = to_num_list(&args[0])?;
-    let b = to_num_list(&args[1])?;
+/// Cyclic-free convolution of `a` and `b` modulo `m`, residues in `[0, m)`.
+fn ntt_residues(a: &[i64], b: &[i64], m: i64) -> Vec<i64> {
     let mut n = 1usize;
     while n < a.len() + b.len() {
         n <<= 1;
     }
     let mut fa: Vec<i64> = vec![0; n];
     let mut fb: Vec<i64> = vec![0; n];
-    for i in 0..a.len() {
-        fa[i] = (a[i] as i64 % MOD + MOD) % MOD;
+    for (i, x) in a.iter().enumerate() {
+        fa[i] = x.rem_euclid(m);
     }
-    for i in 0..b.len() {
-        fb[i] = (b[i] as i64 % MOD + MOD) % MOD;
+    for (i, x) in b.iter().enumerate() {
+        fb[i] = x.rem_euclid(m);
     }
-    ntt(&mut fa, false);
-    ntt(&mut fb, false);
+    ntt(&mut fa, false, m);
+    ntt(&mut fb, false, m);
     for i in 0..n {
-        fa[i] = fa[i] * fb[i] % MOD;
+        fa[i] = fa[i] * fb[i] % m;
     }
-    ntt(&mut fa, true);
-    let mut res = Vec::new();
-    for i in 0..(a.len() + b.len() - 1) {
-        res.push(Value::SmallInt(fa[i] as i64));
-    }
-    Ok(Value::make_list(res))
+    ntt(&mut fa, true, m);
+    fa.truncate(a.len() + b.len() - 1);
+    fa
 }
 
-// --- DSU ---
+/// Both integer input lists and the coefficient bound computed by `ntt_inputs`.
+type NttInputs = (Vec<i64>, Vec<i64>, Option<i128>);
 
-fn dsu_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
-    if args.len() != 1 {
-        return Err(RuntimeError::new("dsu_new(n)", None));
+/// Integer inputs of a convolution, plus a bound on every output coefficient's magnitude
+/// (`min(len) · max|a| · max|b|`); `None` when the bound itself exceeds `i128`.
+fn ntt_inputs(name: &str, args: &[Value]) -> Result<NttInputs, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new(format!("{}(a, b)", name), None));
+    }
+    let to_ints = |v: &Value| -> Result<Vec<i64>, RuntimeError> {
+        to_num_list(v)?
+            .into_iter()
+            .map(|x| {
+                if x.fract() == 0.0 && x.abs() < i64::MAX as f64 {
+                    Ok(x as i64)
+                } else {
+                    Err(RuntimeError::new(format!("{}: inputs must be integers", name), None))
+                }
+            })
+            .collect()
+    };
+    let a = to_ints(&args[0])?;
+    let b = to_ints(&args[1])?;
+    if a.is_empty() || b.is_empty() {
+        return Err(RuntimeError::new(format!("{}: inputs must be non-empty", name), None));
     }
-    let n = args[0].as_i64().ok_or_else(|| RuntimeError::new("n must be number", None))? as usize;
-    let mut parent = Vec::new();
-    let mut rank = Vec::new();
-    for i in 0..n {
-        parent.push(Value::SmallInt(i as i64));
-        rank.push(Value::SmallInt(0));
+    if a.len() + b.len() > NTT_MAX_LEN {
+        return Err(RuntimeError::new(format!("{}: result longer than {} coefficients", name, NTT_MAX_LEN), None));
     }
-    let mut map = std::collections::HashMap::new();
-    map.insert("p".into(), Value::make_list(parent));
-    map.insert("r".into(), Value::make_list(rank));
-    Ok(Value::make_map(map))
+    let max_abs = |xs: &[i64]| xs.iter().map(|x| x.unsigned_abs() as i128).max().unwrap_or(0);
+    let bound = (a.len().min(b.len()) as i128).checked_mul(max_abs(&a)).and_then(|p| p.checked_mul(max_abs(&b)));
+    Ok((a, b, bound))
 }
 
-fn dsu_find(args: Vec<Value>) -> Result<Value, RuntimeError> {
-    if args.len() != 2 {
-        return Err(RuntimeError::new("dsu_find(dsu, x)", None));
+/// Map a residue back to the signed value it represents when results may be negative.
+fn signed_residue(r: i128, m: i128, signed: bool) -> i128 {
+    if signed && r > m / 2 {
+        r - m
+    } else {
+        r
     }
-    let mut dsu = args[0].clone();
-    let x = to_i64_local(&args[1])? as usize;
-    let (mut parent, rank) = extract_dsu(&dsu)?;
-    let root = find_internal(x, &mut parent);
-    let mut map = std::collections::HashMap::new();
-    map.insert("p".into(), Value::make_list(parent.into_iter().map(Value::SmallInt).collect()));
-    map.insert("r".into(), Value::make_list(rank.into_iter().map(Value::SmallInt).collect()));
-    dsu = Value::make_map(map);
-    Ok(Value::make_list(vec![Value::SmallInt(root as i64), dsu]))
 }
 
-fn dsu_union(args: Vec<Value>) -> Result<Value, RuntimeError> {
-    if args.len() != 3 {
-        return Err(RuntimeError::new("dsu_union(dsu, a, b)", None));
-    }
-    let mut dsu = args[0].clone();
-    let a = to_i64_local(&args[1])? as usize;
-    let b = to_i64_local(&args[2])? as usize;
-    let (mut parent, mut rank) = extract_dsu(&dsu)?;
-    let ra = find_internal(a, &mut parent);
-    let rb = find_internal(b, &mut parent);
-    if ra != rb {
-        if rank[ra] < rank[rb] {
-            parent[ra] = rb as i64;
-        } else if rank[ra] > rank[rb] {
-            parent[rb] = ra as i64;
-        } else {
-            parent[rb] = ra as i64;
-            rank[ra] += 1;
+fn ntt_convolve(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b, bound) = ntt_inputs("ntt_convolve", &args)?;
+    let signed = a.iter().chain(&b).any(|x| *x < 0);
+    // with negative inputs results span (-m/2, m/2), otherwise [0, m)
+    let limit = if signed { MOD as i128 / 2 } else { MOD as i128 - 1 };
+    if bound.is_none_or(|bound| bound > limit) {
+        return Err(RuntimeError::new(
+            format!(
+                "ntt_convolve: coefficients may exceed {} (modulus {}); use ntt_convolve_big",
+                limit, MOD
+            ),
+            None,
+        ));
+    }
+    let res = ntt_residues(&a, &b, MOD)
+        .into_iter()
+        .map(|r| Value::SmallInt(signed_residue(r as i128, MOD as i128, signed) as i64))
+        .collect();
+    Ok(Value::make_list(res))
+}
+
+/// Three-prime NTT combined with CRT: exact for coefficients up to `i64` range.
+fn ntt_convolve_big(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b, bound) = ntt_inputs("ntt_convolve_big", &args)?;
+    if bound.is_none_or(|bound| bound > i64::MAX as i128) {
+        return Err(RuntimeError::new("ntt_convolve_big: coefficients may exceed the 64-bit integer range", None));
+    }
+    let signed = a.iter().chain(&b).any(|x| *x < 0);
+    let [m1, m2, m3] = BIG_MODS.map(|m| m as i128);
+    let [r1, r2, r3] = BIG_MODS.map(|m| ntt_residues(&a, &b, m));
+    let m12 = m1 * m2;
+    let inv_m1_mod_m2 = mod_pow(BIG_MODS[0] % BIG_MODS[1], BIG_MODS[1] - 2, BIG_MODS[1]) as i128;
+    let inv_m12_mod_m3 = mod_pow((m12 % m3) as i64, BIG_MODS[2] - 2, BIG_MODS[2]) as i128;
+    let res = (0..r1.len())
+        .map(|i| {
+            let (x1, x2, x3) = (r1[i] as i128, r2[i] as i128, r3[i] as i128);
+            // Garner: x = x1 + m1·t1 + m1·m2·t2
+            let t1 = (x2 - x1).rem_euclid(m2) * inv_m1_mod_m2 % m2;
+            let x12 = x1 + m1 * t1;
+            let t2 = (x3 - x12 % m3).rem_euclid(m3) * inv_m12_mod_m3 % m3;
+            let x = x12 + m12 * t2;
+            Value::SmallInt(signed_residue(x, m12 * m3, signed) as i64)
+        })
+        .collect();
+    Ok(Value::make_list(res))
+}
+
+// --- Fenwick tree (binary indexed tree) ---
+// 0-based layout: node i holds the sum of elements (i & (i + 1))..=i. The `data` list is
+// updated in place, so each operation costs O(log n) (O(log rows · log cols) in 2D).
+
+fn bit_field(bit: &Value, key: &str, name: &str) -> Result<Value, RuntimeError> {
+    if let Value::RcObj(rc) = bit {
+        if let NauxObj::Map(map) = rc.as_ref() {
+            if let Some(v) = map.borrow().get(key) {
+                return Ok(v.clone());
+            }
         }
     }
-    let mut map = std::collections::HashMap::new();
-    map.insert("p".into(), Value::make_list(parent.into_iter().map(Value::SmallInt).collect()));
-    map.insert("r".into(), Value::make_list(rank.into_iter().map(Value::SmallInt).collect()));
-    dsu = Value::make_map(map);
-    Ok(dsu)
+    Err(RuntimeError::new(format!("{}: first arg must be a Fenwick tree", name), None))
 }
 
-fn extract_dsu(dsu: &Value) -> Result<(Vec<i64>, Vec<i64>), RuntimeError> {
-    if let Value::RcObj(rc) = dsu {
-        if let NauxObj::Map(map) = rc.as_ref() {
-            let mb = map.borrow();
-            let p = mb.get("p").ok_or(RuntimeError::new("dsu missing p", None))?;
-            let r = mb.get("r").ok_or(RuntimeError::new("dsu missing r", None))?;
-            let parent = to_num_list(p)?.into_iter().map(|x| x as i64).collect();
-            let rank = to_num_list(r)?.into_iter().map(|x| x as i64).collect();
-            return Ok((parent, rank));
+fn bit_dim(bit: &Value, key: &str, name: &str) -> Result<usize, RuntimeError> {
+    match bit_field(bit, key, name)? {
+        Value::SmallInt(n) if n >= 0 => Ok(n as usize),
+        _ => Err(RuntimeError::new(format!("{}: invalid Fenwick tree size", name), None)),
+    }
+}
+
+/// Runs `f` on the tree's `data` list (which must hold `len` cells), borrowed mutably.
+fn with_bit_data<T>(bit: &Value, len: usize, name: &str, f: impl FnOnce(&mut [Value]) -> T) -> Result<T, RuntimeError> {
+    if let Value::RcObj(rc) = bit_field(bit, "data", name)? {
+        if let NauxObj::List(list) = rc.as_ref() {
+            let mut data = list.borrow_mut();
+            if data.len() == len {
+                return Ok(f(&mut data));
+            }
         }
     }
-    Err(RuntimeError::new("invalid dsu", None))
+    Err(RuntimeError::new(format!("{}: invalid Fenwick tree data", name), None))
 }
 
-fn find_internal(x: usize, parent: &mut Vec<i64>) -> usize {
-    if parent[x] as usize != x {
-        parent[x] = find_internal(parent[x] as usize, parent) as i64;
+/// Integer argument in `0..=max` (`0..max` when `inclusive` is false).
+fn bit_index(val: &Value, max: usize, inclusive: bool, name: &str, arg: &str) -> Result<usize, RuntimeError> {
+    let end = if inclusive { max + 1 } else { max };
+    match val.as_i64() {
+        Some(i) if i >= 0 && (i as usize) < end => Ok(i as usize),
+        Some(i) => Err(RuntimeError::new(format!("{}: {} = {} out of range 0..{}", name, arg, i, end), None)),
+        None => Err(RuntimeError::new(format!("{}: {} must be an integer", name, arg), None)),
     }
-    parent[x] as usize
 }
 
-// --- SEGMENT TREE (simple array-based sum) ---
+fn bit_cell(v: &Value) -> f64 {
+    v.as_f64().unwrap_or(0.0)
+}
 
-fn segtree_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
+fn bit_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
-        return Err(RuntimeError::new("segtree_new(list)", None));
+        return Err(RuntimeError::new("bit_new(n)", None));
     }
-    let arr = to_num_list(&args[0])?;
-    let mut map = std::collections::HashMap::new();
-    map.insert("data".into(), Value::make_list(arr.into_iter().map(Value::Float).collect()));
+    let n = bit_index(&args[0], i32::MAX as usize, false, "bit_new", "n")?;
+    let mut map = HashMap::new();
+    map.insert("data".into(), Value::make_list(vec![Value::Float(0.0); n]));
+    map.insert("n".into(), Value::SmallInt(n as i64));
     Ok(Value::make_map(map))
 }
 
-fn segtree_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
+/// Adds `delta` to element `idx` in place and returns the same tree.
+fn bit_update(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 3 {
-        return Err(RuntimeError::new("segtree_query(tree, l, r)", None));
+        return Err(RuntimeError::new("bit_update(bit, idx, delta)", None));
     }
-    let data = extract_data(&args[0])?;
-    let l = to_i64_local(&args[1])? as usize;
-    let r = to_i64_local(&args[2])? as usize;
-    let mut sum = 0.0;
-    for i in l..r.min(data.len()) {
-        sum += data[i];
+    let n = bit_dim(&args[0], "n", "bit_update")?;
+    let mut i = bit_index(&args[1], n, false, "bit_update", "idx")?;
+    let delta = args[2].as_f64().ok_or_else(|| RuntimeError::new("bit_update: delta must be number", None))?;
+    with_bit_data(&args[0], n, "bit_update", |data| {
+        while i < n {
+            data[i] = Value::Float(bit_cell(&data[i]) + delta);
+            i |= i + 1;
+        }
+    })?;
+    Ok(args[0].clone())
+}
+
+/// Sum of the first `end` elements.
+fn bit_prefix(bit: &Value, n: usize, mut end: usize, name: &str) -> Result<f64, RuntimeError> {
+    with_bit_data(bit, n, name, |data| {
+        let mut sum = 0.0;
+        while end > 0 {
+            sum += bit_cell(&data[end - 1]);
+            end &= end - 1;
+        }
+        sum
+    })
+}
+
+/// Sum of elements `0..idx` (idx excluded).
+fn bit_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("bit_query(bit, idx)", None));
     }
-    Ok(Value::Float(sum))
+    let n = bit_dim(&args[0], "n", "bit_query")?;
+    let end = bit_index(&args[1], n, true, "bit_query", "idx")?;
+    Ok(Value::Float(bit_prefix(&args[0], n, end, "bit_query")?))
 }
 
-fn segtree_update(args: Vec<Value>) -> Result<Value, RuntimeError> {
+/// Sum of elements `l..r`, half-open like `segtree_query`.
+fn bit_range_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 3 {
-        return Err(RuntimeError::new("segtree_update(tree, idx, val)", None));
-    }
-    let mut data = extract_data(&args[0])?;
-    let idx = to_i64_local(&args[1])? as usize;
-    let val = to_i64_local(&args[2])? as f64;
-    if idx < data.len() {
-        data[idx] = val;
+        return Err(RuntimeError::new("bit_range_query(bit, l, r)", None));
     }
-    let mut map = std::collections::HashMap::new();
-    map.insert("data".into(), Value::make_list(data.into_iter().map(Value::Float).collect()));
+    let n = bit_dim(&args[0], "n", "bit_range_query")?;
+    let r = bit_index(&args[2], n, true, "bit_range_query", "r")?;
+    let l = bit_index(&args[1], r, true, "bit_range_query", "l")?;
+    Ok(Value::Float(bit_prefix(&args[0], n, r, "bit_range_query")? - bit_prefix(&args[0], n, l, "bit_range_query")?))
+}
+
+/// `{data, rows, cols}` with `data` holding the row-major rows × cols tree.
+fn bit2d_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("bit2d_new(rows, cols)", None));
+    }
+    let rows = bit_index(&args[0], i32::MAX as usize, false, "bit2d_new", "rows")?;
+    let cols = bit_index(&args[1], i32::MAX as usize, false, "bit2d_new", "cols")?;
+    let cells = rows
+        .checked_mul(cols)
+        .filter(|c| *c <= i32::MAX as usize)
+        .ok_or_else(|| RuntimeError::new("bit2d_new: too many cells", None))?;
+    let mut map = HashMap::new();
+    map.insert("data".into(), Value::make_list(vec![Value::Float(0.0); cells]));
+    map.insert("rows".into(), Value::SmallInt(rows as i64));
+    map.insert("cols".into(), Value::SmallInt(cols as i64));
     Ok(Value::make_map(map))
 }
 
-fn extract_data(tree: &Value) -> Result<Vec<f64>, RuntimeError> {
-    if let Value::RcObj(rc) = tree {
-        if let NauxObj::Map(map) = rc.as_ref() {
-            if let Some(val) = map.borrow().get("data") {
-                return to_num_list(val);
+/// Adds `delta` to cell (r, c) in place and returns the same tree.
+fn bit2d_update(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 4 {
+        return Err(RuntimeError::new("bit2d_update(bit2d, r, c, delta)", None));
+    }
+    let rows = bit_dim(&args[0], "rows", "bit2d_update")?;
+    let cols = bit_dim(&args[0], "cols", "bit2d_update")?;
+    let mut i = bit_index(&args[1], rows, false, "bit2d_update", "r")?;
+    let c = bit_index(&args[2], cols, false, "bit2d_update", "c")?;
+    let delta = args[3].as_f64().ok_or_else(|| RuntimeError::new("bit2d_update: delta must be number", None))?;
+    with_bit_data(&args[0], rows * cols, "bit2d_update", |data| {
+        while i < rows {
+            let mut j = c;
+            while j < cols {
+                let k = i * cols + j;
+                data[k] = Value::Float(bit_cell(&data[k]) + delta);
+                j |= j + 1;
             }
+            i |= i + 1;
         }
+    })?;
+    Ok(args[0].clone())
+}
+
+/// Sum over the rectangle of rows `0..r` and columns `0..c`.
+fn bit2d_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("bit2d_query(bit2d, r, c)", None));
+    }
+    let rows = bit_dim(&args[0], "rows", "bit2d_query")?;
+    let cols = bit_dim(&args[0], "cols", "bit2d_query")?;
+    let mut i = bit_index(&args[1], rows, true, "bit2d_query", "r")?;
+    let c = bit_index(&args[2], cols, true, "bit2d_query", "c")?;
+    let sum = with_bit_data(&args[0], rows * cols, "bit2d_query", |data| {
+        let mut sum = 0.0;
+        while i > 0 {
+            let mut j = c;
+            while j > 0 {
+                sum += bit_cell(&data[(i - 1) * cols + j - 1]);
+                j &= j - 1;
+            }
+            i &= i - 1;
+        }
+        sum
+    })?;
+    Ok(Value::Float(sum))
+}
+
+// --- Sparse table (static range min / max) ---
+// `table[j][i]` is the min (or max) of `[i, i + 2^j)`. Both operations are idempotent, so a
+// query covers `[l, r)` with two overlapping power-of-two blocks in O(1).
+
+fn sparse_op(val: Option<&Value>, name: &str) -> Result<Ordering, RuntimeError> {
+    match val.map(|v| v.as_text()) {
+        None => Ok(Ordering::Less),
+        Some(Some(op)) if op == "min" => Ok(Ordering::Less),
+        Some(Some(op)) if op == "max" => Ok(Ordering::Greater),
+        _ => Err(RuntimeError::new(format!("{}: op must be \"min\" or \"max\"", name), None)),
+    }
+}
+
+/// The better of `a` and `b`: `keep` is the ordering `a` must have against `b` to win.
+fn sparse_pick<'a>(a: &'a Value, b: &'a Value, keep: Ordering) -> &'a Value {
+    if total_cmp(b, a) == keep {
+        b
+    } else {
+        a
     }
-    Err(RuntimeError::new("invalid segtree", None))
+}
+
+/// `{table, log, op, n}` over `items`, built in O(n log n).
+fn build_sparse_table(items: Vec<Value>, keep: Ordering) -> Value {
+    let n = items.len();
+    let mut log = vec![0usize; n + 1];
+    for i in 2..=n {
+        log[i] = log[i / 2] + 1;
+    }
+    let mut table = vec![items];
+    let mut width = 1;
+    while 2 * width <= n {
+        let prev = &table[table.len() - 1];
+        let row = (0..=n - 2 * width).map(|i| sparse_pick(&prev[i], &prev[i + width], keep).clone()).collect();
+        table.push(row);
+        width *= 2;
+    }
+    let op = if keep == Ordering::Less { "min" } else { "max" };
+    let mut map = HashMap::new();
+    map.insert("table".into(), Value::make_list(table.into_iter().map(Value::make_list).collect()));
+    map.insert("log".into(), Value::make_list(log.into_iter().map(|l| Value::SmallInt(l as i64)).collect()));
+    map.insert("op".into(), Value::make_text(op));
+    map.insert("n".into(), Value::SmallInt(n as i64));
+    Value::make_map(map)
+}
+
+fn sparse_table_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(RuntimeError::new("sparse_table_new(list, op?)", None));
+    }
+    let keep = sparse_op(args.get(1), "sparse_table_new")?;
+    let items = match &args[0] {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().clone(),
+            _ => return Err(RuntimeError::new("sparse_table_new: first arg must be list", None)),
+        },
+        _ => return Err(RuntimeError::new("sparse_table_new: first arg must be list", None)),
+    };
+    Ok(build_sparse_table(items, keep))
+}
+
+/// Cell `i` of list `list`, without copying the list.
+fn list_cell(list: &Value, i: usize) -> Option<Value> {
+    match list {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().get(i).cloned(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Min or max of elements `l..r` (non-empty).
+fn sparse_table_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("sparse_table_query(st, l, r)", None));
+    }
+    let bad = || RuntimeError::new("sparse_table_query: first arg must be a sparse table", None);
+    let n = match map_field(&args[0], "n") {
+        Some(Value::SmallInt(n)) if n >= 0 => n as usize,
+        _ => return Err(bad()),
+    };
+    let keep = sparse_op(map_field(&args[0], "op").as_ref(), "sparse_table_query")?;
+    let r = bit_index(&args[2], n, true, "sparse_table_query", "r")?;
+    let l = bit_index(&args[1], r, true, "sparse_table_query", "l")?;
+    if l == r {
+        return Err(RuntimeError::new(format!("sparse_table_query: empty range {}..{}", l, r), None));
+    }
+    let log = map_field(&args[0], "log").ok_or_else(bad)?;
+    let j = list_cell(&log, r - l).and_then(|v| v.as_i64()).ok_or_else(bad)? as usize;
+    let row = list_cell(&map_field(&args[0], "table").ok_or_else(bad)?, j).ok_or_else(bad)?;
+    let a = list_cell(&row, l).ok_or_else(bad)?;
+    let b = list_cell(&row, r - (1 << j)).ok_or_else(bad)?;
+    Ok(sparse_pick(&a, &b, keep).clone())
+}
+
+fn sparse_table_update(_args: Vec<Value>) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::new(
+        "sparse_table_update: sparse tables are static; use segtree_update for point updates",
+        None,
+    ))
+}
+
+/// A sparse table over the elements of a `segtree_new` tree.
+fn sparse_table_from_segtree(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(RuntimeError::new("sparse_table_from_segtree(st, op?)", None));
+    }
+    let keep = sparse_op(args.get(1), "sparse_table_from_segtree")?;
+    let bad = || RuntimeError::new("sparse_table_from_segtree: first arg must be a segment tree", None);
+    let (size, n) = match (map_field(&args[0], "size"), map_field(&args[0], "n")) {
+        (Some(Value::SmallInt(size)), Some(Value::SmallInt(n))) if 0 <= n && n <= size => (size as usize, n as usize),
+        _ => return Err(bad()),
+    };
+    let leaves = match map_field(&args[0], "tree") {
+        Some(Value::RcObj(rc)) => match rc.as_ref() {
+            NauxObj::List(tree) if tree.borrow().len() == 2 * size => tree.borrow()[size..size + n].to_vec(),
+            _ => return Err(bad()),
+        },
+        _ => return Err(bad()),
+    };
+    Ok(build_sparse_table(leaves, keep))
 }
 
 // --- Pollard Rho factorization (u64) ---
@@ -732,8 +1601,13 @@ fn eval_line(line: &Line, x: i64) -> f64 {
     line.m * x as f64 + line.b
 }
 
+/// Floor midpoint, so negative ranges still split into two strictly smaller halves.
+fn lichao_mid(l: i64, r: i64) -> i64 {
+    l + (r - l).div_euclid(2)
+}
+
 fn add_line_node(node: &mut Node, new_line: Line) {
-    let mid = (node.l + node.r) / 2;
+    let mid = lichao_mid(node.l, node.r);
     let (mut low, mut high) = (node.line.clone(), new_line);
     if eval_line(&low, mid) > eval_line(&high, mid) {
         std::mem::swap(&mut low, &mut high);
@@ -757,17 +1631,23 @@ fn add_line_node(node: &mut Node, new_line: Line) {
     }
 }
 
-fn query_node(node: &Node, x: i64) -> f64 {
-    let mut res = eval_line(&node.line, x);
-    let mid = (node.l + node.r) / 2;
-    if x <= mid {
-        if let Some(ref left) = node.left {
-            res = res.min(query_node(left, x));
-        }
-    } else if let Some(ref right) = node.right {
-        res = res.min(query_node(right, x));
-    }
-    res
+/// Minimum at `x`, walking the stored tree along a single root-to-leaf path instead of
+/// converting it back into `Node`s.
+fn query_value(tree: &Value, x: i64) -> Result<f64, RuntimeError> {
+    let bad = || RuntimeError::new("lichao_query: invalid Li Chao tree", None);
+    let mut res = f64::INFINITY;
+    let mut cur = tree.clone();
+    while !matches!(cur, Value::Null) {
+        let l = map_field(&cur, "l").and_then(|v| v.as_i64()).ok_or_else(bad)?;
+        let r = map_field(&cur, "r").and_then(|v| v.as_i64()).ok_or_else(bad)?;
+        let line = map_field(&cur, "line").ok_or_else(bad)?;
+        let m = map_field(&line, "m").and_then(|v| v.as_f64()).ok_or_else(bad)?;
+        let b = map_field(&line, "b").and_then(|v| v.as_f64()).ok_or_else(bad)?;
+        res = res.min(eval_line(&Line { m, b }, x));
+        let side = if x <= lichao_mid(l, r) { "left" } else { "right" };
+        cur = map_field(&cur, side).unwrap_or(Value::Null);
+    }
+    Ok(res)
 }
 
 fn node_to_value(node: &Node) -> Value {
@@ -853,17 +1733,53 @@ fn lichao_add(args: Vec<Value>) -> Result<Value, RuntimeError> {
         return Err(RuntimeError::new("lichao_add(tree, m, b)", None));
     }
     let mut node = value_to_node(&args[0])?;
-    let m = to_i64_local(&args[1])? as f64;
+    let m = args[1].as_f64().ok_or_else(|| RuntimeError::new("m must be number", None))?;
     let b = args[2].as_f64().ok_or_else(|| RuntimeError::new("b must be number", None))?;
     add_line_node(&mut node, Line { m, b });
     Ok(node_to_value(&node))
 }
 
+/// Adds every `[m, b]` line, converting the tree to and from a `Value` only once.
+fn lichao_add_many(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("lichao_add_many(tree, lines)", None));
+    }
+    let mut node = value_to_node(&args[0])?;
+    let lines = match &args[1] {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().clone(),
+            _ => return Err(RuntimeError::new("lichao_add_many: lines must be a list", None)),
+        },
+        _ => return Err(RuntimeError::new("lichao_add_many: lines must be a list", None)),
+    };
+    for (i, line) in lines.iter().enumerate() {
+        let bad = || RuntimeError::new(format!("lichao_add_many: line {} must be [m, b]", i), None);
+        let (m, b) = match line {
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::List(mb) => match mb.borrow().as_slice() {
+                    [m, b] => (m.as_f64().ok_or_else(bad)?, b.as_f64().ok_or_else(bad)?),
+                    _ => return Err(bad()),
+                },
+                _ => return Err(bad()),
+            },
+            _ => return Err(bad()),
+        };
+        add_line_node(&mut node, Line { m, b });
+    }
+    Ok(node_to_value(&node))
+}
+
 fn lichao_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::new("lichao_query(tree, x)", None));
     }
-    let node = value_to_node(&args[0])?;
     let x = to_i64_local(&args[1])?;
-    Ok(Value::Float(query_node(&node, x)))
+    let (l, r) = match (map_field(&args[0], "l"), map_field(&args[0], "r")) {
+        (Some(Value::SmallInt(l)), Some(Value::SmallInt(r))) => (l, r),
+        _ => return Err(RuntimeError::new("lichao_query: invalid Li Chao tree", None)),
+    };
+    if x < l || x > r {
+        return Err(RuntimeError::new(format!("lichao_query: x = {} out of range [{}, {}]", x, l, r), None));
+    }
+    Ok(Value::Float(query_value(&args[0], x)?))
 }
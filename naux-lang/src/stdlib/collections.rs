@@ -1,22 +1,46 @@
-use std::cmp::Reverse;
-use std::collections::{BTreeSet, BinaryHeap, HashMap, VecDeque};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 
 use crate::runtime::env::Env;
 use crate::runtime::error::RuntimeError;
 use crate::runtime::value::{NauxObj, Value};
+use crate::stdlib::map::map_key;
 
 pub fn register_collections(env: &mut Env) {
     env.set_builtin("set_new", set_new);
     env.set_builtin("set_add", set_add);
     env.set_builtin("set_contains", set_contains);
+    env.set_builtin("set_remove", set_remove);
+    env.set_builtin("set_size", set_size);
+    env.set_builtin("set_union", set_union);
+    env.set_builtin("set_intersection", set_intersection);
+    env.set_builtin("set_difference", set_difference);
+    env.set_builtin("set_symmetric_difference", set_symmetric_difference);
+    env.set_builtin("set_to_list", set_to_list);
+    env.set_builtin("set_is_subset", set_is_subset);
+    env.set_builtin("set_is_superset", set_is_superset);
+    env.set_builtin("hash", hash);
 
     env.set_builtin("queue_new", queue_new);
     env.set_builtin("queue_push", queue_push);
     env.set_builtin("queue_pop", queue_pop);
 
+    env.set_builtin("deque_new", deque_new);
+    env.set_builtin("deque_push_front", deque_push_front);
+    env.set_builtin("deque_push_back", deque_push_back);
+    env.set_builtin("deque_pop_front", deque_pop_front);
+    env.set_builtin("deque_pop_back", deque_pop_back);
+
     env.set_builtin("pq_new", pq_new);
     env.set_builtin("pq_push", pq_push);
+    env.set_builtin("pq_push_keyed", pq_push_keyed);
+    env.set_builtin("pq_from_list", pq_from_list);
     env.set_builtin("pq_pop_min", pq_pop_min);
+    env.set_builtin("pq_pop_max", pq_pop_max);
+    env.set_builtin("pq_peek_min", pq_peek_min);
+    env.set_builtin("pq_peek_max", pq_peek_max);
+    env.set_builtin("pq_size", pq_size);
+    env.set_builtin("pq_is_empty", pq_is_empty);
 
     env.set_builtin("stack_new", stack_new);
     env.set_builtin("stack_push", stack_push);
@@ -25,10 +49,26 @@ pub fn register_collections(env: &mut Env) {
     env.set_builtin("dsu_new", dsu_new);
     env.set_builtin("dsu_find", dsu_find);
     env.set_builtin("dsu_union", dsu_union);
+    env.set_builtin("dsu_connected", dsu_connected);
+    env.set_builtin("dsu_count", dsu_count);
 
     env.set_builtin("segtree_new", segtree_new);
     env.set_builtin("segtree_query", segtree_query);
     env.set_builtin("segtree_update", segtree_update);
+
+    env.set_builtin("lru_new", lru_new);
+    env.set_builtin("lru_get", lru_get);
+    env.set_builtin("lru_put", lru_put);
+
+    env.set_builtin("counter_new", counter_new);
+    env.set_builtin("counter_add", counter_add);
+    env.set_builtin("counter_get", counter_get);
+    env.set_builtin("counter_most_common", counter_most_common);
+
+    env.set_builtin("trie_new", trie_new);
+    env.set_builtin("trie_insert", trie_insert);
+    env.set_builtin("trie_contains", trie_contains);
+    env.set_builtin("trie_prefix", trie_prefix);
 }
 
 fn set_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
@@ -60,6 +100,77 @@ fn set_contains(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Err(RuntimeError::new("set_contains: first arg must be set", None))
 }
 
+/// Returns a new set without `value`; the input set is unchanged.
+fn set_remove(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("set_remove(set, value)", None));
+    }
+    let kept = expect_set(&args[0], "set_remove: first arg must be set")?.into_iter().filter(|v| *v != args[1]);
+    Ok(Value::make_set(kept.collect()))
+}
+
+fn set_size(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("set_size(set)", None));
+    }
+    Ok(Value::SmallInt(expect_set(&args[0], "set_size: arg must be set")?.len() as i64))
+}
+
+/// Both operands of a binary set function, checked and copied out.
+fn set_pair(args: &[Value], name: &str) -> Result<(BTreeSet<Value>, BTreeSet<Value>), RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new(format!("{}(a, b)", name), None));
+    }
+    let msg = format!("{}: args must be sets", name);
+    Ok((expect_set(&args[0], &msg)?, expect_set(&args[1], &msg)?))
+}
+
+fn set_union(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = set_pair(&args, "set_union")?;
+    Ok(Value::make_set(a.union(&b).cloned().collect()))
+}
+
+fn set_intersection(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = set_pair(&args, "set_intersection")?;
+    Ok(Value::make_set(a.intersection(&b).cloned().collect()))
+}
+
+/// Elements of `a` that are not in `b`.
+fn set_difference(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = set_pair(&args, "set_difference")?;
+    Ok(Value::make_set(a.difference(&b).cloned().collect()))
+}
+
+fn set_symmetric_difference(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = set_pair(&args, "set_symmetric_difference")?;
+    Ok(Value::make_set(a.symmetric_difference(&b).cloned().collect()))
+}
+
+/// Elements in ascending value order.
+fn set_to_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("set_to_list(set)", None));
+    }
+    Ok(Value::make_list(expect_set(&args[0], "set_to_list: arg must be set")?.into_iter().collect()))
+}
+
+fn set_is_subset(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = set_pair(&args, "set_is_subset")?;
+    Ok(Value::Bool(a.is_subset(&b)))
+}
+
+fn set_is_superset(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = set_pair(&args, "set_is_superset")?;
+    Ok(Value::Bool(a.is_superset(&b)))
+}
+
+fn hash(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    match args.as_slice() {
+        [v] => Ok(Value::SmallInt(v.stable_hash())),
+        _ => Err(RuntimeError::new("hash(value)", None)),
+    }
+}
+
 fn queue_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_list(Vec::new())) // using List as queue storage (VecDeque not stored in Value)
 }
@@ -83,6 +194,48 @@ fn queue_pop(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_list(vec![val, updated]))
 }
 
+// Deques are lists like queues; every op copies, so both ends cost O(n).
+fn deque_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
+    Ok(Value::make_list(Vec::new()))
+}
+
+fn deque_push_front(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("deque_push_front(deque, value)", None));
+    }
+    let mut d = VecDeque::from(expect_list(&args[0], "deque_push_front: first arg must be list/deque")?);
+    d.push_front(args[1].clone());
+    Ok(Value::make_list(d.into_iter().collect()))
+}
+
+fn deque_push_back(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("deque_push_back(deque, value)", None));
+    }
+    let mut d = expect_list(&args[0], "deque_push_back: first arg must be list/deque")?;
+    d.push(args[1].clone());
+    Ok(Value::make_list(d))
+}
+
+fn deque_pop_front(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("deque_pop_front(deque)", None));
+    }
+    let mut d = VecDeque::from(expect_list(&args[0], "deque_pop_front: first arg must be list/deque")?);
+    let val = d.pop_front().unwrap_or(Value::Null);
+    let updated = Value::make_list(d.into_iter().collect::<Vec<_>>());
+    Ok(Value::make_list(vec![val, updated]))
+}
+
+fn deque_pop_back(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("deque_pop_back(deque)", None));
+    }
+    let mut d = expect_list(&args[0], "deque_pop_back: first arg must be list/deque")?;
+    let val = d.pop().unwrap_or(Value::Null);
+    Ok(Value::make_list(vec![val, Value::make_list(d)]))
+}
+
 fn pq_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_pq(Vec::new()))
 }
@@ -91,19 +244,87 @@ fn pq_push(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::new("pq_push(pq, value)", None));
     }
-    let mut heap = to_min_heap(args[0].clone())?;
-    heap.push(Reverse(args[1].clone()));
-    Ok(Value::make_pq(from_min_heap(heap)))
+    let mut heap = expect_pq(&args[0], "pq_push: first arg must be priority queue")?;
+    minmax_push(&mut heap, args[1].clone());
+    Ok(Value::make_pq(heap))
+}
+
+/// Pushes the entry `[priority, value]`; entries order by priority first, then by value.
+fn pq_push_keyed(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("pq_push_keyed(pq, priority, value)", None));
+    }
+    let mut heap = expect_pq(&args[0], "pq_push_keyed: first arg must be priority queue")?;
+    if args[1].as_f64().is_none() {
+        return Err(RuntimeError::new("pq_push_keyed: priority must be number", None));
+    }
+    minmax_push(&mut heap, Value::make_list(vec![args[1].clone(), args[2].clone()]));
+    Ok(Value::make_pq(heap))
+}
+
+/// Builds the heap bottom-up in O(n).
+fn pq_from_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("pq_from_list(list)", None));
+    }
+    let mut heap = expect_list(&args[0], "pq_from_list: arg must be list")?;
+    for i in (0..heap.len() / 2).rev() {
+        minmax_trickle_down(&mut heap, i);
+    }
+    Ok(Value::make_pq(heap))
 }
 
 fn pq_pop_min(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::new("pq_pop_min(pq)", None));
     }
-    let mut heap = to_min_heap(args[0].clone())?;
-    let val = heap.pop().map(|r| r.0).unwrap_or(Value::Null);
-    let updated = Value::make_pq(from_min_heap(heap));
-    Ok(Value::make_list(vec![val, updated]))
+    let mut heap = expect_pq(&args[0], "pq_pop_min: first arg must be priority queue")?;
+    let val = if heap.is_empty() { Value::Null } else { minmax_remove(&mut heap, 0) };
+    Ok(Value::make_list(vec![val, Value::make_pq(heap)]))
+}
+
+fn pq_pop_max(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("pq_pop_max(pq)", None));
+    }
+    let mut heap = expect_pq(&args[0], "pq_pop_max: first arg must be priority queue")?;
+    let val = match minmax_max_index(&heap) {
+        Some(i) => minmax_remove(&mut heap, i),
+        None => Value::Null,
+    };
+    Ok(Value::make_list(vec![val, Value::make_pq(heap)]))
+}
+
+fn pq_peek_min(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("pq_peek_min(pq)", None));
+    }
+    let heap = expect_pq(&args[0], "pq_peek_min: first arg must be priority queue")?;
+    Ok(heap.first().cloned().unwrap_or(Value::Null))
+}
+
+fn pq_peek_max(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("pq_peek_max(pq)", None));
+    }
+    let heap = expect_pq(&args[0], "pq_peek_max: first arg must be priority queue")?;
+    Ok(minmax_max_index(&heap).map(|i| heap[i].clone()).unwrap_or(Value::Null))
+}
+
+fn pq_size(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("pq_size(pq)", None));
+    }
+    let heap = expect_pq(&args[0], "pq_size: first arg must be priority queue")?;
+    Ok(Value::SmallInt(heap.len() as i64))
+}
+
+fn pq_is_empty(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("pq_is_empty(pq)", None));
+    }
+    let heap = expect_pq(&args[0], "pq_is_empty: first arg must be priority queue")?;
+    Ok(Value::Bool(heap.is_empty()))
 }
 
 fn stack_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
@@ -132,7 +353,11 @@ fn dsu_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::new("dsu_new(n)", None));
     }
-    let n = args[0].as_i64().ok_or_else(|| RuntimeError::new("dsu_new: n must be number", None))? as usize;
+    let n = args[0].as_i64().ok_or_else(|| RuntimeError::new("dsu_new: n must be number", None))?;
+    if n < 0 {
+        return Err(RuntimeError::new(format!("dsu_new: n = {} must be non-negative", n), None));
+    }
+    let n = n as usize;
     let mut parent = Vec::with_capacity(n);
     let mut rank = Vec::with_capacity(n);
     for i in 0..n {
@@ -145,35 +370,88 @@ fn dsu_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_map(map))
 }
 
+/// Runs `f` on the parent and rank lists of a DSU map `{p, r}`. The lists are mutated in
+/// place, so every operation costs its amortized union-find time rather than a copy of the
+/// whole structure; all variables holding the same DSU see the change.
+fn with_dsu<T>(
+    val: &Value,
+    name: &str,
+    f: impl FnOnce(&mut Vec<Value>, &mut Vec<Value>) -> Result<T, RuntimeError>,
+) -> Result<T, RuntimeError> {
+    let dsu = match val {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::Map(map) => map.borrow().clone(),
+            _ => return Err(RuntimeError::new(format!("{}: first arg must be map", name), None)),
+        },
+        _ => return Err(RuntimeError::new(format!("{}: first arg must be map", name), None)),
+    };
+    if let (Some(Value::RcObj(p)), Some(Value::RcObj(r))) = (dsu.get("p"), dsu.get("r")) {
+        if let (NauxObj::List(p), NauxObj::List(r)) = (p.as_ref(), r.as_ref()) {
+            if let (Ok(mut parent), Ok(mut rank)) = (p.try_borrow_mut(), r.try_borrow_mut()) {
+                if parent.len() == rank.len() {
+                    return f(&mut parent, &mut rank);
+                }
+            }
+        }
+    }
+    Err(RuntimeError::new(format!("{}: invalid parent/rank lists", name), None))
+}
+
+fn dsu_element(val: &Value, len: usize, name: &str, arg: &str) -> Result<usize, RuntimeError> {
+    let x = val.as_i64().ok_or_else(|| RuntimeError::new(format!("{}: {} must be number", name, arg), None))?;
+    if x < 0 || x as usize >= len {
+        return Err(RuntimeError::new(format!("{}: {} = {} out of range 0..{}", name, arg, x, len), None));
+    }
+    Ok(x as usize)
+}
+
 fn dsu_find(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::new("dsu_find(dsu, x)", None));
     }
-    let mut dsu = expect_map(&args[0], "dsu_find: first arg must be map")?;
-    let x = args[1].as_i64().ok_or_else(|| RuntimeError::new("dsu_find: x must be number", None))? as usize;
-    let mut parent = expect_list(dsu.get("p").unwrap_or(&Value::Null), "dsu_find: missing parent list")?;
-    let _rank = expect_list(dsu.get("r").unwrap_or(&Value::Null), "dsu_find: missing rank list")?;
-    let root = find_internal(x, &mut parent);
-    dsu.insert("p".into(), Value::make_list(parent));
-    Ok(Value::make_list(vec![Value::SmallInt(root as i64), Value::make_map(dsu)]))
+    let root = with_dsu(&args[0], "dsu_find", |parent, _| {
+        let x = dsu_element(&args[1], parent.len(), "dsu_find", "x")?;
+        Ok(find_internal(x, parent))
+    })?;
+    Ok(Value::make_list(vec![Value::SmallInt(root as i64), args[0].clone()]))
 }
 
 fn dsu_union(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 3 {
         return Err(RuntimeError::new("dsu_union(dsu, a, b)", None));
     }
-    let mut dsu = expect_map(&args[0], "dsu_union: first arg must be map")?;
-    let a = args[1].as_i64().ok_or_else(|| RuntimeError::new("dsu_union: a must be number", None))? as usize;
-    let b = args[2].as_i64().ok_or_else(|| RuntimeError::new("dsu_union: b must be number", None))? as usize;
-    let mut parent = expect_list(dsu.get("p").unwrap_or(&Value::Null), "dsu_union: missing parent list")?;
-    let mut rank = expect_list(dsu.get("r").unwrap_or(&Value::Null), "dsu_union: missing rank list")?;
-    union_internal(a, b, &mut parent, &mut rank);
-    dsu.insert("p".into(), Value::make_list(parent));
-    dsu.insert("r".into(), Value::make_list(rank));
-    Ok(Value::make_map(dsu))
+    with_dsu(&args[0], "dsu_union", |parent, rank| {
+        let a = dsu_element(&args[1], parent.len(), "dsu_union", "a")?;
+        let b = dsu_element(&args[2], parent.len(), "dsu_union", "b")?;
+        union_internal(a, b, parent, rank);
+        Ok(())
+    })?;
+    Ok(args[0].clone())
 }
 
-fn find_internal(x: usize, parent: &mut Vec<Value>) -> usize {
+fn dsu_connected(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("dsu_connected(dsu, a, b)", None));
+    }
+    with_dsu(&args[0], "dsu_connected", |parent, _| {
+        let a = dsu_element(&args[1], parent.len(), "dsu_connected", "a")?;
+        let b = dsu_element(&args[2], parent.len(), "dsu_connected", "b")?;
+        Ok(Value::Bool(find_internal(a, parent) == find_internal(b, parent)))
+    })
+}
+
+/// Number of disjoint sets, i.e. of elements that are their own root.
+fn dsu_count(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("dsu_count(dsu)", None));
+    }
+    with_dsu(&args[0], "dsu_count", |parent, _| {
+        let roots = parent.iter().enumerate().filter(|(i, p)| p.as_i64() == Some(*i as i64)).count();
+        Ok(Value::SmallInt(roots as i64))
+    })
+}
+
+pub(crate) fn find_internal(x: usize, parent: &mut Vec<Value>) -> usize {
     let px = parent.get(x).and_then(|v| match v {
         Value::SmallInt(n) => Some(*n as usize),
         Value::Float(n) => Some(*n as usize),
@@ -190,7 +468,7 @@ fn find_internal(x: usize, parent: &mut Vec<Value>) -> usize {
     }
 }
 
-fn union_internal(a: usize, b: usize, parent: &mut Vec<Value>, rank: &mut Vec<Value>) {
+pub(crate) fn union_internal(a: usize, b: usize, parent: &mut Vec<Value>, rank: &mut Vec<Value>) {
     let ra = find_internal(a, parent);
     let rb = find_internal(b, parent);
     if ra == rb {
@@ -223,6 +501,9 @@ fn segtree_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
         return Err(RuntimeError::new("segtree_new(list)", None));
     }
     let arr = expect_list(&args[0], "segtree_new: expected list")?;
+    if arr.iter().any(|v| v.as_f64().is_none()) {
+        return Err(RuntimeError::new("segtree_new: expected list of numbers", None));
+    }
     let n = arr.len();
     let mut size = 1;
     while size < n {
@@ -238,6 +519,7 @@ fn segtree_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
     let mut map = std::collections::HashMap::new();
     map.insert("tree".into(), Value::make_list(tree));
     map.insert("size".into(), Value::SmallInt(size as i64));
+    map.insert("n".into(), Value::SmallInt(n as i64));
     Ok(Value::make_map(map))
 }
 
@@ -251,8 +533,12 @@ fn segtree_query(args: Vec<Value>) -> Result<Value, RuntimeError> {
         .get("size")
         .and_then(|v| v.as_i64())
         .ok_or_else(|| RuntimeError::new("segtree_query: missing size", None))? as usize;
-    let mut l = args[1].as_i64().ok_or_else(|| RuntimeError::new("l must be num", None))? + size as i64;
-    let mut r = args[2].as_i64().ok_or_else(|| RuntimeError::new("r must be num", None))? + size as i64;
+    let l = args[1].as_i64().ok_or_else(|| RuntimeError::new("segtree_query: l must be number", None))?;
+    let r = args[2].as_i64().ok_or_else(|| RuntimeError::new("segtree_query: r must be number", None))?;
+    if l < 0 || l > r || r as usize > size || tree.len() != 2 * size {
+        return Err(RuntimeError::new(format!("segtree_query: invalid range {}..{}", l, r), None));
+    }
+    let (mut l, mut r) = (l + size as i64, r + size as i64);
     let mut res_left = Value::Float(0.0);
     let mut res_right = Value::Float(0.0);
     while l < r {
@@ -280,10 +566,15 @@ fn segtree_update(args: Vec<Value>) -> Result<Value, RuntimeError> {
         .get("size")
         .and_then(|v| v.as_i64())
         .ok_or_else(|| RuntimeError::new("segtree_update: missing size", None))? as usize;
-    let mut pos = args[1].as_i64().ok_or_else(|| RuntimeError::new("idx must be number", None))? as usize + size;
-    if let Some(p) = tree.get_mut(pos) {
-        *p = args[2].clone();
+    let idx = args[1].as_i64().ok_or_else(|| RuntimeError::new("segtree_update: idx must be number", None))?;
+    if idx < 0 || idx as usize >= size || tree.len() != 2 * size {
+        return Err(RuntimeError::new(format!("segtree_update: idx = {} out of range", idx), None));
+    }
+    if args[2].as_f64().is_none() {
+        return Err(RuntimeError::new("segtree_update: val must be number", None));
     }
+    let mut pos = idx as usize + size;
+    tree[pos] = args[2].clone();
     pos >>= 1;
     while pos > 0 {
         let left = tree[pos << 1].clone();
@@ -295,6 +586,245 @@ fn segtree_update(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_map(st))
 }
 
+// LRU caches are plain maps: {"capacity": Int, "order": List<key> (least recent first), "store": Map}.
+fn lru_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let capacity = match args.as_slice() {
+        [Value::SmallInt(n)] if *n > 0 => *n,
+        _ => return Err(RuntimeError::new("lru_new(capacity > 0)", None)),
+    };
+    let mut cache = HashMap::new();
+    cache.insert("capacity".into(), Value::SmallInt(capacity));
+    cache.insert("order".into(), Value::make_list(Vec::new()));
+    cache.insert("store".into(), Value::make_map(HashMap::new()));
+    Ok(Value::make_map(cache))
+}
+
+/// Borrows the cache's (capacity, order, store) parts.
+fn lru_parts(cache: &Value, msg: &str) -> Result<(usize, Value, Value), RuntimeError> {
+    let m = map_cell(cache).ok_or_else(|| RuntimeError::new(msg, None))?.borrow();
+    match (m.get("capacity"), m.get("order"), m.get("store")) {
+        (Some(Value::SmallInt(cap)), Some(order), Some(store)) => Ok((*cap as usize, order.clone(), store.clone())),
+        _ => Err(RuntimeError::new(msg, None)),
+    }
+}
+
+/// Moves `key` to the most-recent end of the order list.
+fn lru_touch(order: &RefCell<Vec<Value>>, key: &str) {
+    let mut order = order.borrow_mut();
+    order.retain(|k| k.as_text().as_deref() != Some(key));
+    order.push(Value::make_text(key));
+}
+
+fn list_cell(val: &Value) -> Option<&RefCell<Vec<Value>>> {
+    match val {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(list) => Some(list),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the cached value (or null) and marks the key as most recently used.
+fn lru_get(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("lru_get(cache, key)", None));
+    }
+    let key = map_key(&args[1]).ok_or_else(|| RuntimeError::new("lru_get: key must be text or number", None))?;
+    let (_, order, store) = lru_parts(&args[0], "lru_get: first arg must be lru cache")?;
+    let (Some(order), Some(store)) = (list_cell(&order), map_cell(&store)) else {
+        return Err(RuntimeError::new("lru_get: first arg must be lru cache", None));
+    };
+    let hit = store.borrow().get(&key).cloned();
+    match hit {
+        Some(v) => {
+            lru_touch(order, &key);
+            Ok(v)
+        }
+        None => Ok(Value::Null),
+    }
+}
+
+/// Inserts in place, evicting the least recently used entry when over capacity.
+fn lru_put(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("lru_put(cache, key, value)", None));
+    }
+    let key = map_key(&args[1]).ok_or_else(|| RuntimeError::new("lru_put: key must be text or number", None))?;
+    let (capacity, order, store) = lru_parts(&args[0], "lru_put: first arg must be lru cache")?;
+    let (Some(order), Some(store)) = (list_cell(&order), map_cell(&store)) else {
+        return Err(RuntimeError::new("lru_put: first arg must be lru cache", None));
+    };
+    store.borrow_mut().insert(key.clone(), args[2].clone());
+    lru_touch(order, &key);
+    while order.borrow().len() > capacity {
+        let oldest = order.borrow_mut().remove(0);
+        if let Some(k) = oldest.as_text() {
+            store.borrow_mut().remove(&k);
+        }
+    }
+    Ok(args[0].clone())
+}
+
+// Counters are plain maps of key -> Int count.
+fn counter_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
+    Ok(Value::make_map(HashMap::new()))
+}
+
+/// Adds `n` (default 1) to the key's count in place.
+fn counter_add(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(RuntimeError::new("counter_add(counter, key, n=1)", None));
+    }
+    let key = map_key(&args[1]).ok_or_else(|| RuntimeError::new("counter_add: key must be text or number", None))?;
+    let n = match args.get(2) {
+        None => 1,
+        Some(Value::SmallInt(n)) => *n,
+        Some(_) => return Err(RuntimeError::new("counter_add: n must be int", None)),
+    };
+    let counts = map_cell(&args[0]).ok_or_else(|| RuntimeError::new("counter_add: first arg must be counter", None))?;
+    let mut counts = counts.borrow_mut();
+    let entry = counts.entry(key).or_insert(Value::SmallInt(0));
+    let current = match entry {
+        Value::SmallInt(c) => *c,
+        _ => return Err(RuntimeError::new("counter_add: counts must be ints", None)),
+    };
+    *entry = Value::SmallInt(current + n);
+    drop(counts);
+    Ok(args[0].clone())
+}
+
+fn counter_get(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("counter_get(counter, key)", None));
+    }
+    let key = map_key(&args[1]).ok_or_else(|| RuntimeError::new("counter_get: key must be text or number", None))?;
+    let counts = map_cell(&args[0]).ok_or_else(|| RuntimeError::new("counter_get: first arg must be counter", None))?;
+    Ok(counts.borrow().get(&key).cloned().unwrap_or(Value::SmallInt(0)))
+}
+
+/// Top-k `[key, count]` pairs, count descending, ties broken by key.
+fn counter_most_common(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (counter, k) = match args.as_slice() {
+        [c] => (c, usize::MAX),
+        [c, Value::SmallInt(k)] if *k >= 0 => (c, *k as usize),
+        _ => return Err(RuntimeError::new("counter_most_common(counter, k)", None)),
+    };
+    let counts = map_cell(counter).ok_or_else(|| RuntimeError::new("counter_most_common: first arg must be counter", None))?;
+    let mut pairs: Vec<(String, i64)> = Vec::new();
+    for (key, count) in counts.borrow().iter() {
+        match count {
+            Value::SmallInt(c) => pairs.push((key.clone(), *c)),
+            _ => return Err(RuntimeError::new("counter_most_common: counts must be ints", None)),
+        }
+    }
+    pairs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let top = pairs
+        .into_iter()
+        .take(k)
+        .map(|(key, count)| Value::make_list(vec![Value::make_text(key), Value::SmallInt(count)]))
+        .collect();
+    Ok(Value::make_list(top))
+}
+
+// Trie nodes are plain maps: {"end": Bool, "children": Map<char, node>}.
+fn trie_node() -> Value {
+    let mut node = HashMap::new();
+    node.insert("end".into(), Value::Bool(false));
+    node.insert("children".into(), Value::make_map(HashMap::new()));
+    Value::make_map(node)
+}
+
+fn map_cell(val: &Value) -> Option<&RefCell<HashMap<String, Value>>> {
+    match val {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::Map(map) => Some(map),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn trie_children(node: &Value) -> Result<Value, RuntimeError> {
+    map_cell(node)
+        .and_then(|m| m.borrow().get("children").cloned())
+        .filter(|c| map_cell(c).is_some())
+        .ok_or_else(|| RuntimeError::new("trie: malformed trie node", None))
+}
+
+/// Follows `word` from `node`, returning the node it ends on (if every char exists).
+fn trie_walk(node: &Value, word: &str) -> Result<Option<Value>, RuntimeError> {
+    let mut cur = node.clone();
+    for ch in word.chars() {
+        let children = trie_children(&cur)?;
+        let next = map_cell(&children).and_then(|m| m.borrow().get(&ch.to_string()).cloned());
+        match next {
+            Some(n) => cur = n,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(cur))
+}
+
+fn trie_is_end(node: &Value) -> bool {
+    map_cell(node).is_some_and(|m| matches!(m.borrow().get("end"), Some(Value::Bool(true))))
+}
+
+fn trie_new(_args: Vec<Value>) -> Result<Value, RuntimeError> {
+    Ok(trie_node())
+}
+
+fn trie_insert(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("trie_insert(trie, word)", None));
+    }
+    let word = args[1].as_text().ok_or_else(|| RuntimeError::new("trie_insert: word must be text", None))?;
+    let mut cur = args[0].clone();
+    for ch in word.chars() {
+        let children = trie_children(&cur)?;
+        let cell = map_cell(&children).expect("checked by trie_children");
+        let next = cell.borrow_mut().entry(ch.to_string()).or_insert_with(trie_node).clone();
+        cur = next;
+    }
+    if let Some(m) = map_cell(&cur) {
+        m.borrow_mut().insert("end".into(), Value::Bool(true));
+    }
+    Ok(args[0].clone())
+}
+
+fn trie_contains(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("trie_contains(trie, word)", None));
+    }
+    let word = args[1].as_text().ok_or_else(|| RuntimeError::new("trie_contains: word must be text", None))?;
+    Ok(Value::Bool(trie_walk(&args[0], &word)?.is_some_and(|n| trie_is_end(&n))))
+}
+
+/// All stored words starting with the prefix, sorted.
+fn trie_prefix(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("trie_prefix(trie, prefix)", None));
+    }
+    let prefix = args[1].as_text().ok_or_else(|| RuntimeError::new("trie_prefix: prefix must be text", None))?;
+    let mut words = Vec::new();
+    if let Some(start) = trie_walk(&args[0], &prefix)? {
+        let mut stack = vec![(start, prefix)];
+        while let Some((node, word)) = stack.pop() {
+            if trie_is_end(&node) {
+                words.push(word.clone());
+            }
+            let children = trie_children(&node)?;
+            if let Some(m) = map_cell(&children) {
+                for (ch, child) in m.borrow().iter() {
+                    stack.push((child.clone(), format!("{}{}", word, ch)));
+                }
+            }
+        }
+    }
+    words.sort();
+    Ok(Value::make_list(words.into_iter().map(Value::make_text).collect()))
+}
+
 fn expect_list(val: &Value, msg: &str) -> Result<Vec<Value>, RuntimeError> {
     if let Value::RcObj(rc) = val {
         if let NauxObj::List(list) = rc.as_ref() {
@@ -304,6 +834,15 @@ fn expect_list(val: &Value, msg: &str) -> Result<Vec<Value>, RuntimeError> {
     Err(RuntimeError::new(msg, None))
 }
 
+fn expect_set(val: &Value, msg: &str) -> Result<BTreeSet<Value>, RuntimeError> {
+    if let Value::RcObj(rc) = val {
+        if let NauxObj::Set(set) = rc.as_ref() {
+            return Ok(set.borrow().clone());
+        }
+    }
+    Err(RuntimeError::new(msg, None))
+}
+
 fn expect_map(val: &Value, msg: &str) -> Result<HashMap<String, Value>, RuntimeError> {
     if let Value::RcObj(rc) = val {
         if let NauxObj::Map(map) = rc.as_ref() {
@@ -323,24 +862,102 @@ fn add_values(a: &Value, b: &Value) -> Value {
     }
 }
 
-fn to_min_heap(v: Value) -> Result<BinaryHeap<Reverse<Value>>, RuntimeError> {
-    if let Value::RcObj(rc) = v {
+fn expect_pq(val: &Value, msg: &str) -> Result<Vec<Value>, RuntimeError> {
+    if let Value::RcObj(rc) = val {
         if let NauxObj::PriorityQueue(data) = rc.as_ref() {
-            let mut heap = BinaryHeap::new();
-            for item in data.borrow().iter() {
-                heap.push(Reverse(item.clone()));
-            }
-            return Ok(heap);
+            return Ok(data.borrow().clone());
         }
     }
-    Err(RuntimeError::new("priority queue expected", None))
+    Err(RuntimeError::new(msg, None))
 }
 
-fn from_min_heap(mut heap: BinaryHeap<Reverse<Value>>) -> Vec<Value> {
-    let mut out = Vec::new();
-    while let Some(Reverse(v)) = heap.pop() {
-        out.push(v);
+// Priority queues are stored as a min-max heap: nodes on even depths (the root included) are
+// no greater than their descendants, nodes on odd depths no smaller. The minimum is the root
+// and the maximum one of its children, so both ends can be peeked in O(1) and popped in O(log n).
+
+fn on_min_level(i: usize) -> bool {
+    (i + 1).ilog2().is_multiple_of(2)
+}
+
+/// Index of the largest element, or None when the heap is empty.
+fn minmax_max_index(heap: &[Value]) -> Option<usize> {
+    match heap.len() {
+        0 => None,
+        1 => Some(0),
+        2 => Some(1),
+        _ => Some(if heap[1] >= heap[2] { 1 } else { 2 }),
+    }
+}
+
+fn minmax_push(heap: &mut Vec<Value>, val: Value) {
+    heap.push(val);
+    let i = heap.len() - 1;
+    if i == 0 {
+        return;
+    }
+    let parent = (i - 1) / 2;
+    let min_level = on_min_level(i);
+    // a node on the wrong side of its parent swaps with it and continues on the other level kind
+    if (min_level && heap[i] > heap[parent]) || (!min_level && heap[i] < heap[parent]) {
+        heap.swap(i, parent);
+        minmax_bubble_up(heap, parent, !min_level);
+    } else {
+        minmax_bubble_up(heap, i, min_level);
+    }
+}
+
+/// Moves `heap[i]` up through its grandparents, which lie on the same level kind.
+fn minmax_bubble_up(heap: &mut [Value], mut i: usize, min_level: bool) {
+    while i >= 3 {
+        let grandparent = ((i - 1) / 2 - 1) / 2;
+        let out_of_order = if min_level { heap[i] < heap[grandparent] } else { heap[i] > heap[grandparent] };
+        if !out_of_order {
+            break;
+        }
+        heap.swap(i, grandparent);
+        i = grandparent;
+    }
+}
+
+/// Removes and returns `heap[i]`, which must be the root or one of its children.
+fn minmax_remove(heap: &mut Vec<Value>, i: usize) -> Value {
+    let val = heap.swap_remove(i);
+    if i < heap.len() {
+        minmax_trickle_down(heap, i);
+    }
+    val
+}
+
+/// Restores the heap below `i` by moving `heap[i]` down, two levels at a time.
+fn minmax_trickle_down(heap: &mut [Value], mut i: usize) {
+    let min_level = on_min_level(i);
+    // true when `a` belongs above `b` on this level kind
+    let before = |a: &Value, b: &Value| if min_level { a < b } else { a > b };
+    loop {
+        let first_child = 2 * i + 1;
+        let first_grandchild = 2 * first_child + 1;
+        let children = first_child..first_child + 2;
+        let grandchildren = first_grandchild..first_grandchild + 4;
+        // the most extreme of the up to six descendants on the next two levels
+        let Some(m) = children
+            .chain(grandchildren)
+            .filter(|&d| d < heap.len())
+            .reduce(|a, b| if before(&heap[b], &heap[a]) { b } else { a })
+        else {
+            return;
+        };
+        if !before(&heap[m], &heap[i]) {
+            return;
+        }
+        heap.swap(m, i);
+        if m <= first_child + 1 {
+            // a child sits on the opposite level kind, so nothing below it can be out of order
+            return;
+        }
+        let parent = (m - 1) / 2;
+        if before(&heap[parent], &heap[m]) {
+            heap.swap(m, parent);
+        }
+        i = m;
     }
-    out.reverse();
-    out
 }
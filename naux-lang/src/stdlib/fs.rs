@@ -0,0 +1,41 @@
+use std::fs;
+
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::Value;
+
+/// Builtins that touch the host filesystem; both engines refuse them outside `~ unsafe`.
+pub const UNSAFE_BUILTINS: &[&str] = &["read_file", "write_file"];
+
+pub const UNSAFE_REQUIRED: &str = "file I/O requires unsafe block";
+
+pub fn register_fs(env: &mut Env) {
+    env.set_builtin("read_file", read_file);
+    env.set_builtin("write_file", write_file);
+}
+
+pub fn requires_unsafe(name: &str) -> bool {
+    UNSAFE_BUILTINS.contains(&name)
+}
+
+fn read_file(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let path = match args.as_slice() {
+        [p] => p.as_text().ok_or_else(|| RuntimeError::new("read_file(path)", None))?,
+        _ => return Err(RuntimeError::new("read_file(path)", None)),
+    };
+    fs::read_to_string(&path)
+        .map(Value::make_text)
+        .map_err(|e| RuntimeError::new(format!("read_file: cannot read {}: {}", path, e), None))
+}
+
+fn write_file(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (path, text) = match args.as_slice() {
+        [p, t] => match (p.as_text(), t.as_text()) {
+            (Some(p), Some(t)) => (p, t),
+            _ => return Err(RuntimeError::new("write_file(path, text)", None)),
+        },
+        _ => return Err(RuntimeError::new("write_file(path, text)", None)),
+    };
+    fs::write(&path, text).map_err(|e| RuntimeError::new(format!("write_file: cannot write {}: {}", path, e), None))?;
+    Ok(Value::Null)
+}
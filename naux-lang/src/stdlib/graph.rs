@@ -1,19 +1,62 @@
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
 
 use crate::runtime::env::Env;
 use crate::runtime::error::RuntimeError;
 use crate::runtime::value::{Graph, NauxObj, Value};
+use crate::stdlib::collections::{find_internal, union_internal};
 
 pub fn register_graph(env: &mut Env) {
     env.set_builtin("graph_new", graph_new);
     env.set_builtin("graph_add_edge", graph_add_edge);
     env.set_builtin("graph_neighbors", graph_neighbors);
+    env.set_builtin("graph_remove_edge", graph_remove_edge);
+    env.set_builtin("graph_remove_node", graph_remove_node);
     env.set_builtin("graph_bfs", graph_bfs);
+    env.set_builtin("graph_dfs", graph_dfs);
+    env.set_builtin("graph_dfs_tree", graph_dfs_tree);
+    env.set_builtin("graph_dfs_paths", graph_dfs_paths);
+    env.set_builtin("graph_edge_weight", graph_edge_weight);
+    env.set_builtin("graph_weight_of", graph_edge_weight);
     env.set_builtin("graph_dijkstra", graph_dijkstra);
+    env.set_builtin("graph_shortest_edges", graph_shortest_edges);
     env.set_builtin("graph_scc", graph_scc_tarjan);
     env.set_builtin("graph_toposort", graph_toposort);
+    env.set_builtin("graph_topo_count", graph_topo_count);
     env.set_builtin("graph_floyd_warshall", graph_floyd_warshall);
+    env.set_builtin("graph_has_cycle", graph_has_cycle);
+    env.set_builtin("graph_is_directed", graph_is_directed);
+    env.set_builtin("graph_to_undirected", graph_to_undirected);
+    env.set_builtin("graph_transpose", graph_transpose);
+    env.set_builtin("graph_reverse", graph_transpose);
+    env.set_builtin("graph_components", graph_components);
+    env.set_builtin("graph_connected", graph_connected);
+    env.set_builtin("graph_bridges", graph_bridges);
+    env.set_builtin("graph_articulation_points", graph_articulation_points);
+    env.set_builtin("graph_eulerian_path", graph_eulerian_path);
+    env.set_builtin("graph_max_flow", graph_max_flow);
+    env.set_builtin("graph_min_cut", graph_min_cut);
+    env.set_builtin("graph_kruskal", graph_kruskal);
+    env.set_builtin("graph_prim", graph_prim);
+}
+
+fn expect_graph<'a>(val: &'a Value, msg: &str) -> Result<&'a RefCell<Graph>, RuntimeError> {
+    if let Value::RcObj(rc) = val {
+        if let NauxObj::Graph(g) = rc.as_ref() {
+            return Ok(g);
+        }
+    }
+    Err(RuntimeError::new(msg, None))
+}
+
+/// Every node in sorted order, including ones that only appear as edge targets.
+fn all_nodes(graph: &Graph) -> Vec<String> {
+    let mut nodes: BTreeSet<String> = graph.adj.keys().cloned().collect();
+    for neigh in graph.adj.values() {
+        nodes.extend(neigh.iter().map(|(v, _)| v.clone()));
+    }
+    nodes.into_iter().collect()
 }
 
 fn graph_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
@@ -74,6 +117,40 @@ fn graph_neighbors(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_list(neigh))
 }
 
+/// Removes every u -> v edge in place (both directions when undirected); returns the graph.
+fn graph_remove_edge(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("graph_remove_edge(graph, u, v)", None));
+    }
+    let u = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_remove_edge: u must be text", None))?;
+    let v = args[2].as_text().ok_or_else(|| RuntimeError::new("graph_remove_edge: v must be text", None))?;
+    let mut graph = expect_graph(&args[0], "graph_remove_edge: first arg must be Graph")?.borrow_mut();
+    let directed = graph.directed;
+    if let Some(neigh) = graph.adj.get_mut(&u) {
+        neigh.retain(|(to, _)| *to != v);
+    }
+    if !directed {
+        if let Some(neigh) = graph.adj.get_mut(&v) {
+            neigh.retain(|(to, _)| *to != u);
+        }
+    }
+    Ok(args[0].clone())
+}
+
+/// Removes the node and all incident edges in place; returns the graph.
+fn graph_remove_node(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("graph_remove_node(graph, node)", None));
+    }
+    let node = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_remove_node: node must be text", None))?;
+    let mut graph = expect_graph(&args[0], "graph_remove_node: first arg must be Graph")?.borrow_mut();
+    graph.adj.remove(&node);
+    for neigh in graph.adj.values_mut() {
+        neigh.retain(|(to, _)| *to != node);
+    }
+    Ok(args[0].clone())
+}
+
 fn graph_bfs(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::new("graph_bfs(graph, start) requires 2 args", None));
@@ -109,20 +186,141 @@ fn graph_bfs(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_list(order))
 }
 
-fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
-    if args.len() < 3 {
-        return Err(RuntimeError::new("graph_dijkstra(graph, source, target)", None));
+/// Longest path, in edges, that `graph_dfs_paths` explores.
+const MAX_DFS_PATH_EDGES: usize = 20;
+
+/// Depth-first visit of everything reachable from a start node, with CLRS-style timestamps:
+/// one clock ticks on every discovery and every finish, starting at 1.
+struct DfsTree {
+    order: Vec<String>,
+    parent: HashMap<String, String>,
+    discovery: HashMap<String, i64>,
+    finish: HashMap<String, i64>,
+}
+
+/// Iterative, so deep graphs cannot overflow the stack; neighbors are tried in insertion
+/// order, giving the same preorder as the recursive algorithm.
+fn dfs_tree(graph: &Graph, start: &str) -> DfsTree {
+    let mut tree = DfsTree { order: Vec::new(), parent: HashMap::new(), discovery: HashMap::new(), finish: HashMap::new() };
+    let mut clock = 1;
+    tree.order.push(start.to_string());
+    tree.discovery.insert(start.to_string(), clock);
+    let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+    while let Some((node, next)) = stack.pop() {
+        let neigh = graph.adj.get(node).map(|v| v.as_slice()).unwrap_or(&[]);
+        clock += 1;
+        if let Some((nbr, _)) = neigh.get(next) {
+            stack.push((node, next + 1));
+            if tree.discovery.contains_key(nbr) {
+                clock -= 1; // no event: the edge leads to a node already seen
+                continue;
+            }
+            tree.order.push(nbr.clone());
+            tree.parent.insert(nbr.clone(), node.to_string());
+            tree.discovery.insert(nbr.clone(), clock);
+            stack.push((nbr, 0));
+        } else {
+            tree.finish.insert(node.to_string(), clock);
+        }
     }
-    let g = match &args[0] {
-        Value::RcObj(rc) => match rc.as_ref() {
-            NauxObj::Graph(g) => g,
-            _ => return Err(RuntimeError::new("graph_dijkstra: first arg must be Graph", None)),
-        },
-        _ => return Err(RuntimeError::new("graph_dijkstra: first arg must be Graph", None)),
-    };
-    let source = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_dijkstra: source must be text", None))?;
-    let target = args[2].as_text().ok_or_else(|| RuntimeError::new("graph_dijkstra: target must be text", None))?;
+    tree
+}
 
+fn graph_dfs(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("graph_dfs(graph, start)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_dfs: first arg must be Graph")?.borrow();
+    let start = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_dfs: start must be text", None))?;
+    let order = dfs_tree(&graph, &start).order;
+    Ok(Value::make_list(order.into_iter().map(Value::make_text).collect()))
+}
+
+/// `{order, parent, discovery, finish}`: preorder, each reached node's tree parent (null for
+/// the start) and its discovery/finish times.
+fn graph_dfs_tree(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("graph_dfs_tree(graph, start)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_dfs_tree: first arg must be Graph")?.borrow();
+    let start = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_dfs_tree: start must be text", None))?;
+    let tree = dfs_tree(&graph, &start);
+    let times = |m: HashMap<String, i64>| Value::make_map(m.into_iter().map(|(k, t)| (k, Value::SmallInt(t))).collect());
+    let mut parent: HashMap<String, Value> =
+        tree.parent.into_iter().map(|(k, p)| (k, Value::make_text(p))).collect();
+    parent.insert(start, Value::Null);
+    let mut out = HashMap::new();
+    out.insert("order".to_string(), Value::make_list(tree.order.into_iter().map(Value::make_text).collect()));
+    out.insert("parent".to_string(), Value::make_map(parent));
+    out.insert("discovery".to_string(), times(tree.discovery));
+    out.insert("finish".to_string(), times(tree.finish));
+    Ok(Value::make_map(out))
+}
+
+/// Every simple path from `start` to `end` with at most `MAX_DFS_PATH_EDGES` edges, found by
+/// backtracking in neighbor insertion order.
+fn graph_dfs_paths(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("graph_dfs_paths(graph, start, end)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_dfs_paths: first arg must be Graph")?.borrow();
+    let start = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_dfs_paths: start must be text", None))?;
+    let end = args[2].as_text().ok_or_else(|| RuntimeError::new("graph_dfs_paths: end must be text", None))?;
+    let mut paths = Vec::new();
+    let mut path = vec![start.as_str()];
+    let mut on_path: HashSet<&str> = HashSet::from([start.as_str()]);
+    // (node, index of the next neighbor to try), mirroring `path`
+    let mut stack: Vec<(&str, usize)> = vec![(start.as_str(), 0)];
+    while let Some((node, next)) = stack.pop() {
+        if node == end {
+            paths.push(Value::make_list(path.iter().map(|n| Value::make_text(*n)).collect()));
+        }
+        let neigh = graph.adj.get(node).map(|v| v.as_slice()).unwrap_or(&[]);
+        let can_extend = node != end && path.len() <= MAX_DFS_PATH_EDGES;
+        match neigh.get(next).filter(|_| can_extend) {
+            Some((nbr, _)) => {
+                stack.push((node, next + 1));
+                if on_path.insert(nbr.as_str()) {
+                    path.push(nbr);
+                    stack.push((nbr, 0));
+                }
+            }
+            None => {
+                on_path.remove(node);
+                path.pop();
+            }
+        }
+    }
+    Ok(Value::make_list(paths))
+}
+
+/// Weight of the edge u -> v (the minimum among parallel edges), or null if absent.
+/// Whole-number weights come back as Int, others as Float.
+fn graph_edge_weight(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("graph_edge_weight(graph, u, v)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_edge_weight: first arg must be Graph")?.borrow();
+    let u = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_edge_weight: u must be text", None))?;
+    let v = args[2].as_text().ok_or_else(|| RuntimeError::new("graph_edge_weight: v must be text", None))?;
+    let weight = graph
+        .adj
+        .get(&u)
+        .into_iter()
+        .flatten()
+        .filter(|(to, _)| *to == v)
+        .map(|(_, w)| *w)
+        .reduce(f64::min);
+    Ok(match weight {
+        Some(w) if w.fract() == 0.0 && w.abs() < i64::MAX as f64 => Value::SmallInt(w as i64),
+        Some(w) => Value::Float(w),
+        None => Value::Null,
+    })
+}
+
+/// Dijkstra from `source`: returns distances and, for each reached node, the
+/// predecessor on its shortest path together with the weight of that edge.
+fn dijkstra(graph: &Graph, source: &str) -> (HashMap<String, f64>, HashMap<String, (String, f64)>) {
     #[derive(Clone)]
     struct State {
         cost: f64,
@@ -146,18 +344,17 @@ fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
         }
     }
 
-    let graph = g.borrow();
     let mut dist: HashMap<String, f64> = HashMap::new();
-    let mut prev: HashMap<String, String> = HashMap::new();
+    let mut prev: HashMap<String, (String, f64)> = HashMap::new();
     for n in graph.adj.keys() {
         dist.insert(n.clone(), f64::INFINITY);
     }
-    dist.insert(source.clone(), 0.0);
+    dist.insert(source.to_string(), 0.0);
 
     let mut heap = BinaryHeap::new();
     heap.push(State {
         cost: 0.0,
-        node: source.clone(),
+        node: source.to_string(),
     });
 
     while let Some(State { cost, node }) = heap.pop() {
@@ -169,7 +366,7 @@ fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
                 let next = cost + *w;
                 if next < *dist.get(nbr).unwrap_or(&f64::INFINITY) {
                     dist.insert(nbr.clone(), next);
-                    prev.insert(nbr.clone(), node.clone());
+                    prev.insert(nbr.clone(), (node.clone(), *w));
                     heap.push(State {
                         cost: next,
                         node: nbr.clone(),
@@ -178,6 +375,25 @@ fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
             }
         }
     }
+    (dist, prev)
+}
+
+fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() < 3 {
+        return Err(RuntimeError::new("graph_dijkstra(graph, source, target)", None));
+    }
+    let g = match &args[0] {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::Graph(g) => g,
+            _ => return Err(RuntimeError::new("graph_dijkstra: first arg must be Graph", None)),
+        },
+        _ => return Err(RuntimeError::new("graph_dijkstra: first arg must be Graph", None)),
+    };
+    let source = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_dijkstra: source must be text", None))?;
+    let target = args[2].as_text().ok_or_else(|| RuntimeError::new("graph_dijkstra: target must be text", None))?;
+
+    let graph = g.borrow();
+    let (dist, prev) = dijkstra(&graph, &source);
 
     if !dist.contains_key(&target) || dist[&target].is_infinite() {
         return Ok(Value::Null);
@@ -186,7 +402,7 @@ fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
     let mut path_nodes = Vec::new();
     let mut cur = target.clone();
     path_nodes.push(Value::make_text(cur.clone()));
-    while let Some(p) = prev.get(&cur) {
+    while let Some((p, _)) = prev.get(&cur) {
         cur = p.clone();
         path_nodes.push(Value::make_text(cur.clone()));
     }
@@ -199,6 +415,34 @@ fn graph_dijkstra(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_map(map))
 }
 
+/// Edges `{u, v, w}` along the shortest path from source to target, or null if unreachable.
+fn graph_shortest_edges(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("graph_shortest_edges(graph, source, target)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_shortest_edges: first arg must be Graph")?.borrow();
+    let source = args[1].as_text().ok_or_else(|| RuntimeError::new("graph_shortest_edges: source must be text", None))?;
+    let target = args[2].as_text().ok_or_else(|| RuntimeError::new("graph_shortest_edges: target must be text", None))?;
+
+    let (dist, prev) = dijkstra(&graph, &source);
+    if dist.get(&target).is_none_or(|d| d.is_infinite()) {
+        return Ok(Value::Null);
+    }
+
+    let mut edges = Vec::new();
+    let mut cur = target;
+    while let Some((p, w)) = prev.get(&cur) {
+        let mut edge = HashMap::new();
+        edge.insert("u".to_string(), Value::make_text(p.clone()));
+        edge.insert("v".to_string(), Value::make_text(cur.clone()));
+        edge.insert("w".to_string(), Value::Float(*w));
+        edges.push(Value::make_map(edge));
+        cur = p.clone();
+    }
+    edges.reverse();
+    Ok(Value::make_list(edges))
+}
+
 // --- SCC (Tarjan) ---
 fn graph_scc_tarjan(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
@@ -337,6 +581,52 @@ fn graph_toposort(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::make_list(order))
 }
 
+/// Largest node count `graph_topo_count` accepts; the subset DP is O(2^n * n).
+const TOPO_COUNT_MAX_NODES: usize = 20;
+
+/// Number of distinct topological orderings, via DP over subsets of already-placed nodes.
+fn graph_topo_count(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_topo_count(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_topo_count: first arg must be Graph")?.borrow();
+    if !graph.directed {
+        return Err(RuntimeError::new("graph_topo_count requires directed graph", None));
+    }
+    let nodes = all_nodes(&graph);
+    let n = nodes.len();
+    if n > TOPO_COUNT_MAX_NODES {
+        return Err(RuntimeError::new(
+            format!("graph_topo_count: graph has {} nodes (max {})", n, TOPO_COUNT_MAX_NODES),
+            None,
+        ));
+    }
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+    // preds[v]: bitmask of nodes that must come before v
+    let mut preds = vec![0u32; n];
+    for (u, neigh) in &graph.adj {
+        for (v, _) in neigh {
+            preds[index[v.as_str()]] |= 1 << index[u.as_str()];
+        }
+    }
+    let mut ways = vec![0u64; 1 << n];
+    ways[0] = 1;
+    for mask in 0..(1usize << n) {
+        if ways[mask] == 0 {
+            continue;
+        }
+        for (v, &p) in preds.iter().enumerate() {
+            if mask & (1 << v) == 0 && (p as usize) & !mask == 0 {
+                ways[mask | (1 << v)] += ways[mask];
+            }
+        }
+    }
+    match ways[(1 << n) - 1] {
+        0 => Err(RuntimeError::new("graph_topo_count: cycle detected", None)),
+        count => Ok(Value::SmallInt(count as i64)),
+    }
+}
+
 // --- Floyd-Warshall ---
 fn graph_floyd_warshall(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
@@ -400,3 +690,690 @@ fn graph_floyd_warshall(args: Vec<Value>) -> Result<Value, RuntimeError> {
     }
     Ok(Value::make_map(outer))
 }
+
+// --- Cycle detection ---
+fn graph_has_cycle(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_has_cycle(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_has_cycle: first arg must be Graph")?.borrow();
+    let found = if graph.directed { directed_has_cycle(&graph) } else { undirected_has_cycle(&graph) };
+    Ok(Value::Bool(found))
+}
+
+/// Iterative DFS with white/gray/black coloring; reaching a gray node means a back edge.
+fn directed_has_cycle(graph: &Graph) -> bool {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Color {
+        Gray,
+        Black,
+    }
+    let mut color: HashMap<&str, Color> = HashMap::new();
+    let nodes = all_nodes(graph);
+    for start in &nodes {
+        if color.contains_key(start.as_str()) {
+            continue;
+        }
+        color.insert(start, Color::Gray);
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+        while let Some((node, next)) = stack.pop() {
+            let neigh = graph.adj.get(node).map(|v| v.as_slice()).unwrap_or(&[]);
+            if let Some((nbr, _)) = neigh.get(next) {
+                stack.push((node, next + 1));
+                match color.get(nbr.as_str()) {
+                    Some(Color::Gray) => return true,
+                    Some(Color::Black) => {}
+                    None => {
+                        color.insert(nbr, Color::Gray);
+                        stack.push((nbr, 0));
+                    }
+                }
+            } else {
+                color.insert(node, Color::Black);
+            }
+        }
+    }
+    false
+}
+
+/// Union-find over edges; each undirected edge is stored in both directions, so only `u <= v` is used.
+fn undirected_has_cycle(graph: &Graph) -> bool {
+    let nodes = all_nodes(graph);
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+    let mut parent: Vec<Value> = (0..nodes.len()).map(|i| Value::SmallInt(i as i64)).collect();
+    let mut rank = vec![Value::SmallInt(0); nodes.len()];
+    for (u, neigh) in &graph.adj {
+        for (v, _) in neigh {
+            if u > v {
+                continue;
+            }
+            if u == v {
+                return true;
+            }
+            let (a, b) = (index[u.as_str()], index[v.as_str()]);
+            if find_internal(a, &mut parent) == find_internal(b, &mut parent) {
+                return true;
+            }
+            union_internal(a, b, &mut parent, &mut rank);
+        }
+    }
+    false
+}
+
+// --- Transpose ---
+/// New graph with every edge reversed (weights kept); undirected graphs are simply copied.
+fn graph_transpose(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_transpose(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_transpose: first arg must be Graph")?.borrow();
+    if !graph.directed {
+        return Ok(Value::make_graph(graph.clone()));
+    }
+    let mut adj: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+    // keep every node present, even those left without outgoing edges
+    for node in all_nodes(&graph) {
+        adj.insert(node, Vec::new());
+    }
+    let mut sources: Vec<&String> = graph.adj.keys().collect();
+    sources.sort();
+    for u in sources {
+        for (v, w) in &graph.adj[u] {
+            adj.entry(v.clone()).or_default().push((u.clone(), *w));
+        }
+    }
+    Ok(Value::make_graph(Graph { directed: true, adj }))
+}
+
+fn graph_is_directed(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_is_directed(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_is_directed: first arg must be Graph")?.borrow();
+    Ok(Value::Bool(graph.directed))
+}
+
+/// New undirected copy: every edge is symmetrized and parallel edges collapse to the minimum weight.
+fn graph_to_undirected(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_to_undirected(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_to_undirected: first arg must be Graph")?.borrow();
+    let mut pairs: HashMap<(String, String), f64> = HashMap::new();
+    for (u, neigh) in &graph.adj {
+        for (v, w) in neigh {
+            let key = if u <= v { (u.clone(), v.clone()) } else { (v.clone(), u.clone()) };
+            let best = pairs.entry(key).or_insert(*w);
+            *best = best.min(*w);
+        }
+    }
+    let mut adj: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+    for node in all_nodes(&graph) {
+        adj.insert(node, Vec::new());
+    }
+    let mut pairs: Vec<_> = pairs.into_iter().collect();
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    // same layout as graph_add_edge on an undirected graph (a self-loop is listed twice)
+    for ((u, v), w) in pairs {
+        adj.entry(u.clone()).or_default().push((v.clone(), w));
+        adj.entry(v).or_default().push((u, w));
+    }
+    Ok(Value::make_graph(Graph { directed: false, adj }))
+}
+
+// --- Connected components ---
+/// Components of the graph, treating directed edges as undirected (weak connectivity).
+/// Each component is sorted by node name, and components are ordered by their first node.
+fn components(graph: &Graph) -> Vec<Vec<String>> {
+    let nodes = all_nodes(graph);
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+    let mut parent: Vec<Value> = (0..nodes.len()).map(|i| Value::SmallInt(i as i64)).collect();
+    let mut rank = vec![Value::SmallInt(0); nodes.len()];
+    for (u, neigh) in &graph.adj {
+        for (v, _) in neigh {
+            union_internal(index[u.as_str()], index[v.as_str()], &mut parent, &mut rank);
+        }
+    }
+    // nodes are already sorted, so grouping in order keeps everything deterministic
+    let mut slot: HashMap<usize, usize> = HashMap::new();
+    let mut comps: Vec<Vec<String>> = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let root = find_internal(i, &mut parent);
+        let k = *slot.entry(root).or_insert_with(|| {
+            comps.push(Vec::new());
+            comps.len() - 1
+        });
+        comps[k].push(node.clone());
+    }
+    comps
+}
+
+fn graph_components(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_components(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_components: first arg must be Graph")?.borrow();
+    let list = components(&graph)
+        .into_iter()
+        .map(|comp| Value::make_list(comp.into_iter().map(Value::make_text).collect()))
+        .collect();
+    Ok(Value::make_list(list))
+}
+
+fn graph_connected(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_connected(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_connected: first arg must be Graph")?.borrow();
+    Ok(Value::Bool(components(&graph).len() <= 1))
+}
+
+// --- Bridges and articulation points (Tarjan low-link) ---
+
+/// Bridges as sorted (u, v) pairs with u < v, and the sorted cut vertices.
+struct CutEdgesAndVertices {
+    bridges: Vec<(String, String)>,
+    articulation: BTreeSet<String>,
+}
+
+/// Iterative low-link DFS from every unvisited node (sorted), O(V + E). Only one copy of the
+/// edge back to the tree parent is skipped, so parallel edges are never bridges.
+fn low_link(graph: &Graph) -> CutEdgesAndVertices {
+    let mut out = CutEdgesAndVertices { bridges: Vec::new(), articulation: BTreeSet::new() };
+    let mut disc: HashMap<String, usize> = HashMap::new();
+    let mut low: HashMap<String, usize> = HashMap::new();
+    let mut timer = 0;
+    for root in all_nodes(graph) {
+        if disc.contains_key(&root) {
+            continue;
+        }
+        disc.insert(root.clone(), timer);
+        low.insert(root.clone(), timer);
+        timer += 1;
+        let mut root_children = 0;
+        // (node, tree parent, next neighbor index, parent edge already skipped)
+        let mut stack: Vec<(String, Option<String>, usize, bool)> = vec![(root.clone(), None, 0, false)];
+        while let Some((node, parent, next, skipped)) = stack.last_mut() {
+            let neigh = graph.adj.get(node.as_str()).map(|v| v.as_slice()).unwrap_or(&[]);
+            if let Some((v, _)) = neigh.get(*next) {
+                *next += 1;
+                if !*skipped && parent.as_deref() == Some(v.as_str()) {
+                    *skipped = true;
+                    continue;
+                }
+                if let Some(&dv) = disc.get(v) {
+                    let lu = low.get_mut(node.as_str()).unwrap();
+                    *lu = (*lu).min(dv);
+                    continue;
+                }
+                if *node == root {
+                    root_children += 1;
+                }
+                let node = node.clone();
+                disc.insert(v.clone(), timer);
+                low.insert(v.clone(), timer);
+                timer += 1;
+                stack.push((v.clone(), Some(node), 0, false));
+            } else {
+                let (node, parent, _, _) = stack.pop().unwrap();
+                if let Some(p) = parent {
+                    let ln = low[&node];
+                    let lp = low.get_mut(&p).unwrap();
+                    *lp = (*lp).min(ln);
+                    if ln > disc[&p] {
+                        out.bridges.push(if p < node { (p.clone(), node) } else { (node, p.clone()) });
+                    }
+                    if p != root && ln >= disc[&p] {
+                        out.articulation.insert(p);
+                    }
+                }
+            }
+        }
+        // the DFS root is a cut vertex exactly when it has more than one tree child
+        if root_children > 1 {
+            out.articulation.insert(root);
+        }
+    }
+    out.bridges.sort();
+    out
+}
+
+fn expect_undirected<'a>(val: &'a Value, name: &str) -> Result<std::cell::Ref<'a, Graph>, RuntimeError> {
+    let graph = expect_graph(val, &format!("{}: first arg must be Graph", name))?.borrow();
+    if graph.directed {
+        return Err(RuntimeError::new(format!("{}: graph must be undirected", name), None));
+    }
+    Ok(graph)
+}
+
+/// `[{from, to}]`: edges whose removal disconnects their component, sorted with from < to.
+fn graph_bridges(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_bridges(graph)", None));
+    }
+    let graph = expect_undirected(&args[0], "graph_bridges")?;
+    let bridges = low_link(&graph)
+        .bridges
+        .into_iter()
+        .map(|(from, to)| {
+            let mut edge = HashMap::new();
+            edge.insert("from".to_string(), Value::make_text(from));
+            edge.insert("to".to_string(), Value::make_text(to));
+            Value::make_map(edge)
+        })
+        .collect();
+    Ok(Value::make_list(bridges))
+}
+
+/// Sorted nodes whose removal disconnects their component.
+fn graph_articulation_points(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_articulation_points(graph)", None));
+    }
+    let graph = expect_undirected(&args[0], "graph_articulation_points")?;
+    let points = low_link(&graph).articulation.into_iter().map(Value::make_text).collect();
+    Ok(Value::make_list(points))
+}
+
+// --- Eulerian path (Hierholzer) ---
+/// Node sequence using every edge exactly once, or null if none exists.
+/// Parallel edges are each consumed once; neighbours are visited in name order.
+fn graph_eulerian_path(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_eulerian_path(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_eulerian_path: first arg must be Graph")?.borrow();
+    let nodes = all_nodes(&graph);
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+
+    // edge list; undirected edges are stored twice in `adj`, so keep one copy each
+    let mut edges: Vec<(usize, usize)> = Vec::new();
+    for (u, neigh) in &graph.adj {
+        let ui = index[u.as_str()];
+        let mut self_loops = 0;
+        for (v, _) in neigh {
+            let vi = index[v.as_str()];
+            if graph.directed || ui < vi {
+                edges.push((ui, vi));
+            } else if ui == vi {
+                // an undirected self-loop appears twice in its own list
+                self_loops += 1;
+                if self_loops % 2 == 1 {
+                    edges.push((ui, vi));
+                }
+            }
+        }
+    }
+    if edges.is_empty() {
+        return Ok(Value::make_list(Vec::new()));
+    }
+
+    let n = nodes.len();
+    let mut incident: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    let mut out_deg = vec![0i64; n];
+    let mut in_deg = vec![0i64; n];
+    for (id, &(u, v)) in edges.iter().enumerate() {
+        incident[u].push((v, id));
+        out_deg[u] += 1;
+        in_deg[v] += 1;
+        if !graph.directed && u != v {
+            incident[v].push((u, id));
+        }
+    }
+    for list in incident.iter_mut() {
+        list.sort();
+    }
+
+    let has_edges = |i: usize| out_deg[i] + in_deg[i] > 0;
+    let mut start = (0..n).find(|&i| has_edges(i)).unwrap_or(0);
+    if graph.directed {
+        let mut starts = 0;
+        let mut ends = 0;
+        for i in 0..n {
+            match out_deg[i] - in_deg[i] {
+                0 => {}
+                1 => {
+                    starts += 1;
+                    start = i;
+                }
+                -1 => ends += 1,
+                _ => return Ok(Value::Null),
+            }
+        }
+        if starts > 1 || ends > 1 || starts != ends {
+            return Ok(Value::Null);
+        }
+    } else {
+        let odd: Vec<usize> = (0..n).filter(|&i| (out_deg[i] + in_deg[i]) % 2 == 1).collect();
+        match odd.len() {
+            0 => {}
+            2 => start = odd[0],
+            _ => return Ok(Value::Null),
+        }
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut next = vec![0usize; n];
+    let mut stack = vec![start];
+    let mut path: Vec<usize> = Vec::new();
+    while let Some(&u) = stack.last() {
+        while next[u] < incident[u].len() && used[incident[u][next[u]].1] {
+            next[u] += 1;
+        }
+        if let Some(&(v, id)) = incident[u].get(next[u]) {
+            used[id] = true;
+            stack.push(v);
+        } else {
+            path.push(u);
+            stack.pop();
+        }
+    }
+    // a disconnected edge set leaves edges unused
+    if path.len() != edges.len() + 1 {
+        return Ok(Value::Null);
+    }
+    path.reverse();
+    Ok(Value::make_list(path.into_iter().map(|i| Value::make_text(nodes[i].clone())).collect()))
+}
+
+// --- Max flow (Dinic) / min cut ---
+
+/// Residual network: edge `e` and its reverse `e ^ 1` are stored side by side.
+struct FlowNetwork {
+    nodes: Vec<String>,
+    adj: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    cap: Vec<f64>,
+}
+
+impl FlowNetwork {
+    /// Edge weights are capacities; the graph must be directed.
+    fn from_graph(graph: &Graph, name: &str) -> Result<FlowNetwork, RuntimeError> {
+        if !graph.directed {
+            return Err(RuntimeError::new(format!("{}: graph must be directed", name), None));
+        }
+        let nodes = all_nodes(graph);
+        let mut net = FlowNetwork { adj: vec![Vec::new(); nodes.len()], to: Vec::new(), cap: Vec::new(), nodes };
+        let mut sources: Vec<&String> = graph.adj.keys().collect();
+        sources.sort();
+        for u in sources {
+            for (v, w) in &graph.adj[u] {
+                if *w < 0.0 {
+                    return Err(RuntimeError::new(format!("{}: capacities must be non-negative", name), None));
+                }
+                let (ui, vi) = (net.index_of(u).unwrap(), net.index_of(v).unwrap());
+                net.adj[ui].push(net.to.len());
+                net.to.push(vi);
+                net.cap.push(*w);
+                net.adj[vi].push(net.to.len());
+                net.to.push(ui);
+                net.cap.push(0.0);
+            }
+        }
+        Ok(net)
+    }
+
+    /// `nodes` is sorted, as returned by `all_nodes`.
+    fn index_of(&self, node: &str) -> Option<usize> {
+        self.nodes.binary_search_by(|n| n.as_str().cmp(node)).ok()
+    }
+
+    /// BFS distances from `s` over edges with residual capacity (`None` = unreachable).
+    fn levels(&self, s: usize) -> Vec<Option<usize>> {
+        let mut level = vec![None; self.nodes.len()];
+        level[s] = Some(0);
+        let mut queue = VecDeque::from([s]);
+        while let Some(u) = queue.pop_front() {
+            for &e in &self.adj[u] {
+                let v = self.to[e];
+                if self.cap[e] > 0.0 && level[v].is_none() {
+                    level[v] = level[u].map(|l| l + 1);
+                    queue.push_back(v);
+                }
+            }
+        }
+        level
+    }
+
+    /// Dinic: repeated BFS level graphs, each saturated by an iterative blocking-flow DFS.
+    fn max_flow(&mut self, s: usize, t: usize) -> f64 {
+        let mut flow = 0.0;
+        loop {
+            let level = self.levels(s);
+            if level[t].is_none() {
+                return flow;
+            }
+            let mut next = vec![0usize; self.nodes.len()];
+            let mut path: Vec<usize> = Vec::new();
+            loop {
+                let u = path.last().map_or(s, |&e| self.to[e]);
+                if u == t {
+                    let bottleneck = path.iter().map(|&e| self.cap[e]).fold(f64::INFINITY, f64::min);
+                    for &e in &path {
+                        self.cap[e] -= bottleneck;
+                        self.cap[e ^ 1] += bottleneck;
+                    }
+                    flow += bottleneck;
+                    // resume from the tail of the first saturated edge
+                    let first_saturated = path.iter().position(|&e| self.cap[e] <= 0.0).unwrap_or(0);
+                    path.truncate(first_saturated);
+                    continue;
+                }
+                let mut advanced = false;
+                while next[u] < self.adj[u].len() {
+                    let e = self.adj[u][next[u]];
+                    let v = self.to[e];
+                    if self.cap[e] > 0.0 && level[v] == level[u].map(|l| l + 1) {
+                        path.push(e);
+                        advanced = true;
+                        break;
+                    }
+                    next[u] += 1;
+                }
+                if advanced {
+                    continue;
+                }
+                // dead end: retreat and skip the edge that led here
+                match path.pop() {
+                    Some(e) => next[self.to[e ^ 1]] += 1,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Remaining capacities as a directed graph; parallel residual edges are merged.
+    fn residual_graph(&self) -> Graph {
+        let mut adj = HashMap::new();
+        for (u, edges) in self.adj.iter().enumerate() {
+            let mut out: BTreeMap<usize, f64> = BTreeMap::new();
+            for &e in edges {
+                if self.cap[e] > 0.0 {
+                    *out.entry(self.to[e]).or_insert(0.0) += self.cap[e];
+                }
+            }
+            let neigh = out.into_iter().map(|(v, c)| (self.nodes[v].clone(), c)).collect();
+            adj.insert(self.nodes[u].clone(), neigh);
+        }
+        Graph { directed: true, adj }
+    }
+}
+
+fn flow_endpoints(args: &[Value], name: &str) -> Result<(String, String), RuntimeError> {
+    let source = args[1].as_text().ok_or_else(|| RuntimeError::new(format!("{}: source must be text", name), None))?;
+    let sink = args[2].as_text().ok_or_else(|| RuntimeError::new(format!("{}: sink must be text", name), None))?;
+    Ok((source, sink))
+}
+
+/// `{flow, residual}`; a missing or unreachable sink gives a flow of 0.
+fn graph_max_flow(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("graph_max_flow(graph, source, sink)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_max_flow: first arg must be Graph")?.borrow();
+    let (source, sink) = flow_endpoints(&args, "graph_max_flow")?;
+    let mut net = FlowNetwork::from_graph(&graph, "graph_max_flow")?;
+    let flow = match (net.index_of(&source), net.index_of(&sink)) {
+        (Some(s), Some(t)) if s != t => net.max_flow(s, t),
+        _ => 0.0,
+    };
+    let mut out = HashMap::new();
+    out.insert("flow".to_string(), Value::Float(flow));
+    out.insert("residual".to_string(), Value::make_graph(net.residual_graph()));
+    Ok(Value::make_map(out))
+}
+
+/// `{cut_value, source_side, sink_side}`: after a max flow, the source side is every node still
+/// reachable from `source` in the residual network, and the cut value equals the flow.
+fn graph_min_cut(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("graph_min_cut(graph, source, sink)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_min_cut: first arg must be Graph")?.borrow();
+    let (source, sink) = flow_endpoints(&args, "graph_min_cut")?;
+    if source == sink {
+        return Err(RuntimeError::new("graph_min_cut: source and sink must differ", None));
+    }
+    let mut net = FlowNetwork::from_graph(&graph, "graph_min_cut")?;
+    let (cut_value, reachable) = match (net.index_of(&source), net.index_of(&sink)) {
+        (Some(s), Some(t)) => (net.max_flow(s, t), net.levels(s)),
+        (Some(s), None) => (0.0, net.levels(s)),
+        (None, _) => (0.0, vec![None; net.nodes.len()]),
+    };
+    let (mut source_side, mut sink_side) = (Vec::new(), Vec::new());
+    for (node, level) in net.nodes.iter().zip(reachable) {
+        let side = if level.is_some() { &mut source_side } else { &mut sink_side };
+        side.push(Value::make_text(node.clone()));
+    }
+    let mut out = HashMap::new();
+    out.insert("cut_value".to_string(), Value::Float(cut_value));
+    out.insert("source_side".to_string(), Value::make_list(source_side));
+    out.insert("sink_side".to_string(), Value::make_list(sink_side));
+    Ok(Value::make_map(out))
+}
+
+// --- Minimum spanning tree (Kruskal, Prim) ---
+/// `{edges: [{from, to, weight}], weight}` for a spanning forest given as (from, to, weight) triples.
+fn mst_result(edges: Vec<(String, String, f64)>) -> Value {
+    let total: f64 = edges.iter().map(|(_, _, w)| w).sum();
+    let edges = edges
+        .into_iter()
+        .map(|(from, to, w)| {
+            let mut edge = HashMap::new();
+            edge.insert("from".to_string(), Value::make_text(from));
+            edge.insert("to".to_string(), Value::make_text(to));
+            edge.insert("weight".to_string(), Value::Float(w));
+            Value::make_map(edge)
+        })
+        .collect();
+    let mut out = HashMap::new();
+    out.insert("edges".to_string(), Value::make_list(edges));
+    out.insert("weight".to_string(), Value::Float(total));
+    Value::make_map(out)
+}
+
+/// Kruskal over the `dsu_*` union-find; ties on weight are broken by (from, to) so the result
+/// is deterministic. Disconnected graphs yield a spanning forest.
+fn graph_kruskal(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("graph_kruskal(graph)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_kruskal: first arg must be Graph")?.borrow();
+    if graph.directed {
+        return Err(RuntimeError::new("graph_kruskal: graph must be undirected", None));
+    }
+    let nodes = all_nodes(&graph);
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+    // each undirected edge is stored in both directions, so only u < v is considered
+    let mut edges: Vec<(&str, &str, f64)> = graph
+        .adj
+        .iter()
+        .flat_map(|(u, neigh)| neigh.iter().map(move |(v, w)| (u.as_str(), v.as_str(), *w)))
+        .filter(|(u, v, _)| u < v)
+        .collect();
+    edges.sort_by(|a, b| a.2.total_cmp(&b.2).then_with(|| (a.0, a.1).cmp(&(b.0, b.1))));
+
+    let mut parent: Vec<Value> = (0..nodes.len()).map(|i| Value::SmallInt(i as i64)).collect();
+    let mut rank = vec![Value::SmallInt(0); nodes.len()];
+    let mut tree = Vec::new();
+    for (u, v, w) in edges {
+        let (a, b) = (index[u], index[v]);
+        if find_internal(a, &mut parent) != find_internal(b, &mut parent) {
+            union_internal(a, b, &mut parent, &mut rank);
+            tree.push((u.to_string(), v.to_string(), w));
+        }
+    }
+    Ok(mst_result(tree))
+}
+
+/// Lazy Prim with a binary heap, grown from `start` (default: the smallest node) and restarted
+/// from the smallest unreached node until every component is spanned.
+fn graph_prim(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    #[derive(PartialEq)]
+    struct Candidate {
+        weight: f64,
+        from: String,
+        to: String,
+    }
+    impl Eq for Candidate {}
+    impl Ord for Candidate {
+        fn cmp(&self, other: &Self) -> Ordering {
+            // reversed for min-heap behavior, ties broken by (from, to)
+            other
+                .weight
+                .total_cmp(&self.weight)
+                .then_with(|| (&other.from, &other.to).cmp(&(&self.from, &self.to)))
+        }
+    }
+    impl PartialOrd for Candidate {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    if args.is_empty() || args.len() > 2 {
+        return Err(RuntimeError::new("graph_prim(graph, start?)", None));
+    }
+    let graph = expect_graph(&args[0], "graph_prim: first arg must be Graph")?.borrow();
+    if graph.directed {
+        return Err(RuntimeError::new("graph_prim: graph must be undirected", None));
+    }
+    let mut roots = all_nodes(&graph);
+    match args.get(1) {
+        None | Some(Value::Null) => {}
+        Some(v) => {
+            let start = v.as_text().ok_or_else(|| RuntimeError::new("graph_prim: start must be text", None))?;
+            let pos = roots
+                .iter()
+                .position(|n| *n == start)
+                .ok_or_else(|| RuntimeError::new(format!("graph_prim: unknown start node '{}'", start), None))?;
+            let start = roots.remove(pos);
+            roots.insert(0, start);
+        }
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut tree = Vec::new();
+    for root in roots {
+        if !visited.insert(root.clone()) {
+            continue;
+        }
+        let mut heap = BinaryHeap::new();
+        let push_edges = |heap: &mut BinaryHeap<Candidate>, visited: &HashSet<String>, node: &str| {
+            for (nbr, w) in graph.adj.get(node).into_iter().flatten() {
+                if !visited.contains(nbr) {
+                    heap.push(Candidate { weight: *w, from: node.to_string(), to: nbr.clone() });
+                }
+            }
+        };
+        push_edges(&mut heap, &visited, &root);
+        while let Some(Candidate { weight, from, to }) = heap.pop() {
+            if !visited.insert(to.clone()) {
+                continue;
+            }
+            push_edges(&mut heap, &visited, &to);
+            tree.push((from, to, weight));
+        }
+    }
+    Ok(mst_result(tree))
+}
@@ -1 +1,451 @@
-// TODO: list helpers
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::{total_cmp, NauxObj, Value};
+
+pub fn register_list(env: &mut Env) {
+    env.set_builtin("list_sort", list_sort);
+    env.set_builtin("list_sort_by_key", list_sort_by_key);
+    env.set_builtin("list_lcs", list_lcs);
+    env.set_builtin("list_diff", list_diff);
+    env.set_builtin("list_patch", list_patch);
+    env.set_builtin("list_push", list_push);
+    env.set_builtin("list_pop", list_pop);
+    env.set_builtin("list_shift", list_shift);
+    env.set_builtin("list_unshift", list_unshift);
+    env.set_builtin("list_slice", list_slice);
+    env.set_builtin("list_concat", list_concat);
+    env.set_builtin("list_reverse", list_reverse);
+    env.set_builtin("list_flatten", list_flatten);
+    env.set_builtin("list_zip", list_zip);
+    env.set_builtin("list_unique", list_unique);
+    // the higher-order functions need to call back into the engine, which intercepts them
+    // before builtin dispatch; these entries only make the names known (check, completion)
+    env.set_builtin("list_map", |_| Err(RuntimeError::new("list_map must be called directly", None)));
+    env.set_builtin("list_filter", |_| Err(RuntimeError::new("list_filter must be called directly", None)));
+    env.set_builtin("list_reduce", |_| Err(RuntimeError::new("list_reduce must be called directly", None)));
+    env.set_builtin("list_find", |_| Err(RuntimeError::new("list_find must be called directly", None)));
+    env.set_builtin("list_find_index", |_| Err(RuntimeError::new("list_find_index must be called directly", None)));
+    env.set_builtin("list_every", |_| Err(RuntimeError::new("list_every must be called directly", None)));
+    env.set_builtin("list_some", |_| Err(RuntimeError::new("list_some must be called directly", None)));
+    env.set_builtin("list_flat_map", |_| Err(RuntimeError::new("list_flat_map must be called directly", None)));
+    env.set_builtin("list_sort_by", |_| Err(RuntimeError::new("list_sort_by must be called directly", None)));
+}
+
+/// List functions taking a callback as their second argument. Both engines dispatch these
+/// through `call_higher_order`, passing a closure that calls a function value with arguments.
+pub const HIGHER_ORDER: &[&str] = &[
+    "list_map",
+    "list_filter",
+    "list_reduce",
+    "list_find",
+    "list_find_index",
+    "list_every",
+    "list_some",
+    "list_flat_map",
+    "list_sort_by",
+];
+
+pub fn is_higher_order(name: &str) -> bool {
+    HIGHER_ORDER.contains(&name)
+}
+
+/// Calls a callback `f` with arguments on behalf of a higher-order list function.
+pub type CallbackFn<'a> = dyn FnMut(&Value, Vec<Value>) -> Result<Value, RuntimeError> + 'a;
+
+/// Runs the higher-order list function `name`; `call(f, args)` invokes the callback `f`
+/// (whatever the engine accepts as a function value) and returns its result.
+pub fn call_higher_order(
+    name: &str,
+    args: Vec<Value>,
+    call: &mut CallbackFn,
+) -> Result<Value, RuntimeError> {
+    let expected = if name == "list_reduce" { 3 } else { 2 };
+    if args.len() != expected {
+        let usage = if name == "list_reduce" { "list_reduce(list, fn, init)".to_string() } else { format!("{}(list, fn)", name) };
+        return Err(RuntimeError::new(usage, None));
+    }
+    let items = expect_list(&args[0], &format!("{}: first arg must be list", name))?;
+    let f = &args[1];
+    match name {
+        "list_map" => {
+            let mut out = Vec::with_capacity(items.len());
+            for item in items {
+                out.push(call(f, vec![item])?);
+            }
+            Ok(Value::make_list(out))
+        }
+        "list_filter" => {
+            let mut out = Vec::new();
+            for item in items {
+                if call(f, vec![item.clone()])?.truthy() {
+                    out.push(item);
+                }
+            }
+            Ok(Value::make_list(out))
+        }
+        "list_reduce" => {
+            let mut acc = args[2].clone();
+            for item in items {
+                acc = call(f, vec![acc, item])?;
+            }
+            Ok(acc)
+        }
+        "list_find" | "list_find_index" => {
+            for (i, item) in items.into_iter().enumerate() {
+                if call(f, vec![item.clone()])?.truthy() {
+                    return Ok(if name == "list_find" { item } else { Value::SmallInt(i as i64) });
+                }
+            }
+            Ok(if name == "list_find" { Value::Null } else { Value::SmallInt(-1) })
+        }
+        "list_every" => {
+            for item in items {
+                if !call(f, vec![item])?.truthy() {
+                    return Ok(Value::Bool(false));
+                }
+            }
+            Ok(Value::Bool(true))
+        }
+        "list_some" => {
+            for item in items {
+                if call(f, vec![item])?.truthy() {
+                    return Ok(Value::Bool(true));
+                }
+            }
+            Ok(Value::Bool(false))
+        }
+        "list_flat_map" => {
+            let mut out = Vec::new();
+            for item in items {
+                let mapped = call(f, vec![item])?;
+                out.extend(expect_list(&mapped, "list_flat_map: fn must return a list")?);
+            }
+            Ok(Value::make_list(out))
+        }
+        "list_sort_by" => {
+            let mut keyed = Vec::with_capacity(items.len());
+            for item in items {
+                keyed.push((call(f, vec![item.clone()])?, item));
+            }
+            // stable, so items with equal keys keep their order
+            keyed.sort_by(|a, b| total_cmp(&a.0, &b.0));
+            Ok(Value::make_list(keyed.into_iter().map(|(_, item)| item).collect()))
+        }
+        _ => Err(RuntimeError::new(format!("{} is not a higher-order list function", name), None)),
+    }
+}
+
+fn list_push(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_push(list, val)", None));
+    }
+    let mut items = expect_list(&args[0], "list_push: first arg must be list")?;
+    items.push(args[1].clone());
+    Ok(Value::make_list(items))
+}
+
+/// `[last, rest]`; the input list is left unchanged.
+fn list_pop(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("list_pop(list)", None));
+    }
+    let mut items = expect_list(&args[0], "list_pop: arg must be list")?;
+    let Some(last) = items.pop() else {
+        return Err(RuntimeError::new("list_pop: list is empty", None));
+    };
+    Ok(Value::make_list(vec![last, Value::make_list(items)]))
+}
+
+/// `[first, rest]`; the input list is left unchanged.
+fn list_shift(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("list_shift(list)", None));
+    }
+    let mut items = expect_list(&args[0], "list_shift: arg must be list")?;
+    if items.is_empty() {
+        return Err(RuntimeError::new("list_shift: list is empty", None));
+    }
+    let first = items.remove(0);
+    Ok(Value::make_list(vec![first, Value::make_list(items)]))
+}
+
+fn list_unshift(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_unshift(list, val)", None));
+    }
+    let mut items = expect_list(&args[0], "list_unshift: first arg must be list")?;
+    items.insert(0, args[1].clone());
+    Ok(Value::make_list(items))
+}
+
+/// Items `start..end` (end defaults to the length); both bounds are clamped to the list.
+fn list_slice(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(RuntimeError::new("list_slice(list, start, end?)", None));
+    }
+    let items = expect_list(&args[0], "list_slice: first arg must be list")?;
+    let start = index_arg(&args[1], "list_slice: start must be a non-negative integer")?.min(items.len());
+    let end = match args.get(2) {
+        Some(v) => index_arg(v, "list_slice: end must be a non-negative integer")?.min(items.len()),
+        None => items.len(),
+    };
+    Ok(Value::make_list(items[start..end.max(start)].to_vec()))
+}
+
+fn list_concat(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_concat(a, b)", None));
+    }
+    let mut a = expect_list(&args[0], "list_concat: args must be lists")?;
+    a.extend(expect_list(&args[1], "list_concat: args must be lists")?);
+    Ok(Value::make_list(a))
+}
+
+fn list_reverse(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("list_reverse(list)", None));
+    }
+    let mut items = expect_list(&args[0], "list_reverse: arg must be list")?;
+    items.reverse();
+    Ok(Value::make_list(items))
+}
+
+/// Splices nested lists into their parent, `depth` levels deep (default 1).
+fn list_flatten(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(RuntimeError::new("list_flatten(list, depth?)", None));
+    }
+    let items = expect_list(&args[0], "list_flatten: first arg must be list")?;
+    let depth = match args.get(1) {
+        Some(v) => index_arg(v, "list_flatten: depth must be a non-negative integer")?,
+        None => 1,
+    };
+    let mut out = Vec::new();
+    flatten_into(items, depth, &mut out);
+    Ok(Value::make_list(out))
+}
+
+fn flatten_into(items: Vec<Value>, depth: usize, out: &mut Vec<Value>) {
+    for item in items {
+        match expect_list(&item, "") {
+            Ok(inner) if depth > 0 => flatten_into(inner, depth - 1, out),
+            _ => out.push(item),
+        }
+    }
+}
+
+/// Pairs items up as `{a, b}` maps, stopping at the shorter list.
+fn list_zip(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_zip(a, b)", None));
+    }
+    let a = expect_list(&args[0], "list_zip: args must be lists")?;
+    let b = expect_list(&args[1], "list_zip: args must be lists")?;
+    let pairs = a
+        .into_iter()
+        .zip(b)
+        .map(|(x, y)| {
+            let mut pair = HashMap::new();
+            pair.insert("a".to_string(), x);
+            pair.insert("b".to_string(), y);
+            Value::make_map(pair)
+        })
+        .collect();
+    Ok(Value::make_list(pairs))
+}
+
+/// Keeps the first occurrence of each value (by `==`).
+fn list_unique(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("list_unique(list)", None));
+    }
+    let mut out: Vec<Value> = Vec::new();
+    for item in expect_list(&args[0], "list_unique: arg must be list")? {
+        if !out.contains(&item) {
+            out.push(item);
+        }
+    }
+    Ok(Value::make_list(out))
+}
+
+/// Returns a sorted copy; mixed types follow the `total_cmp` order (null < bool < number < text < containers).
+fn list_sort(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("list_sort(list)", None));
+    }
+    let mut items = expect_list(&args[0], "list_sort: arg must be list")?;
+    items.sort_by(total_cmp);
+    Ok(Value::make_list(items))
+}
+
+/// Sorts maps by the field `key` (ordered like `list_sort`). Stable: maps with equal keys
+/// keep their input order.
+fn list_sort_by_key(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_sort_by_key(list, key)", None));
+    }
+    let items = expect_list(&args[0], "list_sort_by_key: first arg must be list")?;
+    let key = args[1].as_text().ok_or_else(|| RuntimeError::new("list_sort_by_key: key must be text", None))?;
+    let mut keyed = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        let field = match &item {
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::Map(map) => map.borrow().get(&key).cloned(),
+                _ => None,
+            },
+            _ => None,
+        };
+        let field = field.ok_or_else(|| {
+            RuntimeError::new(format!("list_sort_by_key: item {} is not a map with field '{}'", i, key), None)
+        })?;
+        keyed.push((field, item));
+    }
+    let sorted = merge_sort_by(keyed, &|a, b| total_cmp(&a.0, &b.0));
+    Ok(Value::make_list(sorted.into_iter().map(|(_, item)| item).collect()))
+}
+
+/// Top-down merge sort. Ties take from the left run, so equal elements keep their order.
+fn merge_sort_by<T>(mut items: Vec<T>, cmp: &dyn Fn(&T, &T) -> Ordering) -> Vec<T> {
+    if items.len() <= 1 {
+        return items;
+    }
+    let right = items.split_off(items.len() / 2);
+    let (left, right) = (merge_sort_by(items, cmp), merge_sort_by(right, cmp));
+    let mut out = Vec::with_capacity(left.len() + right.len());
+    let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        let next = if cmp(r, l) == Ordering::Less { right.next() } else { left.next() };
+        out.extend(next);
+    }
+    out.extend(left);
+    out.extend(right);
+    out
+}
+
+/// LCS length table: `dp[i][j]` is the LCS length of `a[i..]` and `b[j..]`.
+fn lcs_table(a: &[Value], b: &[Value]) -> Vec<Vec<usize>> {
+    let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            dp[i][j] = if a[i] == b[j] { dp[i + 1][j + 1] + 1 } else { dp[i + 1][j].max(dp[i][j + 1]) };
+        }
+    }
+    dp
+}
+
+/// Walks the LCS table front to back, yielding `(op, value)` pairs; deletions come before additions.
+fn edit_script(a: &[Value], b: &[Value]) -> Vec<(&'static str, Value)> {
+    let dp = lcs_table(a, b);
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::new();
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push(("keep", a[i].clone()));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || dp[i + 1][j] >= dp[i][j + 1]) {
+            ops.push(("del", a[i].clone()));
+            i += 1;
+        } else {
+            ops.push(("add", b[j].clone()));
+            j += 1;
+        }
+    }
+    ops
+}
+
+fn list_lcs(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_lcs(a, b)", None));
+    }
+    let a = expect_list(&args[0], "list_lcs: args must be lists")?;
+    let b = expect_list(&args[1], "list_lcs: args must be lists")?;
+    let common = edit_script(&a, &b)
+        .into_iter()
+        .filter(|(op, _)| *op == "keep")
+        .map(|(_, v)| v)
+        .collect();
+    Ok(Value::make_list(common))
+}
+
+/// Edit script turning `a` into `b`: a list of `{op, value}` maps with op `keep`, `add` or `del`.
+fn list_diff(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_diff(a, b)", None));
+    }
+    let a = expect_list(&args[0], "list_diff: args must be lists")?;
+    let b = expect_list(&args[1], "list_diff: args must be lists")?;
+    let script = edit_script(&a, &b)
+        .into_iter()
+        .map(|(op, value)| {
+            let mut entry = HashMap::new();
+            entry.insert("op".to_string(), Value::make_text(op));
+            entry.insert("value".to_string(), value);
+            Value::make_map(entry)
+        })
+        .collect();
+    Ok(Value::make_list(script))
+}
+
+/// Applies a `list_diff` script to `a`; `keep`/`del` entries must match `a` in order.
+fn list_patch(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("list_patch(list, diff)", None));
+    }
+    let a = expect_list(&args[0], "list_patch: first arg must be list")?;
+    let script = expect_list(&args[1], "list_patch: diff must be list")?;
+    let mut out = Vec::new();
+    let mut pos = 0;
+    for entry in &script {
+        let (op, value) = match entry {
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::Map(m) => {
+                    let m = m.borrow();
+                    (m.get("op").and_then(|v| v.as_text()), m.get("value").cloned())
+                }
+                _ => (None, None),
+            },
+            _ => (None, None),
+        };
+        let (Some(op), Some(value)) = (op, value) else {
+            return Err(RuntimeError::new("list_patch: diff entries must be {op, value} maps", None));
+        };
+        match op.as_str() {
+            "add" => out.push(value),
+            "keep" | "del" => {
+                if a.get(pos) != Some(&value) {
+                    return Err(RuntimeError::new("list_patch: diff does not match list", None));
+                }
+                if op == "keep" {
+                    out.push(value);
+                }
+                pos += 1;
+            }
+            _ => return Err(RuntimeError::new(format!("list_patch: unknown op '{}'", op), None)),
+        }
+    }
+    if pos != a.len() {
+        return Err(RuntimeError::new("list_patch: diff does not cover the whole list", None));
+    }
+    Ok(Value::make_list(out))
+}
+
+fn index_arg(val: &Value, msg: &str) -> Result<usize, RuntimeError> {
+    match val {
+        Value::SmallInt(n) if *n >= 0 => Ok(*n as usize),
+        Value::Float(f) if *f >= 0.0 && f.fract() == 0.0 => Ok(*f as usize),
+        _ => Err(RuntimeError::new(msg, None)),
+    }
+}
+
+fn expect_list(val: &Value, msg: &str) -> Result<Vec<Value>, RuntimeError> {
+    if let Value::RcObj(rc) = val {
+        if let NauxObj::List(list) = rc.as_ref() {
+            return Ok(list.borrow().clone());
+        }
+    }
+    Err(RuntimeError::new(msg, None))
+}
@@ -1 +1,31 @@
-// TODO: map helpers
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::{NauxObj, Value};
+
+pub fn register_map(env: &mut Env) {
+    env.set_builtin("map_set", map_set);
+}
+
+/// Map keys are text; numbers are accepted and stored as their text form.
+pub(crate) fn map_key(val: &Value) -> Option<String> {
+    match val {
+        Value::SmallInt(n) => Some(n.to_string()),
+        Value::Float(f) => Some(f.to_string()),
+        other => other.as_text(),
+    }
+}
+
+/// Inserts in place and returns the same map; number keys are stored as their text form.
+fn map_set(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("map_set(map, key, value)", None));
+    }
+    let key = map_key(&args[1]).ok_or_else(|| RuntimeError::new("map_set: key must be text or number", None))?;
+    if let Value::RcObj(rc) = &args[0] {
+        if let NauxObj::Map(m) = rc.as_ref() {
+            m.borrow_mut().insert(key, args[2].clone());
+            return Ok(Value::RcObj(rc.clone()));
+        }
+    }
+    Err(RuntimeError::new("map_set: first arg must be map", None))
+}
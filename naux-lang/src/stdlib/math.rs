@@ -3,14 +3,24 @@
 
 use crate::runtime::env::Env;
 use crate::runtime::error::RuntimeError;
-use crate::runtime::value::Value;
+use crate::runtime::value::{NauxObj, Value};
 
 pub fn register_math(env: &mut Env) {
     env.set_builtin("gcd", gcd);
     env.set_builtin("lcm", lcm);
+    env.set_builtin("gcd_list", gcd_list);
+    env.set_builtin("lcm_list", lcm_list);
     env.set_builtin("pow_mod", pow_mod);
+    env.set_builtin("mod_euclid", mod_euclid);
     env.set_builtin("is_prime", is_prime);
     env.set_builtin("sieve", sieve);
+    env.set_builtin("approx_eq", approx_eq);
+    env.set_builtin("clamp", clamp);
+    env.set_builtin("lerp", lerp);
+    env.set_builtin("map_range", map_range);
+    env.set_builtin("factorial", factorial);
+    env.set_builtin("choose", choose);
+    env.set_builtin("catalan", catalan);
 }
 
 fn to_i64(v: &Value) -> Result<i64, RuntimeError> {
@@ -21,18 +31,15 @@ fn to_i64(v: &Value) -> Result<i64, RuntimeError> {
     }
 }
 
+fn to_f64(v: &Value) -> Result<f64, RuntimeError> {
+    v.as_f64().ok_or_else(|| RuntimeError::new("expected number", None))
+}
+
 fn gcd(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::new("gcd(a,b)", None));
     }
-    let mut a = to_i64(&args[0])?.abs();
-    let mut b = to_i64(&args[1])?.abs();
-    while b != 0 {
-        let t = b;
-        b = a % b;
-        a = t;
-    }
-    Ok(Value::SmallInt(a))
+    Ok(Value::SmallInt(gcd_i64(to_i64(&args[0])?, to_i64(&args[1])?)))
 }
 
 fn lcm(args: Vec<Value>) -> Result<Value, RuntimeError> {
@@ -52,6 +59,77 @@ fn lcm(args: Vec<Value>) -> Result<Value, RuntimeError> {
     }
 }
 
+/// Non-empty list of integers (integral floats are accepted) for the `*_list` reducers.
+fn int_list(args: &[Value], usage: &str) -> Result<Vec<i64>, RuntimeError> {
+    let items = match args {
+        [Value::RcObj(rc)] => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().clone(),
+            _ => return Err(RuntimeError::new(usage, None)),
+        },
+        _ => return Err(RuntimeError::new(usage, None)),
+    };
+    if items.is_empty() {
+        return Err(RuntimeError::new(format!("{}: list must not be empty", usage), None));
+    }
+    items
+        .iter()
+        .map(|v| expect_int(v).ok_or_else(|| RuntimeError::new(format!("{}: expected integers", usage), None)))
+        .collect()
+}
+
+/// Integer value of `v`; integral floats count, since interpreter arithmetic yields floats.
+fn expect_int(v: &Value) -> Option<i64> {
+    match v {
+        Value::SmallInt(n) => Some(*n),
+        Value::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Some(*f as i64),
+        _ => None,
+    }
+}
+
+/// Non-negative integer argument for the combinatorics builtins.
+fn expect_count(v: &Value, usage: &str) -> Result<i64, RuntimeError> {
+    match expect_int(v) {
+        Some(n) if n >= 0 => Ok(n),
+        Some(_) => Err(RuntimeError::new(format!("{}: arguments must be non-negative", usage), None)),
+        None => Err(RuntimeError::new(format!("{}: expected integers", usage), None)),
+    }
+}
+
+fn overflow(usage: &str) -> RuntimeError {
+    RuntimeError::new(format!("{}: result overflows a 64-bit integer", usage), None)
+}
+
+fn gcd_i64(mut a: i64, mut b: i64) -> i64 {
+    a = a.abs();
+    b = b.abs();
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+    a
+}
+
+fn gcd_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let nums = int_list(&args, "gcd_list(list)")?;
+    Ok(Value::SmallInt(nums.into_iter().fold(0, gcd_i64)))
+}
+
+fn lcm_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let nums = int_list(&args, "lcm_list(list)")?;
+    let mut acc = nums[0].abs();
+    for &n in &nums[1..] {
+        if acc == 0 || n == 0 {
+            acc = 0;
+            continue;
+        }
+        acc = (acc / gcd_i64(acc, n))
+            .checked_mul(n.abs())
+            .ok_or_else(|| overflow("lcm_list(list)"))?;
+    }
+    Ok(Value::SmallInt(acc))
+}
+
 fn pow_mod(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 3 {
         return Err(RuntimeError::new("pow_mod(base, exp, mod)", None));
@@ -74,6 +152,22 @@ fn pow_mod(args: Vec<Value>) -> Result<Value, RuntimeError> {
     Ok(Value::SmallInt(res))
 }
 
+/// Euclidean remainder: always in `[0, |b|)`, unlike the truncated `%` operator.
+fn mod_euclid(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("mod_euclid(a, b)", None));
+    }
+    match (&args[0], &args[1]) {
+        (Value::SmallInt(_), Value::SmallInt(0)) => Err(RuntimeError::new("mod must be non-zero", None)),
+        (Value::SmallInt(a), Value::SmallInt(b)) => Ok(Value::SmallInt(a.wrapping_rem_euclid(*b))),
+        (a, b) => match (a.as_f64(), b.as_f64()) {
+            (Some(_), Some(0.0)) => Err(RuntimeError::new("mod must be non-zero", None)),
+            (Some(x), Some(y)) => Ok(Value::Float(x.rem_euclid(y))),
+            _ => Err(RuntimeError::new("expected number", None)),
+        },
+    }
+}
+
 fn is_prime(args: Vec<Value>) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::new("is_prime(n)", None));
@@ -128,3 +222,110 @@ fn sieve(args: Vec<Value>) -> Result<Value, RuntimeError> {
     }
     Ok(Value::make_list(primes))
 }
+
+/// `|a - b| <= eps`; unlike `==` the tolerance is chosen by the caller.
+pub fn approx_eq(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("approx_eq(a, b, eps)", None));
+    }
+    let (a, b, eps) = (to_f64(&args[0])?, to_f64(&args[1])?, to_f64(&args[2])?);
+    if eps.is_nan() || eps < 0.0 {
+        return Err(RuntimeError::new("approx_eq(a, b, eps): eps must be a non-negative number", None));
+    }
+    Ok(Value::Bool((a - b).abs() <= eps))
+}
+
+fn clamp(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("clamp(x, lo, hi)", None));
+    }
+    if let (Value::SmallInt(x), Value::SmallInt(lo), Value::SmallInt(hi)) = (&args[0], &args[1], &args[2]) {
+        if lo > hi {
+            return Err(RuntimeError::new("clamp(x, lo, hi): lo must not exceed hi", None));
+        }
+        return Ok(Value::SmallInt(*x.max(lo).min(hi)));
+    }
+    let (x, lo, hi) = (to_f64(&args[0])?, to_f64(&args[1])?, to_f64(&args[2])?);
+    if lo > hi {
+        return Err(RuntimeError::new("clamp(x, lo, hi): lo must not exceed hi", None));
+    }
+    Ok(Value::Float(x.max(lo).min(hi)))
+}
+
+fn lerp(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("lerp(a, b, t)", None));
+    }
+    let (a, b, t) = (to_f64(&args[0])?, to_f64(&args[1])?, to_f64(&args[2])?);
+    Ok(Value::Float(a + (b - a) * t))
+}
+
+/// Linearly maps `x` from `[in_lo, in_hi]` onto `[out_lo, out_hi]` (no clamping);
+/// an empty input range maps everything to `out_lo`.
+fn map_range(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 5 {
+        return Err(RuntimeError::new("map_range(x, in_lo, in_hi, out_lo, out_hi)", None));
+    }
+    let nums = args.iter().map(to_f64).collect::<Result<Vec<_>, _>>()?;
+    let (x, in_lo, in_hi, out_lo, out_hi) = (nums[0], nums[1], nums[2], nums[3], nums[4]);
+    if in_hi == in_lo {
+        return Ok(Value::Float(out_lo));
+    }
+    Ok(Value::Float(out_lo + (x - in_lo) * (out_hi - out_lo) / (in_hi - in_lo)))
+}
+
+fn factorial(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    const USAGE: &str = "factorial(n)";
+    if args.len() != 1 {
+        return Err(RuntimeError::new(USAGE, None));
+    }
+    let n = expect_count(&args[0], USAGE)?;
+    (2..=n)
+        .try_fold(1i64, |acc, k| acc.checked_mul(k))
+        .map(Value::SmallInt)
+        .ok_or_else(|| overflow(USAGE))
+}
+
+/// Binomial coefficient via the multiplicative formula; each partial product
+/// `C(n, i)` is exact, so only the final value has to fit in an i64.
+fn binomial(n: i64, k: i64, usage: &str) -> Result<i64, RuntimeError> {
+    if k > n {
+        return Ok(0);
+    }
+    let k = k.min(n - k);
+    let mut acc: i128 = 1;
+    for i in 0..k {
+        acc = acc * (n - i) as i128 / (i + 1) as i128;
+        if acc > i64::MAX as i128 {
+            return Err(overflow(usage));
+        }
+    }
+    Ok(acc as i64)
+}
+
+fn choose(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    const USAGE: &str = "choose(n, k)";
+    if args.len() != 2 {
+        return Err(RuntimeError::new(USAGE, None));
+    }
+    let n = expect_count(&args[0], USAGE)?;
+    let k = expect_count(&args[1], USAGE)?;
+    Ok(Value::SmallInt(binomial(n, k, USAGE)?))
+}
+
+fn catalan(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    const USAGE: &str = "catalan(n)";
+    if args.len() != 1 {
+        return Err(RuntimeError::new(USAGE, None));
+    }
+    let n = expect_count(&args[0], USAGE)?;
+    // C(i+1) = C(i) * 2(2i+1) / (i+2), exact at every step
+    let mut acc: i128 = 1;
+    for i in 0..n as i128 {
+        acc = acc * 2 * (2 * i + 1) / (i + 2);
+        if acc > i64::MAX as i128 {
+            return Err(overflow(USAGE));
+        }
+    }
+    Ok(Value::SmallInt(acc as i64))
+}
@@ -0,0 +1,420 @@
+use std::cell::RefCell;
+
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::{NauxObj, Value};
+
+pub fn register_matrix(env: &mut Env) {
+    env.set_builtin("mat_new", mat_new);
+    env.set_builtin("mat_from_list", mat_from_list);
+    env.set_builtin("mat_identity", mat_identity);
+    env.set_builtin("mat_get", mat_get);
+    env.set_builtin("mat_set", mat_set);
+    env.set_builtin("mat_rows", mat_rows);
+    env.set_builtin("mat_cols", mat_cols);
+    env.set_builtin("mat_to_list", mat_to_list);
+    env.set_builtin("mat_add", mat_add);
+    env.set_builtin("mat_sub", mat_sub);
+    env.set_builtin("mat_mul", mat_mul);
+    env.set_builtin("mat_mul_mod", mat_mul_mod);
+    env.set_builtin("mat_scalar_mul", mat_scalar_mul);
+    env.set_builtin("mat_transpose", mat_transpose);
+    env.set_builtin("mat_det", mat_det);
+    env.set_builtin("mat_inv", mat_inv);
+    env.set_builtin("mat_pow", mat_pow);
+}
+
+type Rows = Vec<Vec<f64>>;
+
+/// Pivots smaller than this are treated as zero by `mat_det` and `mat_inv`.
+const SINGULAR_EPS: f64 = 1e-12;
+
+fn expect_matrix<'a>(val: &'a Value, msg: &str) -> Result<&'a RefCell<Rows>, RuntimeError> {
+    if let Value::RcObj(rc) = val {
+        if let NauxObj::Matrix(m) = rc.as_ref() {
+            return Ok(m);
+        }
+    }
+    Err(RuntimeError::new(msg, None))
+}
+
+fn dims(m: &Rows) -> (usize, usize) {
+    (m.len(), m.first().map_or(0, |r| r.len()))
+}
+
+fn shape(m: &Rows) -> String {
+    let (r, c) = dims(m);
+    format!("{}x{}", r, c)
+}
+
+fn index_arg(val: &Value, msg: &str) -> Result<usize, RuntimeError> {
+    match val {
+        Value::SmallInt(n) if *n >= 0 => Ok(*n as usize),
+        Value::Float(f) if *f >= 0.0 && f.fract() == 0.0 => Ok(*f as usize),
+        _ => Err(RuntimeError::new(msg, None)),
+    }
+}
+
+fn number_arg(val: &Value, msg: &str) -> Result<f64, RuntimeError> {
+    val.as_f64().ok_or_else(|| RuntimeError::new(msg, None))
+}
+
+fn identity(n: usize) -> Rows {
+    (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+}
+
+fn mat_new(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(RuntimeError::new("mat_new(rows, cols, fill?)", None));
+    }
+    let rows = index_arg(&args[0], "mat_new: rows must be a non-negative integer")?;
+    let cols = index_arg(&args[1], "mat_new: cols must be a non-negative integer")?;
+    let fill = match args.get(2) {
+        Some(v) => number_arg(v, "mat_new: fill must be number")?,
+        None => 0.0,
+    };
+    Ok(Value::make_matrix(vec![vec![fill; cols]; rows]))
+}
+
+fn mat_from_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_from_list(list_of_lists)", None));
+    }
+    let rows = match &args[0] {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(list) => list.borrow().clone(),
+            _ => return Err(RuntimeError::new("mat_from_list: arg must be a list of lists", None)),
+        },
+        _ => return Err(RuntimeError::new("mat_from_list: arg must be a list of lists", None)),
+    };
+    let mut out: Rows = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let items = match row {
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::List(items) => items.borrow().clone(),
+                _ => return Err(RuntimeError::new(format!("mat_from_list: row {} is not a list", i), None)),
+            },
+            _ => return Err(RuntimeError::new(format!("mat_from_list: row {} is not a list", i), None)),
+        };
+        let nums = items
+            .iter()
+            .map(|v| v.as_f64())
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(|| RuntimeError::new(format!("mat_from_list: row {} has a non-number", i), None))?;
+        if let Some(first) = out.first() {
+            if first.len() != nums.len() {
+                return Err(RuntimeError::new(
+                    format!("mat_from_list: row {} has {} columns, expected {}", i, nums.len(), first.len()),
+                    None,
+                ));
+            }
+        }
+        out.push(nums);
+    }
+    Ok(Value::make_matrix(out))
+}
+
+fn mat_identity(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_identity(n)", None));
+    }
+    let n = index_arg(&args[0], "mat_identity: n must be a non-negative integer")?;
+    Ok(Value::make_matrix(identity(n)))
+}
+
+/// Row and column arguments at `args[1]` / `args[2]`, checked against the matrix bounds.
+fn cell_arg(m: &Rows, args: &[Value], name: &str) -> Result<(usize, usize), RuntimeError> {
+    let r = index_arg(&args[1], &format!("{}: row must be a non-negative integer", name))?;
+    let c = index_arg(&args[2], &format!("{}: col must be a non-negative integer", name))?;
+    let (rows, cols) = dims(m);
+    if r >= rows || c >= cols {
+        return Err(RuntimeError::new(
+            format!("{}: index ({}, {}) out of bounds for {} matrix", name, r, c, shape(m)),
+            None,
+        ));
+    }
+    Ok((r, c))
+}
+
+fn mat_get(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("mat_get(mat, r, c)", None));
+    }
+    let m = expect_matrix(&args[0], "mat_get: first arg must be matrix")?.borrow();
+    let (r, c) = cell_arg(&m, &args, "mat_get")?;
+    Ok(Value::Float(m[r][c]))
+}
+
+/// Writes the cell in place and returns the same matrix.
+fn mat_set(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 4 {
+        return Err(RuntimeError::new("mat_set(mat, r, c, val)", None));
+    }
+    let cell = expect_matrix(&args[0], "mat_set: first arg must be matrix")?;
+    let val = number_arg(&args[3], "mat_set: val must be number")?;
+    let (r, c) = cell_arg(&cell.borrow(), &args, "mat_set")?;
+    cell.borrow_mut()[r][c] = val;
+    Ok(args[0].clone())
+}
+
+fn mat_rows(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_rows(mat)", None));
+    }
+    let m = expect_matrix(&args[0], "mat_rows: arg must be matrix")?.borrow();
+    Ok(Value::SmallInt(dims(&m).0 as i64))
+}
+
+fn mat_cols(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_cols(mat)", None));
+    }
+    let m = expect_matrix(&args[0], "mat_cols: arg must be matrix")?.borrow();
+    Ok(Value::SmallInt(dims(&m).1 as i64))
+}
+
+fn mat_to_list(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_to_list(mat)", None));
+    }
+    let m = expect_matrix(&args[0], "mat_to_list: arg must be matrix")?.borrow();
+    let rows = m.iter().map(|r| Value::make_list(r.iter().map(|x| Value::Float(*x)).collect())).collect();
+    Ok(Value::make_list(rows))
+}
+
+/// The two matrix operands of a binary builtin `name(a, b)`.
+fn matrix_pair(args: &[Value], name: &str) -> Result<(Rows, Rows), RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new(format!("{}(a, b)", name), None));
+    }
+    let a = expect_matrix(&args[0], &format!("{}: first arg must be matrix", name))?.borrow().clone();
+    let b = expect_matrix(&args[1], &format!("{}: second arg must be matrix", name))?.borrow().clone();
+    Ok((a, b))
+}
+
+fn elementwise(args: Vec<Value>, name: &str, op: fn(f64, f64) -> f64) -> Result<Value, RuntimeError> {
+    let (a, b) = matrix_pair(&args, name)?;
+    if dims(&a) != dims(&b) {
+        return Err(RuntimeError::new(format!("{}: shapes {} and {} differ", name, shape(&a), shape(&b)), None));
+    }
+    let out = a.iter().zip(&b).map(|(ra, rb)| ra.iter().zip(rb).map(|(x, y)| op(*x, *y)).collect()).collect();
+    Ok(Value::make_matrix(out))
+}
+
+fn mat_add(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    elementwise(args, "mat_add", |x, y| x + y)
+}
+
+fn mat_sub(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    elementwise(args, "mat_sub", |x, y| x - y)
+}
+
+fn multiply(a: &Rows, b: &Rows) -> Rows {
+    let (n, k) = dims(a);
+    let m = dims(b).1;
+    let mut out = vec![vec![0.0; m]; n];
+    for i in 0..n {
+        for p in 0..k {
+            let x = a[i][p];
+            for j in 0..m {
+                out[i][j] += x * b[p][j];
+            }
+        }
+    }
+    out
+}
+
+/// Product with every entry reduced into `0..modulus`; entries must be integers so the
+/// intermediate products can be computed exactly in i128.
+fn multiply_mod(a: &Rows, b: &Rows, modulus: i128) -> Rows {
+    let (n, k) = dims(a);
+    let m = dims(b).1;
+    let mut out = vec![vec![0.0; m]; n];
+    for i in 0..n {
+        for j in 0..m {
+            let mut acc: i128 = 0;
+            for p in 0..k {
+                acc = (acc + (a[i][p] as i128) * (b[p][j] as i128)).rem_euclid(modulus);
+            }
+            out[i][j] = acc as f64;
+        }
+    }
+    out
+}
+
+fn check_mul_shapes(a: &Rows, b: &Rows, name: &str) -> Result<(), RuntimeError> {
+    if dims(a).1 != dims(b).0 {
+        return Err(RuntimeError::new(format!("{}: cannot multiply {} by {}", name, shape(a), shape(b)), None));
+    }
+    Ok(())
+}
+
+/// Modulus argument plus `rows` reduced into `0..modulus`; errors on non-integral entries.
+fn modulus_arg(val: &Value, matrices: &mut [&mut Rows], name: &str) -> Result<i128, RuntimeError> {
+    let modulus = match val {
+        Value::SmallInt(n) if *n > 0 => *n as i128,
+        Value::Float(f) if *f >= 1.0 && f.fract() == 0.0 && *f < i64::MAX as f64 => *f as i128,
+        _ => return Err(RuntimeError::new(format!("{}: modulus must be a positive integer", name), None)),
+    };
+    for m in matrices.iter_mut() {
+        for x in m.iter_mut().flatten() {
+            if x.fract() != 0.0 || x.abs() >= i64::MAX as f64 {
+                return Err(RuntimeError::new(format!("{}: entries must be integers", name), None));
+            }
+            *x = (*x as i128).rem_euclid(modulus) as f64;
+        }
+    }
+    Ok(modulus)
+}
+
+fn mat_mul(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (a, b) = matrix_pair(&args, "mat_mul")?;
+    check_mul_shapes(&a, &b, "mat_mul")?;
+    Ok(Value::make_matrix(multiply(&a, &b)))
+}
+
+fn mat_mul_mod(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 3 {
+        return Err(RuntimeError::new("mat_mul_mod(a, b, m)", None));
+    }
+    let (mut a, mut b) = matrix_pair(&args[..2], "mat_mul_mod")?;
+    check_mul_shapes(&a, &b, "mat_mul_mod")?;
+    let modulus = modulus_arg(&args[2], &mut [&mut a, &mut b], "mat_mul_mod")?;
+    Ok(Value::make_matrix(multiply_mod(&a, &b, modulus)))
+}
+
+fn mat_scalar_mul(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        return Err(RuntimeError::new("mat_scalar_mul(mat, s)", None));
+    }
+    let m = expect_matrix(&args[0], "mat_scalar_mul: first arg must be matrix")?.borrow();
+    let s = number_arg(&args[1], "mat_scalar_mul: s must be number")?;
+    Ok(Value::make_matrix(m.iter().map(|r| r.iter().map(|x| x * s).collect()).collect()))
+}
+
+fn mat_transpose(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_transpose(mat)", None));
+    }
+    let m = expect_matrix(&args[0], "mat_transpose: arg must be matrix")?.borrow();
+    let (rows, cols) = dims(&m);
+    Ok(Value::make_matrix((0..cols).map(|j| (0..rows).map(|i| m[i][j]).collect()).collect()))
+}
+
+fn square_arg(val: &Value, name: &str) -> Result<Rows, RuntimeError> {
+    let m = expect_matrix(val, &format!("{}: first arg must be matrix", name))?.borrow().clone();
+    let (rows, cols) = dims(&m);
+    if rows != cols {
+        return Err(RuntimeError::new(format!("{}: matrix must be square, got {}", name, shape(&m)), None));
+    }
+    Ok(m)
+}
+
+/// Index of the row at or below `col` with the largest pivot candidate (partial pivoting).
+fn pivot_row(m: &Rows, col: usize) -> usize {
+    (col..m.len()).max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs())).unwrap_or(col)
+}
+
+/// Determinant from the LU decomposition with partial pivoting: the product of U's diagonal,
+/// negated once per row swap.
+fn mat_det(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_det(mat)", None));
+    }
+    let mut lu = square_arg(&args[0], "mat_det")?;
+    let n = lu.len();
+    let mut det = 1.0;
+    for col in 0..n {
+        let p = pivot_row(&lu, col);
+        if lu[p][col].abs() < SINGULAR_EPS {
+            return Ok(Value::Float(0.0));
+        }
+        if p != col {
+            lu.swap(p, col);
+            det = -det;
+        }
+        det *= lu[col][col];
+        let (upper, below) = lu.split_at_mut(col + 1);
+        let pivot = &upper[col];
+        for row in below {
+            let factor = row[col] / pivot[col];
+            for (x, p) in row[col..].iter_mut().zip(&pivot[col..]) {
+                *x -= factor * p;
+            }
+        }
+    }
+    Ok(Value::Float(det))
+}
+
+/// Gauss-Jordan elimination with partial pivoting; null when the matrix is singular.
+fn mat_inv(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        return Err(RuntimeError::new("mat_inv(mat)", None));
+    }
+    let mut a = square_arg(&args[0], "mat_inv")?;
+    let n = a.len();
+    let mut inv = identity(n);
+    for col in 0..n {
+        let p = pivot_row(&a, col);
+        if a[p][col].abs() < SINGULAR_EPS {
+            return Ok(Value::Null);
+        }
+        a.swap(p, col);
+        inv.swap(p, col);
+        let pivot = a[col][col];
+        for k in 0..n {
+            a[col][k] /= pivot;
+            inv[col][k] /= pivot;
+        }
+        for row in 0..n {
+            let factor = a[row][col];
+            if row == col || factor == 0.0 {
+                continue;
+            }
+            for k in 0..n {
+                a[row][k] -= factor * a[col][k];
+                inv[row][k] -= factor * inv[col][k];
+            }
+        }
+    }
+    Ok(Value::make_matrix(inv))
+}
+
+/// `mat^n` by repeated squaring (O(log n) products); with a modulus every product is reduced
+/// as in `mat_mul_mod`, which keeps linear recurrences exact for huge `n`.
+fn mat_pow(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(RuntimeError::new("mat_pow(mat, n, m?)", None));
+    }
+    let mut base = square_arg(&args[0], "mat_pow")?;
+    let mut n = match &args[1] {
+        Value::SmallInt(n) if *n >= 0 => *n as u64,
+        Value::Float(f) if *f >= 0.0 && f.fract() == 0.0 && *f < u64::MAX as f64 => *f as u64,
+        _ => return Err(RuntimeError::new("mat_pow: n must be a non-negative integer", None)),
+    };
+    let modulus = match args.get(2) {
+        Some(m) => Some(modulus_arg(m, &mut [&mut base], "mat_pow")?),
+        None => None,
+    };
+    let mul = |a: &Rows, b: &Rows| match modulus {
+        Some(m) => multiply_mod(a, b, m),
+        None => multiply(a, b),
+    };
+    let mut result = identity(base.len());
+    if let Some(m) = modulus {
+        // the identity itself must be reduced when the modulus is 1
+        for x in result.iter_mut().flatten() {
+            *x = (*x as i128).rem_euclid(m) as f64;
+        }
+    }
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mul(&result, &base);
+        }
+        n >>= 1;
+        if n > 0 {
+            base = mul(&base, &base);
+        }
+    }
+    Ok(Value::make_matrix(result))
+}
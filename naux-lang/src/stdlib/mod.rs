@@ -2,10 +2,15 @@ pub mod list;
 pub mod map;
 pub mod math;
 pub mod string;
+pub mod regex;
+pub mod matrix;
 pub mod graph;
 pub mod collections;
 pub mod algo;
 pub mod test;
+pub mod time;
+pub mod sys;
+pub mod fs;
 
 use crate::runtime::env::Env;
 
@@ -15,6 +20,12 @@ pub fn register_all(env: &mut Env) {
     math::register_math(env);
     algo::register_algo(env);
     test::register_tests(env);
-    // list::register_list(env);
-    // string::register_string(env);
+    time::register_time(env);
+    sys::register_sys(env);
+    fs::register_fs(env);
+    list::register_list(env);
+    map::register_map(env);
+    string::register_string(env);
+    regex::register_regex(env);
+    matrix::register_matrix(env);
 }
@@ -0,0 +1,777 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::{NauxObj, Value};
+
+pub fn register_regex(env: &mut Env) {
+    env.set_builtin("regex_compile", regex_compile);
+    env.set_builtin("regex_match", regex_match);
+    env.set_builtin("regex_find_all", regex_find_all);
+    env.set_builtin("regex_replace", regex_replace);
+    env.set_builtin("regex_split", regex_split);
+}
+
+/// Longest `{m,n}` bound accepted; counted repetition is unrolled when compiling.
+const MAX_REPEAT: usize = 1000;
+
+/// Most instructions a compiled pattern may have; nested counted repetition multiplies quickly.
+const MAX_PROGRAM: usize = 100_000;
+
+/// Most (instruction, position) marks kept in a dense table; larger searches use a sparse set.
+const MAX_DENSE_VISITED: usize = 1 << 22;
+
+/// A compiled pattern: a backtracking program over chars, run with a visited set so every
+/// (instruction, position) pair is tried at most once per search.
+///
+/// Syntax: literals, `.` (any char but `\n`), classes `[a-z]`/`[^...]`, `\d \w \s` and their
+/// negations, `\b \B`, anchors `^ $`, groups `(...)`, `(?:...)`, `(?P<name>...)`/`(?<name>...)`,
+/// alternation `|`, and `* + ? {m} {m,} {m,n}` with lazy `?` variants.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    source: String,
+    prog: Vec<Inst>,
+    /// Name of each capture group, index 0 being the whole match.
+    names: Vec<Option<String>>,
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Bol,
+    Eol,
+    WordBoundary(bool),
+    Save(usize),
+    /// Try the first target, then the second.
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+#[derive(Debug, Clone)]
+struct Class {
+    items: Vec<ClassItem>,
+    negated: bool,
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match *self {
+            ClassItem::Range(lo, hi) => lo <= c && c <= hi,
+            ClassItem::Digit(want) => c.is_ascii_digit() == want,
+            ClassItem::Word(want) => is_word(c) == want,
+            ClassItem::Space(want) => c.is_whitespace() == want,
+        }
+    }
+}
+
+impl Class {
+    fn matches(&self, c: char) -> bool {
+        self.items.iter().any(|item| item.matches(c)) != self.negated
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Bol,
+    Eol,
+    WordBoundary(bool),
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat { node: Box<Node>, min: usize, max: Option<usize>, greedy: bool },
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    names: &'a mut Vec<Option<String>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, what: &str) -> String {
+        format!("{} at position {}", what, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 { branches.pop().unwrap() } else { Node::Alt(branches) })
+    }
+
+    fn concat(&mut self) -> Result<Node, String> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            items.push(self.quantified(atom)?);
+        }
+        Ok(match items.len() {
+            0 => Node::Empty,
+            1 => items.pop().unwrap(),
+            _ => Node::Concat(items),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let c = self.peek().ok_or_else(|| self.error("unexpected end of pattern"))?;
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Bol,
+            '$' => Node::Eol,
+            '[' => Node::Class(self.class()?),
+            '(' => self.group()?,
+            '\\' => self.escape(false)?,
+            '*' | '+' | '?' => {
+                self.pos -= 1;
+                return Err(self.error("repetition operator with nothing to repeat"));
+            }
+            c => Node::Char(c),
+        })
+    }
+
+    fn group(&mut self) -> Result<Node, String> {
+        let index = if self.eat('?') {
+            if self.eat(':') {
+                None
+            } else {
+                let _ = self.eat('P');
+                if !self.eat('<') {
+                    return Err(self.error("unsupported group flag"));
+                }
+                let start = self.pos;
+                while self.peek().is_some_and(is_word) {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                if name.is_empty() || !self.eat('>') {
+                    return Err(self.error("invalid capture group name"));
+                }
+                if self.names.iter().any(|n| n.as_deref() == Some(name.as_str())) {
+                    return Err(self.error(&format!("duplicate capture group name '{}'", name)));
+                }
+                self.names.push(Some(name));
+                Some(self.names.len() - 1)
+            }
+        } else {
+            self.names.push(None);
+            Some(self.names.len() - 1)
+        };
+        let inner = self.alternation()?;
+        if !self.eat(')') {
+            return Err(self.error("unclosed group"));
+        }
+        Ok(Node::Group(Box::new(inner), index))
+    }
+
+    /// After a `\`; inside a class only char-valued escapes and `\d \w \s` are allowed.
+    fn escape(&mut self, in_class: bool) -> Result<Node, String> {
+        let c = self.peek().ok_or_else(|| self.error("incomplete escape"))?;
+        self.pos += 1;
+        let class = |item| Node::Class(Class { items: vec![item], negated: false });
+        Ok(match c {
+            'd' => class(ClassItem::Digit(true)),
+            'D' => class(ClassItem::Digit(false)),
+            'w' => class(ClassItem::Word(true)),
+            'W' => class(ClassItem::Word(false)),
+            's' => class(ClassItem::Space(true)),
+            'S' => class(ClassItem::Space(false)),
+            'b' if !in_class => Node::WordBoundary(true),
+            'B' if !in_class => Node::WordBoundary(false),
+            'n' => Node::Char('\n'),
+            't' => Node::Char('\t'),
+            'r' => Node::Char('\r'),
+            c if !c.is_alphanumeric() => Node::Char(c),
+            c => {
+                self.pos -= 1;
+                return Err(self.error(&format!("unknown escape '\\{}'", c)));
+            }
+        })
+    }
+
+    fn class(&mut self) -> Result<Class, String> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("unclosed character class"))?;
+            if c == ']' && !first {
+                self.pos += 1;
+                break;
+            }
+            first = false;
+            self.pos += 1;
+            let lo = if c == '\\' {
+                match self.escape(true)? {
+                    Node::Char(ch) => ch,
+                    Node::Class(mut cls) => {
+                        items.append(&mut cls.items);
+                        continue;
+                    }
+                    _ => unreachable!("escape in a class yields a char or a class"),
+                }
+            } else {
+                c
+            };
+            // `a-z`, but a `-` before `]` is literal
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&n| n != ']') {
+                self.pos += 1;
+                let mut hi = self.peek().ok_or_else(|| self.error("unclosed character class"))?;
+                self.pos += 1;
+                if hi == '\\' {
+                    hi = match self.escape(true)? {
+                        Node::Char(ch) => ch,
+                        _ => return Err(self.error("invalid class range")),
+                    };
+                }
+                if hi < lo {
+                    return Err(self.error("invalid class range"));
+                }
+                items.push(ClassItem::Range(lo, hi));
+            } else {
+                items.push(ClassItem::Range(lo, lo));
+            }
+        }
+        Ok(Class { items, negated })
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('{') => match self.counted()? {
+                Some(bounds) => bounds,
+                None => return Ok(atom),
+            },
+            Some(op @ ('*' | '+' | '?')) => {
+                self.pos += 1;
+                match op {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    _ => (0, Some(1)),
+                }
+            }
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Bol | Node::Eol | Node::WordBoundary(_)) {
+            return Err(self.error("repetition operator with nothing to repeat"));
+        }
+        let greedy = !self.eat('?');
+        if matches!(self.peek(), Some('*' | '+' | '?')) {
+            return Err(self.error("repetition operator with nothing to repeat"));
+        }
+        Ok(Node::Repeat { node: Box::new(atom), min, max, greedy })
+    }
+
+    /// `{m}`, `{m,}` or `{m,n}` at the cursor, consumed when valid; anything else is a literal `{`.
+    fn counted(&mut self) -> Result<Option<(usize, Option<usize>)>, String> {
+        let start = self.pos;
+        self.pos += 1;
+        let min = self.number();
+        let bounds = match (min, self.peek()) {
+            (Some(m), Some('}')) => Some((m, Some(m))),
+            (Some(m), Some(',')) => {
+                self.pos += 1;
+                let max = self.number();
+                if self.peek() == Some('}') {
+                    Some((m, max))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        let Some((min, max)) = bounds else {
+            self.pos = start;
+            return Ok(None);
+        };
+        self.pos += 1; // the `}`
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error("invalid repetition range"));
+        }
+        if min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(self.error(&format!("repetition count above {}", MAX_REPEAT)));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().ok()
+    }
+}
+
+fn compile_node(node: &Node, prog: &mut Vec<Inst>) -> Result<(), String> {
+    match node {
+        Node::Empty => {}
+        Node::Char(c) => prog.push(Inst::Char(*c)),
+        Node::Any => prog.push(Inst::Any),
+        Node::Class(c) => prog.push(Inst::Class(c.clone())),
+        Node::Bol => prog.push(Inst::Bol),
+        Node::Eol => prog.push(Inst::Eol),
+        Node::WordBoundary(b) => prog.push(Inst::WordBoundary(*b)),
+        Node::Group(inner, index) => {
+            if let Some(i) = index {
+                prog.push(Inst::Save(2 * i));
+            }
+            compile_node(inner, prog)?;
+            if let Some(i) = index {
+                prog.push(Inst::Save(2 * i + 1));
+            }
+        }
+        Node::Concat(items) => {
+            for item in items {
+                compile_node(item, prog)?;
+            }
+        }
+        Node::Alt(branches) => {
+            let mut jumps = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                if i + 1 < branches.len() {
+                    let split = prog.len();
+                    prog.push(Inst::Split(split + 1, 0));
+                    compile_node(branch, prog)?;
+                    jumps.push(prog.len());
+                    prog.push(Inst::Jmp(0));
+                    let next = prog.len();
+                    prog[split] = Inst::Split(split + 1, next);
+                } else {
+                    compile_node(branch, prog)?;
+                }
+            }
+            let end = prog.len();
+            for j in jumps {
+                prog[j] = Inst::Jmp(end);
+            }
+        }
+        Node::Repeat { node, min, max, greedy } => {
+            for _ in 0..*min {
+                compile_node(node, prog)?;
+                check_program_size(prog)?;
+            }
+            let split = |body: usize, skip: usize| if *greedy { Inst::Split(body, skip) } else { Inst::Split(skip, body) };
+            match max {
+                None => {
+                    let start = prog.len();
+                    prog.push(Inst::Jmp(0));
+                    compile_node(node, prog)?;
+                    prog.push(Inst::Jmp(start));
+                    let end = prog.len();
+                    prog[start] = split(start + 1, end);
+                }
+                Some(max) => {
+                    let mut holes = Vec::new();
+                    for _ in *min..*max {
+                        holes.push(prog.len());
+                        prog.push(Inst::Jmp(0));
+                        compile_node(node, prog)?;
+                        check_program_size(prog)?;
+                    }
+                    let end = prog.len();
+                    for h in holes {
+                        prog[h] = split(h + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rejects patterns whose unrolled repetitions grow past `MAX_PROGRAM`.
+fn check_program_size(prog: &[Inst]) -> Result<(), String> {
+    if prog.len() > MAX_PROGRAM {
+        return Err(format!("pattern too large: more than {} instructions after expanding repetitions", MAX_PROGRAM));
+    }
+    Ok(())
+}
+
+/// Capture slots of one match: `slots[2i]..slots[2i + 1]` is group `i`, as char indices.
+type Slots = Vec<Option<usize>>;
+
+/// (instruction, position) states already tried. Bumping the generation clears it, so
+/// `find_all` allocates it once rather than per match. Past `MAX_DENSE_VISITED` states the
+/// marks live in a map holding only the states actually reached.
+struct Visited {
+    marks: Marks,
+    generation: u32,
+    width: usize,
+}
+
+enum Marks {
+    Dense(Vec<u32>),
+    Sparse(HashMap<usize, u32>),
+}
+
+impl Visited {
+    fn new(prog_len: usize, text_len: usize) -> Self {
+        let width = text_len + 1;
+        let marks = match prog_len.checked_mul(width) {
+            Some(size) if size <= MAX_DENSE_VISITED => Marks::Dense(vec![0; size]),
+            _ => Marks::Sparse(HashMap::new()),
+        };
+        Visited { marks, generation: 1, width }
+    }
+
+    fn clear(&mut self) {
+        self.generation += 1;
+    }
+
+    /// Marks the state, returning whether it was already marked.
+    fn visit(&mut self, pc: usize, pos: usize) -> bool {
+        let key = pc * self.width + pos;
+        let mark = match &mut self.marks {
+            Marks::Dense(marks) => &mut marks[key],
+            Marks::Sparse(marks) => marks.entry(key).or_insert(0),
+        };
+        std::mem::replace(mark, self.generation) == self.generation
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, String> {
+        let mut names = vec![None];
+        let mut parser = Parser { chars: pattern.chars().collect(), pos: 0, names: &mut names };
+        let ast = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unmatched ')'"));
+        }
+        let mut prog = vec![Inst::Save(0)];
+        compile_node(&ast, &mut prog)?;
+        prog.push(Inst::Save(1));
+        prog.push(Inst::Match);
+        Ok(Regex { source: pattern.to_string(), prog, names })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the whole of `text` matches.
+    pub fn is_full_match(&self, text: &[char]) -> bool {
+        let mut visited = Visited::new(self.prog.len(), text.len());
+        self.run(text, 0, true, &mut visited).is_some()
+    }
+
+    /// Leftmost match starting at or after char index `from`.
+    fn find_at(&self, text: &[char], from: usize, visited: &mut Visited) -> Option<Slots> {
+        // a state that failed for an earlier start fails again, so the set is shared
+        visited.clear();
+        (from..=text.len()).find_map(|start| self.run(text, start, false, visited))
+    }
+
+    /// Every non-overlapping match, left to right; an empty match never directly follows another match.
+    fn find_all(&self, text: &[char]) -> Vec<Slots> {
+        let mut out: Vec<Slots> = Vec::new();
+        let mut visited = Visited::new(self.prog.len(), text.len());
+        let mut from = 0;
+        while from <= text.len() {
+            let Some(slots) = self.find_at(text, from, &mut visited) else { break };
+            let (start, end) = (slots[0].unwrap(), slots[1].unwrap());
+            if start == end && out.last().is_some_and(|prev| prev[1] == Some(start)) {
+                from = start + 1;
+                continue;
+            }
+            from = if end == start { end + 1 } else { end };
+            out.push(slots);
+        }
+        out
+    }
+
+    fn run(&self, text: &[char], start: usize, full: bool, visited: &mut Visited) -> Option<Slots> {
+        enum Job {
+            Run(usize, usize),
+            Restore(usize, Option<usize>),
+        }
+        let mut slots: Slots = vec![None; 2 * self.names.len()];
+        let mut jobs = vec![Job::Run(0, start)];
+        while let Some(job) = jobs.pop() {
+            let (mut pc, mut pos) = match job {
+                Job::Run(pc, pos) => (pc, pos),
+                Job::Restore(slot, old) => {
+                    slots[slot] = old;
+                    continue;
+                }
+            };
+            loop {
+                if visited.visit(pc, pos) {
+                    break;
+                }
+                match &self.prog[pc] {
+                    Inst::Char(c) => {
+                        if text.get(pos) != Some(c) {
+                            break;
+                        }
+                        pos += 1;
+                    }
+                    Inst::Any => {
+                        if text.get(pos).is_none_or(|&c| c == '\n') {
+                            break;
+                        }
+                        pos += 1;
+                    }
+                    Inst::Class(class) => {
+                        if !text.get(pos).is_some_and(|&c| class.matches(c)) {
+                            break;
+                        }
+                        pos += 1;
+                    }
+                    Inst::Bol => {
+                        if pos != 0 {
+                            break;
+                        }
+                    }
+                    Inst::Eol => {
+                        if pos != text.len() {
+                            break;
+                        }
+                    }
+                    Inst::WordBoundary(want) => {
+                        let before = pos > 0 && is_word(text[pos - 1]);
+                        let after = text.get(pos).is_some_and(|&c| is_word(c));
+                        if (before != after) != *want {
+                            break;
+                        }
+                    }
+                    Inst::Save(slot) => {
+                        jobs.push(Job::Restore(*slot, slots[*slot]));
+                        slots[*slot] = Some(pos);
+                    }
+                    Inst::Split(first, second) => {
+                        jobs.push(Job::Run(*second, pos));
+                        pc = *first;
+                        continue;
+                    }
+                    Inst::Jmp(target) => {
+                        pc = *target;
+                        continue;
+                    }
+                    Inst::Match => {
+                        if full && pos != text.len() {
+                            break;
+                        }
+                        return Some(slots);
+                    }
+                }
+                pc += 1;
+            }
+        }
+        None
+    }
+}
+
+/// A pattern argument: a compiled regex, or text compiled on the spot.
+fn regex_arg(val: &Value, func: &str) -> Result<Rc<NauxObj>, RuntimeError> {
+    if let Value::RcObj(rc) = val {
+        match rc.as_ref() {
+            NauxObj::Regex(_) => return Ok(rc.clone()),
+            NauxObj::Text(p) => {
+                let re = Regex::new(p).map_err(|e| invalid_pattern(func, &e))?;
+                return Ok(Rc::new(NauxObj::Regex(re)));
+            }
+            _ => {}
+        }
+    }
+    Err(RuntimeError::new(format!("{}: pattern must be text or regex, got {}", func, val.type_name()), None))
+}
+
+fn invalid_pattern(func: &str, err: &str) -> RuntimeError {
+    RuntimeError::new(format!("{}: invalid pattern: {}", func, err), None)
+}
+
+fn with_regex<T>(rc: &NauxObj, f: impl FnOnce(&Regex) -> T) -> T {
+    match rc {
+        NauxObj::Regex(re) => f(re),
+        _ => unreachable!("regex_arg only returns regex objects"),
+    }
+}
+
+fn text_arg(val: &Value, func: &str, what: &str) -> Result<Vec<char>, RuntimeError> {
+    val.as_text()
+        .map(|s| s.chars().collect())
+        .ok_or_else(|| RuntimeError::new(format!("{}: {} must be text, got {}", func, what, val.type_name()), None))
+}
+
+fn arity(args: &[Value], n: usize, usage: &str) -> Result<(), RuntimeError> {
+    if args.len() != n {
+        return Err(RuntimeError::new(usage, None));
+    }
+    Ok(())
+}
+
+fn span_text(text: &[char], start: Option<usize>, end: Option<usize>) -> Option<String> {
+    Some(text[start?..end?].iter().collect())
+}
+
+fn regex_compile(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "regex_compile(pattern)")?;
+    let pattern = args[0].as_text().ok_or_else(|| {
+        RuntimeError::new(format!("regex_compile: pattern must be text, got {}", args[0].type_name()), None)
+    })?;
+    let re = Regex::new(&pattern).map_err(|e| invalid_pattern("regex_compile", &e))?;
+    Ok(Value::RcObj(Rc::new(NauxObj::Regex(re))))
+}
+
+/// True only when the pattern matches the whole input.
+fn regex_match(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "regex_match(pattern, input)")?;
+    let re = regex_arg(&args[0], "regex_match")?;
+    let input = text_arg(&args[1], "regex_match", "input")?;
+    Ok(Value::Bool(with_regex(&re, |re| re.is_full_match(&input))))
+}
+
+/// `{start, end, text, groups}` per match (char offsets); `groups` maps each group's number
+/// (`"1"`, `"2"`, ...) and name, if it has one, to its text or null when it did not take part.
+fn regex_find_all(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "regex_find_all(pattern, input)")?;
+    let re = regex_arg(&args[0], "regex_find_all")?;
+    let input = text_arg(&args[1], "regex_find_all", "input")?;
+    let matches = with_regex(&re, |re| {
+        re.find_all(&input)
+            .into_iter()
+            .map(|slots| {
+                let mut groups = HashMap::new();
+                for (i, name) in re.names.iter().enumerate().skip(1) {
+                    let value = span_text(&input, slots[2 * i], slots[2 * i + 1]).map(Value::make_text).unwrap_or(Value::Null);
+                    if let Some(name) = name {
+                        groups.insert(name.clone(), value.clone());
+                    }
+                    groups.insert(i.to_string(), value);
+                }
+                let mut m = HashMap::new();
+                m.insert("start".to_string(), Value::SmallInt(slots[0].unwrap() as i64));
+                m.insert("end".to_string(), Value::SmallInt(slots[1].unwrap() as i64));
+                m.insert("text".to_string(), Value::make_text(span_text(&input, slots[0], slots[1]).unwrap()));
+                m.insert("groups".to_string(), Value::make_map(groups));
+                Value::make_map(m)
+            })
+            .collect()
+    });
+    Ok(Value::make_list(matches))
+}
+
+/// Replaces every match. In `replacement`, `$1`/`${1}` and `$name`/`${name}` insert a group
+/// (empty when it did not take part) and `$$` a literal `$`.
+fn regex_replace(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 3, "regex_replace(pattern, input, replacement)")?;
+    let re = regex_arg(&args[0], "regex_replace")?;
+    let input = text_arg(&args[1], "regex_replace", "input")?;
+    let replacement = text_arg(&args[2], "regex_replace", "replacement")?;
+    with_regex(&re, |re| {
+        let mut out = String::new();
+        let mut last = 0;
+        for slots in re.find_all(&input) {
+            let (start, end) = (slots[0].unwrap(), slots[1].unwrap());
+            out.extend(&input[last..start]);
+            expand(re, &replacement, &input, &slots, &mut out)?;
+            last = end;
+        }
+        out.extend(&input[last..]);
+        Ok(Value::make_text(out))
+    })
+}
+
+fn expand(re: &Regex, replacement: &[char], input: &[char], slots: &Slots, out: &mut String) -> Result<(), RuntimeError> {
+    let mut i = 0;
+    while i < replacement.len() {
+        let c = replacement[i];
+        i += 1;
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+        if replacement.get(i) == Some(&'$') {
+            out.push('$');
+            i += 1;
+            continue;
+        }
+        let braced = replacement.get(i) == Some(&'{');
+        let start = if braced { i + 1 } else { i };
+        let mut end = start;
+        while end < replacement.len() && is_word(replacement[end]) {
+            end += 1;
+        }
+        if braced && replacement.get(end) != Some(&'}') {
+            return Err(RuntimeError::new("regex_replace: unclosed '${' in replacement", None));
+        }
+        let name: String = replacement[start..end].iter().collect();
+        if name.is_empty() {
+            out.push('$');
+            continue;
+        }
+        let group = match name.parse::<usize>() {
+            Ok(n) => Some(n).filter(|n| *n < re.names.len()),
+            Err(_) => re.names.iter().position(|n| n.as_deref() == Some(name.as_str())),
+        };
+        let Some(g) = group else {
+            return Err(RuntimeError::new(format!("regex_replace: no group '{}' in pattern", name), None));
+        };
+        if let Some(text) = span_text(input, slots[2 * g], slots[2 * g + 1]) {
+            out.push_str(&text);
+        }
+        i = if braced { end + 1 } else { end };
+    }
+    Ok(())
+}
+
+/// Pieces of `input` between matches (empty matches split between chars).
+fn regex_split(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "regex_split(pattern, input)")?;
+    let re = regex_arg(&args[0], "regex_split")?;
+    let input = text_arg(&args[1], "regex_split", "input")?;
+    let pieces = with_regex(&re, |re| {
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for slots in re.find_all(&input) {
+            let (start, end) = (slots[0].unwrap(), slots[1].unwrap());
+            // an empty match at either edge would only add an empty piece
+            if start == end && (start == 0 || start == input.len()) {
+                continue;
+            }
+            pieces.push(Value::make_text(input[last..start].iter().collect::<String>()));
+            last = end;
+        }
+        pieces.push(Value::make_text(input[last..].iter().collect::<String>()));
+        pieces
+    });
+    Ok(Value::make_list(pieces))
+}
@@ -1 +1,238 @@
-// TODO: string helpers
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::{NauxObj, Value};
+
+pub fn register_string(env: &mut Env) {
+    env.set_builtin("str_len", str_len);
+    env.set_builtin("str_slice", str_slice);
+    env.set_builtin("str_split", str_split);
+    env.set_builtin("str_join", str_join);
+    env.set_builtin("str_trim", str_trim);
+    env.set_builtin("str_trim_start", str_trim_start);
+    env.set_builtin("str_trim_end", str_trim_end);
+    env.set_builtin("str_upper", str_upper);
+    env.set_builtin("str_lower", str_lower);
+    env.set_builtin("str_starts_with", str_starts_with);
+    env.set_builtin("str_ends_with", str_ends_with);
+    env.set_builtin("str_contains", str_contains);
+    env.set_builtin("str_replace", str_replace);
+    env.set_builtin("str_replace_all", str_replace_all);
+    env.set_builtin("str_index_of", str_index_of);
+    env.set_builtin("str_char_at", str_char_at);
+    env.set_builtin("str_repeat", str_repeat);
+    env.set_builtin("str_pad_start", str_pad_start);
+    env.set_builtin("str_pad_end", str_pad_end);
+}
+
+fn arity(args: &[Value], n: usize, usage: &str) -> Result<(), RuntimeError> {
+    if args.len() != n {
+        return Err(RuntimeError::new(usage, None));
+    }
+    Ok(())
+}
+
+fn text_arg(val: &Value, func: &str, what: &str) -> Result<String, RuntimeError> {
+    val.as_text()
+        .ok_or_else(|| RuntimeError::new(format!("{}: {} must be text, got {}", func, what, val.type_name()), None))
+}
+
+/// Non-negative integer argument (integral floats count).
+fn count_arg(val: &Value, func: &str, what: &str) -> Result<usize, RuntimeError> {
+    let n = match val {
+        Value::SmallInt(n) => Some(*n),
+        Value::Float(f) if f.fract() == 0.0 => Some(*f as i64),
+        _ => None,
+    };
+    match n {
+        Some(n) if n >= 0 => Ok(n as usize),
+        _ => Err(RuntimeError::new(format!("{}: {} must be a non-negative integer", func, what), None)),
+    }
+}
+
+fn text(s: impl Into<String>) -> Result<Value, RuntimeError> {
+    Ok(Value::make_text(s))
+}
+
+/// Length in chars, not bytes.
+fn str_len(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "str_len(text)")?;
+    Ok(Value::SmallInt(text_arg(&args[0], "str_len", "text")?.chars().count() as i64))
+}
+
+/// Chars `start..end` (end defaults to the length); both bounds are clamped to the text.
+fn str_slice(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(RuntimeError::new("str_slice(text, start, end?)", None));
+    }
+    let s = text_arg(&args[0], "str_slice", "text")?;
+    let len = s.chars().count();
+    let start = count_arg(&args[1], "str_slice", "start")?.min(len);
+    let end = match args.get(2) {
+        Some(v) => count_arg(v, "str_slice", "end")?.min(len),
+        None => len,
+    };
+    text(s.chars().skip(start).take(end.saturating_sub(start)).collect::<String>())
+}
+
+/// An empty delimiter splits into single chars.
+fn str_split(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_split(text, delimiter)")?;
+    let s = text_arg(&args[0], "str_split", "text")?;
+    let delim = text_arg(&args[1], "str_split", "delimiter")?;
+    let parts: Vec<Value> = if delim.is_empty() {
+        s.chars().map(|c| Value::make_text(c.to_string())).collect()
+    } else {
+        s.split(delim.as_str()).map(Value::make_text).collect()
+    };
+    Ok(Value::make_list(parts))
+}
+
+fn str_join(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_join(list, glue)")?;
+    let glue = text_arg(&args[1], "str_join", "glue")?;
+    let items = match &args[0] {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().clone(),
+            _ => return Err(RuntimeError::new("str_join: first arg must be list", None)),
+        },
+        _ => return Err(RuntimeError::new("str_join: first arg must be list", None)),
+    };
+    let parts = items
+        .iter()
+        .map(|v| text_arg(v, "str_join", "every element"))
+        .collect::<Result<Vec<_>, _>>()?;
+    text(parts.join(&glue))
+}
+
+fn str_trim(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "str_trim(text)")?;
+    text(text_arg(&args[0], "str_trim", "text")?.trim())
+}
+
+fn str_trim_start(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "str_trim_start(text)")?;
+    text(text_arg(&args[0], "str_trim_start", "text")?.trim_start())
+}
+
+fn str_trim_end(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "str_trim_end(text)")?;
+    text(text_arg(&args[0], "str_trim_end", "text")?.trim_end())
+}
+
+fn str_upper(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "str_upper(text)")?;
+    text(text_arg(&args[0], "str_upper", "text")?.to_uppercase())
+}
+
+fn str_lower(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 1, "str_lower(text)")?;
+    text(text_arg(&args[0], "str_lower", "text")?.to_lowercase())
+}
+
+fn str_starts_with(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_starts_with(text, prefix)")?;
+    let s = text_arg(&args[0], "str_starts_with", "text")?;
+    let prefix = text_arg(&args[1], "str_starts_with", "prefix")?;
+    Ok(Value::Bool(s.starts_with(prefix.as_str())))
+}
+
+fn str_ends_with(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_ends_with(text, suffix)")?;
+    let s = text_arg(&args[0], "str_ends_with", "text")?;
+    let suffix = text_arg(&args[1], "str_ends_with", "suffix")?;
+    Ok(Value::Bool(s.ends_with(suffix.as_str())))
+}
+
+fn str_contains(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_contains(text, needle)")?;
+    let s = text_arg(&args[0], "str_contains", "text")?;
+    let needle = text_arg(&args[1], "str_contains", "needle")?;
+    Ok(Value::Bool(s.contains(needle.as_str())))
+}
+
+/// Replaces the first occurrence only.
+fn str_replace(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 3, "str_replace(text, old, new)")?;
+    let s = text_arg(&args[0], "str_replace", "text")?;
+    let old = text_arg(&args[1], "str_replace", "old")?;
+    let new = text_arg(&args[2], "str_replace", "new")?;
+    text(s.replacen(old.as_str(), &new, 1))
+}
+
+fn str_replace_all(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 3, "str_replace_all(text, old, new)")?;
+    let s = text_arg(&args[0], "str_replace_all", "text")?;
+    let old = text_arg(&args[1], "str_replace_all", "old")?;
+    let new = text_arg(&args[2], "str_replace_all", "new")?;
+    if old.is_empty() {
+        return Err(RuntimeError::new("str_replace_all: old must not be empty", None));
+    }
+    text(s.replace(old.as_str(), &new))
+}
+
+/// Char index of the first occurrence, -1 when absent.
+fn str_index_of(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_index_of(text, needle)")?;
+    let s = text_arg(&args[0], "str_index_of", "text")?;
+    let needle = text_arg(&args[1], "str_index_of", "needle")?;
+    let idx = s.find(needle.as_str()).map(|byte| s[..byte].chars().count() as i64).unwrap_or(-1);
+    Ok(Value::SmallInt(idx))
+}
+
+fn str_char_at(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_char_at(text, idx)")?;
+    let s = text_arg(&args[0], "str_char_at", "text")?;
+    let idx = count_arg(&args[1], "str_char_at", "idx")?;
+    match s.chars().nth(idx) {
+        Some(c) => text(c.to_string()),
+        None => Err(RuntimeError::new(
+            format!("str_char_at: index {} out of range for text of length {}", idx, s.chars().count()),
+            None,
+        )),
+    }
+}
+
+/// Longest text, in bytes, `str_repeat` and the padding builtins will build.
+const MAX_BUILT_TEXT: usize = 1 << 28;
+
+/// Fails when `unit` bytes repeated `n` times would exceed `MAX_BUILT_TEXT`.
+fn check_built_len(unit: usize, n: usize, func: &str) -> Result<(), RuntimeError> {
+    match unit.checked_mul(n) {
+        Some(total) if total <= MAX_BUILT_TEXT => Ok(()),
+        _ => Err(RuntimeError::new(format!("{}: result longer than {} bytes", func, MAX_BUILT_TEXT), None)),
+    }
+}
+
+fn str_repeat(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    arity(&args, 2, "str_repeat(text, n)")?;
+    let s = text_arg(&args[0], "str_repeat", "text")?;
+    let n = count_arg(&args[1], "str_repeat", "n")?;
+    check_built_len(s.len(), n, "str_repeat")?;
+    text(s.repeat(n))
+}
+
+/// Padding shared by `str_pad_start`/`str_pad_end`: how many `fill` chars reach `len` chars.
+fn pad_args(args: &[Value], func: &str) -> Result<(String, String), RuntimeError> {
+    arity(args, 3, &format!("{}(text, len, char)", func))?;
+    let s = text_arg(&args[0], func, "text")?;
+    let len = count_arg(&args[1], func, "len")?;
+    let fill = text_arg(&args[2], func, "char")?;
+    let mut fill_chars = fill.chars();
+    let (Some(c), None) = (fill_chars.next(), fill_chars.next()) else {
+        return Err(RuntimeError::new(format!("{}: char must be a single character", func), None));
+    };
+    let missing = len.saturating_sub(s.chars().count());
+    check_built_len(c.len_utf8(), missing, func)?;
+    let pad = c.to_string().repeat(missing);
+    Ok((s, pad))
+}
+
+fn str_pad_start(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (s, pad) = pad_args(&args, "str_pad_start")?;
+    text(pad + &s)
+}
+
+fn str_pad_end(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let (s, pad) = pad_args(&args, "str_pad_end")?;
+    text(s + &pad)
+}
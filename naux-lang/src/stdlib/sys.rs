@@ -0,0 +1,48 @@
+//! Host access for scripts: CLI arguments and environment variables.
+//!
+//! Environment variables often hold secrets (tokens, keys), so `env_get` only works after the
+//! host opts in with `allow_env(true)` (`naux run --allow-env`). Arguments are always readable
+//! since the user passed them explicitly.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::Value;
+
+static ENV_ALLOWED: AtomicBool = AtomicBool::new(false);
+static SCRIPT_ARGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn register_sys(env: &mut Env) {
+    env.set_builtin("env_get", env_get);
+    env.set_builtin("args", args);
+}
+
+pub fn allow_env(on: bool) {
+    ENV_ALLOWED.store(on, Ordering::SeqCst);
+}
+
+/// Arguments forwarded after `--` on the command line.
+pub fn set_script_args(args: Vec<String>) {
+    *SCRIPT_ARGS.lock().unwrap() = args;
+}
+
+fn env_get(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let name = match args.as_slice() {
+        [v] => v.as_text().ok_or_else(|| RuntimeError::new("env_get(name)", None))?,
+        _ => return Err(RuntimeError::new("env_get(name)", None)),
+    };
+    if !ENV_ALLOWED.load(Ordering::SeqCst) {
+        return Err(RuntimeError::new("env_get is disabled; run with --allow-env", None));
+    }
+    Ok(std::env::var(&name).map(Value::make_text).unwrap_or(Value::Null))
+}
+
+fn args(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if !args.is_empty() {
+        return Err(RuntimeError::new("args()", None));
+    }
+    let items = SCRIPT_ARGS.lock().unwrap().iter().cloned().map(Value::make_text).collect();
+    Ok(Value::make_list(items))
+}
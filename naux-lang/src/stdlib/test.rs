@@ -1,9 +1,21 @@
 use crate::runtime::env::Env;
 use crate::runtime::error::RuntimeError;
 use crate::runtime::value::Value;
+use crate::stdlib::math::approx_eq;
 
 pub fn register_tests(env: &mut Env) {
     env.set_builtin("assert_equal", builtin_assert_equal);
+    env.set_builtin("assert_throws", builtin_assert_throws);
+    env.set_builtin("assert_near", builtin_assert_near);
+}
+
+/// Tolerance `assert_near` uses when no epsilon is passed; loose enough for FFT-style round-off.
+pub const DEFAULT_NEAR_EPSILON: f64 = 1e-9;
+
+/// Both engines intercept `assert_throws` calls before builtin dispatch (the target function
+/// must run inside the interpreter); this entry only keeps the name known to `check`.
+fn builtin_assert_throws(_args: Vec<Value>) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::new("assert_throws must be called directly", None))
 }
 
 fn builtin_assert_equal(args: Vec<Value>) -> Result<Value, RuntimeError> {
@@ -18,3 +30,12 @@ fn builtin_assert_equal(args: Vec<Value>) -> Result<Value, RuntimeError> {
         Ok(Value::Bool(false))
     }
 }
+
+fn builtin_assert_near(mut args: Vec<Value>) -> Result<Value, RuntimeError> {
+    match args.len() {
+        2 => args.push(Value::Float(DEFAULT_NEAR_EPSILON)),
+        3 => {}
+        _ => return Err(RuntimeError::new("assert_near(a, b, eps?)", None)),
+    }
+    approx_eq(args)
+}
@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::runtime::env::Env;
+use crate::runtime::error::RuntimeError;
+use crate::runtime::value::Value;
+
+/// Longest pause `sleep` will honor, so a script can't hang the host.
+pub const MAX_SLEEP_MS: i64 = 5_000;
+
+// In deterministic mode (`naux test`) `now_ms` reads a fake clock that only `sleep` and reads advance.
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+static FAKE_CLOCK_MS: AtomicI64 = AtomicI64::new(0);
+
+pub fn register_time(env: &mut Env) {
+    env.set_builtin("now_ms", now_ms);
+    env.set_builtin("sleep", sleep);
+}
+
+/// Switch the deterministic clock on or off; either way it restarts from 0.
+pub fn set_deterministic(on: bool) {
+    DETERMINISTIC.store(on, Ordering::SeqCst);
+    FAKE_CLOCK_MS.store(0, Ordering::SeqCst);
+}
+
+fn now_ms(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    if !args.is_empty() {
+        return Err(RuntimeError::new("now_ms()", None));
+    }
+    if DETERMINISTIC.load(Ordering::SeqCst) {
+        return Ok(Value::SmallInt(FAKE_CLOCK_MS.fetch_add(1, Ordering::SeqCst)));
+    }
+    let ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    Ok(Value::SmallInt(ms as i64))
+}
+
+fn sleep(args: Vec<Value>) -> Result<Value, RuntimeError> {
+    let ms = match args.as_slice() {
+        [v] => v.as_f64().ok_or_else(|| RuntimeError::new("sleep(ms)", None))? as i64,
+        _ => return Err(RuntimeError::new("sleep(ms)", None)),
+    };
+    if ms < 0 {
+        return Err(RuntimeError::new("sleep: ms must be >= 0", None));
+    }
+    let ms = ms.min(MAX_SLEEP_MS);
+    thread::sleep(Duration::from_millis(ms as u64));
+    if DETERMINISTIC.load(Ordering::SeqCst) {
+        FAKE_CLOCK_MS.fetch_add(ms, Ordering::SeqCst);
+    }
+    Ok(Value::Null)
+}
@@ -25,12 +25,15 @@ pub enum TokenKind {
     AndAnd,
     OrOr,
     Colon,
+    Question,   // ?
     Op(String),
 
     // Literals / idents
     Ident(String),
     Number(f64),
     StringLit(String),
+    /// A string literal containing `{expr}` interpolations.
+    StringInterp(Vec<StringPart>),
 
     // Keywords
     If,
@@ -42,6 +45,10 @@ pub enum TokenKind {
     Loop,
     Each,
     While,
+    Break,
+    Continue,
+    Try,
+    Catch,
     End,
     In,
 
@@ -49,7 +56,15 @@ pub enum TokenKind {
     Eof,
 }
 
-#[derive(Debug, Clone)]
+/// Piece of an interpolated string: literal text or the tokens of an embedded expression
+/// (terminated by `Eof`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    Expr(Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
@@ -44,6 +44,15 @@ pub enum Instr {
     EmitText,
     EmitButton,
     EmitLog,
+    EmitTable,
+    EmitProgress,
+    EnterUnsafe,
+    ExitUnsafe,
+    /// Start of a `~ try` body; errors until the matching `LeaveTry` jump to the handler.
+    EnterTry(usize),
+    LeaveTry,
+    /// Pop a value and raise it as a `throw(...)` error.
+    Throw,
     Return,
 }
 
@@ -55,6 +64,7 @@ pub struct FunctionBytecode {
     pub locals: Vec<String>, // includes params first, then locals
     pub code: Bytecode,
     pub spans: Vec<Option<Span>>,
+    pub memo: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +75,303 @@ pub struct Program {
     pub functions: HashMap<String, FunctionBytecode>,
 }
 
+/// Magic bytes at the start of a serialized `.nxb` program.
+pub const NXBC_MAGIC: &[u8; 4] = b"NXBC";
+/// Bump whenever the opcode table or section layout changes.
+pub const NXBC_VERSION: u16 = 1;
+
+impl Program {
+    /// Serialize to the `.nxb` format: magic, version, the main chunk, then every function
+    /// (sorted by name) as name, memo flag, params, locals and instructions.
+    /// Spans are not stored; a loaded program reports errors without source context.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = ByteWriter::default();
+        w.buf.extend_from_slice(NXBC_MAGIC);
+        w.buf.extend_from_slice(&NXBC_VERSION.to_le_bytes());
+        w.strings(&self.main_locals);
+        w.code(&self.main);
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort();
+        w.len(names.len());
+        for name in names {
+            let f = &self.functions[name];
+            w.string(name);
+            w.buf.push(f.memo as u8);
+            w.strings(&f.params);
+            w.strings(&f.locals);
+            w.code(&f.code);
+        }
+        w.buf
+    }
+
+    /// Inverse of [`Program::to_bytes`]. Rejects wrong magic, other versions and truncated input.
+    pub fn from_bytes(data: &[u8]) -> Result<Program, String> {
+        if !is_bytecode(data) {
+            return Err("not a NAUX bytecode file (missing NXBC magic)".into());
+        }
+        let mut r = ByteReader { data, pos: NXBC_MAGIC.len() };
+        let version = r.u16()?;
+        if version != NXBC_VERSION {
+            return Err(format!(
+                "unsupported bytecode version {} (this build reads version {}); rebuild with `naux build`",
+                version, NXBC_VERSION
+            ));
+        }
+        let main_locals = r.strings()?;
+        let main = r.code()?;
+        let mut functions = HashMap::new();
+        for _ in 0..r.len()? {
+            let name = r.string()?;
+            let memo = r.u8()? != 0;
+            let params = r.strings()?;
+            let locals = r.strings()?;
+            let code = r.code()?;
+            let spans = vec![None; code.len()];
+            functions.insert(name, FunctionBytecode { params, locals, code, spans, memo });
+        }
+        if r.pos != data.len() {
+            return Err(format!("trailing data in bytecode at offset {}", r.pos));
+        }
+        let main_spans = vec![None; main.len()];
+        Ok(Program { main, main_locals, main_spans, functions })
+    }
+}
+
+/// True when `data` starts with the `.nxb` magic bytes.
+pub fn is_bytecode(data: &[u8]) -> bool {
+    data.starts_with(NXBC_MAGIC)
+}
+
+#[derive(Default)]
+struct ByteWriter {
+    buf: Vec<u8>,
+}
+
+impl ByteWriter {
+    fn len(&mut self, n: usize) {
+        self.buf.extend_from_slice(&(n as u32).to_le_bytes());
+    }
+
+    fn string(&mut self, s: &str) {
+        self.len(s.len());
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    fn strings(&mut self, items: &[String]) {
+        self.len(items.len());
+        for s in items {
+            self.string(s);
+        }
+    }
+
+    fn code(&mut self, code: &[Instr]) {
+        self.len(code.len());
+        for instr in code {
+            self.instr(instr);
+        }
+    }
+
+    fn instr(&mut self, i: &Instr) {
+        match i {
+            Instr::ConstNum(n) => {
+                self.buf.push(0);
+                self.buf.extend_from_slice(&n.to_le_bytes());
+            }
+            Instr::ConstText(s) => {
+                self.buf.push(1);
+                self.string(s);
+            }
+            Instr::ConstBool(b) => {
+                self.buf.push(2);
+                self.buf.push(*b as u8);
+            }
+            Instr::PushNull => self.buf.push(3),
+            Instr::LoadVar(v) => {
+                self.buf.push(4);
+                self.string(v);
+            }
+            Instr::StoreVar(v) => {
+                self.buf.push(5);
+                self.string(v);
+            }
+            Instr::LoadLocal(idx) => {
+                self.buf.push(6);
+                self.len(*idx);
+            }
+            Instr::StoreLocal(idx) => {
+                self.buf.push(7);
+                self.len(*idx);
+            }
+            Instr::Add => self.buf.push(8),
+            Instr::Sub => self.buf.push(9),
+            Instr::Mul => self.buf.push(10),
+            Instr::Div => self.buf.push(11),
+            Instr::Mod => self.buf.push(12),
+            Instr::Eq => self.buf.push(13),
+            Instr::Ne => self.buf.push(14),
+            Instr::Gt => self.buf.push(15),
+            Instr::Ge => self.buf.push(16),
+            Instr::Lt => self.buf.push(17),
+            Instr::Le => self.buf.push(18),
+            Instr::And => self.buf.push(19),
+            Instr::Or => self.buf.push(20),
+            Instr::Jump(t) => {
+                self.buf.push(21);
+                self.len(*t);
+            }
+            Instr::JumpIfFalse(t) => {
+                self.buf.push(22);
+                self.len(*t);
+            }
+            Instr::CallBuiltin(n, argc) => {
+                self.buf.push(23);
+                self.string(n);
+                self.len(*argc);
+            }
+            Instr::CallFn(n, argc) => {
+                self.buf.push(24);
+                self.string(n);
+                self.len(*argc);
+            }
+            Instr::MakeList(n) => {
+                self.buf.push(25);
+                self.len(*n);
+            }
+            Instr::MakeMap(keys) => {
+                self.buf.push(26);
+                self.strings(keys);
+            }
+            Instr::LoadField(f) => {
+                self.buf.push(27);
+                self.string(f);
+            }
+            Instr::EmitSay => self.buf.push(28),
+            Instr::EmitAsk => self.buf.push(29),
+            Instr::EmitFetch => self.buf.push(30),
+            Instr::EmitUi(k) => {
+                self.buf.push(31);
+                self.string(k);
+            }
+            Instr::EmitText => self.buf.push(32),
+            Instr::EmitButton => self.buf.push(33),
+            Instr::EmitLog => self.buf.push(34),
+            Instr::EmitTable => self.buf.push(35),
+            Instr::EmitProgress => self.buf.push(36),
+            Instr::EnterUnsafe => self.buf.push(37),
+            Instr::ExitUnsafe => self.buf.push(38),
+            Instr::EnterTry(t) => {
+                self.buf.push(39);
+                self.len(*t);
+            }
+            Instr::LeaveTry => self.buf.push(40),
+            Instr::Throw => self.buf.push(41),
+            Instr::Return => self.buf.push(42),
+        }
+    }
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl ByteReader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len());
+        let end = end.ok_or_else(|| format!("truncated bytecode at offset {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(b))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let n = self.len()?;
+        let at = self.pos;
+        let bytes = self.take(n)?.to_vec();
+        String::from_utf8(bytes).map_err(|_| format!("invalid UTF-8 string in bytecode at offset {}", at))
+    }
+
+    fn strings(&mut self) -> Result<Vec<String>, String> {
+        let n = self.len()?;
+        (0..n).map(|_| self.string()).collect()
+    }
+
+    fn code(&mut self) -> Result<Bytecode, String> {
+        let n = self.len()?;
+        (0..n).map(|_| self.instr()).collect()
+    }
+
+    fn instr(&mut self) -> Result<Instr, String> {
+        let at = self.pos;
+        Ok(match self.u8()? {
+            0 => Instr::ConstNum(self.f64()?),
+            1 => Instr::ConstText(self.string()?),
+            2 => Instr::ConstBool(self.u8()? != 0),
+            3 => Instr::PushNull,
+            4 => Instr::LoadVar(self.string()?),
+            5 => Instr::StoreVar(self.string()?),
+            6 => Instr::LoadLocal(self.len()?),
+            7 => Instr::StoreLocal(self.len()?),
+            8 => Instr::Add,
+            9 => Instr::Sub,
+            10 => Instr::Mul,
+            11 => Instr::Div,
+            12 => Instr::Mod,
+            13 => Instr::Eq,
+            14 => Instr::Ne,
+            15 => Instr::Gt,
+            16 => Instr::Ge,
+            17 => Instr::Lt,
+            18 => Instr::Le,
+            19 => Instr::And,
+            20 => Instr::Or,
+            21 => Instr::Jump(self.len()?),
+            22 => Instr::JumpIfFalse(self.len()?),
+            23 => Instr::CallBuiltin(self.string()?, self.len()?),
+            24 => Instr::CallFn(self.string()?, self.len()?),
+            25 => Instr::MakeList(self.len()?),
+            26 => Instr::MakeMap(self.strings()?),
+            27 => Instr::LoadField(self.string()?),
+            28 => Instr::EmitSay,
+            29 => Instr::EmitAsk,
+            30 => Instr::EmitFetch,
+            31 => Instr::EmitUi(self.string()?),
+            32 => Instr::EmitText,
+            33 => Instr::EmitButton,
+            34 => Instr::EmitLog,
+            35 => Instr::EmitTable,
+            36 => Instr::EmitProgress,
+            37 => Instr::EnterUnsafe,
+            38 => Instr::ExitUnsafe,
+            39 => Instr::EnterTry(self.len()?),
+            40 => Instr::LeaveTry,
+            41 => Instr::Throw,
+            42 => Instr::Return,
+            op => return Err(format!("unknown opcode {} at offset {}", op, at)),
+        })
+    }
+}
+
 /// Result value from VM execution.
 pub type VmResult<T = Value> = Result<T, String>;
 
@@ -104,6 +411,13 @@ pub fn fmt_instr_bc(i: &Instr) -> String {
         Instr::EmitText => "EmitText".into(),
         Instr::EmitButton => "EmitButton".into(),
         Instr::EmitLog => "EmitLog".into(),
+        Instr::EmitTable => "EmitTable".into(),
+        Instr::EmitProgress => "EmitProgress".into(),
+        Instr::EnterUnsafe => "EnterUnsafe".into(),
+        Instr::ExitUnsafe => "ExitUnsafe".into(),
+        Instr::EnterTry(t) => format!("EnterTry {}", t),
+        Instr::LeaveTry => "LeaveTry".into(),
+        Instr::Throw => "Throw".into(),
         Instr::Return => "Return".into(),
         Instr::LoadLocal(idx) => format!("LoadLocal {}", idx),
         Instr::StoreLocal(idx) => format!("StoreLocal {}", idx),
@@ -1,9 +1,11 @@
 // Compiler: AST -> IR -> Bytecode
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, Span, Stmt, UnaryOp};
+use crate::ast::{ActionKind, BinaryOp, Expr, ExprKind, InterpolPart, Span, Stmt, UnaryOp};
+use crate::runtime::value::EQ_EPSILON;
+use crate::stdlib::list::is_higher_order;
 use crate::vm::bytecode::{Bytecode, FunctionBytecode, Instr, Program};
 use crate::vm::ir::{IRBlock, IRFunction, IRInstr, IRNode, IRProgram};
 
@@ -17,13 +19,15 @@ pub fn compile_script(stmts: &[Stmt]) -> Program {
 /// Compile AST into IR (stack-based).
 pub fn compile_ir(stmts: &[Stmt]) -> IRProgram {
     let mut main: Vec<IRNode> = Vec::new();
+    let mut main_loops = LoopStack::default();
     let mut functions: HashMap<String, IRFunction> = HashMap::new();
     for stmt in stmts {
         match stmt {
-            Stmt::FnDef { name, params, body, .. } => {
+            Stmt::FnDef { name, params, body, memo, .. } => {
                 let mut code = Vec::new();
+                let mut loops = LoopStack::default();
                 for s in body {
-                    compile_stmt_ir(s, &mut code);
+                    compile_stmt_ir(s, &mut code, &mut loops);
                 }
                 code.push(IRNode::new(IRInstr::Return, None));
                 functions.insert(
@@ -31,23 +35,43 @@ pub fn compile_ir(stmts: &[Stmt]) -> IRProgram {
                     IRFunction {
                         params: params.clone(),
                         code,
+                        memo: *memo,
                     },
                 );
             }
-            _ => compile_stmt_ir(stmt, &mut main),
+            _ => compile_stmt_ir(stmt, &mut main, &mut main_loops),
         }
     }
     main.push(IRNode::new(IRInstr::Return, None));
     IRProgram { main, functions }
 }
 
+/// Every `CallFn` target that is neither a script function nor in `known`, with its call-site span.
+/// Runs on unoptimized IR so calls in dead branches are still reported.
+pub fn unresolved_calls(ir: &IRProgram, known: &HashSet<String>) -> Vec<(String, Option<Span>)> {
+    let mut out = Vec::new();
+    let mut names: Vec<&String> = ir.functions.keys().collect();
+    names.sort();
+    let blocks = std::iter::once(&ir.main).chain(names.into_iter().map(|n| &ir.functions[n].code));
+    for block in blocks {
+        for node in block {
+            if let IRInstr::CallFn(name, _) = &node.instr {
+                if !known.contains(name) && !ir.functions.contains_key(name) {
+                    out.push((name.clone(), node.span.clone()));
+                }
+            }
+        }
+    }
+    out
+}
+
 /// Peephole optimizer: const-fold basic arith/compare, drop trivial jumps, prune unreachable.
 fn optimize_ir(ir: IRProgram) -> IRProgram {
     let main = optimize_block(ir.main);
     let functions = ir
         .functions
         .into_iter()
-        .map(|(name, f)| (name, IRFunction { params: f.params, code: optimize_block(f.code) }))
+        .map(|(name, f)| (name, IRFunction { params: f.params, code: optimize_block(f.code), memo: f.memo }))
         .collect();
     IRProgram { main, functions }
 }
@@ -59,10 +83,23 @@ fn optimize_block(block: Vec<IRNode>) -> Vec<IRNode> {
     let mut map_old_to_new: Vec<Option<usize>> = vec![None; block.len()];
     let mut const_env: HashMap<String, IRInstr> = HashMap::new();
     let mut prev_const: Option<IRInstr> = None;
+    let jump_targets: HashSet<usize> = block
+        .iter()
+        .filter_map(|n| match n.instr {
+            IRInstr::Jump(t) | IRInstr::JumpIfFalse(t) | IRInstr::EnterTry(t) => Some(t),
+            _ => None,
+        })
+        .collect();
     let mut i = 0;
     while i < block.len() {
+        // Values known before a jump target may not hold on every incoming edge
+        if jump_targets.contains(&i) {
+            const_env.clear();
+            prev_const = None;
+        }
+        let is_target = |k: usize| jump_targets.contains(&k);
         // Const-fold arithmetic/compare on two consts followed by op
-        if i + 2 < block.len() {
+        if i + 2 < block.len() && !is_target(i + 1) && !is_target(i + 2) {
             if let (IRInstr::ConstNum(a), IRInstr::ConstNum(b), op) = (&block[i].instr, &block[i + 1].instr, &block[i + 2].instr) {
                 if let Some(res_num) = fold_num(*a, *b, op) {
                     let new_idx = out.len();
@@ -88,7 +125,7 @@ fn optimize_block(block: Vec<IRNode>) -> Vec<IRNode> {
         }
 
         // Simplify JumpIfFalse fed by ConstBool
-        if i + 1 < block.len() {
+        if i + 1 < block.len() && !is_target(i + 1) {
             if let (IRInstr::ConstBool(b), IRInstr::JumpIfFalse(t)) = (&block[i].instr, &block[i + 1].instr) {
                 if *b {
                     // condition true -> drop both instructions
@@ -166,7 +203,7 @@ fn optimize_block(block: Vec<IRNode>) -> Vec<IRNode> {
     for (pos, node) in out.iter_mut().enumerate() {
         let _orig = orig_idx[pos];
         match node.instr {
-            IRInstr::Jump(ref mut tgt) | IRInstr::JumpIfFalse(ref mut tgt) => {
+            IRInstr::Jump(ref mut tgt) | IRInstr::JumpIfFalse(ref mut tgt) | IRInstr::EnterTry(ref mut tgt) => {
                 if let Some(new_tgt) = remap_target(*tgt, &map_old_to_new) {
                     *tgt = new_tgt;
                 }
@@ -202,11 +239,11 @@ fn prune_unreachable(block: Vec<IRNode>) -> Vec<IRNode> {
         reach[idx] = true;
         match block[idx].instr {
             IRInstr::Jump(t) => dfs(t, block, reach),
-            IRInstr::JumpIfFalse(t) => {
+            IRInstr::JumpIfFalse(t) | IRInstr::EnterTry(t) => {
                 dfs(idx + 1, block, reach);
                 dfs(t, block, reach);
             }
-            IRInstr::Return => {}
+            IRInstr::Return | IRInstr::Throw => {}
             _ => dfs(idx + 1, block, reach),
         }
     }
@@ -223,7 +260,9 @@ fn prune_unreachable(block: Vec<IRNode>) -> Vec<IRNode> {
     // Remap jumps after pruning
     for instr in new_block.iter_mut() {
         match instr {
-            IRNode { instr: IRInstr::Jump(ref mut t), .. } | IRNode { instr: IRInstr::JumpIfFalse(ref mut t), .. } => {
+            IRNode { instr: IRInstr::Jump(ref mut t), .. }
+            | IRNode { instr: IRInstr::JumpIfFalse(ref mut t), .. }
+            | IRNode { instr: IRInstr::EnterTry(ref mut t), .. } => {
                 if let Some(nt) = remap_target(*t, &map_old_new) {
                     *t = nt;
                 }
@@ -239,7 +278,10 @@ fn fold_num(a: f64, b: f64, op: &IRInstr) -> Option<f64> {
         IRInstr::Add => Some(a + b),
         IRInstr::Sub => Some(a - b),
         IRInstr::Mul => Some(a * b),
+        // leave x / 0 and x % 0 to the VM so it reports the error at runtime
+        IRInstr::Div if b == 0.0 => None,
         IRInstr::Div => Some(a / b),
+        IRInstr::Mod if b == 0.0 => None,
         IRInstr::Mod => Some(a % b),
         _ => None,
     }
@@ -247,8 +289,8 @@ fn fold_num(a: f64, b: f64, op: &IRInstr) -> Option<f64> {
 
 fn fold_cmp(a: f64, b: f64, op: &IRInstr) -> Option<bool> {
     match op {
-        IRInstr::Eq => Some((a - b).abs() < f64::EPSILON),
-        IRInstr::Ne => Some((a - b).abs() >= f64::EPSILON),
+        IRInstr::Eq => Some((a - b).abs() <= EQ_EPSILON),
+        IRInstr::Ne => Some((a - b).abs() > EQ_EPSILON),
         IRInstr::Gt => Some(a > b),
         IRInstr::Ge => Some(a >= b),
         IRInstr::Lt => Some(a < b),
@@ -258,15 +300,40 @@ fn fold_cmp(a: f64, b: f64, op: &IRInstr) -> Option<bool> {
 }
 
 fn lower_ir_to_bytecode(ir: IRProgram) -> Program {
-    let (main, main_locals, main_spans) = lower_block(ir.main, &[]);
+    let script_fns: HashSet<String> = ir.functions.keys().cloned().collect();
+    let (main, main_locals, main_spans) = lower_block(resolve_callbacks(ir.main, &[], &script_fns), &[]);
     let mut functions: HashMap<String, FunctionBytecode> = HashMap::new();
     for (name, f) in ir.functions {
-        let (code, locals, spans) = lower_block(f.code, &f.params);
-        functions.insert(name, FunctionBytecode { params: f.params, locals, code, spans });
+        let (code, locals, spans) = lower_block(resolve_callbacks(f.code, &f.params, &script_fns), &f.params);
+        functions.insert(name, FunctionBytecode { params: f.params, locals, code, spans, memo: f.memo });
     }
     Program { main, main_locals, main_spans, functions }
 }
 
+/// Turns each `LoadCallback` into a variable load or a function name, mirroring the
+/// interpreter: a name the block assigns (or a parameter) is read as a variable, as is every
+/// argument of a script function that shadows the builtin; any other name is passed as text.
+fn resolve_callbacks(mut block: IRBlock, params: &[String], script_fns: &HashSet<String>) -> IRBlock {
+    let assigned: HashSet<String> = params
+        .iter()
+        .cloned()
+        .chain(block.iter().filter_map(|node| match &node.instr {
+            IRInstr::StoreVar(name) => Some(name.clone()),
+            _ => None,
+        }))
+        .collect();
+    for node in &mut block {
+        if let IRInstr::LoadCallback { name, callee } = &node.instr {
+            node.instr = if assigned.contains(name) || script_fns.contains(callee) {
+                IRInstr::LoadVar(name.clone())
+            } else {
+                IRInstr::ConstText(name.clone())
+            };
+        }
+    }
+    block
+}
+
 fn lower_block(block: IRBlock, params: &[String]) -> (Bytecode, Vec<String>, Vec<Option<Span>>) {
     let (locals, mapping) = collect_locals(&block, params);
     let mut code = Bytecode::new();
@@ -287,6 +354,7 @@ fn lower_instr(i: IRInstr, slots: &HashMap<String, usize>) -> Instr {
         IRInstr::PushNull => Instr::PushNull,
         IRInstr::LoadVar(s) => Instr::LoadLocal(*slots.get(&s).expect("slot missing")),
         IRInstr::StoreVar(s) => Instr::StoreLocal(*slots.get(&s).expect("slot missing")),
+        IRInstr::LoadCallback { .. } => unreachable!("callbacks are resolved before lowering"),
         IRInstr::Add => Instr::Add,
         IRInstr::Sub => Instr::Sub,
         IRInstr::Mul => Instr::Mul,
@@ -314,16 +382,23 @@ fn lower_instr(i: IRInstr, slots: &HashMap<String, usize>) -> Instr {
         IRInstr::EmitText => Instr::EmitText,
         IRInstr::EmitButton => Instr::EmitButton,
         IRInstr::EmitLog => Instr::EmitLog,
+        IRInstr::EmitTable => Instr::EmitTable,
+        IRInstr::EmitProgress => Instr::EmitProgress,
+        IRInstr::EnterUnsafe => Instr::EnterUnsafe,
+        IRInstr::ExitUnsafe => Instr::ExitUnsafe,
+        IRInstr::EnterTry(t) => Instr::EnterTry(t),
+        IRInstr::LeaveTry => Instr::LeaveTry,
+        IRInstr::Throw => Instr::Throw,
         IRInstr::Return => Instr::Return,
     }
 }
 
-/// Peephole bytecode optimizer: drop immediate LoadLocal after StoreLocal when not a jump target.
+/// Peephole bytecode optimizer: drop self-assignments (LoadLocal x; StoreLocal x) when not a jump target.
 fn optimize_bytecode_block(code: Bytecode, spans: Vec<Option<Span>>) -> (Bytecode, Vec<Option<Span>>) {
     let mut jump_targets: Vec<usize> = Vec::new();
     for instr in &code {
         match instr {
-            Instr::Jump(t) | Instr::JumpIfFalse(t) => jump_targets.push(*t),
+            Instr::Jump(t) | Instr::JumpIfFalse(t) | Instr::EnterTry(t) => jump_targets.push(*t),
             _ => {}
         }
     }
@@ -333,13 +408,10 @@ fn optimize_bytecode_block(code: Bytecode, spans: Vec<Option<Span>>) -> (Bytecod
     let mut i = 0;
     while i < code.len() {
         if i + 1 < code.len() {
-            if let Instr::StoreLocal(a) = code[i] {
-                if let Instr::LoadLocal(b) = code[i + 1] {
+            if let Instr::LoadLocal(a) = code[i] {
+                if let Instr::StoreLocal(b) = code[i + 1] {
                     if a == b && !jump_targets.contains(&(i + 1)) {
-                        let new_idx = out.len();
-                        out.push(code[i].clone());
-                        out_spans.push(spans.get(i).cloned().unwrap_or(None));
-                        map_old_to_new[i] = Some(new_idx);
+                        map_old_to_new[i] = None;
                         map_old_to_new[i + 1] = None;
                         i += 2;
                         continue;
@@ -356,7 +428,7 @@ fn optimize_bytecode_block(code: Bytecode, spans: Vec<Option<Span>>) -> (Bytecod
     // remap jumps
     for instr in out.iter_mut() {
         match instr {
-            Instr::Jump(ref mut t) | Instr::JumpIfFalse(ref mut t) => {
+            Instr::Jump(ref mut t) | Instr::JumpIfFalse(ref mut t) | Instr::EnterTry(ref mut t) => {
                 if let Some(nt) = remap_target(*t, &map_old_to_new) {
                     *t = nt;
                 }
@@ -390,7 +462,52 @@ fn collect_locals(block: &[IRNode], params: &[String]) -> (Vec<String>, HashMap<
     (locals, map)
 }
 
-fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
+/// Jump bookkeeping for the loops enclosing the statement being compiled.
+#[derive(Default)]
+struct LoopStack {
+    frames: Vec<LoopFrame>,
+    /// Open `~ unsafe` blocks; jumping out of a loop closes those opened inside it.
+    unsafe_depth: usize,
+    /// Open `~ try` bodies, likewise left when jumping out of a loop.
+    try_depth: usize,
+}
+
+/// `~ break`/`~ continue` jumps of one loop, patched once its end and step are known.
+struct LoopFrame {
+    unsafe_depth: usize,
+    try_depth: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+impl LoopStack {
+    fn compile_body(&mut self, body: &[Stmt], bc: &mut Vec<IRNode>) -> LoopFrame {
+        self.frames.push(LoopFrame {
+            unsafe_depth: self.unsafe_depth,
+            try_depth: self.try_depth,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+        for s in body {
+            compile_stmt_ir(s, bc, self);
+        }
+        self.frames.pop().expect("loop frame pushed above")
+    }
+}
+
+impl LoopFrame {
+    fn patch(self, bc: &mut [IRNode], continue_target: usize, break_target: usize) {
+        for (jumps, target) in [(self.continues, continue_target), (self.breaks, break_target)] {
+            for pos in jumps {
+                if let IRInstr::Jump(ref mut t) = bc[pos].instr {
+                    *t = target;
+                }
+            }
+        }
+    }
+}
+
+fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>, loops: &mut LoopStack) {
     match stmt {
         Stmt::Assign { name, expr, span } => {
             compile_expr_ir(expr, bc);
@@ -402,14 +519,14 @@ fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
             bc.push(IRNode::new(IRInstr::JumpIfFalse(0), span.clone())); // patched later
 
             for s in then_block {
-                compile_stmt_ir(s, bc);
+                compile_stmt_ir(s, bc, loops);
             }
             let jmp_end_pos = bc.len();
             bc.push(IRNode::new(IRInstr::Jump(0), span.clone())); // patched later
 
             let else_start = bc.len();
             for s in else_block {
-                compile_stmt_ir(s, bc);
+                compile_stmt_ir(s, bc, loops);
             }
             let end = bc.len();
             if let IRInstr::JumpIfFalse(ref mut target) = bc[jmp_false_pos].instr {
@@ -420,16 +537,16 @@ fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
             }
         }
         Stmt::Loop { count, body, span } => {
-            let tmp = "__loop_rem__".to_string();
+            // keyed by position so nested loops keep separate counters
+            let tmp = format!("__loop_rem_{}__", bc.len());
             compile_expr_ir(count, bc);
             bc.push(IRNode::new(IRInstr::StoreVar(tmp.clone()), span.clone()));
             let start = bc.len();
             bc.push(IRNode::new(IRInstr::LoadVar(tmp.clone()), span.clone()));
             let jmp_false = bc.len();
             bc.push(IRNode::new(IRInstr::JumpIfFalse(0), span.clone()));
-            for s in body {
-                compile_stmt_ir(s, bc);
-            }
+            let frame = loops.compile_body(body, bc);
+            let step = bc.len();
             bc.push(IRNode::new(IRInstr::LoadVar(tmp.clone()), span.clone()));
             bc.push(IRNode::new(IRInstr::ConstNum(1.0), span.clone()));
             bc.push(IRNode::new(IRInstr::Sub, span.clone()));
@@ -439,20 +556,20 @@ fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
             if let IRInstr::JumpIfFalse(ref mut target) = bc[jmp_false].instr {
                 *target = end;
             }
+            frame.patch(bc, step, end);
         }
         Stmt::While { cond, body, span } => {
             let start = bc.len();
             compile_expr_ir(cond, bc);
             let jmp_false = bc.len();
             bc.push(IRNode::new(IRInstr::JumpIfFalse(0), span.clone()));
-            for s in body {
-                compile_stmt_ir(s, bc);
-            }
+            let frame = loops.compile_body(body, bc);
             bc.push(IRNode::new(IRInstr::Jump(start), span.clone()));
             let end = bc.len();
             if let IRInstr::JumpIfFalse(ref mut target) = bc[jmp_false].instr {
                 *target = end;
             }
+            frame.patch(bc, start, end);
         }
         Stmt::Return { value, span } => {
             if let Some(expr) = value {
@@ -467,13 +584,14 @@ fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
         }
         Stmt::Rite { body, .. } => {
             for s in body {
-                compile_stmt_ir(s, bc);
+                compile_stmt_ir(s, bc, loops);
             }
         }
         Stmt::FnDef { .. } => {}
-        Stmt::Each { var, iter, body, span } => {
-            let tmp_iter = "__each_iter__".to_string();
-            let tmp_idx = "__each_idx__".to_string();
+        Stmt::Each { index, var, iter, body, span } => {
+            let tag = bc.len();
+            let tmp_iter = format!("__each_iter_{}__", tag);
+            let tmp_idx = format!("__each_idx_{}__", tag);
             compile_expr_ir(iter, bc);
             bc.push(IRNode::new(IRInstr::StoreVar(tmp_iter.clone()), span.clone()));
             bc.push(IRNode::new(IRInstr::ConstNum(0.0), span.clone()));
@@ -489,9 +607,12 @@ fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
             bc.push(IRNode::new(IRInstr::LoadVar(tmp_idx.clone()), span.clone()));
             bc.push(IRNode::new(IRInstr::CallBuiltin("__index".into(), 2), span.clone()));
             bc.push(IRNode::new(IRInstr::StoreVar(var.clone()), span.clone()));
-            for s in body {
-                compile_stmt_ir(s, bc);
+            if let Some(index) = index {
+                bc.push(IRNode::new(IRInstr::LoadVar(tmp_idx.clone()), span.clone()));
+                bc.push(IRNode::new(IRInstr::StoreVar(index.clone()), span.clone()));
             }
+            let frame = loops.compile_body(body, bc);
+            let step = bc.len();
             bc.push(IRNode::new(IRInstr::LoadVar(tmp_idx.clone()), span.clone()));
             bc.push(IRNode::new(IRInstr::ConstNum(1.0), span.clone()));
             bc.push(IRNode::new(IRInstr::Add, span.clone()));
@@ -501,8 +622,61 @@ fn compile_stmt_ir(stmt: &Stmt, bc: &mut Vec<IRNode>) {
             if let IRInstr::JumpIfFalse(ref mut target) = bc[jmp_false].instr {
                 *target = end;
             }
+            frame.patch(bc, step, end);
         }
-        Stmt::Unsafe { .. } | Stmt::Import { .. } => {}
+        Stmt::Unsafe { body, span } => {
+            bc.push(IRNode::new(IRInstr::EnterUnsafe, span.clone()));
+            loops.unsafe_depth += 1;
+            for s in body {
+                compile_stmt_ir(s, bc, loops);
+            }
+            loops.unsafe_depth -= 1;
+            bc.push(IRNode::new(IRInstr::ExitUnsafe, span.clone()));
+        }
+        Stmt::TryCatch { try_block, catch_var, catch_block, span } => {
+            let enter = bc.len();
+            bc.push(IRNode::new(IRInstr::EnterTry(0), span.clone())); // patched to the handler below
+            loops.try_depth += 1;
+            for s in try_block {
+                compile_stmt_ir(s, bc, loops);
+            }
+            loops.try_depth -= 1;
+            bc.push(IRNode::new(IRInstr::LeaveTry, span.clone()));
+            let skip = bc.len();
+            bc.push(IRNode::new(IRInstr::Jump(0), span.clone()));
+            let handler = bc.len();
+            if let IRInstr::EnterTry(ref mut target) = bc[enter].instr {
+                *target = handler;
+            }
+            // the VM enters the handler with the error map on the stack
+            bc.push(IRNode::new(IRInstr::StoreVar(catch_var.clone()), span.clone()));
+            for s in catch_block {
+                compile_stmt_ir(s, bc, loops);
+            }
+            let end = bc.len();
+            if let IRInstr::Jump(ref mut target) = bc[skip].instr {
+                *target = end;
+            }
+        }
+        Stmt::Break { span } | Stmt::Continue { span } => {
+            let (open_unsafe, open_try) = (loops.unsafe_depth, loops.try_depth);
+            // the parser only accepts these inside a loop
+            let Some(frame) = loops.frames.last_mut() else { return };
+            for _ in frame.unsafe_depth..open_unsafe {
+                bc.push(IRNode::new(IRInstr::ExitUnsafe, span.clone()));
+            }
+            for _ in frame.try_depth..open_try {
+                bc.push(IRNode::new(IRInstr::LeaveTry, span.clone()));
+            }
+            let pos = bc.len();
+            bc.push(IRNode::new(IRInstr::Jump(0), span.clone())); // patched when the loop ends
+            if matches!(stmt, Stmt::Break { .. }) {
+                frame.breaks.push(pos);
+            } else {
+                frame.continues.push(pos);
+            }
+        }
+        Stmt::Import { .. } => {}
     }
 }
 
@@ -535,6 +709,14 @@ fn compile_action_ir(action: &ActionKind, span: Option<Span>, bc: &mut Vec<IRNod
             compile_expr_ir(value, bc);
             bc.push(IRNode::new(IRInstr::EmitLog, span.clone()));
         }
+        ActionKind::Table { rows } => {
+            compile_expr_ir(rows, bc);
+            bc.push(IRNode::new(IRInstr::EmitTable, span.clone()));
+        }
+        ActionKind::Progress { value } => {
+            compile_expr_ir(value, bc);
+            bc.push(IRNode::new(IRInstr::EmitProgress, span.clone()));
+        }
     }
 }
 
@@ -544,6 +726,15 @@ fn compile_expr_ir(expr: &Expr, bc: &mut Vec<IRNode>) {
         ExprKind::Number(n) => bc.push(IRNode::new(IRInstr::ConstNum(*n), span)),
         ExprKind::Bool(b) => bc.push(IRNode::new(IRInstr::ConstBool(*b), span)),
         ExprKind::Text(s) => bc.push(IRNode::new(IRInstr::ConstText(s.clone()), span)),
+        ExprKind::Interpolate(parts) => {
+            for part in parts {
+                match part {
+                    InterpolPart::Text(t) => bc.push(IRNode::new(IRInstr::ConstText(t.clone()), span.clone())),
+                    InterpolPart::Expr(e) => compile_expr_ir(e, bc),
+                }
+            }
+            bc.push(IRNode::new(IRInstr::CallBuiltin("__interp".into(), parts.len()), span));
+        }
         ExprKind::Var(name) => bc.push(IRNode::new(IRInstr::LoadVar(name.clone()), span)),
         ExprKind::Unary { op, expr } => {
             compile_expr_ir(expr, bc);
@@ -593,11 +784,34 @@ fn compile_expr_ir(expr: &Expr, bc: &mut Vec<IRNode>) {
             ));
         }
         ExprKind::Call { callee, args } => {
-            for arg in args {
-                compile_expr_ir(arg, bc);
+            for (i, arg) in args.iter().enumerate() {
+                // assert_throws and the higher-order list functions may name their callback;
+                // whether a bare name is a variable is settled when lowering
+                let callback_of = match &callee.kind {
+                    ExprKind::Var(n) if n == "assert_throws" && i == 0 => Some(n),
+                    ExprKind::Var(n) if is_higher_order(n) && i == 1 => Some(n),
+                    _ => None,
+                };
+                match (&arg.kind, callback_of) {
+                    (ExprKind::Var(target), Some(callee)) => {
+                        let instr = IRInstr::LoadCallback { name: target.clone(), callee: callee.clone() };
+                        bc.push(IRNode::new(instr, arg.span.clone()));
+                    }
+                    _ => compile_expr_ir(arg, bc),
+                }
             }
             if let ExprKind::Var(name) = &callee.kind {
-                bc.push(IRNode::new(IRInstr::CallFn(name.clone(), args.len()), span));
+                // Point at the callee name rather than the argument list.
+                let site = callee.span.clone().or(span);
+                if name == "throw" && args.len() == 1 {
+                    bc.push(IRNode::new(IRInstr::Throw, site));
+                } else {
+                    bc.push(IRNode::new(IRInstr::CallFn(name.clone(), args.len()), site));
+                }
+            } else {
+                // function values only exist in the interpreter; fail with a clear message
+                compile_expr_ir(callee, bc);
+                bc.push(IRNode::new(IRInstr::CallBuiltin("__call_value".into(), args.len() + 1), span));
             }
         }
         ExprKind::Index { target, index } => {
@@ -621,5 +835,82 @@ fn compile_expr_ir(expr: &Expr, bc: &mut Vec<IRNode>) {
             compile_expr_ir(target, bc);
             bc.push(IRNode::new(IRInstr::LoadField(field.clone()), span));
         }
+        ExprKind::Ternary { cond, then_expr, else_expr } => {
+            compile_expr_ir(cond, bc);
+            let jmp_false = bc.len();
+            bc.push(IRNode::new(IRInstr::JumpIfFalse(0), span.clone()));
+            compile_expr_ir(then_expr, bc);
+            let jmp_end = bc.len();
+            bc.push(IRNode::new(IRInstr::Jump(0), span));
+            let else_start = bc.len();
+            compile_expr_ir(else_expr, bc);
+            let end = bc.len();
+            if let IRInstr::JumpIfFalse(ref mut t) = bc[jmp_false].instr {
+                *t = else_start;
+            }
+            if let IRInstr::Jump(ref mut t) = bc[jmp_end].instr {
+                *t = end;
+            }
+        }
+        ExprKind::Comprehension { elem, var, iter, cond } => {
+            // Temporaries are keyed by position so nested comprehensions don't share them.
+            let tag = bc.len();
+            let tmp_iter = format!("__comp_iter_{}__", tag);
+            let tmp_idx = format!("__comp_idx_{}__", tag);
+            let tmp_acc = format!("__comp_acc_{}__", tag);
+            // The loop variable lives in its own hidden slot so it never clobbers an outer `$v`.
+            let tmp_var = format!("__comp_var_{}__", tag);
+            compile_expr_ir(iter, bc);
+            bc.push(IRNode::new(IRInstr::StoreVar(tmp_iter.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::ConstNum(0.0), span.clone()));
+            bc.push(IRNode::new(IRInstr::StoreVar(tmp_idx.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::MakeList(0), span.clone()));
+            bc.push(IRNode::new(IRInstr::StoreVar(tmp_acc.clone()), span.clone()));
+            let start = bc.len();
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_idx.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_iter.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::CallBuiltin("len".into(), 1), span.clone()));
+            bc.push(IRNode::new(IRInstr::Lt, span.clone()));
+            let jmp_end = bc.len();
+            bc.push(IRNode::new(IRInstr::JumpIfFalse(0), span.clone()));
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_iter.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_idx.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::CallBuiltin("__index".into(), 2), span.clone()));
+            bc.push(IRNode::new(IRInstr::StoreVar(tmp_var.clone()), span.clone()));
+            let body_start = bc.len();
+            let jmp_skip = cond.as_ref().map(|c| {
+                compile_expr_ir(c, bc);
+                bc.push(IRNode::new(IRInstr::JumpIfFalse(0), span.clone()));
+                bc.len() - 1
+            });
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_acc.clone()), span.clone()));
+            compile_expr_ir(elem, bc);
+            bc.push(IRNode::new(IRInstr::CallBuiltin("__push".into(), 2), span.clone()));
+            bc.push(IRNode::new(IRInstr::StoreVar(tmp_acc.clone()), span.clone()));
+            for node in &mut bc[body_start..] {
+                match &mut node.instr {
+                    IRInstr::LoadVar(name) | IRInstr::StoreVar(name) | IRInstr::LoadCallback { name, .. } if name == var => {
+                        *name = tmp_var.clone()
+                    }
+                    _ => {}
+                }
+            }
+            let next = bc.len();
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_idx.clone()), span.clone()));
+            bc.push(IRNode::new(IRInstr::ConstNum(1.0), span.clone()));
+            bc.push(IRNode::new(IRInstr::Add, span.clone()));
+            bc.push(IRNode::new(IRInstr::StoreVar(tmp_idx), span.clone()));
+            bc.push(IRNode::new(IRInstr::Jump(start), span.clone()));
+            let end = bc.len();
+            bc.push(IRNode::new(IRInstr::LoadVar(tmp_acc), span));
+            if let IRInstr::JumpIfFalse(ref mut target) = bc[jmp_end].instr {
+                *target = end;
+            }
+            if let Some(pos) = jmp_skip {
+                if let IRInstr::JumpIfFalse(ref mut target) = bc[pos].instr {
+                    *target = next;
+                }
+            }
+        }
     }
 }
@@ -3,12 +3,16 @@
 
 use crate::oracle::query_oracle;
 use crate::runtime::env::BuiltinFn;
-use crate::runtime::error::Frame as TraceFrame;
-use crate::runtime::events::RuntimeEvent;
-use crate::runtime::value::{NauxObj, Value};
+use crate::runtime::error::{caught_error_value, Frame as TraceFrame, RuntimeError, THROWN_PREFIX};
+use crate::runtime::events::{progress_event, table_event, RuntimeEvent};
+use crate::runtime::memo::MemoCache;
+use crate::runtime::value::{format_value, NauxObj, Value};
+use crate::stdlib::fs;
+use crate::stdlib::list::{call_higher_order, is_higher_order};
 use crate::vm::bytecode::{disasm_window, FunctionBytecode, Instr, Program, VmResult};
 use crate::vm::jit::run_jit;
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 const JIT_HOT_THRESHOLD: usize = 128;
@@ -16,6 +20,25 @@ const JIT_HOT_THRESHOLD: usize = 128;
 #[derive(Clone, Debug)]
 struct Frame {
     locals: Vec<Value>,
+    /// Nesting of `~ unsafe` blocks; callees inherit the caller's depth.
+    unsafe_depth: usize,
+}
+
+/// An active `~ try` in the running code: where its handler starts and the machine state to
+/// restore before entering it.
+struct TryHandler {
+    handler_ip: usize,
+    stack_len: usize,
+    frames_len: usize,
+    trace_len: usize,
+    unsafe_depth: usize,
+}
+
+/// The message of the error being propagated, next to its latest rendering. Each frame renders
+/// the error again on the way out; a `~ try` handler looks the message up here instead.
+struct Raised {
+    rendered: String,
+    message: String,
 }
 
 /// Execute a compiled program with a stack machine. Handles builtin and user functions.
@@ -25,11 +48,26 @@ pub fn run_program(
     src: &str,
     filename: &str,
 ) -> VmResult<(Value, Vec<RuntimeEvent>)> {
-    let mut frames: Vec<Frame> = vec![Frame { locals: vec![Value::Null; prog.main_locals.len()] }];
+    run_program_with_globals(prog, builtins, src, filename, &mut HashMap::new())
+}
+
+/// Like `run_program`, but top-level variables start from `globals` and are written back to
+/// it when the program stops, even on error. The REPL keeps one map across inputs.
+pub fn run_program_with_globals(
+    prog: &Program,
+    builtins: &HashMap<String, BuiltinFn>,
+    src: &str,
+    filename: &str,
+    globals: &mut HashMap<String, Value>,
+) -> VmResult<(Value, Vec<RuntimeEvent>)> {
+    let locals = prog.main_locals.iter().map(|name| globals.get(name).cloned().unwrap_or(Value::Null)).collect();
+    let mut frames: Vec<Frame> = vec![Frame { locals, unsafe_depth: 0 }];
     let mut stack: Vec<Value> = Vec::new();
     let mut events: Vec<RuntimeEvent> = Vec::new();
     let mut trace: Vec<TraceFrame> = Vec::new();
     let mut jit_cache: HashMap<usize, f64> = HashMap::new();
+    let mut memo = MemoCache::default();
+    let mut raised = None;
     let val = exec_code(
         &prog.main,
         &prog.main_locals,
@@ -43,8 +81,15 @@ pub fn run_program(
         src,
         filename,
         &mut jit_cache,
-    )?;
-    Ok((val, events))
+        &mut memo,
+        &mut raised,
+    );
+    if let Some(main) = frames.first() {
+        for (name, value) in prog.main_locals.iter().zip(&main.locals) {
+            globals.insert(name.clone(), value.clone());
+        }
+    }
+    Ok((val?, events))
 }
 
 fn exec_code(
@@ -60,12 +105,59 @@ fn exec_code(
     src: &str,
     filename: &str,
     jit_cache: &mut HashMap<usize, f64>,
+    memo: &mut MemoCache,
+    raised: &mut Option<Raised>,
 ) -> VmResult<Value> {
     let code_key = code.as_ptr() as usize;
     if let Some(&val) = jit_cache.get(&code_key) {
         return Ok(Value::Float(val));
     }
     let mut ip: usize = 0;
+    let mut handlers: Vec<TryHandler> = Vec::new();
+    loop {
+        let res = exec_from(
+            ip, &mut handlers, code, locals_names, spans, builtins, functions, frames, stack, events, trace, src,
+            filename, jit_cache, memo, raised,
+        );
+        let msg = match res {
+            Ok(val) => return Ok(val),
+            Err(msg) => msg,
+        };
+        // unwind to the innermost open `~ try` of this code, or keep propagating
+        let Some(h) = handlers.pop() else { return Err(msg) };
+        let err = caught_error_value(&raised_message(raised, msg), trace);
+        frames.truncate(h.frames_len);
+        trace.truncate(h.trace_len);
+        stack.truncate(h.stack_len);
+        if let Some(top) = frames.last_mut() {
+            top.unsafe_depth = h.unsafe_depth;
+        }
+        stack.push(err);
+        ip = h.handler_ip;
+    }
+}
+
+/// Run `code` from `start` until it returns or fails; `EnterTry`/`LeaveTry` maintain `handlers`.
+fn exec_from(
+    start: usize,
+    handlers: &mut Vec<TryHandler>,
+    code: &[Instr],
+    locals_names: &[String],
+    spans: &[Option<crate::ast::Span>],
+    builtins: &HashMap<String, BuiltinFn>,
+    functions: &HashMap<String, FunctionBytecode>,
+    frames: &mut Vec<Frame>,
+    stack: &mut Vec<Value>,
+    events: &mut Vec<RuntimeEvent>,
+    trace: &mut Vec<TraceFrame>,
+    src: &str,
+    filename: &str,
+    jit_cache: &mut HashMap<usize, f64>,
+    memo: &mut MemoCache,
+    raised: &mut Option<Raised>,
+) -> VmResult<Value> {
+    let code_key = code.as_ptr() as usize;
+    let mut ip = start;
     let mut hot_counts = vec![0usize; code.len()];
     while ip < code.len() {
         hot_counts[ip] = hot_counts[ip].saturating_add(1);
@@ -84,7 +176,7 @@ fn exec_code(
                 stack.push(load_var_by_name(frames, locals_names, name));
             }
             Instr::StoreVar(name) => {
-                let val = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let val = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 store_var_by_name(frames, locals_names, name, val);
             }
             Instr::LoadLocal(idx) => {
@@ -92,28 +184,28 @@ fn exec_code(
                 stack.push(v);
             }
             Instr::StoreLocal(idx) => {
-                let val = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let val = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 store_local(frames, *idx, val);
             }
-            Instr::Add => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a + b)), |a, b| Value::Float(a + b)), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Sub => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a - b)), |a, b| Value::Float(a - b)), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Mul => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a * b)), |a, b| Value::Float(a * b)), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Div => wrap(num_bin::<fn(i64, i64) -> Value, _>(stack, None, |a, b| Value::Float(a / b)), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Mod => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a % b)), |a, b| Value::Float(a % b)), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Eq => wrap(cmp_op(stack, |a, b| a == b), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Ne => wrap(cmp_op(stack, |a, b| a != b), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Gt => wrap(cmp_num(stack, |a, b| a > b), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Ge => wrap(cmp_num(stack, |a, b| a >= b), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Lt => wrap(cmp_num(stack, |a, b| a < b), code, spans, ip, stack, src, filename, trace, jit_cache)?,
-            Instr::Le => wrap(cmp_num(stack, |a, b| a <= b), code, spans, ip, stack, src, filename, trace, jit_cache)?,
+            Instr::Add => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a + b)), |a, b| Value::Float(a + b)), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Sub => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a - b)), |a, b| Value::Float(a - b)), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Mul => wrap(num_bin(stack, Some(|a, b| Value::SmallInt(a * b)), |a, b| Value::Float(a * b)), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Div => wrap(div_op(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Mod => wrap(mod_op(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Eq => wrap(cmp_op(stack, |a, b| a == b), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Ne => wrap(cmp_op(stack, |a, b| a != b), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Gt => wrap(cmp_ord(stack, |o| o == Some(Ordering::Greater)), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Ge => wrap(cmp_ord(stack, |o| matches!(o, Some(Ordering::Greater | Ordering::Equal))), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Lt => wrap(cmp_ord(stack, |o| o == Some(Ordering::Less)), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
+            Instr::Le => wrap(cmp_ord(stack, |o| matches!(o, Some(Ordering::Less | Ordering::Equal))), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?,
             Instr::And => {
-                let rhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
-                let lhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let rhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                let lhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 stack.push(Value::Bool(lhs.truthy() && rhs.truthy()));
             }
             Instr::Or => {
-                let rhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
-                let lhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let rhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                let lhs = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 stack.push(Value::Bool(lhs.truthy() || rhs.truthy()));
             }
             Instr::Jump(target) => {
@@ -121,14 +213,14 @@ fn exec_code(
                 continue;
             }
             Instr::JumpIfFalse(target) => {
-                let cond = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let cond = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 if !cond.truthy() {
                     ip = *target;
                     continue;
                 }
             }
             Instr::CallBuiltin(name, argc) => {
-                wrap(call_builtin(name, *argc, builtins, stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                wrap(call_builtin(name, *argc, builtins, frames, stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
             }
             Instr::CallFn(name, argc) => {
                 // try user function first, fall back to builtin set
@@ -149,6 +241,8 @@ fn exec_code(
                             src,
                             filename,
                             jit_cache,
+                            memo,
+                            raised,
                         ),
                         code,
                         spans,
@@ -158,15 +252,70 @@ fn exec_code(
                         filename,
                         trace,
                         jit_cache,
+                        raised,
                     )?;
+                } else if name == "assert_throws" {
+                    // run the target, then unwind whatever the failed call left behind
+                    let (target, needle, base) =
+                        wrap(prepare_assert_throws(*argc, stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                    let call_argc = stack.len() - base;
+                    let (frames_len, trace_len) = (frames.len(), trace.len());
+                    let res = match functions.get(&target) {
+                        Some(func) => {
+                            let call_span = spans.get(ip).cloned().unwrap_or(None);
+                            call_function(
+                                &target, func, call_argc, builtins, functions, frames, stack, events, trace, call_span, src,
+                                filename, jit_cache, memo, raised,
+                            )
+                        }
+                        None => call_builtin(&target, call_argc, builtins, frames, stack),
+                    };
+                    frames.truncate(frames_len);
+                    trace.truncate(trace_len);
+                    stack.truncate(base);
+                    let res = res.map_err(|msg| raised_message(raised, msg));
+                    let out = wrap(assert_throws_outcome(res, &target, &needle), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                    stack.push(out);
+                } else if is_higher_order(name) {
+                    let mut args = Vec::new();
+                    for _ in 0..*argc {
+                        args.push(wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?);
+                    }
+                    args.reverse();
+                    let call_span = spans.get(ip).cloned().unwrap_or(None);
+                    let mut call = |f: &Value, call_args: Vec<Value>| -> Result<Value, RuntimeError> {
+                        let Some(target) = f.as_text() else {
+                            return Err(RuntimeError::new(
+                                format!("{}: the VM takes callbacks by function name, got {}", name, f.type_name()),
+                                None,
+                            ));
+                        };
+                        let argc = call_args.len();
+                        stack.extend(call_args);
+                        let res = match functions.get(&target) {
+                            Some(func) => call_function(
+                                &target, func, argc, builtins, functions, frames, stack, events, trace,
+                                call_span.clone(), src, filename, jit_cache, memo, raised,
+                            ),
+                            None => call_builtin(&target, argc, builtins, frames, stack),
+                        };
+                        // both calls push their result; the list function collects it instead
+                        res.inspect(|_| {
+                            stack.pop();
+                        })
+                        .map_err(|msg| RuntimeError::new(msg, None))
+                    };
+                    let res = call_higher_order(name, args, &mut call).map_err(|e| e.message);
+                    let out = wrap(res, code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                    stack.push(out);
                 } else {
-                    wrap(call_builtin(name, *argc, builtins, stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                    wrap(call_builtin(name, *argc, builtins, frames, stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 }
             }
             Instr::MakeList(len) => {
                 let mut items = Vec::new();
                 for _ in 0..*len {
-                    items.push(wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?);
+                    items.push(wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?);
                 }
                 items.reverse();
                 stack.push(Value::make_list(items));
@@ -174,13 +323,13 @@ fn exec_code(
             Instr::MakeMap(keys) => {
                 let mut map = std::collections::HashMap::new();
                 for key in keys.iter().rev() {
-                    let val = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                    let val = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                     map.insert(key.clone(), val);
                 }
                 stack.push(Value::make_map(map));
             }
             Instr::LoadField(field) => {
-                let target = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let target = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 match target {
                     Value::RcObj(rc) => match rc.as_ref() {
                         NauxObj::Map(m) => {
@@ -193,35 +342,69 @@ fn exec_code(
                 }
             }
             Instr::EmitSay => {
-                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 events.push(RuntimeEvent::Say(format_value(&v)));
             }
             Instr::EmitAsk => {
-                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 let prompt = format_value(&v);
-                events.push(RuntimeEvent::Ask { prompt: prompt.clone(), answer: String::new() });
-                let ans = query_oracle(&prompt);
-                events.push(RuntimeEvent::Ask { prompt, answer: ans });
+                let answer = query_oracle(&prompt);
+                events.push(RuntimeEvent::Ask { prompt, answer });
             }
             Instr::EmitFetch => {
-                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 events.push(RuntimeEvent::Fetch { target: format_value(&v) });
             }
             Instr::EmitUi(kind) => {
                 events.push(RuntimeEvent::Ui { kind: kind.clone(), props: Vec::new() });
             }
             Instr::EmitText => {
-                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 events.push(RuntimeEvent::Text(format_value(&v)));
             }
             Instr::EmitButton => {
-                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 events.push(RuntimeEvent::Button(format_value(&v)));
             }
             Instr::EmitLog => {
-                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache)?;
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
                 events.push(RuntimeEvent::Log(format_value(&v)));
             }
+            Instr::EmitProgress => {
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                let ev = wrap(progress_event(&v), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                events.push(ev);
+            }
+            Instr::EmitTable => {
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                let ev = wrap(table_event(&v, format_value), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                events.push(ev);
+            }
+            Instr::EnterUnsafe => {
+                if let Some(top) = frames.last_mut() {
+                    top.unsafe_depth += 1;
+                }
+            }
+            Instr::ExitUnsafe => {
+                if let Some(top) = frames.last_mut() {
+                    top.unsafe_depth = top.unsafe_depth.saturating_sub(1);
+                }
+            }
+            Instr::EnterTry(handler_ip) => handlers.push(TryHandler {
+                handler_ip: *handler_ip,
+                stack_len: stack.len(),
+                frames_len: frames.len(),
+                trace_len: trace.len(),
+                unsafe_depth: frames.last().map_or(0, |f| f.unsafe_depth),
+            }),
+            Instr::LeaveTry => {
+                handlers.pop();
+            }
+            Instr::Throw => {
+                let v = wrap(pop(stack), code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+                let thrown: VmResult<()> = Err(format!("{}{}", THROWN_PREFIX, format_value(&v)));
+                wrap(thrown, code, spans, ip, stack, src, filename, trace, jit_cache, raised)?;
+            }
             Instr::Return => {
                 let ret = stack.pop().unwrap_or(Value::Null);
                 return Ok(ret);
@@ -241,6 +424,14 @@ fn exec_code(
     Ok(stack.pop().unwrap_or(Value::Null))
 }
 
+/// The message `rendered` was raised with, taken from `raised` when it records that error.
+fn raised_message(raised: &mut Option<Raised>, rendered: String) -> String {
+    match raised.take() {
+        Some(r) if r.rendered == rendered => r.message,
+        _ => rendered,
+    }
+}
+
 fn wrap<T>(
     res: VmResult<T>,
     code: &[Instr],
@@ -251,11 +442,30 @@ fn wrap<T>(
     filename: &str,
     trace: &[TraceFrame],
     jit_cache: &HashMap<usize, f64>,
+    raised: &mut Option<Raised>,
 ) -> VmResult<T> {
-    res.map_err(|msg| vm_error(&msg, code, spans, ip, stack, src, filename, trace, jit_cache))
+    res.map_err(|msg| {
+        let rendered = vm_error(&msg, code, spans, ip, stack, src, filename, trace, jit_cache);
+        // a callee's error arrives already rendered; keep the message it was raised with
+        let message = match raised.take() {
+            Some(prev) if prev.rendered == msg => prev.message,
+            _ => msg.strip_prefix("RuntimeError: ").unwrap_or(&msg).to_string(),
+        };
+        *raised = Some(Raised { rendered: rendered.clone(), message });
+        rendered
+    })
 }
 
-fn call_builtin(name: &str, argc: usize, builtins: &HashMap<String, BuiltinFn>, stack: &mut Vec<Value>) -> VmResult<Value> {
+fn call_builtin(
+    name: &str,
+    argc: usize,
+    builtins: &HashMap<String, BuiltinFn>,
+    frames: &[Frame],
+    stack: &mut Vec<Value>,
+) -> VmResult<Value> {
+    if fs::requires_unsafe(name) && frames.last().map_or(0, |f| f.unsafe_depth) == 0 {
+        return Err(fs::UNSAFE_REQUIRED.into());
+    }
     let mut args = Vec::new();
     for _ in 0..argc {
         args.push(pop(stack)?);
@@ -299,6 +509,31 @@ fn call_builtin(name: &str, argc: usize, builtins: &HashMap<String, BuiltinFn>,
         return Ok(result);
     }
 
+    // Joins the pieces of an interpolated string, formatting them like `!say`.
+    if name == "__interp" {
+        let out = Value::make_text(args.iter().map(format_value).collect::<String>());
+        stack.push(out.clone());
+        return Ok(out);
+    }
+
+    // Appends in place; used by list comprehensions to grow their accumulator.
+    if name == "__push" && args.len() == 2 {
+        let list = args[0].clone();
+        if let Value::RcObj(rc) = &list {
+            if let NauxObj::List(v) = rc.as_ref() {
+                v.borrow_mut().push(args[1].clone());
+                stack.push(list.clone());
+                return Ok(list);
+            }
+        }
+        return Err("__push expects a list".into());
+    }
+
+    // Emitted for calls through an expression such as `$fs[0]()`.
+    if name == "__call_value" {
+        return Err("Calling a function value needs the interpreter (--engine interp); the VM calls functions by name".into());
+    }
+
     if let Some(f) = builtins.get(name) {
         match f(args) {
             Ok(v) => {
@@ -326,14 +561,21 @@ fn call_function(
     src: &str,
     filename: &str,
     jit_cache: &mut HashMap<usize, f64>,
+    memo: &mut MemoCache,
+    raised: &mut Option<Raised>,
 ) -> VmResult<Value> {
     let mut args = Vec::new();
     for _ in 0..argc {
         args.push(pop(stack)?);
     }
     args.reverse();
+    if let Some(hit) = func.memo.then(|| memo.get(fn_name, &args)).flatten() {
+        stack.push(hit.clone());
+        return Ok(hit);
+    }
     trace.push(TraceFrame { name: fn_name.into(), span: call_span.clone() });
-    frames.push(Frame { locals: vec![Value::Null; func.locals.len()] });
+    let unsafe_depth = frames.last().map_or(0, |f| f.unsafe_depth);
+    frames.push(Frame { locals: vec![Value::Null; func.locals.len()], unsafe_depth });
     for (i, _param) in func.params.iter().enumerate() {
         if let Some(val) = args.get(i) {
             store_local(frames, i, val.clone());
@@ -352,13 +594,46 @@ fn call_function(
         src,
         filename,
         jit_cache,
+        memo,
+        raised,
     )?;
     frames.pop();
     trace.pop();
+    if func.memo {
+        memo.insert(fn_name, args, ret.clone());
+    }
     stack.push(ret.clone());
     Ok(ret)
 }
 
+/// Pops `assert_throws(name, substring, args...)` operands and pushes `args` back for the call.
+/// Returns the target name, the expected substring and the stack height to unwind to.
+fn prepare_assert_throws(argc: usize, stack: &mut Vec<Value>) -> VmResult<(String, String, usize)> {
+    let mut args = Vec::new();
+    for _ in 0..argc {
+        args.push(pop(stack)?);
+    }
+    args.reverse();
+    if args.len() < 2 {
+        return Err("assert_throws(fn, message_substring, args...)".into());
+    }
+    let (Some(target), Some(needle)) = (args[0].as_text(), args[1].as_text()) else {
+        return Err("assert_throws: expected a function name and a text substring".into());
+    };
+    let base = stack.len();
+    stack.extend(args.into_iter().skip(2));
+    Ok((target, needle, base))
+}
+
+/// Succeeds only when the call failed with `needle` in the message it was raised with.
+fn assert_throws_outcome(res: VmResult<Value>, target: &str, needle: &str) -> VmResult<Value> {
+    match res.as_ref().map_err(|msg| msg.strip_prefix("RuntimeError: ").unwrap_or(msg)) {
+        Ok(_) => Err(format!("assert_throws: expected {} to fail with '{}', but it succeeded", target, needle)),
+        Err(message) if message.contains(needle) => Ok(Value::Bool(true)),
+        Err(message) => Err(format!("assert_throws: expected an error containing '{}', got '{}'", needle, message)),
+    }
+}
+
 fn load_local(frames: &[Frame], idx: usize) -> Value {
     frames.last().and_then(|f| f.locals.get(idx)).cloned().unwrap_or(Value::Null)
 }
@@ -410,6 +685,22 @@ where
     }
 }
 
+/// Division always yields a float; a zero divisor is an error, as in the tree-walker.
+fn div_op(stack: &mut Vec<Value>) -> Result<(), String> {
+    if matches!(stack.last().and_then(|v| v.as_f64()), Some(d) if d == 0.0) {
+        return Err("Division by zero".into());
+    }
+    num_bin::<fn(i64, i64) -> Value, _>(stack, None, |a, b| Value::Float(a / b))
+}
+
+/// Truncated remainder (sign follows the dividend, like Rust's `%`); a zero divisor is an error.
+fn mod_op(stack: &mut Vec<Value>) -> Result<(), String> {
+    if matches!(stack.last().and_then(|v| v.as_f64()), Some(d) if d == 0.0) {
+        return Err("Modulo by zero".into());
+    }
+    num_bin(stack, Some(|a: i64, b: i64| Value::SmallInt(a.wrapping_rem(b))), |a, b| Value::Float(a % b))
+}
+
 fn bin_op<F>(stack: &mut Vec<Value>, f: F) -> Result<(), String>
 where
     F: Fn(f64, f64) -> Value,
@@ -435,50 +726,22 @@ where
     Ok(())
 }
 
-fn cmp_num<F>(stack: &mut Vec<Value>, f: F) -> Result<(), String>
+fn cmp_ord<F>(stack: &mut Vec<Value>, f: F) -> Result<(), String>
 where
-    F: Fn(f64, f64) -> bool,
+    F: Fn(Option<Ordering>) -> bool,
 {
     let rhs = pop(stack)?;
     let lhs = pop(stack)?;
     if let (Value::SmallInt(a), Value::SmallInt(b)) = (&lhs, &rhs) {
-        stack.push(Value::Bool(f(*a as f64, *b as f64)));
+        stack.push(Value::Bool(f(Some(a.cmp(b)))));
         return Ok(());
     }
-    match (lhs.as_f64(), rhs.as_f64()) {
-        (Some(a), Some(b)) => {
-            stack.push(Value::Bool(f(a, b)));
+    match lhs.compare(&rhs) {
+        Ok(ord) => {
+            stack.push(Value::Bool(f(ord)));
             Ok(())
         }
-        _ => Err("Type error in numeric comparison".into()),
-    }
-}
-
-fn format_value(v: &Value) -> String {
-    match v {
-        Value::RcObj(rc) => match rc.as_ref() {
-            NauxObj::Text(s) => s.clone(),
-            NauxObj::List(list) => {
-                let items: Vec<String> = list.borrow().iter().map(format_value).collect();
-                format!("List [{}]", items.join(", "))
-            }
-            NauxObj::Map(map) => {
-                let entries: Vec<String> = map.borrow().iter().map(|(k, v)| format!("{}:{}", k, format_value(v))).collect();
-                format!("Map {{{}}}", entries.join(", "))
-            }
-            NauxObj::Graph(g) => {
-                let gb = g.borrow();
-                let edges: usize = gb.adj.values().map(|v| v.len()).sum();
-                format!("Graph(nodes={}, edges={})", gb.adj.len(), edges)
-            }
-            NauxObj::Set(s) => format!("Set len={}", s.borrow().len()),
-            NauxObj::PriorityQueue(pq) => format!("PriorityQueue len={}", pq.borrow().len()),
-            NauxObj::Function(_) => "<fn>".into(),
-        },
-        Value::SmallInt(n) => n.to_string(),
-        Value::Float(n) => n.to_string(),
-        Value::Bool(b) => b.to_string(),
-        other => format!("{:?}", other),
+        Err(msg) => Err(format!("Type error in comparison: {}", msg)),
     }
 }
 
@@ -499,7 +762,7 @@ fn vm_error(
     if let Some(sp) = spans.get(ip).and_then(|s| s.clone()) {
         let line_idx = sp.line.saturating_sub(1);
         let line_text = src.lines().nth(line_idx).unwrap_or("");
-        let caret = format!("{}^", " ".repeat(sp.column.saturating_sub(1)));
+        let caret = sp.caret();
         writeln!(&mut out, "  at {}:{}:{}", filename, sp.line, sp.column).ok();
         writeln!(&mut out, "  {}", line_text).ok();
         writeln!(&mut out, "  {}", caret).ok();
@@ -512,7 +775,7 @@ fn vm_error(
             if let Some(sp) = &frame.span {
                 let line_idx = sp.line.saturating_sub(1);
                 let line_text = src.lines().nth(line_idx).unwrap_or("");
-                let caret = format!("{}^", " ".repeat(sp.column.saturating_sub(1)));
+                let caret = sp.caret();
                 writeln!(&mut out, "    at {} ({}:{}:{})", frame.name, filename, sp.line, sp.column).ok();
                 writeln!(&mut out, "      {}", line_text).ok();
                 writeln!(&mut out, "      {}", caret).ok();
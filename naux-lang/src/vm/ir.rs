@@ -15,6 +15,10 @@ pub enum IRInstr {
     PushNull,
     LoadVar(String),
     StoreVar(String),
+    /// A bare name in a callback slot of `callee` (`assert_throws`, the higher-order list
+    /// builtins). Lowered to `LoadVar` when the block assigns the name or `callee` is a script
+    /// function, otherwise to the name as text.
+    LoadCallback { name: String, callee: String },
     Add,
     Sub,
     Mul,
@@ -42,6 +46,15 @@ pub enum IRInstr {
     EmitText,
     EmitButton,
     EmitLog,
+    EmitTable,
+    EmitProgress,
+    EnterUnsafe,
+    ExitUnsafe,
+    /// Start of a `~ try` body; errors until the matching `LeaveTry` jump to the handler.
+    EnterTry(usize),
+    LeaveTry,
+    /// Pop a value and raise it as a `throw(...)` error.
+    Throw,
     Return,
 }
 
@@ -63,6 +76,7 @@ pub type IRBlock = Vec<IRNode>;
 pub struct IRFunction {
     pub params: Vec<String>,
     pub code: IRBlock,
+    pub memo: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +120,7 @@ pub fn fmt_instr(i: &IRInstr) -> String {
         IRInstr::PushNull => "PushNull".into(),
         IRInstr::LoadVar(v) => format!("LoadVar {}", v),
         IRInstr::StoreVar(v) => format!("StoreVar {}", v),
+        IRInstr::LoadCallback { name, callee } => format!("LoadCallback {} for {}", name, callee),
         IRInstr::Add => "Add".into(),
         IRInstr::Sub => "Sub".into(),
         IRInstr::Mul => "Mul".into(),
@@ -133,6 +148,13 @@ pub fn fmt_instr(i: &IRInstr) -> String {
         IRInstr::EmitText => "EmitText".into(),
         IRInstr::EmitButton => "EmitButton".into(),
         IRInstr::EmitLog => "EmitLog".into(),
+        IRInstr::EmitTable => "EmitTable".into(),
+        IRInstr::EmitProgress => "EmitProgress".into(),
+        IRInstr::EnterUnsafe => "EnterUnsafe".into(),
+        IRInstr::ExitUnsafe => "ExitUnsafe".into(),
+        IRInstr::EnterTry(t) => format!("EnterTry {}", t),
+        IRInstr::LeaveTry => "LeaveTry".into(),
+        IRInstr::Throw => "Throw".into(),
         IRInstr::Return => "Return".into(),
     }
 }
@@ -7,7 +7,7 @@ use crate::runtime::events::RuntimeEvent;
 use crate::runtime::value::Value;
 use crate::vm::compiler::compile_script;
 use crate::vm::interpreter::run_program;
-use crate::vm::bytecode::VmResult;
+use crate::vm::bytecode::{Program, VmResult};
 use crate::vm::jit::run_jit as jit_entry;
 
 /// Compile AST to bytecode and execute via VM using env builtins. Returns events and final value.
@@ -16,11 +16,21 @@ pub fn run_vm(
     src: &str,
     filename: &str,
 ) -> VmResult<(Vec<RuntimeEvent>, crate::runtime::value::Value)> {
+    let prog = compile_script(stmts);
+    run_compiled(&prog, src, filename)
+}
+
+/// Load a serialized `.nxb` program and execute it without lexing, parsing or compiling.
+pub fn run_bytecode(data: &[u8], filename: &str) -> VmResult<(Vec<RuntimeEvent>, crate::runtime::value::Value)> {
+    let prog = Program::from_bytes(data)?;
+    run_compiled(&prog, "", filename)
+}
+
+fn run_compiled(prog: &Program, src: &str, filename: &str) -> VmResult<(Vec<RuntimeEvent>, crate::runtime::value::Value)> {
     let mut env = Env::new();
     crate::stdlib::register_all(&mut env);
     let builtins: HashMap<String, crate::runtime::env::BuiltinFn> = env.builtins();
-    let prog = compile_script(stmts);
-    let (val, events) = run_program(&prog, &builtins, src, filename)?;
+    let (val, events) = run_program(prog, &builtins, src, filename)?;
     Ok((events, val))
 }
 
@@ -22,3 +22,16 @@ fn action_variants_emit_events() {
 fn dummy_text(s: &str) -> naux::ast::Expr {
     naux::ast::Expr::new(naux::ast::ExprKind::Text(s.to_string()), None)
 }
+
+#[test]
+fn assignments_emit_no_events_on_either_engine() {
+    let src = "$a = 1\n$b = $a + 1\n~ each $x in [1, 2]\n    $c = $x\n~ end\n!say $b\n";
+    let mut streams = Vec::new();
+    for engine in [naux::Engine::Interp, naux::Engine::Vm] {
+        let out = naux::run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}", out.errors);
+        streams.push(out.events);
+    }
+    assert_eq!(streams[0], vec![RuntimeEvent::Say("2".into())]);
+    assert_eq!(streams[0], streams[1]);
+}
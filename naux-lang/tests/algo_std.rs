@@ -1,7 +1,7 @@
 use naux::lexer::lex;
 use naux::parser::parser::Parser;
 use naux::runtime::eval_script;
-use naux::runtime::value::Value;
+use naux::runtime::value::{NauxObj, Value};
 
 fn run(src: &str, var: &str) -> Value {
     let tokens = lex(src).unwrap();
@@ -11,20 +11,31 @@ fn run(src: &str, var: &str) -> Value {
     env.get(var).unwrap_or(Value::Null)
 }
 
+/// Seeded linear congruential generator for the randomized checks against naive models.
+struct Lcg(u64);
+
+impl Lcg {
+    /// The next pseudo-random number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) as usize % n
+    }
+}
+
 #[test]
 fn math_pow_mod_and_sieve() {
     let src = r#"
     $pm = pow_mod(2, 10, 1000)
     $pr = sieve(10)
     "#;
-    assert_eq!(run(src, "pm"), Value::Number(24.0));
+    assert_eq!(run(src, "pm"), Value::SmallInt(24));
     assert_eq!(
         run(src, "pr"),
-        Value::List(vec![
-            Value::Number(2.0),
-            Value::Number(3.0),
-            Value::Number(5.0),
-            Value::Number(7.0)
+        Value::make_list(vec![
+            Value::SmallInt(2),
+            Value::SmallInt(3),
+            Value::SmallInt(5),
+            Value::SmallInt(7)
         ])
     );
 }
@@ -37,7 +48,154 @@ fn dsu_union_find() {
     $res = dsu_find($ds, 1)
     $root = $res[0]
     "#;
-    assert_eq!(run(src, "root"), Value::Number(0.0));
+    assert_eq!(run(src, "root"), Value::SmallInt(0));
+}
+
+#[test]
+fn dsu_connected_and_count_after_unions() {
+    let src = r#"
+    $ds = dsu_new(6)
+    $before = dsu_count($ds)
+    $ds = dsu_union($ds, 0, 1)
+    $ds = dsu_union($ds, 2, 3)
+    $ds = dsu_union($ds, 1, 3)
+    $ds = dsu_union($ds, 0, 2)
+    $joined = dsu_connected($ds, 0, 3)
+    $apart = dsu_connected($ds, 0, 4)
+    $count = dsu_count($ds)
+    "#;
+    assert_eq!(run(src, "before"), Value::SmallInt(6));
+    assert_eq!(run(src, "joined"), Value::Bool(true));
+    assert_eq!(run(src, "apart"), Value::Bool(false));
+    // {0, 1, 2, 3}, {4}, {5}
+    assert_eq!(run(src, "count"), Value::SmallInt(3));
+}
+
+#[test]
+fn dsu_matches_reference_over_random_operations() {
+    // Reference: explicit component labels, relabelled on every union.
+    let n = 40;
+    let mut label: Vec<usize> = (0..n).collect();
+    let mut src = format!("$ds = dsu_new({})\n", n);
+    let mut rng = Lcg(2024);
+    let mut checks = Vec::new();
+    let mut expected = Vec::new();
+    for k in 0..120 {
+        let (a, b) = (rng.below(n), rng.below(n));
+        if rng.below(3) == 0 {
+            src.push_str(&format!("$c{} = dsu_connected($ds, {}, {})\n", k, a, b));
+            checks.push(format!("$c{}", k));
+            expected.push(Value::Bool(label[a] == label[b]));
+        } else {
+            src.push_str(&format!("$ds = dsu_union($ds, {}, {})\n", a, b));
+            let (from, to) = (label[b], label[a]);
+            label.iter_mut().filter(|l| **l == from).for_each(|l| *l = to);
+        }
+        if k % 20 == 19 {
+            src.push_str(&format!("$n{} = dsu_count($ds)\n", k));
+            checks.push(format!("$n{}", k));
+            let mut distinct = label.clone();
+            distinct.sort();
+            distinct.dedup();
+            expected.push(Value::SmallInt(distinct.len() as i64));
+        }
+    }
+    // Finding every element compresses each path to point straight at its root.
+    for x in 0..n {
+        src.push_str(&format!("$_ = dsu_find($ds, {})\n", x));
+    }
+    src.push_str(&format!("$out = [{}]\n$p = $ds.p\n", checks.join(", ")));
+    assert_eq!(run(&src, "out"), Value::make_list(expected));
+    let parents: Vec<usize> = match run(&src, "p") {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().iter().map(|v| v.as_i64().unwrap() as usize).collect(),
+            other => panic!("expected list, got {:?}", other),
+        },
+        other => panic!("expected list, got {:?}", other),
+    };
+    for x in 0..n {
+        assert_eq!(parents[parents[x]], parents[x], "element {} not compressed", x);
+        assert_eq!(label[x], label[parents[x]]);
+    }
+}
+
+#[test]
+fn dsu_ranks_stay_logarithmic_and_updates_are_shared() {
+    // Union by rank keeps every rank below log2(n) + 1 even for a long chain of unions.
+    let src = r#"
+    $ds = dsu_new(64)
+    $alias = $ds
+    $prev = 0
+    $i = 1
+    ~ while $i < 64
+        $_ = dsu_union($ds, $prev, $i)
+        $prev = $i
+        $i = $i + 1
+    ~ end
+    $shared = dsu_connected($alias, 0, 63)
+    $r = $ds.r
+    "#;
+    assert_eq!(run(src, "shared"), Value::Bool(true));
+    let max_rank = match run(src, "r") {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => items.borrow().iter().filter_map(|v| v.as_i64()).max().unwrap(),
+            other => panic!("expected list, got {:?}", other),
+        },
+        other => panic!("expected list, got {:?}", other),
+    };
+    assert!(max_rank <= 6, "max rank {}", max_rank);
+}
+
+#[test]
+fn fenwick_prefix_and_range_sums() {
+    let src = r#"
+    $b = bit_new(6)
+    $b = bit_update($b, 0, 3)
+    $b = bit_update($b, 2, 5)
+    $_ = bit_update($b, 5, 1.5)
+    $b = bit_update($b, 2, -1)
+    $all = bit_query($b, 6)
+    $first_three = bit_query($b, 3)
+    $none = bit_query($b, 0)
+    $middle = bit_range_query($b, 1, 5)
+    $tail = bit_range_query($b, 5, 6)
+    "#;
+    assert_eq!(run(src, "all"), Value::Float(8.5));
+    assert_eq!(run(src, "first_three"), Value::Float(7.0));
+    assert_eq!(run(src, "none"), Value::Float(0.0));
+    assert_eq!(run(src, "middle"), Value::Float(4.0));
+    // updates are in place, so the unassigned result above still counts
+    assert_eq!(run(src, "tail"), Value::Float(1.5));
+}
+
+#[test]
+fn fenwick_matches_brute_force_sums() {
+    let n = 37;
+    let mut naive = vec![0i64; n];
+    let mut src = format!("$b = bit_new({})\n$g = bit2d_new(5, 8)\n", n);
+    let mut rng = Lcg(42);
+    let mut grid = vec![vec![0i64; 8]; 5];
+    for _ in 0..60 {
+        let (i, delta) = (rng.below(n), rng.below(21) as i64 - 10);
+        naive[i] += delta;
+        src.push_str(&format!("$b = bit_update($b, {}, {})\n", i, delta));
+        let (r, c) = (rng.below(5), rng.below(8));
+        grid[r][c] += delta;
+        src.push_str(&format!("$g = bit2d_update($g, {}, {}, {})\n", r, c, delta));
+    }
+    let mut expected = Vec::new();
+    let mut queries = Vec::new();
+    for _ in 0..20 {
+        let (a, b) = (rng.below(n + 1), rng.below(n + 1));
+        let (l, r) = (a.min(b), a.max(b));
+        queries.push(format!("bit_range_query($b, {}, {})", l, r));
+        expected.push(Value::Float(naive[l..r].iter().sum::<i64>() as f64));
+        let (qr, qc) = (rng.below(6), rng.below(9));
+        queries.push(format!("bit2d_query($g, {}, {})", qr, qc));
+        expected.push(Value::Float(grid[..qr].iter().map(|row| row[..qc].iter().sum::<i64>()).sum::<i64>() as f64));
+    }
+    src.push_str(&format!("$out = [{}]\n", queries.join(", ")));
+    assert_eq!(run(&src, "out"), Value::make_list(expected));
 }
 
 #[test]
@@ -48,8 +206,132 @@ fn segtree_sum() {
     $st = segtree_update($st, 2, 10)
     $sum2 = segtree_query($st, 0, 4)
     "#;
-    assert_eq!(run(src, "sum"), Value::Number(10.0));
-    assert_eq!(run(src, "sum2"), Value::Number(17.0));
+    assert_eq!(run(src, "sum"), Value::SmallInt(10));
+    assert_eq!(run(src, "sum2"), Value::SmallInt(17));
+}
+
+#[test]
+fn segtree_is_the_iterative_tree_and_matches_brute_force() {
+    // 13 leaves pad to a 16-wide tree, so `size` shows which implementation is registered.
+    let mut naive: Vec<i64> = (0..13).map(|i| i * 3 - 7).collect();
+    let items: Vec<String> = naive.iter().map(|v| v.to_string()).collect();
+    let list = format!("[{}]", items.join(", "));
+    assert_eq!(run(&format!("$size = segtree_new({}).size", list), "size"), Value::SmallInt(16));
+    let mut src = format!("$st = segtree_new({})\n", list);
+    let mut rng = Lcg(7);
+    let mut queries = Vec::new();
+    let mut expected = Vec::new();
+    for k in 0..30 {
+        let (i, v) = (rng.below(naive.len()), rng.below(41) as i64 - 20);
+        naive[i] = v;
+        src.push_str(&format!("$st = segtree_update($st, {}, {})\n", i, v));
+        let (a, b) = (rng.below(naive.len() + 1), rng.below(naive.len() + 1));
+        let (l, r) = (a.min(b), a.max(b));
+        src.push_str(&format!("$q{} = segtree_query($st, {}, {})\n", k, l, r));
+        queries.push(format!("$q{}", k));
+        expected.push(naive[l..r].iter().sum::<i64>());
+    }
+    src.push_str(&format!("$out = [{}]\n", queries.join(", ")));
+    assert_eq!(run(&src, "out"), ints(&expected));
+}
+
+#[test]
+fn sparse_table_min_max_match_brute_force() {
+    let mut rng = Lcg(99);
+    let xs: Vec<i64> = (0..29).map(|_| rng.below(100) as i64 - 50).collect();
+    let items: Vec<String> = xs.iter().map(|v| v.to_string()).collect();
+    let mut src = format!(
+        "$xs = [{}]\n$mn = sparse_table_new($xs, \"min\")\n$mx = sparse_table_new($xs, \"max\")\n",
+        items.join(", ")
+    );
+    let mut queries = Vec::new();
+    let mut expected = Vec::new();
+    for _ in 0..40 {
+        let l = rng.below(xs.len());
+        let r = l + 1 + rng.below(xs.len() - l);
+        queries.push(format!("sparse_table_query($mn, {}, {})", l, r));
+        expected.push(*xs[l..r].iter().min().unwrap());
+        queries.push(format!("sparse_table_query($mx, {}, {})", l, r));
+        expected.push(*xs[l..r].iter().max().unwrap());
+    }
+    src.push_str(&format!("$out = [{}]\n", queries.join(", ")));
+    assert_eq!(run(&src, "out"), ints(&expected));
+}
+
+#[test]
+fn sparse_table_from_segtree_and_static_errors() {
+    let src = r#"
+    $seg = segtree_new([5, 3, 8, 1, 9])
+    $st = sparse_table_from_segtree($seg, "max")
+    $whole = sparse_table_query($st, 0, 5)
+    $head = sparse_table_query($st, 0, 2)
+    $mn = sparse_table_from_segtree($seg)
+    $low = sparse_table_query($mn, 1, 4)
+    $n = $mn.n
+    "#;
+    assert_eq!(run(src, "whole"), Value::SmallInt(9));
+    assert_eq!(run(src, "head"), Value::SmallInt(5));
+    assert_eq!(run(src, "low"), Value::SmallInt(1));
+    assert_eq!(run(src, "n"), Value::SmallInt(5));
+    assert_eq!(
+        run_err("$st = sparse_table_new([1, 2])\n$st = sparse_table_update($st, 0, 5)"),
+        "sparse_table_update: sparse tables are static; use segtree_update for point updates"
+    );
+    assert_eq!(
+        run_err("$st = sparse_table_new([1, 2, 3])\n$q = sparse_table_query($st, 1, 1)"),
+        "sparse_table_query: empty range 1..1"
+    );
+    assert_eq!(
+        run_err("$st = sparse_table_new([1, 2, 3])\n$q = sparse_table_query($st, 0, 4)"),
+        "sparse_table_query: r = 4 out of range 0..4"
+    );
+    assert_eq!(run_err("$st = sparse_table_new([1], \"sum\")"), "sparse_table_new: op must be \"min\" or \"max\"");
+}
+
+#[test]
+fn segtree_rejects_bad_indices_and_values() {
+    assert_eq!(run_err("$st = segtree_new([1, \"a\"])"), "segtree_new: expected list of numbers");
+    assert_eq!(
+        run_err("$st = segtree_new([1, 2, 3])\n$q = segtree_query($st, 2, 1)"),
+        "segtree_query: invalid range 2..1"
+    );
+    assert_eq!(
+        run_err("$st = segtree_new([1, 2, 3])\n$q = segtree_query($st, 0, 9)"),
+        "segtree_query: invalid range 0..9"
+    );
+    assert_eq!(
+        run_err("$st = segtree_new([1, 2, 3])\n$st = segtree_update($st, -1, 5)"),
+        "segtree_update: idx = -1 out of range"
+    );
+}
+
+#[test]
+fn lichao_batch_add_matches_brute_force_minima() {
+    let lines = [(1.0, 0.0), (-1.0, 10.0), (0.0, 3.0), (0.5, -2.0), (-2.0, -15.0)];
+    let list: Vec<String> = lines.iter().map(|(m, b)| format!("[{}, {}]", m, b)).collect();
+    let mut src = format!("$t = lichao_add_many(lichao_new(-10, 10), [{}])\n$u = lichao_new(-10, 10)\n", list.join(", "));
+    for (m, b) in &lines {
+        src.push_str(&format!("$u = lichao_add($u, {}, {})\n", m, b));
+    }
+    let xs: Vec<i64> = (-10..=10).collect();
+    let batch: Vec<String> = xs.iter().map(|x| format!("lichao_query($t, {})", x)).collect();
+    let single: Vec<String> = xs.iter().map(|x| format!("lichao_query($u, {})", x)).collect();
+    src.push_str(&format!("$batch = [{}]\n$single = [{}]\n", batch.join(", "), single.join(", ")));
+    let expected = Value::make_list(
+        xs.iter()
+            .map(|&x| Value::Float(lines.iter().map(|(m, b)| m * x as f64 + b).fold(f64::INFINITY, f64::min)))
+            .collect(),
+    );
+    assert_eq!(run(&src, "batch"), expected);
+    assert_eq!(run(&src, "single"), expected);
+}
+
+#[test]
+fn lichao_rejects_queries_outside_the_range() {
+    let src = "$t = lichao_add_many(lichao_new(0, 8), [[1, 2]])\n$q = lichao_query($t, 9)";
+    assert_eq!(run_err(src), "lichao_query: x = 9 out of range [0, 8]");
+    let src = "$t = lichao_add_many(lichao_new(0, 8), [[1, 2], [3]])";
+    assert_eq!(run_err(src), "lichao_add_many: line 1 must be [m, b]");
 }
 
 #[test]
@@ -58,8 +340,8 @@ fn lis_and_knapsack() {
     $lis = lis_length([10,9,2,5,3,7,101,18])
     $val = knapsack_01([2,3,4,5], [3,4,5,6], 5)
     "#;
-    assert_eq!(run(src, "lis"), Value::Number(4.0));
-    assert_eq!(run(src, "val"), Value::Number(7.0));
+    assert_eq!(run(src, "lis"), Value::SmallInt(4));
+    assert_eq!(run(src, "val"), Value::SmallInt(7));
 }
 
 #[test]
@@ -69,6 +351,386 @@ fn lower_upper_bound() {
     $lb = lower_bound($a, 4)
     $ub = upper_bound($a, 4)
     "#;
-    assert_eq!(run(src, "lb"), Value::Number(2.0));
-    assert_eq!(run(src, "ub"), Value::Number(4.0));
+    assert_eq!(run(src, "lb"), Value::SmallInt(2));
+    assert_eq!(run(src, "ub"), Value::SmallInt(4));
+}
+
+#[test]
+fn binary_search_encodes_insertion_point() {
+    let src = r#"
+    $a = [1, 3, 3, 5]
+    $found = binary_search($a, 3)
+    $front = binary_search($a, 0)
+    $middle = binary_search($a, 4)
+    $back = binary_search($a, 9)
+    $empty = binary_search([], 2)
+    $words = binary_search_values(["ant", "bee", "cat"], "bee")
+    $missing = binary_search_values(["ant", "bee", "cat"], "bug")
+    "#;
+    // found: first matching index; absent: -(insertion point) - 1
+    assert_eq!(run(src, "found"), Value::SmallInt(1));
+    assert_eq!(run(src, "front"), Value::SmallInt(-1));
+    assert_eq!(run(src, "middle"), Value::SmallInt(-4));
+    assert_eq!(run(src, "back"), Value::SmallInt(-5));
+    assert_eq!(run(src, "empty"), Value::SmallInt(-1));
+    assert_eq!(run(src, "words"), Value::SmallInt(1));
+    assert_eq!(run(src, "missing"), Value::SmallInt(-3));
+}
+
+#[test]
+fn edit_distance_chars_and_lists() {
+    let src = r#"
+    $a = edit_distance("kitten", "sitting")
+    $b = edit_distance("", "abc")
+    $c = edit_distance("héllo", "hello")
+    $d = edit_distance("日本語", "日本")
+    $e = edit_distance_list([1, 2, 3], [1, 3, 4])
+    "#;
+    assert_eq!(run(src, "a"), Value::SmallInt(3));
+    assert_eq!(run(src, "b"), Value::SmallInt(3));
+    assert_eq!(run(src, "c"), Value::SmallInt(1));
+    assert_eq!(run(src, "d"), Value::SmallInt(1));
+    assert_eq!(run(src, "e"), Value::SmallInt(2));
+}
+
+#[test]
+fn manacher_longest_palindrome() {
+    let src = r#"
+    $a = longest_palindrome("babad")
+    $b = longest_palindrome("cbbd")
+    $c = longest_palindrome("")
+    $d = longest_palindrome("xéabaéy")
+    $p = palindrome_lengths("aba")
+    $e = palindrome_lengths("")
+    "#;
+    assert_eq!(run(src, "a"), Value::make_text("bab"));
+    assert_eq!(run(src, "b"), Value::make_text("bb"));
+    assert_eq!(run(src, "c"), Value::make_text(""));
+    assert_eq!(run(src, "d"), Value::make_text("éabaé"));
+    assert_eq!(
+        run(src, "p"),
+        Value::make_list([0, 1, 0, 3, 0, 1, 0].iter().map(|&v| Value::SmallInt(v)).collect())
+    );
+    assert_eq!(run(src, "e"), Value::make_list(vec![Value::SmallInt(0)]));
+}
+
+#[test]
+fn palindrome_helpers_match_naive_scans() {
+    let is_pal = |w: &[char]| w.iter().eq(w.iter().rev());
+    let mut rng = Lcg(5);
+    let mut words: Vec<String> = vec![String::new(), "a".into(), "abba".into(), "racecar".into(), "xéé".into()];
+    for _ in 0..25 {
+        let len = rng.below(14);
+        words.push((0..len).map(|_| ['a', 'b', 'c'][rng.below(3)]).collect());
+    }
+    for w in &words {
+        let c: Vec<char> = w.chars().collect();
+        let n = c.len();
+        let odd: Vec<i64> = (0..n)
+            .map(|i| (0..=i.min(n - 1 - i)).rev().find(|&r| is_pal(&c[i - r..=i + r])).unwrap() as i64)
+            .collect();
+        let even: Vec<i64> =
+            (0..n).map(|i| (0..=i.min(n - i)).rev().find(|&r| is_pal(&c[i - r..i + r])).unwrap() as i64).collect();
+        let mut distinct = std::collections::HashSet::new();
+        let mut longest = 0;
+        for i in 0..n {
+            for j in i + 1..=n {
+                if is_pal(&c[i..j]) {
+                    distinct.insert(c[i..j].to_vec());
+                    longest = longest.max(j - i);
+                }
+            }
+        }
+        let src = format!(
+            "$odd = palindrome_radius_array(\"{w}\")\n$even = palindrome_radius_even(\"{w}\")\n$count = all_palindromic_substrings_count(\"{w}\")\n$is = is_palindrome(\"{w}\")\n$longest = len(longest_palindrome(\"{w}\"))\n"
+        );
+        assert_eq!(run(&src, "odd"), ints(&odd), "odd radii of {:?}", w);
+        assert_eq!(run(&src, "even"), ints(&even), "even radii of {:?}", w);
+        assert_eq!(run(&src, "count"), Value::SmallInt(distinct.len() as i64), "distinct count of {:?}", w);
+        assert_eq!(run(&src, "is"), Value::Bool(is_pal(&c)), "is_palindrome {:?}", w);
+        assert_eq!(run(&src, "longest"), Value::SmallInt(longest as i64), "longest of {:?}", w);
+    }
+}
+
+#[test]
+fn rabin_karp_matches_kmp() {
+    let cases = [
+        ("abababa", "aba"),
+        ("aaaaa", "aa"),
+        ("hello world", "o"),
+        ("short", "longer pattern"),
+        ("abc", ""),
+        ("xin chào chào", "chào"),
+    ];
+    for (text, pat) in cases {
+        let src = format!("$k = kmp_search(\"{text}\", \"{pat}\")\n$r = rabin_karp(\"{text}\", \"{pat}\")\n");
+        assert_eq!(run(&src, "r"), run(&src, "k"), "{text:?} / {pat:?}");
+    }
+    let src = "$r = rabin_karp(\"abababa\", \"aba\")\n";
+    assert_eq!(
+        run(src, "r"),
+        Value::make_list(vec![Value::SmallInt(0), Value::SmallInt(2), Value::SmallInt(4)])
+    );
+}
+
+#[test]
+fn aho_corasick_overlaps_and_suffixes() {
+    let src = r#"
+    $m = aho_corasick("ushers", ["he", "she", "his", "hers"])
+    $n = aho_corasick("aaa", ["a", "aa"])
+    "#;
+    let hit = |idx: i64, start: i64| {
+        let mut m = std::collections::HashMap::new();
+        m.insert("pattern_index".to_string(), Value::SmallInt(idx));
+        m.insert("start".to_string(), Value::SmallInt(start));
+        Value::make_map(m)
+    };
+    // "he" is a suffix of "she": both are reported, plus the overlapping "hers"
+    assert_eq!(run(src, "m"), Value::make_list(vec![hit(1, 1), hit(0, 2), hit(3, 2)]));
+    assert_eq!(
+        run(src, "n"),
+        Value::make_list(vec![hit(0, 0), hit(1, 0), hit(0, 1), hit(1, 1), hit(0, 2)])
+    );
+}
+
+fn ac_hit(pattern: &str, start: i64, end: i64) -> Value {
+    let mut m = std::collections::HashMap::new();
+    m.insert("pattern".to_string(), Value::make_text(pattern));
+    m.insert("start".to_string(), Value::SmallInt(start));
+    m.insert("end".to_string(), Value::SmallInt(end));
+    Value::make_map(m)
+}
+
+#[test]
+fn aho_corasick_automaton_finds_three_patterns() {
+    let src = r#"
+    $ac = aho_corasick_new(["cat", "dog", "bird"])
+    $hits = aho_corasick_search($ac, "a cat, a dog and a bird; another cat")
+    $again = aho_corasick_search($ac, "no pets here")
+    "#;
+    assert_eq!(
+        run(src, "hits"),
+        Value::make_list(vec![ac_hit("cat", 2, 5), ac_hit("dog", 9, 12), ac_hit("bird", 19, 23), ac_hit("cat", 33, 36)])
+    );
+    assert_eq!(run(src, "again"), Value::make_list(vec![]));
+}
+
+#[test]
+fn aho_corasick_search_is_non_overlapping_and_char_based() {
+    let src = r#"
+    $ac = aho_corasick_new(["he", "she", "hers", "aa"])
+    $ushers = aho_corasick_search($ac, "ushers")
+    $aaaaa = aho_corasick_search($ac, "aaaaa")
+    $uni = aho_corasick_search(aho_corasick_new(["đá"]), "cờ đá đá")
+    $states = len(aho_corasick_new(["he", "she"]).fail)
+    "#;
+    // "she" ends first and the scan restarts after it, so "he" and "hers" are skipped
+    assert_eq!(run(src, "ushers"), Value::make_list(vec![ac_hit("she", 1, 4)]));
+    assert_eq!(run(src, "aaaaa"), Value::make_list(vec![ac_hit("aa", 0, 2), ac_hit("aa", 2, 4)]));
+    assert_eq!(run(src, "uni"), Value::make_list(vec![ac_hit("đá", 3, 5), ac_hit("đá", 6, 8)]));
+    // root + h, he + s, sh, she
+    assert_eq!(run(src, "states"), Value::SmallInt(6));
+    assert_eq!(
+        run_err("$m = aho_corasick_search({goto: []}, \"x\")"),
+        "aho_corasick_search: first arg must be an aho_corasick_new automaton"
+    );
+}
+
+#[test]
+fn math_clamp_lerp_map_range() {
+    let src = r#"
+    $lo = clamp(-5, 0, 10)
+    $hi = clamp(42, 0, 10)
+    $mid = clamp(7, 0, 10)
+    $f = clamp(1.5, 0, 1)
+    $l = lerp(10, 20, 0.5)
+    $x = lerp(0, 10, 1.5)
+    $m = map_range(5, 0, 10, 100, 200)
+    $rev = map_range(2, 0, 10, 10, 0)
+    $deg = map_range(3, 4, 4, 7, 9)
+    "#;
+    assert_eq!(run(src, "lo"), Value::SmallInt(0));
+    assert_eq!(run(src, "hi"), Value::SmallInt(10));
+    assert_eq!(run(src, "mid"), Value::SmallInt(7));
+    assert_eq!(run(src, "f"), Value::Float(1.0));
+    assert_eq!(run(src, "l"), Value::Float(15.0));
+    assert_eq!(run(src, "x"), Value::Float(15.0));
+    assert_eq!(run(src, "m"), Value::Float(150.0));
+    assert_eq!(run(src, "rev"), Value::Float(8.0));
+    assert_eq!(run(src, "deg"), Value::Float(7.0));
+}
+
+#[test]
+fn math_clamp_rejects_inverted_bounds() {
+    let tokens = lex("$c = clamp(1, 5, 0)\n").unwrap();
+    let ast = Parser::from_tokens(&tokens).unwrap();
+    let (_env, _events, errs) = eval_script(&ast);
+    assert!(errs.iter().any(|e| e.message.contains("lo must not exceed hi")), "{:?}", errs);
+}
+
+#[test]
+fn math_gcd_and_lcm_over_lists() {
+    let src = r#"
+    $g = gcd_list([12, 18, 24])
+    $l = lcm_list([4, 6])
+    $one = gcd_list([-9])
+    $z = lcm_list([3, 0, 5])
+    $f = gcd_list([2 * 3, 9])
+    "#;
+    assert_eq!(run(src, "g"), Value::SmallInt(6));
+    assert_eq!(run(src, "l"), Value::SmallInt(12));
+    assert_eq!(run(src, "one"), Value::SmallInt(9));
+    assert_eq!(run(src, "z"), Value::SmallInt(0));
+    assert_eq!(run(src, "f"), Value::SmallInt(3));
+}
+
+#[test]
+fn math_list_reducers_reject_bad_input() {
+    for (src, needle) in [
+        ("$x = gcd_list([])\n", "list must not be empty"),
+        ("$x = gcd_list([1.5, 3])\n", "expected integers"),
+        ("$x = lcm_list([\"a\"])\n", "expected integers"),
+        ("$x = lcm_list([1000000007, 998244353, 1000000009])\n", "overflows"),
+    ] {
+        let tokens = lex(src).unwrap();
+        let ast = Parser::from_tokens(&tokens).unwrap();
+        let (_env, _events, errs) = eval_script(&ast);
+        assert!(errs.iter().any(|e| e.message.contains(needle)), "{}: {:?}", src, errs);
+    }
+}
+
+#[test]
+fn math_combinatorics() {
+    let src = r#"
+    $f0 = factorial(0)
+    $f5 = factorial(5)
+    $f20 = factorial(20)
+    $c = choose(5, 2)
+    $c0 = choose(3, 5)
+    $cbig = choose(62, 31)
+    $cat = catalan(5)
+    $cat35 = catalan(35)
+    "#;
+    assert_eq!(run(src, "f0"), Value::SmallInt(1));
+    assert_eq!(run(src, "f5"), Value::SmallInt(120));
+    assert_eq!(run(src, "f20"), Value::SmallInt(2432902008176640000));
+    assert_eq!(run(src, "c"), Value::SmallInt(10));
+    assert_eq!(run(src, "c0"), Value::SmallInt(0));
+    assert!(matches!(run(src, "cbig"), Value::SmallInt(465428353255261088)));
+    assert_eq!(run(src, "cat"), Value::SmallInt(42));
+    assert!(matches!(run(src, "cat35"), Value::SmallInt(3116285494907301262)));
+}
+
+#[test]
+fn math_combinatorics_reject_bad_input() {
+    for (src, needle) in [
+        ("$x = factorial(-1)\n", "non-negative"),
+        ("$x = choose(5, 1.5)\n", "expected integers"),
+        ("$x = factorial(21)\n", "overflows"),
+        ("$x = catalan(36)\n", "overflows"),
+        ("$x = choose(100, 50)\n", "overflows"),
+    ] {
+        let tokens = lex(src).unwrap();
+        let ast = Parser::from_tokens(&tokens).unwrap();
+        let (_env, _events, errs) = eval_script(&ast);
+        assert!(errs.iter().any(|e| e.message.contains(needle)), "{}: {:?}", src, errs);
+    }
+}
+
+#[test]
+fn fft_convolve_rounds_integer_inputs() {
+    let src = r#"
+    $f = fft_convolve([1, 2, 3, 4], [5, 6, 7])
+    $n = ntt_convolve([1, 2, 3, 4], [5, 6, 7])
+    $x = fft_convolve([0.5], [3])
+    "#;
+    let expected: Vec<Value> = [5, 16, 34, 52, 45, 28].into_iter().map(Value::SmallInt).collect();
+    assert_eq!(run(src, "f"), Value::make_list(expected));
+    assert_eq!(run(src, "f"), run(src, "n"));
+    let x = run(src, "x");
+    let first = match &x {
+        Value::RcObj(obj) => match obj.as_ref() {
+            NauxObj::List(items) => items.borrow()[0].clone(),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    };
+    assert!(matches!(first, Value::Float(v) if (v - 1.5).abs() < 1e-9), "{:?}", x);
+}
+
+fn run_err(src: &str) -> String {
+    let tokens = lex(src).unwrap();
+    let ast = Parser::from_tokens(&tokens).unwrap();
+    let (_env, _events, errs) = eval_script(&ast);
+    errs.first().map(|e| e.message.clone()).unwrap_or_default()
+}
+
+fn ints(xs: &[i64]) -> Value {
+    Value::make_list(xs.iter().copied().map(Value::SmallInt).collect())
+}
+
+#[test]
+fn ntt_convolve_in_range_matches_fft_and_handles_negatives() {
+    let src = r#"
+    $n = ntt_convolve([3, -1, 4], [1, 5, -9, 2])
+    $f = fft_convolve([3, -1, 4], [1, 5, -9, 2])
+    "#;
+    assert_eq!(run(src, "n"), ints(&[3, 14, -28, 35, -38, 8]));
+    assert_eq!(run(src, "n"), run(src, "f"));
+}
+
+#[test]
+fn ntt_convolve_errors_when_results_could_wrap() {
+    let err = run_err("$x = ntt_convolve([100000, 100000], [100000])\n");
+    assert!(err.contains("ntt_convolve_big"), "{}", err);
+    assert!(run_err("$x = ntt_convolve([1.5], [2])\n").contains("integers"));
+}
+
+#[test]
+fn ntt_convolve_big_uses_crt_for_large_coefficients() {
+    let src = r#"
+    $b = ntt_convolve_big([100000, -100000, 3], [100000, 7])
+    "#;
+    assert_eq!(
+        run(src, "b"),
+        ints(&[10_000_000_000, -10_000_000_000 + 700_000, 300_000 - 700_000, 21])
+    );
+    let err = run_err("$x = ntt_convolve_big([4000000000, 4000000000], [4000000000])\n");
+    assert!(err.contains("64-bit"), "{}", err);
+}
+
+#[test]
+fn suffix_automaton_counts_and_finds_substrings() {
+    let src = r#"
+    $sam = suffix_automaton("abab")
+    $count = sam_distinct_substrings($sam)
+    $has = sam_contains($sam, "bab")
+    $missing = sam_contains($sam, "bb")
+    $empty = sam_contains($sam, "")
+    $uni = sam_distinct_substrings(suffix_automaton("héé"))
+    "#;
+    assert_eq!(run(src, "count"), Value::SmallInt(7));
+    assert_eq!(run(src, "has"), Value::Bool(true));
+    assert_eq!(run(src, "missing"), Value::Bool(false));
+    assert_eq!(run(src, "empty"), Value::Bool(true));
+    // h, é, hé, éé, héé
+    assert_eq!(run(src, "uni"), Value::SmallInt(5));
+}
+
+#[test]
+fn string_period_and_rotation_use_the_z_function() {
+    let src = r#"
+    $p = string_period("ababab")
+    $partial = string_period("abcab")
+    $aperiodic = string_period("abcd")
+    $rot = is_rotation("abcde", "cdeab")
+    $not_rot = is_rotation("abcde", "abced")
+    $short = is_rotation("abc", "ab")
+    "#;
+    assert_eq!(run(src, "p"), Value::SmallInt(2));
+    assert_eq!(run(src, "partial"), Value::SmallInt(3));
+    assert_eq!(run(src, "aperiodic"), Value::SmallInt(4));
+    assert_eq!(run(src, "rot"), Value::Bool(true));
+    assert_eq!(run(src, "not_rot"), Value::Bool(false));
+    assert_eq!(run(src, "short"), Value::Bool(false));
 }
@@ -0,0 +1,33 @@
+use naux::runtime::value::{Value, EQ_EPSILON};
+use naux::{run_source, Engine};
+
+mod common;
+use common::value_on_both;
+
+#[test]
+fn equality_is_tight_but_approx_eq_tolerates_round_off() {
+    let src = "$a = 0.1\n$b = 0.2\n^ [$a + $b == 0.3, approx_eq($a + $b, 0.3, 0.000000001), approx_eq(1, 1.1, 0.01)]\n";
+    let expected = Value::make_list(vec![Value::Bool(false), Value::Bool(true), Value::Bool(false)]);
+    assert_eq!(value_on_both(src), expected);
+    assert_eq!(value_on_both("^ 0.1 + 0.2 == 0.3\n"), Value::Bool(false));
+}
+
+#[test]
+fn assert_near_defaults_to_a_loose_tolerance() {
+    let src = "^ [assert_near(0.1 + 0.2, 0.3), assert_near(1, 1.5), assert_near(1, 1.5, 0.5)]\n";
+    let expected = Value::make_list(vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn approx_eq_rejects_negative_tolerance() {
+    let out = run_source("^ approx_eq(1, 1, -1)\n", Engine::Interp).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("non-negative")), "{:?}", out.errors);
+}
+
+#[test]
+fn exact_equality_still_matches_ints_and_floats() {
+    assert_eq!(EQ_EPSILON, 0.0);
+    let expected = Value::make_list(vec![Value::Bool(true), Value::Bool(false)]);
+    assert_eq!(value_on_both("$x = 1\n^ [$x == 1.0, $x != 1.0]\n"), expected);
+}
@@ -0,0 +1,68 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+const BOOM: &str = "~ fn boom()\n    ^ 1 / 0\n~ end\n~ fn fine()\n    ^ 1\n~ end\n";
+
+#[test]
+fn passes_when_call_fails_with_message() {
+    let src = format!("{BOOM}^ assert_throws(boom, \"Division by zero\")\n");
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(&src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::Bool(true), "{:?}", engine);
+    }
+}
+
+#[test]
+fn fails_when_call_succeeds() {
+    let src = format!("{BOOM}^ assert_throws(fine, \"Division by zero\")\n");
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(&src, engine).unwrap();
+        assert!(
+            out.errors.iter().any(|e| e.message.contains("but it succeeded")),
+            "{:?}: {:?}",
+            engine,
+            out.errors
+        );
+    }
+}
+
+#[test]
+fn fails_on_other_message_and_accepts_name_text_and_args() {
+    let src = format!("{BOOM}^ assert_throws(\"boom\", \"index out of range\")\n");
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(&src, engine).unwrap();
+        assert!(
+            out.errors.iter().any(|e| e.message.contains("expected an error containing")),
+            "{:?}: {:?}",
+            engine,
+            out.errors
+        );
+    }
+    let src = "~ fn div($a, $b)\n    ^ $a / $b\n~ end\n^ assert_throws(\"div\", \"zero\", 4, 0)\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::Bool(true), "{:?}", engine);
+    }
+}
+
+#[test]
+fn target_held_in_a_variable() {
+    let src = format!("{BOOM}$target = \"boom\"\n^ assert_throws($target, \"Division by zero\")\n");
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(&src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::Bool(true), "{:?}", engine);
+    }
+}
+
+#[test]
+fn script_function_named_assert_throws_gets_plain_arguments() {
+    let src = "~ fn assert_throws($a, $b)\n    ^ $a + $b\n~ end\n$x = 40\n^ assert_throws($x, 2)\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::SmallInt(42), "{:?}", engine);
+    }
+}
@@ -0,0 +1,41 @@
+use naux::ast::pretty_print_ast;
+use naux::lexer::lex;
+use naux::parser::Parser;
+
+#[test]
+fn ast_dump_shows_kinds_nesting_and_spans() {
+    let src = r#"~ fn inc($n)
+    ^ $n + 1
+~ end
+~ if inc(1) > 1
+    !say "big"
+~ end
+"#;
+    let ast = Parser::from_tokens(&lex(src).unwrap()).unwrap();
+    let dump = pretty_print_ast(&ast);
+    assert!(dump.contains("FnDef inc($n) @1:3"), "{}", dump);
+    assert!(dump.contains("    Return"), "{}", dump);
+    assert!(dump.contains("Binary Add @2:7-2:12"), "{}", dump);
+    assert!(dump.contains("If @4:3"), "{}", dump);
+    assert!(dump.contains("Var inc @4:6"), "{}", dump);
+    assert!(dump.contains("Action Say"), "{}", dump);
+    assert!(dump.contains("Text \"big\""), "{}", dump);
+}
+
+#[test]
+fn ast_json_keeps_kinds_fields_and_spans() {
+    let src = "$x = 1 + 2\n!say $x\n";
+    let ast = Parser::from_tokens(&lex(src).unwrap()).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&naux::ast::ast_to_json(&ast)).unwrap();
+    let stmts = json.as_array().unwrap();
+    assert_eq!(stmts.len(), 2);
+
+    let assign = &stmts[0]["Assign"];
+    assert_eq!(assign["name"], "x");
+    let add = &assign["expr"];
+    assert_eq!(add["kind"]["Binary"]["op"], "Add");
+    assert_eq!(add["kind"]["Binary"]["left"]["kind"]["Number"], 1.0);
+    assert_eq!(add["span"]["line"], 1);
+
+    assert_eq!(stmts[1]["Action"]["action"]["Say"]["value"]["kind"]["Var"], "x");
+}
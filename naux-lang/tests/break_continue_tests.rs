@@ -0,0 +1,66 @@
+use naux::lexer::lex;
+use naux::parser::parser::Parser;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::{ints, value_on_both};
+
+#[test]
+fn break_leaves_a_while_loop() {
+    let src = "$i = 0\n~ while true\n    $i = $i + 1\n    ~ if $i > 4\n        ~ break\n    ~ end\n~ end\n^ $i\n";
+    assert_eq!(value_on_both(src), Value::SmallInt(5));
+}
+
+#[test]
+fn continue_skips_to_the_next_each_item() {
+    let src = "~ fn first_even($xs)\n    ~ each $x in $xs\n        ~ if $x % 2\n            ~ continue\n        ~ end\n        ^ $x\n    ~ end\n    ^ 0\n~ end\n^ first_even([1, 3, 4, 6])\n";
+    assert_eq!(value_on_both(src), Value::SmallInt(4));
+}
+
+#[test]
+fn break_and_continue_in_a_counted_loop() {
+    let src = "$n = 0\n$hits = 0\n~ loop 100\n    $n = $n + 1\n    ~ if $n > 6\n        ~ break\n    ~ end\n    ~ if $n < 3\n        ~ continue\n    ~ end\n    $hits = $hits + 1\n~ end\n^ [$n, $hits]\n";
+    assert_eq!(value_on_both(src), ints(&[7, 4]));
+}
+
+#[test]
+fn break_only_leaves_the_innermost_loop() {
+    // the outer loop keeps going after each inner `~ break`
+    let src = "$outer = 0\n$inner = 0\n~ while $outer < 3\n    $outer = $outer + 1\n    ~ while true\n        $inner = $inner + 1\n        ~ break\n    ~ end\n~ end\n^ [$outer, $inner]\n";
+    assert_eq!(value_on_both(src), ints(&[3, 3]));
+}
+
+#[test]
+fn break_inside_a_nested_rite_targets_the_loop() {
+    let src = "$i = 0\n~ while $i < 10\n    $i = $i + 1\n    ~ rite\n        ~ if $i > 2\n            ~ break\n        ~ end\n    ~ end\n~ end\n^ $i\n";
+    assert_eq!(value_on_both(src), Value::SmallInt(3));
+}
+
+#[test]
+fn return_from_inside_a_loop_still_returns() {
+    let src = "~ fn first_big($xs)\n    ~ each $x in $xs\n        ~ if $x > 5\n            ^ $x\n        ~ end\n    ~ end\n    ^ 0\n~ end\n^ first_big([1, 7, 9])\n";
+    assert_eq!(value_on_both(src), Value::SmallInt(7));
+}
+
+#[test]
+fn break_out_of_unsafe_closes_it() {
+    let src = "~ loop 3\n    ~ unsafe\n        ~ break\n    ~ end\n~ end\n^ read_file(\"/nonexistent\")\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(
+            out.errors.iter().any(|e| e.message.contains("unsafe")),
+            "{:?}: {:?}",
+            engine,
+            out.errors
+        );
+    }
+}
+
+#[test]
+fn break_outside_a_loop_is_a_parse_error() {
+    for src in ["~ break\n", "~ if true\n    ~ continue\n~ end\n", "~ loop 2\n    ~ fn f()\n        ~ break\n    ~ end\n~ end\n"] {
+        let err = Parser::from_tokens(&lex(src).unwrap()).unwrap_err();
+        assert!(err.message.contains("outside of a loop"), "{}: {}", src, err.message);
+    }
+}
@@ -0,0 +1,75 @@
+use naux::ast::append_main_call;
+use naux::cli::run::execute_bytecode;
+use naux::lexer::lex;
+use naux::parser::parser::Parser;
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+use naux::vm::bytecode::{disasm_block, is_bytecode, Program, NXBC_VERSION};
+use naux::vm::compiler::compile_script;
+
+fn compile(src: &str) -> Program {
+    let tokens = lex(src).expect("lex");
+    let mut ast = Parser::from_tokens(&tokens).expect("parse");
+    append_main_call(&mut ast);
+    compile_script(&ast)
+}
+
+const SCRIPT: &str = r#"
+~ fn add($a, $b)
+    ^ $a + $b
+~ end
+~ fn main()
+    $m = {k: "v"}
+    ~ try
+        $_ = throw("boom")
+    ~ catch $e
+        !say $e.message
+    ~ end
+    !say $m.k
+    ^ add(40, 2)
+~ end
+"#;
+
+#[test]
+fn round_trip_preserves_code() {
+    let prog = compile(SCRIPT);
+    let bytes = prog.to_bytes();
+    assert!(is_bytecode(&bytes));
+    let loaded = Program::from_bytes(&bytes).expect("load");
+    assert_eq!(disasm_block(&loaded.main), disasm_block(&prog.main));
+    assert_eq!(loaded.main_locals, prog.main_locals);
+    assert_eq!(loaded.functions.len(), prog.functions.len());
+    for (name, f) in &prog.functions {
+        let g = &loaded.functions[name];
+        assert_eq!(g.params, f.params);
+        assert_eq!(g.locals, f.locals);
+        assert_eq!(g.memo, f.memo);
+        assert_eq!(disasm_block(&g.code), disasm_block(&f.code));
+    }
+    assert_eq!(loaded.to_bytes(), bytes);
+}
+
+#[test]
+fn loaded_program_runs_like_source() {
+    let res = execute_bytecode(&compile(SCRIPT).to_bytes(), "main.nxb");
+    assert!(res.is_ok(), "{:?}", res.errors);
+    assert!(matches!(&res.events[..], [RuntimeEvent::Say(a), RuntimeEvent::Say(b)] if a == "boom" && b == "v"));
+    assert_eq!(res.value, Value::SmallInt(42));
+}
+
+#[test]
+fn incompatible_version_is_rejected() {
+    let mut bytes = compile("^ 1\n").to_bytes();
+    bytes[4..6].copy_from_slice(&(NXBC_VERSION + 1).to_le_bytes());
+    let err = Program::from_bytes(&bytes).unwrap_err();
+    assert!(err.contains("unsupported bytecode version"), "{}", err);
+}
+
+#[test]
+fn truncated_and_foreign_input_is_rejected() {
+    let bytes = compile(SCRIPT).to_bytes();
+    let err = Program::from_bytes(&bytes[..bytes.len() - 3]).unwrap_err();
+    assert!(err.contains("truncated"), "{}", err);
+    assert!(Program::from_bytes(b"!say \"hi\"\n").unwrap_err().contains("magic"));
+    assert!(!execute_bytecode(b"NXBC", "bad.nxb").is_ok());
+}
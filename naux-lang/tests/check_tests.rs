@@ -0,0 +1,187 @@
+use naux::cli::check::{check_source, Severity};
+
+fn errors(src: &str) -> Vec<String> {
+    check_source(src)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| d.message)
+        .collect()
+}
+
+#[test]
+fn check_accepts_valid_program() {
+    let src = r#"
+~ fn double($n)
+    ^ $n * 2
+~ end
+$x = double(4)
+!say $x
+$m = gcd(12, len([1, 2]))
+"#;
+    assert!(errors(src).is_empty(), "{:?}", errors(src));
+}
+
+#[test]
+fn check_flags_undefined_function() {
+    let errs = errors("$x = dubble(4)\n");
+    assert_eq!(errs, vec!["Unknown function: dubble".to_string()]);
+}
+
+#[test]
+fn check_reports_parse_errors() {
+    let errs = errors("~ if true\n!say 1\n");
+    assert_eq!(errs.len(), 1);
+    assert!(errs[0].starts_with("Parse error"));
+}
+
+#[test]
+fn check_flags_typo_in_unreachable_branch() {
+    let src = r#"
+~ fn greet($n)
+    ^ $n
+~ end
+~ if false
+    $x = gret(1)
+~ end
+"#;
+    let diags: Vec<_> = check_source(src).into_iter().filter(|d| d.severity == Severity::Error).collect();
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].message, "Unknown function: gret");
+    let span = diags[0].span.as_ref().expect("call site span");
+    assert_eq!((span.line, span.column), (6, 10));
+}
+
+fn warnings(src: &str) -> Vec<String> {
+    check_source(src)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .map(|d| d.message)
+        .collect()
+}
+
+#[test]
+fn check_warns_on_unused_variable() {
+    let src = "$tmp = 1\n$x = 2\n!say $x\n";
+    assert_eq!(warnings(src), vec!["Unused variable: $tmp".to_string()]);
+    assert!(errors(src).is_empty());
+}
+
+#[test]
+fn check_warns_on_unused_function() {
+    let src = r#"
+~ fn helper($n)
+    ^ helper($n - 1)
+~ end
+~ fn _scratch()
+    ^ 0
+~ end
+~ fn main()
+    ^ 0
+~ end
+"#;
+    assert_eq!(warnings(src), vec!["Unused function: helper".to_string()]);
+}
+
+#[test]
+fn check_counts_reads_inside_loops_and_branches() {
+    let src = r#"
+$total = 0
+$_ignored = 5
+~ loop 3
+    ~ if $total < 10
+        $total = $total + 1
+    ~ end
+~ end
+"#;
+    assert!(warnings(src).is_empty(), "{:?}", warnings(src));
+}
+
+fn notes(src: &str) -> Vec<String> {
+    check_source(src)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Note)
+        .map(|d| d.message)
+        .collect()
+}
+
+#[test]
+fn check_notes_tail_recursion() {
+    let src = r#"
+~ fn count($n, $acc)
+    ~ if $n < 1
+        ^ $acc
+    ~ end
+    ^ count($n - 1, $acc + 1)
+~ end
+!say count(10, 0)
+"#;
+    assert_eq!(notes(src), vec!["Tail call to count (eligible for TCO)".to_string()]);
+    assert!(warnings(src).is_empty(), "{:?}", warnings(src));
+}
+
+#[test]
+fn check_warns_on_non_tail_recursion() {
+    let src = r#"
+~ fn sum($n)
+    ~ if $n < 1
+        ^ 0
+    ~ end
+    ^ $n + sum($n - 1)
+~ end
+!say sum(10)
+"#;
+    assert_eq!(
+        warnings(src),
+        vec!["Non-tail recursive call to sum may overflow the stack on large inputs".to_string()]
+    );
+    assert!(notes(src).is_empty());
+    let diag = check_source(src).into_iter().find(|d| d.severity == Severity::Warning).unwrap();
+    let span = diag.span.expect("call span");
+    assert_eq!((span.line, span.column), (6, 12));
+}
+
+#[test]
+fn check_treats_ternary_branches_as_tail_position() {
+    let src = r#"
+~ fn count($n, $acc)
+    ^ $n < 1 ? $acc : count($n - 1, $acc + 1)
+~ end
+!say count(10, 0)
+"#;
+    assert_eq!(notes(src), vec!["Tail call to count (eligible for TCO)".to_string()]);
+    assert!(warnings(src).is_empty(), "{:?}", warnings(src));
+}
+
+#[test]
+fn check_reports_operations_that_always_fail() {
+    let src = "$t = \"a\"\n$n = $t - 1\n$m = -[1]\n$c = 1 < \"b\"\n$s = $t + 2\n!say [$n, $m, $c, $s]\n";
+    assert_eq!(
+        errors(src),
+        vec![
+            "Type error: `-` needs numbers, got Text".to_string(),
+            "Type error: cannot negate List".to_string(),
+            "Type error: cannot compare Number with Text".to_string(),
+            "Type error: cannot add Text and Number".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn check_forgets_types_that_depend_on_control_flow() {
+    let src = r#"
+$x = "a"
+~ if true
+    $x = 1
+~ end
+$i = "s"
+~ loop 2
+    $y = $i - 1
+    $i = 0
+~ end
+~ fn f($p)
+    ^ $p * 2
+~ end
+!say [$x - 1, $y, f(1)]
+"#;
+    assert!(errors(src).is_empty(), "{:?}", errors(src));
+}
@@ -0,0 +1,46 @@
+use naux::cli::run::execute;
+use naux::cli::DefaultEngine;
+use naux::runtime::events::{quiet_events, RuntimeEvent};
+use naux::runtime::value::Value;
+
+#[test]
+fn execute_collects_events_without_printing() {
+    let src = "!say \"hello\"\n$x = 40 + 2\n^ $x\n";
+    for engine in [DefaultEngine::Vm, DefaultEngine::Interp] {
+        let res = execute(src, "main.nx", engine);
+        assert!(res.is_ok(), "{:?}", res.errors);
+        assert!(matches!(res.events.first(), Some(RuntimeEvent::Say(s)) if s == "hello"));
+        assert_eq!(res.value, Value::SmallInt(42));
+    }
+}
+
+#[test]
+fn execute_renders_parse_errors_with_filename() {
+    let res = execute("$x =\n", "broken.nx", DefaultEngine::Vm);
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0].contains("broken.nx:"));
+    assert!(res.events.is_empty());
+}
+
+#[test]
+fn execute_renders_runtime_errors() {
+    let res = execute("!say $nope\n", "main.nx", DefaultEngine::Interp);
+    assert!(!res.is_ok());
+    assert!(res.errors[0].contains("Variable not found"));
+}
+
+#[test]
+fn execute_rejects_llvm_engine() {
+    let res = execute("!say 1\n", "main.nx", DefaultEngine::Llvm);
+    assert!(!res.is_ok());
+}
+
+#[test]
+fn quiet_drops_log_events_and_keeps_say() {
+    let src = "$x = 1\n!say \"hello\"\n!fetch \"https://example.com\"\n";
+    for engine in [DefaultEngine::Vm, DefaultEngine::Interp] {
+        let mut events = execute(src, "main.nx", engine).events;
+        events.push(RuntimeEvent::Log("debug".into()));
+        assert_eq!(quiet_events(events), vec![RuntimeEvent::Say("hello".into())]);
+    }
+}
@@ -0,0 +1,64 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::{ints, value_on};
+
+// Function values and captures are implemented by the tree-walking interpreter.
+fn value_of(src: &str) -> Value {
+    value_on(src, Engine::Interp)
+}
+
+#[test]
+fn closures_made_in_a_loop_capture_each_counter_value() {
+    let src = "~ fn make_all()\n    $fs = []\n    $i = 0\n    ~ loop 3\n        $i = $i + 1\n        ~ fn get()\n            ^ $i\n        ~ end\n        $fs = queue_push($fs, get)\n    ~ end\n    ^ $fs\n~ end\n$fs = make_all()\n^ [$fs[0](), $fs[1](), $fs[2]()]\n";
+    assert_eq!(value_of(src), ints(&[1, 2, 3]));
+}
+
+#[test]
+fn a_returned_closure_keeps_its_parameters() {
+    let src = "~ fn adder($n)\n    ~ fn add($x)\n        ^ $x + $n\n    ~ end\n    ^ add\n~ end\n$add5 = adder(5)\n$add7 = adder(7)\n^ [$add5(1), $add7(1)]\n";
+    assert_eq!(value_of(src), ints(&[6, 8]));
+}
+
+#[test]
+fn arguments_shadow_captured_bindings() {
+    let src = "~ fn outer($x)\n    ~ fn inner($x)\n        ^ $x\n    ~ end\n    ^ inner\n~ end\n$f = outer(1)\n^ $f(2)\n";
+    assert_eq!(value_of(src), Value::SmallInt(2));
+}
+
+#[test]
+fn top_level_functions_see_current_globals() {
+    let src = "$count = 0\n~ fn show()\n    ^ $count\n~ end\n$count = 5\n^ show()\n";
+    assert_eq!(value_of(src), Value::SmallInt(5));
+}
+
+#[test]
+fn calling_a_non_function_value_is_an_error() {
+    let out = run_source("$xs = [1]\n^ $xs[0]()\n", Engine::Interp).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("Invalid call target")), "{:?}", out.errors);
+}
+
+#[test]
+fn closures_made_in_a_top_level_each_capture_the_item() {
+    let src = "$fs = {}\n~ each $x in [1, 2, 3]\n    ~ fn get()\n        ^ $x\n    ~ end\n    $_ = map_set($fs, \"f{$x}\", get)\n~ end\n^ [$fs[\"f1\"](), $fs[\"f2\"](), $fs[\"f3\"]()]\n";
+    assert_eq!(value_of(src), ints(&[1, 2, 3]));
+}
+
+#[test]
+fn closures_made_in_a_top_level_loop_capture_each_counter_value() {
+    let src = "$fs = []\n$i = 0\n~ loop 3\n    $i = $i + 1\n    ~ fn get()\n        ^ $i\n    ~ end\n    $fs = queue_push($fs, get)\n~ end\n^ [$fs[0](), $fs[1](), $fs[2]()]\n";
+    assert_eq!(value_of(src), ints(&[1, 2, 3]));
+}
+
+#[test]
+fn closures_made_in_a_top_level_while_capture_each_counter_value() {
+    let src = "$fs = []\n$i = 0\n~ while $i < 2\n    $i = $i + 1\n    ~ fn get()\n        ^ $i * 10\n    ~ end\n    $fs = queue_push($fs, get)\n~ end\n^ [$fs[0](), $fs[1]()]\n";
+    assert_eq!(value_of(src), ints(&[10, 20]));
+}
+
+#[test]
+fn the_vm_rejects_calling_function_values_clearly() {
+    let out = run_source("~ fn one()\n    ^ 1\n~ end\n$fs = [one]\n^ $fs[0]()\n", Engine::Vm).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("Calling a function value needs the interpreter")), "{:?}", out.errors);
+}
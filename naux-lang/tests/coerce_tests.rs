@@ -0,0 +1,64 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+
+fn value_of(expr: &str) -> Value {
+    common::value_on_both(&format!("^ {}\n", expr))
+}
+
+fn error_of(expr: &str) -> String {
+    common::error_of(&format!("^ {}\n", expr))
+}
+
+#[test]
+fn int_truncates_and_parses() {
+    assert!(matches!(value_of("int(\"42\")"), Value::SmallInt(42)));
+    assert!(matches!(value_of("int(3.9)"), Value::SmallInt(3)));
+    assert!(matches!(value_of("int(-3.9)"), Value::SmallInt(-3)));
+    assert!(matches!(value_of("int(\" 7.5 \")"), Value::SmallInt(7)));
+    assert!(matches!(value_of("int(\"-12\")"), Value::SmallInt(-12)));
+    assert!(matches!(value_of("int(\"9007199254740993\")"), Value::SmallInt(9007199254740993)));
+    assert!(matches!(value_of("int(7)"), Value::SmallInt(7)));
+}
+
+#[test]
+fn bools_convert_to_one_and_zero() {
+    assert!(matches!(value_of("int(true)"), Value::SmallInt(1)));
+    assert!(matches!(value_of("int(false)"), Value::SmallInt(0)));
+    assert!(matches!(value_of("float(true)"), Value::Float(f) if f == 1.0));
+}
+
+#[test]
+fn float_promotes_and_parses() {
+    assert!(matches!(value_of("float(2)"), Value::Float(f) if f == 2.0));
+    assert!(matches!(value_of("float(\"2.5\")"), Value::Float(f) if f == 2.5));
+    assert!(matches!(value_of("float(\"1e3\")"), Value::Float(f) if f == 1000.0));
+}
+
+#[test]
+fn coerced_text_works_in_arithmetic() {
+    assert_eq!(value_of("int(\"40\") + 2"), Value::SmallInt(42));
+}
+
+#[test]
+fn non_numeric_values_are_errors() {
+    assert!(error_of("int(\"abc\")").contains("int(value): cannot convert \"abc\" to a number"));
+    assert!(error_of("float(\"\")").contains("float(value): cannot convert"));
+    assert!(error_of("int([1])").contains("cannot convert"));
+    assert!(error_of("int()").contains("int(value)"));
+    let vm = run_source("^ int(\"abc\")\n", Engine::Vm).unwrap();
+    assert!(!vm.errors.is_empty());
+}
+
+#[test]
+fn nan_and_infinity_texts_are_errors() {
+    for text in ["nan", "NaN", "inf", "-infinity", " +Inf "] {
+        for func in ["int", "float"] {
+            let expr = format!("{func}(\"{text}\")");
+            assert!(error_of(&expr).contains("cannot convert"), "{expr}");
+            let vm = run_source(&format!("^ {expr}\n"), Engine::Vm).unwrap();
+            assert!(!vm.errors.is_empty(), "vm {expr}");
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use naux::lexer::lex;
 use naux::parser::parser::Parser;
 use naux::runtime::eval_script;
-use naux::runtime::value::Value;
+use naux::runtime::value::{NauxObj, Value};
 
 fn run(src: &str, var: &str) -> Value {
     let tokens = lex(src).unwrap();
@@ -11,6 +11,16 @@ fn run(src: &str, var: &str) -> Value {
     env.get(var).unwrap_or(Value::Null)
 }
 
+fn list_items(v: &Value) -> Option<Vec<Value>> {
+    match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            NauxObj::List(items) => Some(items.borrow().clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 #[test]
 fn set_add_contains() {
     let src = r#"
@@ -21,6 +31,31 @@ fn set_add_contains() {
     assert_eq!(run(src, "out"), Value::Bool(true));
 }
 
+#[test]
+fn set_algebra_returns_new_sets() {
+    let src = r#"
+    $a = set_add(set_add(set_add(set_new(), 3), 1), 2)
+    $b = set_add(set_add(set_new(), 2), 4)
+    $union = set_to_list(set_union($a, $b))
+    $inter = set_to_list(set_intersection($a, $b))
+    $diff = set_to_list(set_difference($a, $b))
+    $sym = set_to_list(set_symmetric_difference($a, $b))
+    $removed = set_size(set_remove($a, 3))
+    $size = set_size($a)
+    $sub = set_is_subset(set_remove($a, 3), $a)
+    $sup = set_is_superset($a, $b)
+"#;
+    let ints = |xs: &[i64]| Value::make_list(xs.iter().map(|n| Value::SmallInt(*n)).collect());
+    assert_eq!(run(src, "union"), ints(&[1, 2, 3, 4]));
+    assert_eq!(run(src, "inter"), ints(&[2]));
+    assert_eq!(run(src, "diff"), ints(&[1, 3]));
+    assert_eq!(run(src, "sym"), ints(&[1, 3, 4]));
+    assert_eq!(run(src, "removed"), Value::SmallInt(2));
+    assert_eq!(run(src, "size"), Value::SmallInt(3));
+    assert_eq!(run(src, "sub"), Value::Bool(true));
+    assert_eq!(run(src, "sup"), Value::Bool(false));
+}
+
 #[test]
 fn queue_push_pop() {
     let src = r#"
@@ -30,16 +65,17 @@ fn queue_push_pop() {
     $res = queue_pop($q)
 "#;
     // queue_pop returns [head, new_queue]
-    match run(src, "res") {
-        Value::List(items) => {
-            assert_eq!(items.get(0), Some(&Value::Number(1.0)));
+    let res = run(src, "res");
+    match list_items(&res) {
+        Some(items) => {
+            assert_eq!(items.get(0), Some(&Value::SmallInt(1)));
             // second element should be the remaining queue [2]
-            match items.get(1) {
-                Some(Value::List(rest)) => assert_eq!(rest, &vec![Value::Number(2.0)]),
-                other => panic!("unexpected tail queue: {:?}", other),
+            match items.get(1).and_then(list_items) {
+                Some(rest) => assert_eq!(rest, vec![Value::SmallInt(2)]),
+                None => panic!("unexpected tail queue: {:?}", items.get(1)),
             }
         }
-        other => panic!("unexpected queue_pop result: {:?}", other),
+        None => panic!("unexpected queue_pop result: {:?}", res),
     }
 }
 
@@ -52,20 +88,172 @@ fn priority_queue_push_pop() {
     $pq = pq_push($pq, 3)
     $res = pq_pop_min($pq)
 "#;
-    match run(src, "res") {
-        Value::List(items) => {
-            assert_eq!(items.get(0), Some(&Value::Number(1.0))); // min element
+    let res = run(src, "res");
+    match list_items(&res) {
+        Some(items) => {
+            assert_eq!(items.get(0), Some(&Value::SmallInt(1))); // min element
             // remainder should be a priority queue with 3,5
             match items.get(1) {
-                Some(Value::PriorityQueue(v)) => {
-                    // order inside pq storage is implementation-defined; check set equality
-                    let mut got = v.clone();
-                    got.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                    assert_eq!(got, vec![Value::Number(3.0), Value::Number(5.0)]);
-                }
+                Some(Value::RcObj(rc)) => match rc.as_ref() {
+                    NauxObj::PriorityQueue(v) => {
+                        // order inside pq storage is implementation-defined; check set equality
+                        let mut got = v.borrow().clone();
+                        got.sort();
+                        assert_eq!(got, vec![Value::SmallInt(3), Value::SmallInt(5)]);
+                    }
+                    other => panic!("expected priority queue, got {:?}", other),
+                },
                 other => panic!("expected priority queue, got {:?}", other),
             }
         }
-        other => panic!("unexpected pq_pop_min result: {:?}", other),
+        None => panic!("unexpected pq_pop_min result: {:?}", res),
     }
 }
+
+#[test]
+fn priority_queue_min_max_peek_and_keyed() {
+    let src = r#"
+    $pq = pq_from_list([4, 9, 1, 7, 3])
+    $ends = [pq_peek_min($pq), pq_peek_max($pq), pq_size($pq), pq_is_empty($pq)]
+    $hi = pq_pop_max($pq)
+    $lo = pq_pop_min($hi[1])
+    $left = [$hi[0], $lo[0], pq_size($lo[1]), pq_peek_max($lo[1])]
+    $empty = [pq_peek_min(pq_new()), pq_pop_max(pq_new())[0], pq_is_empty(pq_new())]
+    $tasks = pq_push_keyed(pq_push_keyed(pq_push_keyed(pq_new(), 2, "write"), 5, "ship"), 1, "plan")
+    $next = pq_peek_min($tasks)
+    $same = pq_push(pq_push(pq_new(), 1), 2) == pq_from_list([2, 1])
+"#;
+    let ints = |xs: &[i64]| Value::make_list(xs.iter().map(|x| Value::SmallInt(*x)).collect());
+    assert_eq!(run(src, "ends"), Value::make_list(vec![Value::SmallInt(1), Value::SmallInt(9), Value::SmallInt(5), Value::Bool(false)]));
+    assert_eq!(run(src, "left"), ints(&[9, 1, 3, 7]));
+    assert_eq!(run(src, "empty"), Value::make_list(vec![Value::Null, Value::Null, Value::Bool(true)]));
+    assert_eq!(run(src, "next"), Value::make_list(vec![Value::SmallInt(1), Value::make_text("plan")]));
+    // equal contents compare equal whatever order they were pushed in
+    assert_eq!(run(src, "same"), Value::Bool(true));
+}
+
+#[test]
+fn priority_queue_drains_in_order_from_both_ends() {
+    let mut x: i64 = 12345;
+    let values: Vec<i64> = (0..200)
+        .map(|_| {
+            x = (x * 1103515245 + 12345) % 2147483648;
+            x % 50
+        })
+        .collect();
+    let list = values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+    let src = format!(
+        "$xs = [{list}]\n$built = pq_new()\n$i = 0\n~ while $i < len($xs)\n    $built = pq_push($built, $xs[$i])\n    $i = $i + 1\n~ end\n$heap = pq_from_list([{list}])\n$desc = []\n~ while !pq_is_empty($heap)\n    $r = pq_pop_max($heap)\n    $desc = list_push($desc, $r[0])\n    $heap = $r[1]\n~ end\n$asc = []\n~ while !pq_is_empty($built)\n    $r = pq_pop_min($built)\n    $asc = list_push($asc, $r[0])\n    $built = $r[1]\n~ end\n"
+    );
+    let mut sorted = values.clone();
+    sorted.sort();
+    let ints = |xs: &[i64]| Value::make_list(xs.iter().map(|x| Value::SmallInt(*x)).collect());
+    assert_eq!(run(&src, "asc"), ints(&sorted));
+    sorted.reverse();
+    assert_eq!(run(&src, "desc"), ints(&sorted));
+}
+
+#[test]
+fn hash_equal_values_hash_equal() {
+    let h = |expr: &str| run(&format!("$h = hash({})\n", expr), "h");
+    assert_eq!(h(r#"[1, "x", {k: 2, j: true}]"#), h(r#"[1, "x", {j: true, k: 2}]"#));
+    assert_ne!(h(r#"[1, "x", {k: 2, j: true}]"#), h(r#"[1, "y", {k: 2, j: true}]"#));
+    assert_eq!(h("2"), h("2.0"));
+    assert_ne!(h("1"), h(r#""1""#));
+}
+
+#[test]
+fn hash_is_stable_across_runs() {
+    // pinned: changing the canonical encoding breaks persisted buckets
+    assert_eq!(run("$h = hash(\"naux\")\n", "h"), Value::SmallInt(7646163819764995990));
+    assert_eq!(Value::make_text("naux").stable_hash(), 7646163819764995990);
+}
+
+#[test]
+fn trie_insert_and_prefix() {
+    let src = r#"
+    $t = trie_new()
+    $_ = trie_insert($t, "car")
+    $_ = trie_insert($t, "cart")
+    $_ = trie_insert($t, "cat")
+    $_ = trie_insert($t, "dog")
+    $has = [trie_contains($t, "car"), trie_contains($t, "ca"), trie_contains($t, "cart")]
+    $ca = trie_prefix($t, "ca")
+    $none = trie_prefix($t, "z")
+    $all = trie_prefix($t, "")
+"#;
+    let texts = |xs: &[&str]| Value::make_list(xs.iter().map(|s| Value::make_text(*s)).collect());
+    assert_eq!(
+        run(src, "has"),
+        Value::make_list(vec![Value::Bool(true), Value::Bool(false), Value::Bool(true)])
+    );
+    assert_eq!(run(src, "ca"), texts(&["car", "cart", "cat"]));
+    assert_eq!(run(src, "none"), texts(&[]));
+    assert_eq!(run(src, "all"), texts(&["car", "cart", "cat", "dog"]));
+}
+
+#[test]
+fn lru_evicts_least_recently_used() {
+    let src = r#"
+    $c = lru_new(2)
+    $_ = lru_put($c, "a", 1)
+    $_ = lru_put($c, "b", 2)
+    $_ = lru_put($c, "c", 3)
+    $first = [lru_get($c, "a"), lru_get($c, "b"), lru_get($c, "c")]
+    $d = lru_new(2)
+    $_ = lru_put($d, "a", 1)
+    $_ = lru_put($d, "b", 2)
+    $_ = lru_get($d, "a")
+    $_ = lru_put($d, 7, 3)
+    $second = [lru_get($d, "a"), lru_get($d, "b"), lru_get($d, 7)]
+"#;
+    assert_eq!(
+        run(src, "first"),
+        Value::make_list(vec![Value::Null, Value::SmallInt(2), Value::SmallInt(3)])
+    );
+    // reading "a" made "b" the oldest entry
+    assert_eq!(
+        run(src, "second"),
+        Value::make_list(vec![Value::SmallInt(1), Value::Null, Value::SmallInt(3)])
+    );
+}
+
+#[test]
+fn counter_most_common_words() {
+    let src = r#"
+    $c = counter_new()
+~ each $w in ["b", "a", "c", "a", "b", "a", "d", "c"]
+    $_ = counter_add($c, $w)
+~ end
+    $_ = counter_add($c, "d", 1)
+    $top = counter_most_common($c, 2)
+    $missing = counter_get($c, "zzz")
+"#;
+    let pair = |k: &str, n: i64| Value::make_list(vec![Value::make_text(k), Value::SmallInt(n)]);
+    // b, c and d tie on 2; the key order keeps "b" ahead
+    assert_eq!(run(src, "top"), Value::make_list(vec![pair("a", 3), pair("b", 2)]));
+    assert_eq!(run(src, "missing"), Value::SmallInt(0));
+}
+
+#[test]
+fn deque_both_ends() {
+    let src = r#"
+    $d = deque_new()
+    $d = deque_push_back($d, 2)
+    $d = deque_push_front($d, 1)
+    $d = deque_push_back($d, 3)
+    $snapshot = $d
+    $r = deque_pop_back($d)
+    $back = $r[0]
+    $r = deque_pop_front($r[1])
+    $front = $r[0]
+    $rest = $r[1]
+    $empty = deque_pop_front(deque_new())
+"#;
+    let ints = |xs: &[i64]| Value::make_list(xs.iter().map(|x| Value::SmallInt(*x)).collect());
+    assert_eq!(run(src, "snapshot"), ints(&[1, 2, 3]));
+    assert_eq!(run(src, "back"), Value::SmallInt(3));
+    assert_eq!(run(src, "front"), Value::SmallInt(1));
+    assert_eq!(run(src, "rest"), ints(&[2]));
+    assert_eq!(list_items(&run(src, "empty")).unwrap()[0], Value::Null);
+}
@@ -0,0 +1,29 @@
+use naux::lexer::lex;
+use naux::parser::error::format_parse_error;
+use naux::parser::Parser;
+use naux::renderer::color;
+use naux::vm::run::run_vm;
+
+// One test so toggling the global color switch can't race with another test.
+#[test]
+fn errors_are_plain_text_and_colored_only_when_printed() {
+    let bad = "$x = (1 +\n";
+    let err = Parser::from_tokens(&lex(bad).unwrap()).unwrap_err();
+    let ok = "$x = 1 / 0\n!say nope(1)\n";
+    let ast = Parser::from_tokens(&lex(ok).unwrap()).unwrap();
+
+    // messages are data for library callers, so even an enabled switch leaves them plain
+    color::set_enabled(true);
+    let parse_msg = format_parse_error(bad, &err, "main.nx");
+    let vm_msg = run_vm(&ast, ok, "main.nx").unwrap_err();
+    let colored = color::diagnostic(&parse_msg);
+    let vm_colored = color::diagnostic(&vm_msg);
+    color::set_enabled(false);
+    assert!(!parse_msg.contains('\x1b'), "{}", parse_msg);
+    assert!(!vm_msg.contains('\x1b'), "{}", vm_msg);
+    assert!(colored.contains("\x1b[31mParse error:"), "{}", colored);
+    assert!(colored.contains("\x1b[36m^"), "{}", colored);
+    assert!(vm_colored.contains("\x1b[31mVM error:"), "{}", vm_colored);
+    assert!(vm_colored.contains("\x1b[2m$x = 1 / 0"), "{}", vm_colored);
+    assert_eq!(color::diagnostic(&parse_msg), parse_msg);
+}
@@ -0,0 +1,62 @@
+//! Helpers shared by the integration tests. Each test crate uses a subset of them.
+#![allow(dead_code)]
+
+use naux::runtime::value::{NauxObj, Value};
+use naux::{run_source, Engine, RunOutput};
+
+/// Runs `src` on the interpreter and then the VM.
+pub fn run_both(src: &str) -> Vec<RunOutput> {
+    [Engine::Interp, Engine::Vm].into_iter().map(|engine| run_source(src, engine).unwrap()).collect()
+}
+
+/// Runs `src` on one engine and returns its value, failing on any error.
+pub fn value_on(src: &str, engine: Engine) -> Value {
+    let out = run_source(src, engine).unwrap();
+    assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+    out.value
+}
+
+/// Runs `src` on both engines, failing on any error or when their values differ.
+pub fn value_on_both(src: &str) -> Value {
+    let mut values: Vec<Value> = [Engine::Interp, Engine::Vm].into_iter().map(|engine| value_on(src, engine)).collect();
+    assert_eq!(values[0], values[1], "engines disagree on {}", src);
+    values.remove(0)
+}
+
+/// The first error the interpreter reports for `src`, or "" when it succeeds.
+pub fn error_of(src: &str) -> String {
+    let out = run_source(src, Engine::Interp).unwrap();
+    out.errors.first().map(|e| e.message.clone()).unwrap_or_default()
+}
+
+pub fn ints(items: &[i64]) -> Value {
+    Value::make_list(items.iter().map(|n| Value::SmallInt(*n)).collect())
+}
+
+pub fn bools(items: &[bool]) -> Value {
+    Value::make_list(items.iter().map(|b| Value::Bool(*b)).collect())
+}
+
+pub fn text(s: &str) -> Value {
+    Value::make_text(s)
+}
+
+pub fn texts(items: &[&str]) -> Value {
+    Value::make_list(items.iter().map(|s| text(s)).collect())
+}
+
+/// The items of a list value.
+pub fn items(v: &Value) -> Vec<Value> {
+    let Value::RcObj(rc) = v else { panic!("not a list: {:?}", v) };
+    let NauxObj::List(items) = rc.as_ref() else { panic!("not a list: {:?}", v) };
+    let items = items.borrow().clone();
+    items
+}
+
+/// The value under `key` in a map value.
+pub fn field(v: &Value, key: &str) -> Value {
+    let Value::RcObj(rc) = v else { panic!("not a map: {:?}", v) };
+    let NauxObj::Map(m) = rc.as_ref() else { panic!("not a map: {:?}", v) };
+    let found = m.borrow().get(key).cloned();
+    found.unwrap_or_else(|| panic!("no key {}", key))
+}
@@ -0,0 +1,36 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::{texts, value_on_both};
+
+#[test]
+fn texts_compare_lexicographically() {
+    let src = r#"^ ["apple" < "banana", "apple" > "banana", "b" >= "b", "Zed" < "abc", "ab" <= "a"]"#;
+    let expected = [true, false, true, true, false].iter().map(|b| Value::Bool(*b)).collect();
+    assert_eq!(value_on_both(src), Value::make_list(expected));
+}
+
+#[test]
+fn sorts_strings_by_rank() {
+    let src = r#"~ fn rank($x, $xs)
+    ^ len([$y for $y in $xs if $y < $x])
+~ end
+$words = ["pear", "apple", "fig", "banana"]
+^ [[$w for $w in $words if rank($w, $words) < $i + 1 && rank($w, $words) > $i - 1][0] for $i in [0, 1, 2, 3]]
+"#;
+    assert_eq!(value_on_both(src), texts(&["apple", "banana", "fig", "pear"]));
+}
+
+#[test]
+fn numbers_still_compare_numerically() {
+    assert_eq!(value_on_both("^ [10 > 9, 2.5 < 3, 1 >= 1.0]"), Value::make_list(vec![Value::Bool(true); 3]));
+}
+
+#[test]
+fn mixed_types_are_an_error() {
+    let out = run_source("^ \"a\" < 1\n", Engine::Interp).unwrap();
+    assert!(out.errors[0].message.contains("cannot compare text with number"), "{:?}", out.errors);
+    let out = run_source("^ \"a\" < 1\n", Engine::Vm).unwrap();
+    assert!(out.errors[0].message.contains("cannot compare text with number"), "{:?}", out.errors);
+}
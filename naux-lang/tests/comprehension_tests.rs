@@ -0,0 +1,42 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::{ints, value_on_both};
+
+#[test]
+fn mapped_comprehension() {
+    let src = "$xs = [1, 2, 3]\n^ [$x * 2 for $x in $xs]\n";
+    assert_eq!(value_on_both(src), ints(&[2, 4, 6]));
+}
+
+#[test]
+fn filtered_comprehension() {
+    let src = "$xs = [3, -1, 0, 5, -7]\n^ [$x for $x in $xs if $x > 0]\n";
+    assert_eq!(value_on_both(src), ints(&[3, 5]));
+}
+
+#[test]
+fn nested_comprehension() {
+    let src = "^ [[$v + 1 for $v in $row if $v < 5] for $row in [[1, 2], [3], [4, 5, 6]]]\n";
+    let expected = Value::make_list(vec![ints(&[2, 3]), ints(&[4]), ints(&[5])]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn comprehension_over_empty_list() {
+    assert_eq!(value_on_both("^ [$x for $x in []]\n"), ints(&[]));
+}
+
+#[test]
+fn comprehension_requires_list() {
+    let out = run_source("^ [$x for $x in 5]\n", Engine::Interp).unwrap();
+    assert!(out.errors[0].message.contains("Comprehension expects a list"));
+}
+
+#[test]
+fn comprehension_variable_does_not_leak() {
+    let src = "$x = 10\n$ys = [$x * 2 for $x in [1, 2]]\n^ [$x, $ys]\n";
+    let expected = Value::make_list(vec![Value::SmallInt(10), ints(&[2, 4])]);
+    assert_eq!(value_on_both(src), expected);
+}
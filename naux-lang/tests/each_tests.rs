@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+#[test]
+fn each_with_index_builds_index_map() {
+    let src = r#"$m = {}
+~ each $i, $v in ["a", "b", "c"]
+    $_m = map_set($m, $i, $v)
+~ end
+^ $m
+"#;
+    let expected: HashMap<String, Value> =
+        [("0", "a"), ("1", "b"), ("2", "c")].iter().map(|(k, v)| (k.to_string(), Value::make_text(*v))).collect();
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::make_map(expected.clone()), "{:?}", engine);
+    }
+}
+
+#[test]
+fn single_variable_binds_element() {
+    let src = "~ each $v in [10, 20]\n    !say $v\n~ end\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        let said: Vec<_> = out.events.iter().filter_map(|e| if let RuntimeEvent::Say(s) = e { Some(s.as_str()) } else { None }).collect();
+        assert_eq!(said, vec!["10", "20"], "{:?}", engine);
+    }
+}
+
+#[test]
+fn index_counts_from_zero() {
+    let src = "~ each i, v in [\"x\", \"y\"]\n    !say $i\n~ end\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        let said: Vec<_> = out.events.iter().filter_map(|e| if let RuntimeEvent::Say(s) = e { Some(s.as_str()) } else { None }).collect();
+        assert_eq!(said, vec!["0", "1"], "{:?}", engine);
+    }
+}
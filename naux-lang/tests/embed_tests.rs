@@ -0,0 +1,35 @@
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine, NauxError};
+
+#[test]
+fn run_source_returns_events_and_value() {
+    let src = r#"
+~ fn add($a, $b)
+    ^ $a + $b
+~ end
+!say "hi"
+$x = add(2, 5)
+^ $x
+"#;
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).expect("script should parse");
+        assert!(out.is_ok(), "runtime errors on {:?}: {:?}", engine, out.errors);
+        assert!(out.events.iter().any(|e| matches!(e, RuntimeEvent::Say(s) if s == "hi")));
+        assert_eq!(out.value, Value::SmallInt(7));
+    }
+}
+
+#[test]
+fn run_source_collects_runtime_errors() {
+    let out = run_source("!say $missing\n", Engine::Interp).expect("script should parse");
+    assert!(!out.is_ok());
+    assert!(out.errors[0].message.contains("Variable not found"));
+}
+
+#[test]
+fn run_source_reports_parse_errors() {
+    let err = run_source("$x =\n", Engine::Vm).unwrap_err();
+    assert!(matches!(err, NauxError::Parse(_)));
+    assert!(err.to_string().contains("Parse error"));
+}
@@ -16,7 +16,7 @@ fn arithmetic_precedence() {
     let src = r#"
     $out = 1 + 2 * 3
 "#;
-    assert_eq!(run(src), Value::Number(7.0));
+    assert_eq!(run(src), Value::SmallInt(7));
 }
 
 #[test]
@@ -32,7 +32,7 @@ fn unary_negation() {
     let src = r#"
     $out = -5 + 2
 "#;
-    assert_eq!(run(src), Value::Number(-3.0));
+    assert_eq!(run(src), Value::SmallInt(-3));
 }
 
 #[test]
@@ -40,5 +40,5 @@ fn builtin_len_string() {
     let src = r#"
     $out = len("naux")
 "#;
-    assert_eq!(run(src), Value::Number(4.0));
+    assert_eq!(run(src), Value::SmallInt(4));
 }
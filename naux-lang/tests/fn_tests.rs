@@ -20,7 +20,7 @@ fn simple_function_return() {
 
     $res = add(2, 3)
 "#;
-    assert_eq!(run(src, "res"), Value::Number(5.0));
+    assert_eq!(run(src, "res"), Value::SmallInt(5));
 }
 
 #[test]
@@ -40,5 +40,5 @@ fn nested_call_and_shadow() {
     $out = twice($x)
 "#;
     // twice(10) -> inc(10)=11 -> inc(11)=12
-    assert_eq!(run(src, "out"), Value::Number(12.0));
+    assert_eq!(run(src, "out"), Value::SmallInt(12));
 }
@@ -0,0 +1,85 @@
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::{format_value, NauxObj, Value};
+use naux::{run_source, Engine};
+
+/// A list `[head, <itself>]`.
+fn self_list(head: i64) -> Value {
+    let list = Value::make_list(vec![Value::SmallInt(head)]);
+    if let Value::RcObj(rc) = &list {
+        if let NauxObj::List(items) = rc.as_ref() {
+            items.borrow_mut().push(list.clone());
+        }
+    }
+    list
+}
+
+#[test]
+fn self_referential_list_formats_as_cycle() {
+    assert_eq!(format_value(&self_list(1)), "List [1, <cycle>]");
+}
+
+#[test]
+fn shared_but_acyclic_values_print_in_full() {
+    let inner = Value::make_list(vec![Value::SmallInt(2)]);
+    let outer = Value::make_list(vec![inner.clone(), inner]);
+    assert_eq!(format_value(&outer), "List [List [2], List [2]]");
+}
+
+#[test]
+fn map_keys_print_in_sorted_order() {
+    let src = "$m = {b: 2, a: 1, c: [true]}\n!say $m\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(
+            out.events.contains(&RuntimeEvent::Say("Map {a:1, b:2, c:List [true]}".into())),
+            "{:?}: {:?}",
+            engine,
+            out.events
+        );
+    }
+}
+
+#[test]
+fn map_containing_itself_can_be_said() {
+    let src = "$m = {x: 1}\n$_ = map_set($m, \"me\", $m)\n!say $m\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(
+            out.events.contains(&RuntimeEvent::Say("Map {me:<cycle>, x:1}".into())),
+            "{:?}: {:?}",
+            engine,
+            out.events
+        );
+    }
+}
+
+#[test]
+fn equality_terminates_on_self_referential_lists() {
+    assert_eq!(self_list(1), self_list(1));
+    assert_ne!(self_list(1), self_list(2));
+    let a = self_list(1);
+    assert_eq!(a, a.clone());
+}
+
+#[test]
+fn equality_terminates_on_cyclic_maps_in_both_engines() {
+    let src = "$a = {x: 1}\n$_ = map_set($a, \"me\", $a)\n$b = {x: 1}\n$_ = map_set($b, \"me\", $b)\n$c = {x: 2}\n$_ = map_set($c, \"me\", $c)\n^ [assert_equal($a, $b, \"\"), assert_equal($a, $c, \"\")]\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::make_list(vec![Value::Bool(true), Value::Bool(false)]), "{:?}", engine);
+    }
+}
+
+#[test]
+fn to_text_formats_like_say_and_survives_cycles() {
+    let src = "$m = {x: 1}\n$_ = map_set($m, \"me\", $m)\n^ [to_text($m), to_text(5), to_text(\"a\"), to_text([1, \"b\"])]\n";
+    let expected = Value::make_list(
+        ["Map {me:<cycle>, x:1}", "5", "a", "List [1, b]"].iter().map(|s| Value::make_text(s.to_string())).collect(),
+    );
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, expected, "{:?}", engine);
+    }
+}
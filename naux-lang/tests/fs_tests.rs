@@ -0,0 +1,46 @@
+use naux::run_source;
+use naux::runtime::value::Value;
+use naux::Engine;
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir().join(format!("naux_fs_{}_{}", std::process::id(), name)).to_string_lossy().replace('\\', "/")
+}
+
+#[test]
+fn read_file_inside_unsafe_works() {
+    let path = temp_path("read.txt");
+    std::fs::write(&path, "xin chao").unwrap();
+    let src = format!("$t = \"\"\n~ unsafe\n    $t = read_file(\"{}\")\n~ end\n^ $t\n", path);
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(&src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::make_text("xin chao"));
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn write_file_round_trips() {
+    let path = temp_path("write.txt");
+    let src = format!("~ unsafe\n    $_ok = write_file(\"{}\", \"abc\")\n~ end\n", path);
+    let out = run_source(&src, Engine::Vm).unwrap();
+    assert!(out.errors.is_empty(), "{:?}", out.errors);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "abc");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn file_io_outside_unsafe_errors() {
+    let src = format!("^ read_file(\"{}\")\n", temp_path("never.txt"));
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(&src, engine).unwrap();
+        assert!(out.errors.iter().any(|e| e.message.contains("file I/O requires unsafe block")), "{:?}", engine);
+    }
+}
+
+#[test]
+fn missing_file_is_runtime_error() {
+    let src = format!("~ unsafe\n    $_t = read_file(\"{}\")\n~ end\n", temp_path("missing.txt"));
+    let out = run_source(&src, Engine::Interp).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("cannot read")));
+}
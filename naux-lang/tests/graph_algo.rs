@@ -22,11 +22,11 @@ fn bfs_small_graph() {
 "#;
     assert_eq!(
         run_and_get(src, "order"),
-        Value::List(vec![
-            Value::Text("A".into()),
-            Value::Text("B".into()),
-            Value::Text("C".into()),
-            Value::Text("D".into()),
+        Value::make_list(vec![
+            Value::make_text("A"),
+            Value::make_text("B"),
+            Value::make_text("C"),
+            Value::make_text("D"),
         ])
     );
 }
@@ -45,16 +45,538 @@ fn dijkstra_path() {
     let expected: std::collections::HashMap<String, Value> = [
         (
             "path".into(),
-            Value::List(vec![
-                Value::Text("S".into()),
-                Value::Text("A".into()),
-                Value::Text("B".into()),
-                Value::Text("T".into()),
+            Value::make_list(vec![
+                Value::make_text("S"),
+                Value::make_text("A"),
+                Value::make_text("B"),
+                Value::make_text("T"),
             ]),
         ),
-        ("distance".into(), Value::Number(4.0)),
+        ("distance".into(), Value::Float(4.0)),
     ]
     .into_iter()
     .collect();
-    assert_eq!(run_and_get(src, "path"), Value::Map(expected));
+    assert_eq!(run_and_get(src, "path"), Value::make_map(expected));
+}
+
+fn has_cycle(directed: bool, edges: &[(&str, &str)]) -> Value {
+    let mut src = format!("$g = graph_new({})\n", directed);
+    for (u, v) in edges {
+        src.push_str(&format!("$_ = graph_add_edge($g, \"{}\", \"{}\")\n", u, v));
+    }
+    src.push_str("$out = graph_has_cycle($g)\n");
+    run_and_get(&src, "out")
+}
+
+#[test]
+fn has_cycle_directed() {
+    assert_eq!(has_cycle(true, &[("A", "B"), ("A", "C"), ("B", "D"), ("C", "D")]), Value::Bool(false));
+    assert_eq!(has_cycle(true, &[("A", "B"), ("B", "C"), ("C", "A")]), Value::Bool(true));
+}
+
+#[test]
+fn has_cycle_undirected() {
+    assert_eq!(has_cycle(false, &[("A", "B"), ("A", "C"), ("C", "D")]), Value::Bool(false));
+    assert_eq!(has_cycle(false, &[("A", "B"), ("B", "C"), ("C", "A")]), Value::Bool(true));
+}
+
+#[test]
+fn transpose_flips_directed_edges() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 3)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $t = graph_transpose($g)
+    $out = [graph_neighbors($t, "A"), graph_neighbors($t, "B"), graph_neighbors($t, "C")]
+"#;
+    let a = || Value::make_list(vec![Value::make_text("A")]);
+    assert_eq!(run_and_get(src, "out"), Value::make_list(vec![Value::make_list(vec![]), a(), a()]));
+}
+
+#[test]
+fn transpose_twice_is_equivalent() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 2)
+    $_ = graph_add_edge($g, "B", "C", 5)
+    $tt = graph_reverse(graph_transpose($g))
+    $out = [graph_neighbors($tt, "A"), graph_neighbors($tt, "B"), graph_neighbors($tt, "C"), graph_dijkstra($tt, "A", "C")]
+"#;
+    let out = run_and_get(src, "out");
+    let Value::RcObj(rc) = &out else { panic!() };
+    let naux::runtime::value::NauxObj::List(items) = rc.as_ref() else { panic!() };
+    let items = items.borrow();
+    assert_eq!(items[0], Value::make_list(vec![Value::make_text("B")]));
+    assert_eq!(items[1], Value::make_list(vec![Value::make_text("C")]));
+    assert_eq!(items[2], Value::make_list(vec![]));
+    let Value::RcObj(m) = &items[3] else { panic!() };
+    let naux::runtime::value::NauxObj::Map(m) = m.as_ref() else { panic!() };
+    assert_eq!(m.borrow()["distance"], Value::Float(7.0));
+}
+
+fn texts(items: &[&str]) -> Value {
+    Value::make_list(items.iter().map(|s| Value::make_text(*s)).collect())
+}
+
+#[test]
+fn components_two_groups() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "D", "C", 1)
+    $_ = graph_add_edge($g, "B", "A", 1)
+    $_ = graph_add_edge($g, "E", "D", 1)
+    $out = [graph_components($g), graph_connected($g)]
+"#;
+    let comps = Value::make_list(vec![texts(&["A", "B"]), texts(&["C", "D", "E"])]);
+    assert_eq!(run_and_get(src, "out"), Value::make_list(vec![comps, Value::Bool(false)]));
+}
+
+#[test]
+fn components_single_group() {
+    let src = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "B", "C", 1)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $out = [graph_components($g), graph_connected($g)]
+"#;
+    let comps = Value::make_list(vec![texts(&["A", "B", "C"])]);
+    assert_eq!(run_and_get(src, "out"), Value::make_list(vec![comps, Value::Bool(true)]));
+}
+
+#[test]
+fn shortest_edges_sum_to_distance() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 4)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $_ = graph_add_edge($g, "C", "B", 2)
+    $_ = graph_add_edge($g, "B", "D", 5)
+    $edges = graph_shortest_edges($g, "A", "D")
+    $res = graph_dijkstra($g, "A", "D")
+    $none = graph_shortest_edges($g, "D", "A")
+"#;
+    let as_list = |v: &Value| match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            naux::runtime::value::NauxObj::List(items) => items.borrow().clone(),
+            _ => panic!("expected list"),
+        },
+        _ => panic!("expected list"),
+    };
+    let as_map = |v: &Value| match v {
+        Value::RcObj(rc) => match rc.as_ref() {
+            naux::runtime::value::NauxObj::Map(m) => m.borrow().clone(),
+            _ => panic!("expected map"),
+        },
+        _ => panic!("expected map"),
+    };
+    let edges = as_list(&run_and_get(src, "edges"));
+    let hops: Vec<(Value, Value)> = edges.iter().map(|e| {
+        let m = as_map(e);
+        (m["u"].clone(), m["v"].clone())
+    }).collect();
+    assert_eq!(
+        hops,
+        vec![
+            (Value::make_text("A"), Value::make_text("C")),
+            (Value::make_text("C"), Value::make_text("B")),
+            (Value::make_text("B"), Value::make_text("D")),
+        ]
+    );
+    let total: f64 = edges.iter().map(|e| match as_map(e)["w"] {
+        Value::Float(w) => w,
+        ref other => panic!("unexpected weight {:?}", other),
+    }).sum();
+    assert_eq!(as_map(&run_and_get(src, "res"))["distance"], Value::Float(total));
+    assert_eq!(run_and_get(src, "none"), Value::Null);
+}
+
+#[test]
+fn eulerian_path_bridges_style() {
+    // two odd-degree vertices (C, D): the path must start at one and end at the other
+    let src = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $_ = graph_add_edge($g, "B", "C", 1)
+    $_ = graph_add_edge($g, "B", "D", 1)
+    $_ = graph_add_edge($g, "C", "D", 1)
+    $_ = graph_add_edge($g, "A", "D", 1)
+    $path = graph_eulerian_path($g)
+"#;
+    let Value::RcObj(rc) = run_and_get(src, "path") else { panic!("expected a path") };
+    let naux::runtime::value::NauxObj::List(items) = rc.as_ref() else { panic!("expected a list") };
+    let path: Vec<String> = items.borrow().iter().map(|v| v.as_text().unwrap()).collect();
+    assert_eq!(path.len(), 8);
+    let mut used: Vec<(String, String)> = path
+        .windows(2)
+        .map(|w| if w[0] < w[1] { (w[0].clone(), w[1].clone()) } else { (w[1].clone(), w[0].clone()) })
+        .collect();
+    used.sort();
+    let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+    assert_eq!(
+        used,
+        vec![pair("A", "B"), pair("A", "B"), pair("A", "C"), pair("A", "D"), pair("B", "C"), pair("B", "D"), pair("C", "D")]
+    );
+}
+
+#[test]
+fn eulerian_path_none_with_many_odd_vertices() {
+    // Königsberg: all four land masses have odd degree
+    let src = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $_ = graph_add_edge($g, "A", "D", 1)
+    $_ = graph_add_edge($g, "B", "D", 1)
+    $_ = graph_add_edge($g, "C", "D", 1)
+    $path = graph_eulerian_path($g)
+"#;
+    assert_eq!(run_and_get(src, "path"), Value::Null);
+}
+
+#[test]
+fn max_flow_small_network() {
+    // classic CLRS network, max flow 23
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "s", "v1", 16)
+    $_ = graph_add_edge($g, "s", "v2", 13)
+    $_ = graph_add_edge($g, "v2", "v1", 4)
+    $_ = graph_add_edge($g, "v1", "v3", 12)
+    $_ = graph_add_edge($g, "v3", "v2", 9)
+    $_ = graph_add_edge($g, "v2", "v4", 14)
+    $_ = graph_add_edge($g, "v4", "v3", 7)
+    $_ = graph_add_edge($g, "v3", "t", 20)
+    $_ = graph_add_edge($g, "v4", "t", 4)
+    $_ = graph_add_edge($g, "x", "y", 5)
+    $full = graph_max_flow($g, "s", "t")
+    $none = graph_max_flow($g, "s", "y")
+    $out = [$full.flow, $none.flow]
+"#;
+    assert_eq!(run_and_get(src, "out"), Value::make_list(vec![Value::Float(23.0), Value::Float(0.0)]));
+}
+
+#[test]
+fn max_flow_residual_and_min_cut_on_clrs_network() {
+    let network = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "s", "v1", 16)
+    $_ = graph_add_edge($g, "s", "v2", 13)
+    $_ = graph_add_edge($g, "v2", "v1", 4)
+    $_ = graph_add_edge($g, "v1", "v3", 12)
+    $_ = graph_add_edge($g, "v3", "v2", 9)
+    $_ = graph_add_edge($g, "v2", "v4", 14)
+    $_ = graph_add_edge($g, "v4", "v3", 7)
+    $_ = graph_add_edge($g, "v3", "t", 20)
+    $_ = graph_add_edge($g, "v4", "t", 4)
+"#;
+    let src = format!(
+        "{}$res = graph_max_flow($g, \"s\", \"t\")\n$residual = $res.residual\n$again = graph_max_flow($residual, \"s\", \"t\")\n$left = $again.flow\n$cut = graph_min_cut($g, \"s\", \"t\")\n$out = [$cut.cut_value, $cut.source_side, $cut.sink_side]\n",
+        network
+    );
+    // the residual network admits no further augmenting path
+    assert_eq!(run_and_get(&src, "left"), Value::Float(0.0));
+    assert_eq!(
+        run_and_get(&src, "out"),
+        Value::make_list(vec![Value::Float(23.0), texts(&["s", "v1", "v2", "v4"]), texts(&["t", "v3"])])
+    );
+}
+
+#[test]
+fn max_flow_with_parallel_paths_and_bottlenecks() {
+    // Ford-Fulkerson's worst case for naive DFS: two wide paths joined by a unit edge.
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "s", "a", 1000)
+    $_ = graph_add_edge($g, "s", "b", 1000)
+    $_ = graph_add_edge($g, "a", "b", 1)
+    $_ = graph_add_edge($g, "a", "t", 1000)
+    $_ = graph_add_edge($g, "b", "t", 1000)
+    $_ = graph_add_edge($g, "b", "t", 0.5)
+    $res = graph_max_flow($g, "s", "t")
+    $flow = $res.flow
+    $cut = graph_min_cut($g, "s", "t")
+    $sink_side = $cut.sink_side
+"#;
+    assert_eq!(run_and_get(src, "flow"), Value::Float(2000.0));
+    assert_eq!(run_and_get(src, "sink_side"), texts(&["a", "b", "t"]));
+}
+
+#[test]
+fn max_flow_requires_directed_graph() {
+    let src = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "s", "t", 3)
+    $_ = graph_min_cut($g, "s", "t")
+"#;
+    let tokens = lex(src).unwrap();
+    let ast = Parser::from_tokens(&tokens).unwrap();
+    let (_env, _events, errs) = eval_script(&ast);
+    assert_eq!(errs[0].message, "graph_min_cut: graph must be directed");
+}
+
+#[test]
+fn edge_weight_lookup() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 4)
+    $_ = graph_add_edge($g, "A", "B", 2.5)
+    $_ = graph_add_edge($g, "B", "C", 7)
+    $u = graph_new(false)
+    $_ = graph_add_edge($u, "X", "Y", 3)
+    $out = [graph_edge_weight($g, "A", "B"), graph_edge_weight($g, "B", "C"), graph_edge_weight($g, "C", "B"), graph_weight_of($u, "Y", "X"), graph_edge_weight($g, "Z", "A")]
+"#;
+    assert_eq!(
+        run_and_get(src, "out"),
+        Value::make_list(vec![Value::Float(2.5), Value::SmallInt(7), Value::Null, Value::SmallInt(3), Value::Null])
+    );
+}
+
+#[test]
+fn remove_edge_and_node() {
+    let src = r#"
+    $d = graph_new(true)
+    $_ = graph_add_edge($d, "A", "B", 1)
+    $_ = graph_add_edge($d, "B", "A", 1)
+    $_ = graph_add_edge($d, "A", "C", 1)
+    $_ = graph_remove_edge($d, "A", "B")
+    $dir = [graph_neighbors($d, "A"), graph_neighbors($d, "B")]
+    $u = graph_new(false)
+    $_ = graph_add_edge($u, "A", "B", 1)
+    $_ = graph_add_edge($u, "B", "C", 1)
+    $_ = graph_add_edge($u, "C", "A", 1)
+    $_ = graph_remove_edge($u, "B", "A")
+    $und = [graph_neighbors($u, "A"), graph_neighbors($u, "B")]
+    $_ = graph_remove_node($u, "C")
+    $gone = [graph_neighbors($u, "A"), graph_neighbors($u, "B"), graph_neighbors($u, "C")]
+"#;
+    let texts = |xs: &[&str]| Value::make_list(xs.iter().map(|s| Value::make_text(*s)).collect());
+    assert_eq!(run_and_get(src, "dir"), Value::make_list(vec![texts(&["C"]), texts(&["A"])]));
+    assert_eq!(run_and_get(src, "und"), Value::make_list(vec![texts(&["C"]), texts(&["C"])]));
+    assert_eq!(run_and_get(src, "gone"), Value::make_list(vec![texts(&[]), texts(&[]), texts(&[])]));
+}
+
+#[test]
+fn to_undirected_symmetrizes() {
+    let src = r#"
+    $d = graph_new(true)
+    $_ = graph_add_edge($d, "A", "B", 5)
+    $_ = graph_add_edge($d, "B", "A", 2)
+    $_ = graph_add_edge($d, "B", "C", 1)
+    $u = graph_to_undirected($d)
+    $flags = [graph_is_directed($d), graph_is_directed($u)]
+    $adj = [graph_neighbors($u, "A"), graph_neighbors($u, "B"), graph_neighbors($u, "C")]
+    $w = [graph_edge_weight($u, "A", "B"), graph_edge_weight($u, "B", "A"), graph_edge_weight($u, "C", "B")]
+"#;
+    let texts = |xs: &[&str]| Value::make_list(xs.iter().map(|s| Value::make_text(*s)).collect());
+    assert_eq!(run_and_get(src, "flags"), Value::make_list(vec![Value::Bool(true), Value::Bool(false)]));
+    assert_eq!(
+        run_and_get(src, "adj"),
+        Value::make_list(vec![texts(&["B"]), texts(&["A", "C"]), texts(&["B"])])
+    );
+    assert_eq!(
+        run_and_get(src, "w"),
+        Value::make_list(vec![Value::SmallInt(2), Value::SmallInt(2), Value::SmallInt(1)])
+    );
+}
+
+#[test]
+fn topo_count_dag_and_cycle() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $_ = graph_add_edge($g, "B", "D", 1)
+    $_ = graph_add_edge($g, "C", "D", 1)
+    $_ = graph_add_edge($g, "E", "E2", 1)
+    $n = graph_topo_count($g)
+"#;
+    // the diamond has 2 orders; E before E2 interleaves into C(6,2) = 15 slot pairs → 30
+    assert_eq!(run_and_get(src, "n"), Value::SmallInt(30));
+
+    let cyclic = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "B", "A", 1)
+    $n = graph_topo_count($g)
+"#;
+    let tokens = naux::lexer::lex(cyclic).unwrap();
+    let ast = naux::parser::parser::Parser::from_tokens(&tokens).unwrap();
+    let (_env, _events, errs) = naux::runtime::eval_script(&ast);
+    assert!(errs.iter().any(|e| e.message.contains("cycle")), "{:?}", errs);
+}
+
+#[test]
+fn dfs_preorder_and_timestamps() {
+    let src = r#"
+    $g = graph_new(true)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $_ = graph_add_edge($g, "B", "D", 1)
+    $_ = graph_add_edge($g, "C", "D", 1)
+    $order = graph_dfs($g, "A")
+    $t = graph_dfs_tree($g, "A")
+    $p = $t.parent
+    $d = $t.discovery
+    $f = $t.finish
+    $parent = [$p.A, $p.D]
+    $times = [$d.A, $d.D, $f.D, $f.A]
+"#;
+    let text = |s: &str| Value::make_text(s);
+    assert_eq!(
+        run_and_get(src, "order"),
+        Value::make_list(vec![text("A"), text("B"), text("D"), text("C")])
+    );
+    assert_eq!(run_and_get(src, "parent"), Value::make_list(vec![Value::Null, text("B")]));
+    assert_eq!(
+        run_and_get(src, "times"),
+        Value::make_list(vec![Value::SmallInt(1), Value::SmallInt(3), Value::SmallInt(4), Value::SmallInt(8)])
+    );
+}
+
+#[test]
+fn dfs_paths_lists_simple_paths() {
+    let src = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "A", "B", 1)
+    $_ = graph_add_edge($g, "B", "C", 1)
+    $_ = graph_add_edge($g, "A", "C", 1)
+    $paths = graph_dfs_paths($g, "A", "C")
+    $none = graph_dfs_paths($g, "A", "Z")
+"#;
+    let path = |ns: &[&str]| Value::make_list(ns.iter().map(|n| Value::make_text(*n)).collect());
+    assert_eq!(
+        run_and_get(src, "paths"),
+        Value::make_list(vec![path(&["A", "B", "C"]), path(&["A", "C"])])
+    );
+    assert_eq!(run_and_get(src, "none"), Value::make_list(vec![]));
+}
+
+const CLRS_MST_GRAPH: &str = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "a", "b", 4)
+    $_ = graph_add_edge($g, "a", "h", 8)
+    $_ = graph_add_edge($g, "b", "c", 8)
+    $_ = graph_add_edge($g, "b", "h", 11)
+    $_ = graph_add_edge($g, "c", "d", 7)
+    $_ = graph_add_edge($g, "c", "f", 4)
+    $_ = graph_add_edge($g, "c", "i", 2)
+    $_ = graph_add_edge($g, "d", "e", 9)
+    $_ = graph_add_edge($g, "d", "f", 14)
+    $_ = graph_add_edge($g, "e", "f", 10)
+    $_ = graph_add_edge($g, "f", "g", 2)
+    $_ = graph_add_edge($g, "g", "h", 1)
+    $_ = graph_add_edge($g, "g", "i", 6)
+    $_ = graph_add_edge($g, "h", "i", 7)
+"#;
+
+#[test]
+fn kruskal_and_prim_agree_on_clrs_example() {
+    let src = format!(
+        "{}\n    $k = graph_kruskal($g)\n    $p = graph_prim($g, \"e\")\n    $kw = $k.weight\n    $pw = $p.weight\n    $kn = len($k.edges)\n    $pn = len($p.edges)\n",
+        CLRS_MST_GRAPH
+    );
+    assert_eq!(run_and_get(&src, "kw"), Value::Float(37.0));
+    assert_eq!(run_and_get(&src, "pw"), Value::Float(37.0));
+    assert_eq!(run_and_get(&src, "kn"), Value::SmallInt(8));
+    assert_eq!(run_and_get(&src, "pn"), Value::SmallInt(8));
+}
+
+#[test]
+fn mst_spans_forest_of_disconnected_graph() {
+    let src = r#"
+    $g = graph_new(false)
+    $_ = graph_add_edge($g, "A", "B", 3)
+    $_ = graph_add_edge($g, "B", "C", 1)
+    $_ = graph_add_edge($g, "A", "C", 2)
+    $_ = graph_add_edge($g, "X", "Y", 5)
+    $k = graph_kruskal($g)
+    $p = graph_prim($g)
+    $kw = $k.weight
+    $pw = $p.weight
+    $first = $p.edges[0]
+    $first_edge = [$first.from, $first.to, $first.weight]
+"#;
+    assert_eq!(run_and_get(src, "kw"), Value::Float(8.0));
+    assert_eq!(run_and_get(src, "pw"), Value::Float(8.0));
+    // Prim starts at the smallest node, so its first edge is A's cheapest one
+    assert_eq!(
+        run_and_get(src, "first_edge"),
+        Value::make_list(vec![Value::make_text("A"), Value::make_text("C"), Value::Float(2.0)])
+    );
+}
+
+#[test]
+fn mst_rejects_directed_graphs() {
+    for call in ["graph_kruskal($g)", "graph_prim($g)"] {
+        let src = format!("$g = graph_new(true)\n$_ = graph_add_edge($g, \"A\", \"B\", 1)\n$m = {}\n", call);
+        let tokens = lex(&src).unwrap();
+        let ast = Parser::from_tokens(&tokens).unwrap();
+        let (_env, _events, errs) = eval_script(&ast);
+        assert!(errs.iter().any(|e| e.message.contains("undirected")), "{}: {:?}", call, errs);
+    }
+}
+
+fn undirected(edges: &[(&str, &str)]) -> String {
+    let mut src = String::from("$g = graph_new(false)\n");
+    for (u, v) in edges {
+        src.push_str(&format!("$_ = graph_add_edge($g, \"{}\", \"{}\")\n", u, v));
+    }
+    src.push_str("$bridges = graph_bridges($g)\n$cuts = graph_articulation_points($g)\n");
+    src
+}
+
+fn edges(pairs: &[(&str, &str)]) -> Value {
+    Value::make_list(
+        pairs
+            .iter()
+            .map(|(u, v)| {
+                let mut edge = std::collections::HashMap::new();
+                edge.insert("from".to_string(), Value::make_text(*u));
+                edge.insert("to".to_string(), Value::make_text(*v));
+                Value::make_map(edge)
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn bridges_and_cut_vertices_of_a_tree() {
+    let src = undirected(&[("A", "B"), ("B", "C"), ("C", "D"), ("B", "E")]);
+    assert_eq!(run_and_get(&src, "bridges"), edges(&[("A", "B"), ("B", "C"), ("B", "E"), ("C", "D")]));
+    assert_eq!(run_and_get(&src, "cuts"), texts(&["B", "C"]));
+}
+
+#[test]
+fn single_edge_is_a_bridge_without_cut_vertices() {
+    let src = undirected(&[("Y", "X")]);
+    assert_eq!(run_and_get(&src, "bridges"), edges(&[("X", "Y")]));
+    assert_eq!(run_and_get(&src, "cuts"), texts(&[]));
+}
+
+#[test]
+fn complete_graph_has_no_bridges_or_cut_vertices() {
+    let src = undirected(&[("A", "B"), ("A", "C"), ("A", "D"), ("B", "C"), ("B", "D"), ("C", "D")]);
+    assert_eq!(run_and_get(&src, "bridges"), edges(&[]));
+    assert_eq!(run_and_get(&src, "cuts"), texts(&[]));
+}
+
+#[test]
+fn bowtie_and_parallel_edges() {
+    // two triangles sharing C, a pendant edge D-E, and a doubled edge E-F that is not a bridge
+    let src = undirected(&[("A", "B"), ("B", "C"), ("C", "A"), ("C", "D"), ("D", "G"), ("G", "C"), ("D", "E"), ("E", "F"), ("F", "E")]);
+    assert_eq!(run_and_get(&src, "bridges"), edges(&[("D", "E")]));
+    assert_eq!(run_and_get(&src, "cuts"), texts(&["C", "D", "E"]));
+}
+
+#[test]
+fn bridges_require_undirected_graph() {
+    let src = "$g = graph_new(true)\n$_ = graph_add_edge($g, \"A\", \"B\")\n$_ = graph_bridges($g)\n";
+    let tokens = lex(src).unwrap();
+    let ast = Parser::from_tokens(&tokens).unwrap();
+    let (_env, _events, errs) = eval_script(&ast);
+    assert_eq!(errs[0].message, "graph_bridges: graph must be undirected");
 }
@@ -32,5 +32,5 @@ $res = add(2, 5)
     let ast = Parser::from_tokens(&tokens).unwrap();
     let (env, _events, errs) = eval_script(&ast);
     assert!(errs.is_empty(), "runtime errors: {:?}", errs);
-    assert_eq!(env.get("res"), Some(Value::Number(7.0)));
+    assert_eq!(env.get("res"), Some(Value::SmallInt(7)));
 }
@@ -0,0 +1,68 @@
+use naux::cli::format::format_stmts;
+use naux::lexer::lex;
+use naux::parser::parser::Parser;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::value_on_both;
+
+fn text_of(src: &str) -> String {
+    let value = value_on_both(src);
+    value.as_text().unwrap_or_else(|| panic!("expected text, got {:?}", value))
+}
+
+#[test]
+fn splices_variables_and_expressions() {
+    assert_eq!(text_of("$name = \"Naux\"\n^ \"Hello {$name}!\"\n"), "Hello Naux!");
+    assert_eq!(text_of("$a = 2\n^ \"{$a} + {$a} = {$a + $a}\"\n"), "2 + 2 = 4");
+    assert_eq!(text_of("^ \"{len(\"abc\")} chars\"\n"), "3 chars");
+}
+
+#[test]
+fn containers_use_the_say_formatting() {
+    assert_eq!(text_of("^ \"xs={[1, 2]}\"\n"), "xs=List [1, 2]");
+    assert_eq!(text_of("^ \"{ {key: 1} }\"\n"), "Map {key:1}");
+}
+
+#[test]
+fn empty_interpolation_and_escaped_braces() {
+    assert_eq!(text_of("^ \"prefix{}\"\n"), "prefix");
+    assert_eq!(text_of("^ \"\\{literal\\}\"\n"), "{literal}");
+}
+
+#[test]
+fn say_accepts_interpolated_text() {
+    let out = run_source("$n = 3\n!say \"n is {$n}\"\n", Engine::Vm).unwrap();
+    assert!(out.events.iter().any(|e| format!("{:?}", e).contains("n is 3")), "{:?}", out.events);
+}
+
+#[test]
+fn unterminated_interpolation_is_a_lex_error() {
+    let err = lex("^ \"oops {$x\"\n").unwrap_err();
+    assert!(err.message.contains("Unterminated interpolation"), "{}", err.message);
+}
+
+#[test]
+fn parse_errors_point_inside_the_string() {
+    let tokens = lex("^ \"ab {1 +}\"\n").unwrap();
+    let err = Parser::from_tokens(&tokens).unwrap_err();
+    assert_eq!(err.span.line, 1);
+    assert_eq!(err.span.column, 11);
+}
+
+#[test]
+fn formatter_round_trips_interpolation() {
+    let src = "$s = \"a {$x + 1} \\{b\\}\"\n";
+    let ast = Parser::from_tokens(&lex(src).unwrap()).unwrap();
+    let formatted = format_stmts(&ast);
+    assert!(formatted.contains("\"a {$x + 1} \\{b\\}\""), "{}", formatted);
+    let again = Parser::from_tokens(&lex(&formatted).unwrap()).unwrap();
+    assert_eq!(format_stmts(&again), formatted);
+}
+
+#[test]
+fn plain_strings_stay_text_literals() {
+    let out = run_source("^ \"no braces\"\n", Engine::Interp).unwrap();
+    assert_eq!(out.value, Value::make_text("no braces".to_string()));
+}
@@ -7,7 +7,7 @@ use naux::vm::run::run_vm;
 fn vm_value(src: &str) -> Value {
     let tokens = lex(src).unwrap();
     let ast = Parser::from_tokens(&tokens).unwrap();
-    let (_events, val) = run_vm(&ast).expect("vm run");
+    let (_events, val) = run_vm(&ast, src, "<test>").expect("vm run");
     val
 }
 
@@ -0,0 +1,51 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+fn run(src: &str) -> Value {
+    let mut results = Vec::new();
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        results.push(out.value);
+    }
+    assert_eq!(results[0], results[1], "engines disagree");
+    results.remove(0)
+}
+
+fn ints(xs: &[i64]) -> Value {
+    Value::make_list(xs.iter().map(|x| Value::SmallInt(*x)).collect())
+}
+
+#[test]
+fn lcs_of_value_lists() {
+    assert_eq!(run("^ list_lcs([1, 2, 3, 4, 1], [3, 4, 1, 2, 1])\n"), ints(&[3, 4, 1]));
+}
+
+#[test]
+fn patch_round_trips_diff() {
+    let cases = [
+        ("[1, 2, 3, 4]", "[2, 4, 5]"),
+        ("[]", "[\"a\", true]"),
+        ("[1, [2], \"x\"]", "[]"),
+        ("[\"a\", \"b\", \"c\"]", "[\"a\", \"b\", \"c\"]"),
+    ];
+    for (a, b) in cases {
+        let src = format!("^ list_patch({a}, list_diff({a}, {b}))\n");
+        assert_eq!(run(&src), run(&format!("^ {b}\n")), "{a} -> {b}");
+    }
+}
+
+#[test]
+fn diff_ops_follow_lcs() {
+    let src = "$d = list_diff([1, 2, 3], [1, 3, 4])\n^ [$d[0][\"op\"], $d[1][\"op\"], $d[2][\"op\"], $d[3][\"op\"]]\n";
+    let expected = Value::make_list(
+        ["keep", "del", "keep", "add"].iter().map(|s| Value::make_text(*s)).collect(),
+    );
+    assert_eq!(run(src), expected);
+}
+
+#[test]
+fn patch_rejects_mismatched_diff() {
+    let out = run_source("^ list_patch([9], list_diff([1], [2]))\n", Engine::Interp).unwrap();
+    assert!(!out.errors.is_empty());
+}
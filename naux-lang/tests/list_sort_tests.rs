@@ -0,0 +1,61 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::value_on_both;
+
+#[test]
+fn mixed_list_groups_by_type() {
+    let expected = Value::make_list(vec![
+        Value::Bool(true),
+        Value::SmallInt(1),
+        Value::SmallInt(3),
+        Value::make_text("a"),
+        Value::make_text("b"),
+    ]);
+    assert_eq!(value_on_both("^ list_sort([3, \"a\", true, 1, \"b\"])\n"), expected);
+}
+
+#[test]
+fn containers_sort_after_scalars_and_element_wise() {
+    let src = "^ list_sort([[2], \"z\", [1, 5], 0, [1], false])\n";
+    let expected = Value::make_list(vec![
+        Value::Bool(false),
+        Value::SmallInt(0),
+        Value::make_text("z"),
+        Value::make_list(vec![Value::SmallInt(1)]),
+        Value::make_list(vec![Value::SmallInt(1), Value::SmallInt(5)]),
+        Value::make_list(vec![Value::SmallInt(2)]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn numbers_sort_numerically_across_int_and_float() {
+    let expected = Value::make_list(vec![Value::Float(-0.5), Value::SmallInt(2), Value::Float(2.5), Value::SmallInt(10)]);
+    assert_eq!(value_on_both("^ list_sort([10, 2.5, -0.5, 2])\n"), expected);
+}
+
+#[test]
+fn sets_use_the_same_order() {
+    let src = "$s = set_new()\n$s = set_add($s, \"b\")\n$s = set_add($s, 2)\n$s = set_add($s, true)\n^ $s\n";
+    let out = run_source(src, Engine::Vm).unwrap();
+    let Value::RcObj(rc) = out.value else { panic!("expected set") };
+    let naux::runtime::value::NauxObj::Set(items) = rc.as_ref() else { panic!("expected set") };
+    let items: Vec<Value> = items.borrow().iter().cloned().collect();
+    assert_eq!(items, vec![Value::Bool(true), Value::SmallInt(2), Value::make_text("b")]);
+}
+
+#[test]
+fn nan_sorts_after_every_other_number() {
+    // enough items that std's sort checks the comparator for consistency
+    let mut items: Vec<Value> = (0..40).map(|i| if i % 7 == 3 { Value::Float(f64::NAN) } else { Value::SmallInt((i * 17) % 23) }).collect();
+    items.push(Value::make_text("a"));
+    items.sort();
+    let numbers: Vec<f64> = items[..40].iter().map(|v| v.as_f64().unwrap()).collect();
+    let first_nan = numbers.iter().position(|x| x.is_nan()).unwrap();
+    assert!(numbers[..first_nan].windows(2).all(|w| w[0] <= w[1]));
+    assert!(numbers[first_nan..].iter().all(|x| x.is_nan()));
+    assert_eq!(first_nan, 34);
+    assert_eq!(items[40], Value::make_text("a"));
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::{error_of, ints, value_on_both};
+
+const HELPERS: &str = "~ fn double($x)\n    ^ $x * 2\n~ end\n~ fn is_even($x)\n    ^ $x % 2 == 0\n~ end\n~ fn add($a, $b)\n    ^ $a + $b\n~ end\n";
+
+#[test]
+fn push_pop_shift_and_unshift_return_new_lists() {
+    let src = "$xs = [1, 2, 3]\n$p = list_pop($xs)\n$s = list_shift($xs)\n^ [list_push($xs, 4), $p, $s, list_unshift($xs, 0), $xs]\n";
+    let expected = Value::make_list(vec![
+        ints(&[1, 2, 3, 4]),
+        Value::make_list(vec![Value::SmallInt(3), ints(&[1, 2])]),
+        Value::make_list(vec![Value::SmallInt(1), ints(&[2, 3])]),
+        ints(&[0, 1, 2, 3]),
+        ints(&[1, 2, 3]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn slice_concat_reverse_flatten_and_unique() {
+    let src = "^ [list_slice([1, 2, 3, 4], 1, 3), list_slice([1, 2], 1), list_slice([1, 2], 5, 9), list_concat([1], [2, 3]), list_reverse([1, 2, 3]), list_flatten([1, [2, [3]]]), list_flatten([1, [2, [3]]], 2), list_unique([1, 2, 1, 3, 2])]\n";
+    let expected = Value::make_list(vec![
+        ints(&[2, 3]),
+        ints(&[2]),
+        ints(&[]),
+        ints(&[1, 2, 3]),
+        ints(&[3, 2, 1]),
+        Value::make_list(vec![Value::SmallInt(1), Value::SmallInt(2), ints(&[3])]),
+        ints(&[1, 2, 3]),
+        ints(&[1, 2, 3]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn zip_stops_at_the_shorter_list() {
+    let pair = |a: i64, b: &str| {
+        let mut m = HashMap::new();
+        m.insert("a".to_string(), Value::SmallInt(a));
+        m.insert("b".to_string(), Value::make_text(b));
+        Value::make_map(m)
+    };
+    let expected = Value::make_list(vec![pair(1, "x"), pair(2, "y")]);
+    assert_eq!(value_on_both("^ list_zip([1, 2, 3], [\"x\", \"y\"])\n"), expected);
+}
+
+#[test]
+fn higher_order_functions_take_a_function_by_name() {
+    let src = format!(
+        "{}^ [list_map([1, 2, 3], double), list_filter([1, 2, 3, 4], is_even), list_reduce([1, 2, 3], add, 10), list_find([1, 3, 4, 6], is_even), list_find([1, 3], is_even), list_find_index([1, 3, 4], is_even), list_find_index([1], is_even), list_every([2, 4], is_even), list_some([1, 3], is_even)]\n",
+        HELPERS
+    );
+    let expected = Value::make_list(vec![
+        ints(&[2, 4, 6]),
+        ints(&[2, 4]),
+        Value::SmallInt(16),
+        Value::SmallInt(4),
+        Value::Null,
+        Value::SmallInt(2),
+        Value::SmallInt(-1),
+        Value::Bool(true),
+        Value::Bool(false),
+    ]);
+    assert_eq!(value_on_both(&src), expected);
+}
+
+#[test]
+fn flat_map_and_stable_sort_by() {
+    let src = "~ fn twice($x)\n    ^ [$x, $x]\n~ end\n~ fn key($p)\n    ^ $p[0]\n~ end\n^ [list_flat_map([1, 2], twice), list_sort_by([[2, 1], [1, 2], [2, 3], [1, 4]], key)]\n";
+    let pairs = Value::make_list(vec![ints(&[1, 2]), ints(&[1, 4]), ints(&[2, 1]), ints(&[2, 3])]);
+    assert_eq!(value_on_both(src), Value::make_list(vec![ints(&[1, 1, 2, 2]), pairs]));
+}
+
+#[test]
+fn builtins_and_text_names_work_as_callbacks() {
+    let src = "^ [list_map([\"a\", \"b\"], str_upper), list_map([\"ab\"], \"str_len\")]\n";
+    let expected = Value::make_list(vec![
+        Value::make_list(vec![Value::make_text("A"), Value::make_text("B")]),
+        ints(&[2]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn closures_are_callbacks_in_the_interpreter() {
+    let src = "~ fn scaler($k)\n    ~ fn scale($x)\n        ^ $x * $k\n    ~ end\n    ^ scale\n~ end\n$triple = scaler(3)\n^ list_map([1, 2], $triple)\n";
+    let out = run_source(src, Engine::Interp).unwrap();
+    assert!(out.errors.is_empty(), "{:?}", out.errors);
+    assert_eq!(out.value, ints(&[3, 6]));
+}
+
+#[test]
+fn callback_errors_propagate() {
+    let src = "~ fn boom($x)\n    $_ = throw(\"bad item\")\n~ end\n^ list_map([1], boom)\n";
+    assert!(error_of(src).contains("bad item"));
+    let vm = run_source(src, Engine::Vm);
+    let failed = match vm {
+        Ok(out) => !out.errors.is_empty(),
+        Err(_) => true,
+    };
+    assert!(failed);
+    assert!(error_of("^ list_map(1, str_upper)\n").contains("list_map: first arg must be list"));
+    assert!(error_of("^ list_reduce([1], str_upper)\n").contains("list_reduce(list, fn, init)"));
+    assert!(error_of("^ list_pop([])\n").contains("list_pop: list is empty"));
+}
+
+#[test]
+fn sort_by_key_is_stable_for_equal_keys() {
+    let src = "$people = [{name: \"ann\", age: 30}, {name: \"bob\", age: 25}, {name: \"cat\", age: 30}, {name: \"dan\", age: 25}, {name: \"eve\", age: 20}]\n$sorted = list_sort_by_key($people, \"age\")\n$by_name = list_sort_by_key($people, \"name\")\n^ [[$p.name for $p in $sorted], [$q.age for $q in $by_name]]\n";
+    let names = ["eve", "bob", "dan", "ann", "cat"].iter().map(|n| Value::make_text(*n)).collect();
+    let expected = Value::make_list(vec![Value::make_list(names), ints(&[30, 25, 30, 25, 20])]);
+    assert_eq!(value_on_both(src), expected);
+    assert!(error_of("^ list_sort_by_key([{a: 1}, 2], \"a\")\n").contains("item 1 is not a map with field 'a'"));
+}
+
+#[test]
+fn callback_held_in_a_variable() {
+    let src = format!("{HELPERS}$g = \"double\"\n$f = \"len\"\n^ [list_map([1, 2], $g), list_map([[1], [2, 3]], $f), list_filter([1, 2, 3, 4], is_even)]\n");
+    let expected = Value::make_list(vec![ints(&[2, 4]), ints(&[1, 2]), ints(&[2, 4])]);
+    assert_eq!(value_on_both(&src), expected);
+}
+
+#[test]
+fn callback_variable_inside_a_function() {
+    let src = format!("{HELPERS}~ fn apply($xs, $cb)\n    ^ list_map($xs, $cb)\n~ end\n^ apply([3, 4], \"double\")\n");
+    assert_eq!(value_on_both(&src), ints(&[6, 8]));
+}
@@ -0,0 +1,94 @@
+use std::io::Cursor;
+
+use naux::lsp::analysis::{builtin_doc, Analysis, CompletionKind};
+use naux::lsp::{read_message, serve};
+use serde_json::{json, Value};
+
+const DOC: &str = "~ fn add($a, $b)\n    ^ $a + $b\n~ end\n$x = add(1, 2)\n!say $x - \"t\"\n";
+
+fn frame(msgs: &[Value]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for m in msgs {
+        let body = m.to_string();
+        out.extend(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes());
+    }
+    out
+}
+
+fn replies(input: Vec<u8>) -> Vec<Value> {
+    let mut out = Vec::new();
+    serve(Cursor::new(input), &mut out).unwrap();
+    let mut cursor = Cursor::new(out);
+    let mut msgs = Vec::new();
+    while let Some(m) = read_message(&mut cursor).unwrap() {
+        msgs.push(m);
+    }
+    msgs
+}
+
+fn position(id: u64, method: &str, line: u64, character: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0", "id": id, "method": method,
+        "params": { "textDocument": { "uri": "file:///main.nx" }, "position": { "line": line, "character": character } }
+    })
+}
+
+#[test]
+fn session_publishes_diagnostics_and_answers_requests() {
+    let input = frame(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen",
+                "params": { "textDocument": { "uri": "file:///main.nx", "languageId": "naux", "version": 1, "text": DOC } } }),
+        position(2, "textDocument/definition", 3, 6),
+        position(3, "textDocument/hover", 3, 6),
+        position(4, "textDocument/completion", 0, 0),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didChange",
+                "params": { "textDocument": { "uri": "file:///main.nx", "version": 2 }, "contentChanges": [{ "text": "$y = (\n" }] } }),
+        json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]);
+    let msgs = replies(input);
+    assert_eq!(msgs.len(), 7, "{:#?}", msgs);
+    assert_eq!(msgs[0]["result"]["capabilities"]["definitionProvider"], json!(true));
+
+    let diags = &msgs[1]["params"]["diagnostics"];
+    assert_eq!(msgs[1]["method"], "textDocument/publishDiagnostics");
+    assert!(diags.as_array().unwrap().iter().any(|d| d["message"] == "Type error: `-` needs numbers, got Text"
+        && d["severity"] == 1
+        && d["range"]["start"]["line"] == 4));
+
+    assert_eq!(msgs[2]["result"]["range"]["start"]["line"], 0);
+    assert_eq!(msgs[2]["result"]["uri"], "file:///main.nx");
+    assert!(msgs[3]["result"]["contents"]["value"].as_str().unwrap().contains("~ fn add($a, $b)"));
+    let labels: Vec<&str> = msgs[4]["result"].as_array().unwrap().iter().map(|i| i["label"].as_str().unwrap()).collect();
+    assert!(labels.contains(&"add") && labels.contains(&"gcd") && labels.contains(&"while") && labels.contains(&"$x"));
+
+    let parse_diags = msgs[5]["params"]["diagnostics"].as_array().unwrap();
+    assert!(parse_diags[0]["message"].as_str().unwrap().starts_with("Parse error"));
+    assert_eq!(msgs[6]["id"], 5);
+}
+
+#[test]
+fn unknown_requests_get_method_not_found() {
+    let msgs = replies(frame(&[json!({ "jsonrpc": "2.0", "id": 9, "method": "workspace/symbol", "params": {} })]));
+    assert_eq!(msgs[0]["error"]["code"], -32601);
+}
+
+#[test]
+fn variables_resolve_to_their_first_binding() {
+    let analysis = Analysis::new(DOC);
+    let def = analysis.definition(5, 7).expect("definition of $x");
+    assert_eq!((def.line, def.column), (4, 1));
+    assert!(analysis.hover(5, 7).unwrap().contains("$x"));
+    assert!(analysis.definition(1, 1).is_none());
+}
+
+#[test]
+fn builtin_hover_uses_the_spec_entry() {
+    let analysis = Analysis::new("$g = gcd(4, 6)\n!say $g\n");
+    let text = analysis.hover(1, 7).unwrap();
+    assert!(text.contains("builtin `gcd`"), "{}", text);
+    assert!(builtin_doc("approx_eq").contains("`approx_eq(a, b, eps) -> Bool`"));
+    assert!(analysis.completions().iter().any(|c| c.label == "gcd" && c.kind == CompletionKind::Builtin));
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use naux::cli::util::execute_ast;
+use naux::cli::DefaultEngine;
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine, RunOutput};
+
+fn run_both(src: &str) -> Vec<RunOutput> {
+    [Engine::Interp, Engine::Vm]
+        .into_iter()
+        .map(|engine| {
+            let out = run_source(src, engine).unwrap();
+            assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+            out
+        })
+        .collect()
+}
+
+fn said(out: &RunOutput) -> Vec<String> {
+    out.events
+        .iter()
+        .filter_map(|ev| match ev {
+            RuntimeEvent::Say(s) => Some(s.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn main_runs_after_top_level_statements() {
+    let src = "~ fn main()\n    !say \"main\"\n    ^ 42\n~ end\n!say \"top\"\n";
+    for out in run_both(src) {
+        assert_eq!(said(&out), vec!["top", "main"]);
+        assert_eq!(out.value, Value::SmallInt(42));
+    }
+}
+
+#[test]
+fn programs_without_main_run_top_level_only() {
+    let src = "~ fn helper()\n    !say \"never\"\n~ end\n!say \"top\"\n^ 7\n";
+    for out in run_both(src) {
+        assert_eq!(said(&out), vec!["top"]);
+        assert_eq!(out.value, Value::SmallInt(7));
+    }
+}
+
+#[test]
+fn a_top_level_return_skips_main() {
+    let src = "~ fn main()\n    !say \"main\"\n~ end\n^ 1\n";
+    for out in run_both(src) {
+        assert!(said(&out).is_empty());
+        assert_eq!(out.value, Value::SmallInt(1));
+    }
+}
+
+#[test]
+fn main_with_parameters_is_not_called() {
+    let src = "~ fn main($args)\n    !say \"main\"\n~ end\n";
+    for out in run_both(src) {
+        assert!(said(&out).is_empty());
+    }
+}
+
+#[test]
+fn build_rendering_calls_main_on_every_engine() {
+    // `naux build` renders the events of `execute_ast`, which must follow the same entry rule.
+    let src = "~ fn main()\n    !say \"main\"\n~ end\n!say \"top\"\n";
+    let ast = naux::parser::parser::Parser::from_tokens(&naux::lexer::lex(src).unwrap()).unwrap();
+    for engine in [DefaultEngine::Interp, DefaultEngine::Vm] {
+        let events = execute_ast(engine, &ast, src, Path::new("main.nx")).unwrap();
+        let out = RunOutput { events, value: Value::Null, errors: Vec::new() };
+        assert_eq!(said(&out), vec!["top", "main"], "{:?}", engine);
+    }
+}
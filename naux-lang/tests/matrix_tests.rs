@@ -0,0 +1,107 @@
+use naux::runtime::value::{NauxObj, Value};
+
+mod common;
+use common::{error_of, items, value_on_both};
+
+fn rows(rows: &[&[f64]]) -> Value {
+    Value::make_list(rows.iter().map(|r| Value::make_list(r.iter().map(|x| Value::Float(*x)).collect())).collect())
+}
+
+fn matrix(rows: &[&[f64]]) -> Value {
+    Value::make_matrix(rows.iter().map(|r| r.to_vec()).collect())
+}
+
+#[test]
+fn construct_inspect_and_set_in_place() {
+    let src = "$m = mat_new(2, 3, 1.5)\n$_ = mat_set($m, 1, 2, 7)\n^ [mat_rows($m), mat_cols($m), mat_get($m, 1, 2), mat_to_list($m), mat_identity(2)]\n";
+    let expected = Value::make_list(vec![
+        Value::SmallInt(2),
+        Value::SmallInt(3),
+        Value::Float(7.0),
+        rows(&[&[1.5, 1.5, 1.5], &[1.5, 1.5, 7.0]]),
+        matrix(&[&[1.0, 0.0], &[0.0, 1.0]]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn arithmetic_and_transpose() {
+    let src = "$a = mat_from_list([[1, 2], [3, 4]])\n$b = mat_from_list([[5, 6], [7, 8]])\n$v = mat_from_list([[1, 2, 3]])\n^ [mat_add($a, $b), mat_sub($b, $a), mat_mul($a, $b), mat_scalar_mul($a, 2), mat_transpose($v)]\n";
+    let expected = Value::make_list(vec![
+        matrix(&[&[6.0, 8.0], &[10.0, 12.0]]),
+        matrix(&[&[4.0, 4.0], &[4.0, 4.0]]),
+        matrix(&[&[19.0, 22.0], &[43.0, 50.0]]),
+        matrix(&[&[2.0, 4.0], &[6.0, 8.0]]),
+        matrix(&[&[1.0], &[2.0], &[3.0]]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn determinant_and_inverse() {
+    let src = "$a = mat_from_list([[0, 2, 1], [1, 1, 0], [3, 0, 1]])\n$s = mat_from_list([[1, 2], [2, 4]])\n$i = mat_inv(mat_from_list([[4, 7], [2, 6]]))\n^ [mat_det($a), mat_det($s), mat_inv($s), mat_to_list($i)]\n";
+    let out = items(&value_on_both(src));
+    let det = out[0].as_f64().unwrap();
+    assert!((det - (-5.0)).abs() < 1e-9, "det {}", det);
+    assert_eq!(out[1], Value::Float(0.0));
+    assert_eq!(out[2], Value::Null);
+    let expected = [[0.6, -0.7], [-0.2, 0.4]];
+    for (r, row) in items(&out[3]).iter().enumerate() {
+        for (c, cell) in items(row).iter().enumerate() {
+            let x = cell.as_f64().unwrap();
+            assert!((x - expected[r][c]).abs() < 1e-9, "inv[{}][{}] = {}", r, c, x);
+        }
+    }
+}
+
+#[test]
+fn pow_computes_fibonacci_with_and_without_modulus() {
+    let src = "$f = mat_from_list([[1, 1], [1, 0]])\n$p = mat_pow($f, 10)\n$big = mat_pow($f, 1000000000000, 1000000007)\n^ [mat_get($p, 0, 1), mat_pow($f, 0), mat_get($big, 0, 1), mat_mul_mod($f, $f, 2)]\n";
+    let expected = Value::make_list(vec![
+        Value::Float(55.0),
+        matrix(&[&[1.0, 0.0], &[0.0, 1.0]]),
+        // F(10^12) mod 1e9+7
+        Value::Float(730695249.0),
+        matrix(&[&[0.0, 1.0], &[1.0, 1.0]]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn shape_and_argument_errors() {
+    assert!(error_of("$a = mat_new(2, 3)\n^ mat_mul($a, $a)\n").contains("mat_mul: cannot multiply 2x3 by 2x3"));
+    assert!(error_of("$a = mat_new(2, 3)\n^ mat_add($a, mat_new(3, 2))\n").contains("mat_add: shapes 2x3 and 3x2 differ"));
+    assert!(error_of("^ mat_det(mat_new(2, 3))\n").contains("mat_det: matrix must be square, got 2x3"));
+    assert!(error_of("^ mat_get(mat_new(2, 2), 2, 0)\n").contains("mat_get: index (2, 0) out of bounds for 2x2 matrix"));
+    assert!(error_of("^ mat_from_list([[1, 2], [3]])\n").contains("mat_from_list: row 1 has 1 columns, expected 2"));
+    assert!(error_of("^ mat_pow(mat_identity(2), 3, 0)\n").contains("mat_pow: modulus must be a positive integer"));
+}
+
+#[test]
+fn nan_cells_keep_matrix_ordering_total() {
+    let nan = f64::NAN;
+    let mut ms: Vec<Value> = (0..30).map(|i| if i % 4 == 1 { matrix(&[&[1.0, nan]]) } else { matrix(&[&[(i % 5) as f64, 0.0]]) }).collect();
+    ms.sort();
+    let firsts: Vec<(f64, bool)> = ms
+        .iter()
+        .map(|m| {
+            let Value::RcObj(rc) = m else { panic!() };
+            let NauxObj::Matrix(cells) = rc.as_ref() else { panic!() };
+            let cells = cells.borrow();
+            (cells[0][0], cells[0][1].is_nan())
+        })
+        .collect();
+    assert!(firsts.windows(2).all(|w| w[0].0 <= w[1].0));
+    // among rows starting with 1, the NaN cell sorts after 0
+    let ones: Vec<bool> = firsts.iter().filter(|f| f.0 == 1.0).map(|f| f.1).collect();
+    assert!(ones.windows(2).all(|w| !w[0] || w[1]));
+}
+
+#[test]
+fn matrices_compare_cell_by_cell_like_numbers() {
+    let inf = f64::INFINITY;
+    assert_eq!(matrix(&[&[inf, -0.0]]), matrix(&[&[inf, 0.0]]));
+    assert_ne!(matrix(&[&[1.0, 2.0]]), matrix(&[&[1.0, 2.5]]));
+    assert_ne!(matrix(&[&[1.0, 2.0]]), matrix(&[&[1.0, 2.0, 3.0]]));
+    assert_eq!(Value::Float(inf), Value::Float(inf));
+}
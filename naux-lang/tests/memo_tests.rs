@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+const FIB: &str = r#"
+~ fn fib($n) memo
+    ~ if $n < 2
+        ^ $n
+    ~ end
+    ^ fib($n - 1) + fib($n - 2)
+~ end
+^ fib(40)
+"#;
+
+#[test]
+fn memoized_fib_is_fast_and_correct() {
+    for engine in [Engine::Interp, Engine::Vm] {
+        let start = Instant::now();
+        let out = run_source(FIB, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        assert_eq!(out.value, Value::SmallInt(102_334_155));
+        assert!(start.elapsed() < Duration::from_secs(2), "{:?} took {:?}", engine, start.elapsed());
+    }
+}
+
+#[test]
+fn cache_is_keyed_by_arguments() {
+    let src = r#"
+~ fn double($n) memo
+    !say $n
+    ^ $n * 2
+~ end
+$a = double(1)
+$b = double(1)
+$c = double(2)
+^ [$a, $b, $c]
+"#;
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+        let expected = Value::make_list(vec![Value::SmallInt(2), Value::SmallInt(2), Value::SmallInt(4)]);
+        assert_eq!(out.value, expected);
+        // the second double(1) is a cache hit, so its body (and !say) is skipped
+        let says: Vec<_> = out.events.iter().filter(|e| matches!(e, RuntimeEvent::Say(_))).collect();
+        assert_eq!(says.len(), 2, "{:?}", engine);
+    }
+}
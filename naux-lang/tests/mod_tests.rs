@@ -0,0 +1,67 @@
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+const CASES: &[(&str, &str, f64)] = &[
+    ("7", "3", 1.0),
+    ("-7", "3", -1.0),
+    ("7", "-3", 1.0),
+    ("-7", "-3", -1.0),
+    ("6", "-3", 0.0),
+    ("7.5", "2", 1.5),
+    ("-7.5", "2", -1.5),
+    ("7.5", "-2", 1.5),
+];
+
+#[test]
+fn remainder_sign_follows_dividend_in_both_engines() {
+    for (a, b, want) in CASES {
+        // through a function so the VM cannot constant-fold it
+        let runtime = format!("~ fn m($a, $b)\n    ^ $a % $b\n~ end\n^ m({a}, {b})\n");
+        let folded = format!("^ ({a}) % ({b})\n");
+        for src in [&runtime, &folded] {
+            for engine in [Engine::Interp, Engine::Vm] {
+                let out = run_source(src, engine).unwrap();
+                assert!(out.errors.is_empty(), "{:?} {a} % {b}: {:?}", engine, out.errors);
+                assert_eq!(out.value, Value::Float(*want), "{:?}: {a} % {b}", engine);
+            }
+        }
+    }
+}
+
+#[test]
+fn remainder_and_division_by_zero_are_errors_in_both_engines() {
+    for (op, message) in [("%", "Modulo by zero"), ("/", "Division by zero")] {
+        let sources = [
+            format!("^ 7 {op} 0\n"),
+            format!("~ fn m($a, $b)\n    ^ $a {op} $b\n~ end\n^ m(7, 0)\n"),
+            format!("^ 7.5 {op} 0.0\n"),
+            format!("$z = 0\n^ -1 {op} $z\n"),
+        ];
+        for src in &sources {
+            for engine in [Engine::Interp, Engine::Vm] {
+                let out = run_source(src, engine).unwrap();
+                assert!(
+                    out.errors.iter().any(|e| e.message.contains(message)),
+                    "{:?} {src:?}: {:?}",
+                    engine,
+                    out.errors
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn mod_euclid_is_never_negative() {
+    let cases = [("-7, 3", 2.0), ("7, -3", 1.0), ("-7, -3", 2.0), ("7, 3", 1.0), ("-7.5, 2", 0.5)];
+    for (args, want) in cases {
+        let src = format!("^ mod_euclid({args})\n");
+        for engine in [Engine::Interp, Engine::Vm] {
+            let out = run_source(&src, engine).unwrap();
+            assert!(out.errors.is_empty(), "{:?}: {:?}", engine, out.errors);
+            assert_eq!(out.value, Value::Float(want), "{:?}: mod_euclid({args})", engine);
+        }
+    }
+    let out = run_source("^ mod_euclid(1, 0)\n", Engine::Interp).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("mod must be non-zero")));
+}
@@ -1,3 +1,4 @@
+use naux::ast::Stmt;
 use naux::lexer::lex;
 use naux::parser::parser::Parser;
 
@@ -12,3 +13,19 @@ fn parses_fn_block() {
     let ast = Parser::from_tokens(&tokens).unwrap();
     assert_eq!(ast.len(), 1);
 }
+
+#[test]
+fn parses_memo_annotation() {
+    let ast = Parser::from_tokens(&lex("~ fn f($n) memo\n    ^ $n\n~ end\n").unwrap()).unwrap();
+    assert!(matches!(&ast[0], Stmt::FnDef { memo: true, .. }));
+    let ast = Parser::from_tokens(&lex("~ fn f($n)\n    ^ $n\n~ end\n").unwrap()).unwrap();
+    assert!(matches!(&ast[0], Stmt::FnDef { memo: false, .. }));
+}
+
+#[test]
+fn parse_error_is_std_error() {
+    let tokens = lex("$x = )\n").unwrap();
+    let err = Parser::from_tokens(&tokens).unwrap_err();
+    let boxed: Box<dyn std::error::Error> = Box::new(err);
+    assert_eq!(boxed.to_string(), "Expected expression at line 1, col 6");
+}
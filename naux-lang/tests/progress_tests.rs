@@ -0,0 +1,41 @@
+use naux::renderer::cli::{progress_bar, render_cli_to_string};
+use naux::renderer::render_html;
+use naux::runtime::events::RuntimeEvent;
+use naux::{run_source, Engine};
+
+fn progress_events(src: &str, engine: Engine) -> Vec<RuntimeEvent> {
+    let out = run_source(src, engine).expect("parse");
+    assert!(out.is_ok(), "{:?}", out.errors);
+    out.events.into_iter().filter(|e| matches!(e, RuntimeEvent::Progress(_))).collect()
+}
+
+#[test]
+fn progress_is_clamped_on_both_engines() {
+    let src = "!progress 0.5\n!progress 3\n!progress 0 - 1\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        assert_eq!(
+            progress_events(src, engine),
+            vec![RuntimeEvent::Progress(0.5), RuntimeEvent::Progress(1.0), RuntimeEvent::Progress(0.0)]
+        );
+    }
+}
+
+#[test]
+fn cli_renders_bar_in_place() {
+    assert_eq!(progress_bar(0.5), "[##########          ]  50%");
+    let events = vec![RuntimeEvent::Progress(0.25), RuntimeEvent::Progress(1.0), RuntimeEvent::Say("done".into())];
+    let text = render_cli_to_string(&events);
+    assert_eq!(text, "\r[#####               ]  25%\r[####################] 100%\n> done\n");
+}
+
+#[test]
+fn html_renders_progress_element() {
+    let html = render_html(&[RuntimeEvent::Progress(0.75)], &[]);
+    assert!(html.contains("<progress class=\"progress\" value=\"0.75\" max=\"1\"></progress>"));
+}
+
+#[test]
+fn progress_rejects_text() {
+    let out = run_source("!progress \"half\"\n", Engine::Vm).unwrap();
+    assert!(!out.is_ok());
+}
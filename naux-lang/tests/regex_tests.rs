@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use naux::runtime::value::Value;
+
+mod common;
+use common::{bools, error_of, field, items, text, texts, value_on_both};
+
+#[test]
+fn regex_match_requires_the_whole_input() {
+    let src = r#"^ [regex_match("a+b", "aaab"), regex_match("a+b", "aaabc"), regex_match("\\d\{3\}-\\d\{4\}", "555-1234"), regex_match("(cat|dog)s?", "dogs"), regex_match("[^0-9]*", "abc"), regex_match("[^0-9]*", "ab1"), regex_match("colou?r", "color"), regex_match("", "")]"#;
+    assert_eq!(value_on_both(src), bools(&[true, false, true, true, true, false, true, true]));
+}
+
+#[test]
+fn find_all_reports_char_offsets_and_groups() {
+    let src = r#"^ regex_find_all("(?P<key>\\w+)=(\\d+)", "é a=1, bb=22")"#;
+    let found = items(&value_on_both(src));
+    assert_eq!(found.len(), 2);
+    assert_eq!(field(&found[0], "start"), Value::SmallInt(2));
+    assert_eq!(field(&found[0], "end"), Value::SmallInt(5));
+    assert_eq!(field(&found[1], "text"), text("bb=22"));
+    let mut groups = HashMap::new();
+    groups.insert("1".to_string(), text("bb"));
+    groups.insert("key".to_string(), text("bb"));
+    groups.insert("2".to_string(), text("22"));
+    assert_eq!(field(&found[1], "groups"), Value::make_map(groups));
+}
+
+#[test]
+fn unmatched_groups_are_null() {
+    let src = r#"^ regex_find_all("a(x)?", "a")"#;
+    let found = items(&value_on_both(src));
+    assert_eq!(field(&field(&found[0], "groups"), "1"), Value::Null);
+}
+
+#[test]
+fn greedy_and_lazy_repetition() {
+    let src = r#"^ [regex_replace("<.+>", "<a><b>", "X"), regex_replace("<.+?>", "<a><b>", "X"), regex_replace("a\{2,3\}", "aaaaaaa", "-")]"#;
+    assert_eq!(value_on_both(src), texts(&["X", "XX", "--a"]));
+}
+
+#[test]
+fn replace_expands_group_references() {
+    let src = r#"^ [regex_replace("(\\w+)@(\\w+)", "ann@home bob@work", "$2:$1"), regex_replace("(?<y>\\d\{4\})-(?<m>\\d\\d)", "2024-05", "$\{m\}/$\{y\}"), regex_replace("\\$", "5$", "$$ USD"), regex_replace("x*", "ab", "-")]"#;
+    assert_eq!(value_on_both(src), texts(&["home:ann work:bob", "05/2024", "5$ USD", "-a-b-"]));
+}
+
+#[test]
+fn split_on_matches() {
+    let src = r#"^ [regex_split(",\\s*", "a, b,c"), regex_split("\\d", "a1b22c"), regex_split("", "abc")]"#;
+    let expected = Value::make_list(vec![texts(&["a", "b", "c"]), texts(&["a", "b", "", "c"]), texts(&["a", "b", "c"])]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn anchors_and_word_boundaries() {
+    let src = r#"^ [regex_replace("\\bcat\\b", "cat concat cat", "dog"), regex_replace("^a", "aaa", "b"), regex_replace("a$", "aaa", "b")]"#;
+    assert_eq!(value_on_both(src), texts(&["dog concat dog", "baa", "aab"]));
+}
+
+#[test]
+fn compiled_regexes_are_reusable_values() {
+    let src = "$re = regex_compile(\"[a-z]+\")\n^ [regex_match($re, \"abc\"), regex_match($re, \"ABC\"), len(regex_find_all($re, \"ab cd ef\")), $re == regex_compile(\"[a-z]+\")]\n";
+    assert_eq!(value_on_both(src), Value::make_list(vec![
+        Value::Bool(true),
+        Value::Bool(false),
+        Value::SmallInt(3),
+        Value::Bool(true),
+    ]));
+}
+
+#[test]
+fn nested_empty_loops_terminate() {
+    let src = r#"^ [regex_match("(a*)*b", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaac"), regex_match("(a|aa)+$", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")]"#;
+    assert_eq!(value_on_both(src), bools(&[false, true]));
+}
+
+#[test]
+fn invalid_patterns_are_runtime_errors() {
+    assert!(error_of(r#"^ regex_match("(ab", "ab")"#).contains("regex_match: invalid pattern: unclosed group"));
+    assert!(error_of(r#"^ regex_compile("a)")"#).contains("regex_compile: invalid pattern: unmatched ')'"));
+    assert!(error_of(r#"^ regex_split("*a", "a")"#).contains("nothing to repeat"));
+    assert!(error_of(r#"^ regex_find_all("[z-a]", "a")"#).contains("invalid class range"));
+    assert!(error_of(r#"^ regex_replace("a", "a", "$9")"#).contains("no group '9'"));
+    assert!(error_of(r#"^ regex_match(1, "a")"#).contains("pattern must be text or regex, got number"));
+}
+
+#[test]
+fn nested_counted_repetition_is_rejected_before_unrolling() {
+    let err = error_of(r#"^ regex_match("((a\{1000\})\{1000\})\{1000\}", "a")"#);
+    assert!(err.contains("regex_match: invalid pattern: pattern too large"), "{}", err);
+}
+
+#[test]
+fn long_inputs_fall_back_to_a_sparse_visited_set() {
+    let src = r#"$s = str_repeat("ab", 1000)
+^ [regex_match("(ab)\{1,1000\}x?", $s), regex_match("(ab)\{1,999\}", $s), len(regex_find_all("b", $s))]"#;
+    assert_eq!(value_on_both(src), Value::make_list(vec![Value::Bool(true), Value::Bool(false), Value::SmallInt(1000)]));
+}
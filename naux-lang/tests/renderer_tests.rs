@@ -1 +1,19 @@
-// TODO: renderer tests
+use naux::renderer::render_json;
+use naux::runtime::events::{RuntimeEvent, EVENTS_VERSION};
+
+#[test]
+fn json_output_is_versioned_envelope() {
+    let events = vec![RuntimeEvent::Say("hi".into()), RuntimeEvent::UiEnd];
+    let json: serde_json::Value = serde_json::from_str(&render_json(&events, false)).unwrap();
+    assert_eq!(json["naux_events_version"], EVENTS_VERSION);
+    assert_eq!(json["events"][0], serde_json::json!({ "type": "Say", "value": "hi" }));
+    assert_eq!(json["events"][1]["type"], "UiEnd");
+}
+
+#[test]
+fn compact_json_is_single_line() {
+    let events = vec![RuntimeEvent::Say("hi".into()), RuntimeEvent::Text("there".into())];
+    let compact = render_json(&events, true);
+    assert!(!compact.contains('\n'), "{}", compact);
+    assert!(render_json(&events, false).contains('\n'));
+}
@@ -0,0 +1,83 @@
+use std::io::Cursor;
+
+use naux::cli::repl::{open_blocks, run_repl, ReplSession};
+use naux::cli::DefaultEngine;
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+
+#[test]
+fn bindings_persist_between_inputs() {
+    for engine in [DefaultEngine::Interp, DefaultEngine::Vm] {
+        let mut session = ReplSession::new(engine).unwrap();
+        session.eval("$x = 40\n").unwrap();
+        session.eval("~ fn add($a, $b)\n    ^ $a + $b\n~ end\n").unwrap();
+        let out = session.eval("!say \"hi\"\n^ add($x, 2)\n").unwrap();
+        assert_eq!(out.value, Value::SmallInt(42), "{:?}", engine);
+        assert!(matches!(&out.events[..], [RuntimeEvent::Say(s)] if s == "hi"));
+        // earlier events are not reported again
+        let out = session.eval("^ $x\n").unwrap();
+        assert!(out.events.is_empty());
+        assert_eq!(session.events().len(), 1);
+    }
+}
+
+#[test]
+fn bare_expressions_yield_their_value() {
+    let mut session = ReplSession::new(DefaultEngine::Interp).unwrap();
+    assert_eq!(session.eval("1 + 2\n").unwrap().value, Value::SmallInt(3));
+}
+
+#[test]
+fn errors_do_not_end_the_session() {
+    for engine in [DefaultEngine::Interp, DefaultEngine::Vm] {
+        let mut session = ReplSession::new(engine).unwrap();
+        session.eval("$x = 1\n").unwrap();
+        assert!(session.eval("$y = 1 / 0\n").unwrap_err().contains("Division by zero"));
+        assert!(session.eval("$z = \n").is_err());
+        assert_eq!(session.eval("^ $x\n").unwrap().value, Value::SmallInt(1));
+    }
+}
+
+#[test]
+fn unclosed_blocks_request_more_input() {
+    assert_eq!(open_blocks("~ fn f()\n"), 1);
+    assert_eq!(open_blocks("~ fn f()\n    ~ if true\n"), 2);
+    assert_eq!(open_blocks("~ if true\n    !say 1\n~ else\n"), 1);
+    assert_eq!(open_blocks("~ loop 2\n~ end\n"), 0);
+    assert_eq!(open_blocks("$x = 1\n"), 0);
+}
+
+#[test]
+fn run_repl_prints_prompts_events_and_values() {
+    let input = Cursor::new("~ fn twice($n)\n^ $n * 2\n~ end\n!say \"hey\"\ntwice(21)\n\"t\"\n");
+    let mut out = Vec::new();
+    let mut seen = Vec::new();
+    run_repl(input, &mut out, DefaultEngine::Interp, |src| seen.push(src.to_string())).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.contains("... "), "{}", text);
+    assert!(text.contains("hey"));
+    assert!(text.contains("\n42\n") || text.contains("naux> 42\n"), "{}", text);
+    assert!(text.contains("\"t\""));
+    assert_eq!(seen.len(), 4);
+    assert_eq!(seen[0], "~ fn twice($n)\n^ $n * 2\n~ end");
+}
+
+#[test]
+fn jit_engine_is_rejected() {
+    assert!(ReplSession::new(DefaultEngine::Jit).is_err());
+}
+
+#[test]
+fn vm_inputs_are_not_replayed() {
+    let mut session = ReplSession::new(DefaultEngine::Vm).unwrap();
+    session.eval("$n = 0\n!say \"once\"\n").unwrap();
+    let out = session.eval("~ if true\n    ^ 5\n~ end\n").unwrap();
+    assert_eq!(out.value, Value::SmallInt(5));
+    // a `^` nested in an earlier block must not cut later inputs short
+    let out = session.eval("$n = $n + 1\n!say \"again\"\n^ $n\n").unwrap();
+    assert_eq!(out.value, Value::SmallInt(1));
+    assert!(matches!(&out.events[..], [RuntimeEvent::Say(s)] if s == "again"), "{:?}", out.events);
+    assert_eq!(session.eval("^ $n\n").unwrap().value, Value::SmallInt(1));
+    let says = session.events().iter().filter(|e| matches!(e, RuntimeEvent::Say(_))).count();
+    assert_eq!(says, 2);
+}
@@ -1 +1,60 @@
-// TODO: runtime tests
+use naux::runtime::value::{NauxObj, Value};
+
+const DEPTH: usize = 200_000;
+
+/// `[[[...[0]...]]]` nested `DEPTH` lists deep, built without recursion.
+fn deep_list() -> Value {
+    let mut v = Value::make_list(vec![Value::SmallInt(0)]);
+    for _ in 0..DEPTH {
+        v = Value::make_list(vec![v]);
+    }
+    v
+}
+
+fn depth_of(v: &Value) -> usize {
+    let mut depth = 0;
+    let mut cur = v.clone();
+    loop {
+        let next = match &cur {
+            Value::RcObj(rc) => match rc.as_ref() {
+                NauxObj::List(items) => items.borrow().first().cloned(),
+                _ => None,
+            },
+            _ => None,
+        };
+        match next {
+            Some(n) if matches!(n, Value::RcObj(_)) => {
+                depth += 1;
+                cur = n;
+            }
+            _ => return depth,
+        }
+    }
+}
+
+#[test]
+fn cloning_and_dropping_a_deeply_nested_list_does_not_overflow() {
+    let original = deep_list();
+    let shallow = original.clone();
+    let copied = match &original {
+        Value::RcObj(rc) => Value::RcObj(std::rc::Rc::new(rc.as_ref().clone())),
+        _ => unreachable!(),
+    };
+    assert_eq!(depth_of(&copied), DEPTH);
+    drop(original);
+    drop(shallow);
+    // the copy still owns the whole chain; releasing it must not recurse either
+    assert_eq!(depth_of(&copied), DEPTH);
+    drop(copied);
+}
+
+#[test]
+fn dropping_a_deep_map_chain_does_not_overflow() {
+    let mut v = Value::Null;
+    for _ in 0..DEPTH {
+        let mut m = std::collections::HashMap::new();
+        m.insert("next".to_string(), v);
+        v = Value::make_map(m);
+    }
+    drop(v);
+}
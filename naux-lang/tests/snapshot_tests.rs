@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+use naux::cli::test::{run_tests, snapshot_path, SnapshotMode};
+use naux::cli::DefaultEngine;
+
+fn temp_test(name: &str, src: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("naux_snap_{}_{}", std::process::id(), name));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("case_test.nx");
+    std::fs::write(&path, src).unwrap();
+    path
+}
+
+fn run(path: &Path, mode: SnapshotMode) -> (bool, Option<String>) {
+    let summary = run_tests(&[path.to_path_buf()], DefaultEngine::Vm, mode);
+    assert_eq!(summary.results.len(), 1);
+    let result = &summary.results[0];
+    (result.passed, result.message.clone())
+}
+
+#[test]
+fn example_snapshot_matches() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/snapshot_test.nx");
+    assert_eq!(run(&path, SnapshotMode::Compare), (true, None));
+}
+
+#[test]
+fn mismatch_reports_differing_line() {
+    let path = temp_test("mismatch", "!say \"hello\"\n");
+    run(&path, SnapshotMode::Update);
+    std::fs::write(&path, "!say \"bye\"\n").unwrap();
+    let (passed, message) = run(&path, SnapshotMode::Compare);
+    assert!(!passed);
+    let message = message.unwrap();
+    assert!(message.contains("Snapshot mismatch"), "{}", message);
+    assert!(message.contains("\"hello\""), "{}", message);
+    assert!(message.contains("\"bye\""), "{}", message);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn update_rewrites_snapshot() {
+    let path = temp_test("update", "!say \"one\"\n");
+    assert!(!run(&path, SnapshotMode::Compare).0, "missing snapshot must fail");
+    assert!(run(&path, SnapshotMode::Update).0);
+    assert!(std::fs::read_to_string(snapshot_path(&path)).unwrap().contains("\"one\""));
+    std::fs::write(&path, "!say \"two\"\n").unwrap();
+    assert!(run(&path, SnapshotMode::Update).0);
+    assert!(run(&path, SnapshotMode::Compare).0);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,54 @@
+use naux::ast::{ExprKind, Stmt};
+use naux::lexer::lex;
+use naux::parser::error::format_parse_error;
+use naux::parser::Parser;
+use naux::runtime::error::format_runtime_error;
+use naux::runtime::eval_script;
+use naux::token::TokenKind;
+
+fn caret_line(rendered: &str) -> &str {
+    rendered.lines().find(|l| l.trim_start().starts_with('^')).expect("caret line")
+}
+
+#[test]
+fn lexer_records_token_widths() {
+    let tokens = lex("$total = 12345 >= x\n").unwrap();
+    let width = |pred: fn(&TokenKind) -> bool| tokens.iter().find(|t| pred(&t.kind)).unwrap().span.width();
+    assert_eq!(width(|k| matches!(k, TokenKind::Ident(s) if s == "total")), 5);
+    assert_eq!(width(|k| matches!(k, TokenKind::Number(_))), 5);
+    assert_eq!(width(|k| matches!(k, TokenKind::Op(_))), 2);
+}
+
+#[test]
+fn parse_error_caret_spans_number_token() {
+    let src = "!say 1 12345\n";
+    let err = Parser::from_tokens(&lex(src).unwrap()).unwrap_err();
+    let rendered = format_parse_error(src, &err, "main.nx");
+    assert_eq!(caret_line(&rendered).trim(), "^^^^^", "{}", rendered);
+}
+
+#[test]
+fn runtime_error_caret_spans_variable() {
+    let src = "!say $missing\n";
+    let ast = Parser::from_tokens(&lex(src).unwrap()).unwrap();
+    let (_, _, errors) = eval_script(&ast);
+    let rendered = format_runtime_error(src, &errors[0]);
+    assert_eq!(caret_line(&rendered), "      ^^^^^^^^", "{}", rendered);
+}
+
+#[test]
+fn binary_expr_span_covers_both_operands() {
+    let src = "$x = $a + 100\n";
+    let ast = Parser::from_tokens(&lex(src).unwrap()).unwrap();
+    let Stmt::Assign { expr, .. } = &ast[0] else { panic!("expected assignment") };
+    assert!(matches!(expr.kind, ExprKind::Binary { .. }));
+    let span = expr.span.as_ref().unwrap();
+    assert_eq!((span.line, span.column, span.end_line, span.end_column), (1, 6, 1, 13));
+}
+
+#[test]
+fn single_char_spans_default_to_start() {
+    let tokens = lex("+\n").unwrap();
+    let span = &tokens[0].span;
+    assert_eq!((span.end_line, span.end_column), (span.line, span.column));
+}
@@ -0,0 +1,64 @@
+use naux::runtime::value::Value;
+
+mod common;
+use common::{error_of, text, texts, value_on_both};
+
+#[test]
+fn length_slicing_and_char_access_count_chars() {
+    let src = r#"^ [str_len("héllo"), str_slice("héllo", 1, 3), str_slice("héllo", 3), str_slice("abc", 2, 99), str_char_at("héllo", 1), str_index_of("héllo", "llo"), str_index_of("abc", "z")]"#;
+    let expected = Value::make_list(vec![
+        Value::SmallInt(5),
+        text("él"),
+        text("lo"),
+        text("c"),
+        text("é"),
+        Value::SmallInt(2),
+        Value::SmallInt(-1),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn split_and_join_round_trip() {
+    assert_eq!(value_on_both(r#"^ str_split("a,b,,c", ",")"#), texts(&["a", "b", "", "c"]));
+    assert_eq!(value_on_both(r#"^ str_split("ab", "")"#), texts(&["a", "b"]));
+    assert_eq!(value_on_both(r#"^ str_join(str_split("a b c", " "), "-")"#), text("a-b-c"));
+}
+
+#[test]
+fn trimming_case_and_predicates() {
+    let src = r#"^ [str_trim("  x  "), str_trim_start("  x "), str_trim_end(" x  "), str_upper("abC"), str_lower("AbC"), str_starts_with("naux", "na"), str_ends_with("naux", "ux"), str_contains("naux", "z")]"#;
+    let expected = Value::make_list(vec![
+        text("x"),
+        text("x "),
+        text(" x"),
+        text("ABC"),
+        text("abc"),
+        Value::Bool(true),
+        Value::Bool(true),
+        Value::Bool(false),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn replace_repeat_and_pad() {
+    let src = r#"^ [str_replace("a-a-a", "a", "b"), str_replace_all("a-a-a", "a", "b"), str_repeat("ab", 3), str_pad_start("7", 3, "0"), str_pad_end("ab", 4, "."), str_pad_start("long", 2, "0")]"#;
+    assert_eq!(value_on_both(src), texts(&["b-a-a", "b-b-b", "ababab", "007", "ab..", "long"]));
+}
+
+#[test]
+fn non_text_arguments_are_clear_errors() {
+    assert_eq!(error_of("^ str_upper(5)\n"), "str_upper: text must be text, got number");
+    assert!(error_of("^ str_join([1, 2], \",\")\n").contains("every element must be text"));
+    assert!(error_of("^ str_char_at(\"ab\", 5)\n").contains("out of range"));
+    assert!(error_of("^ str_pad_start(\"a\", 3, \"xy\")\n").contains("single character"));
+    assert!(error_of("^ str_repeat(\"a\", -1)\n").contains("non-negative"));
+}
+
+#[test]
+fn oversized_repeat_and_pad_are_errors() {
+    assert!(error_of(r#"^ str_repeat("ab", 9223372036854775807)"#).contains("str_repeat: result longer than"));
+    assert!(error_of(r#"^ str_pad_start("x", 9223372036854775807, "0")"#).contains("str_pad_start: result longer than"));
+    assert!(error_of(r#"^ str_pad_end("x", 300000000, "é")"#).contains("str_pad_end: result longer than"));
+}
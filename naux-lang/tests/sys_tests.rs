@@ -0,0 +1,27 @@
+use naux::run_source;
+use naux::runtime::value::Value;
+use naux::stdlib::sys;
+use naux::Engine;
+
+#[test]
+fn env_get_requires_opt_in() {
+    std::env::set_var("NAUX_SYS_TEST_VAR", "hello");
+    sys::allow_env(false);
+    let out = run_source("^ env_get(\"NAUX_SYS_TEST_VAR\")\n", Engine::Interp).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("--allow-env")));
+
+    sys::allow_env(true);
+    let out = run_source("^ env_get(\"NAUX_SYS_TEST_VAR\")\n", Engine::Interp).unwrap();
+    assert_eq!(out.value, Value::make_text("hello"));
+    let out = run_source("^ env_get(\"NAUX_SYS_TEST_MISSING\")\n", Engine::Interp).unwrap();
+    assert_eq!(out.value, Value::Null);
+    sys::allow_env(false);
+}
+
+#[test]
+fn args_returns_forwarded_argv() {
+    sys::set_script_args(vec!["a".into(), "b c".into()]);
+    let out = run_source("$a = args()\n^ $a[1]\n", Engine::Vm).unwrap();
+    assert_eq!(out.value, Value::make_text("b c"));
+    sys::set_script_args(Vec::new());
+}
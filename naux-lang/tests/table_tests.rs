@@ -0,0 +1,56 @@
+use naux::renderer::cli::render_cli_to_string;
+use naux::renderer::{render_html, render_json};
+use naux::runtime::events::RuntimeEvent;
+use naux::{run_source, Engine};
+
+const SRC: &str = r#"$rows = [{name: "Ann", age: 31}, {name: "Bo", age: 7}]
+!table $rows
+"#;
+
+fn table_events(engine: Engine) -> Vec<RuntimeEvent> {
+    let out = run_source(SRC, engine).expect("parse");
+    assert!(out.is_ok(), "{:?}", out.errors);
+    out.events.into_iter().filter(|e| matches!(e, RuntimeEvent::Table { .. })).collect()
+}
+
+#[test]
+fn table_event_has_sorted_columns_on_both_engines() {
+    for engine in [Engine::Interp, Engine::Vm] {
+        let events = table_events(engine);
+        assert_eq!(
+            events,
+            vec![RuntimeEvent::Table {
+                columns: vec!["age".into(), "name".into()],
+                rows: vec![vec!["31".into(), "Ann".into()], vec!["7".into(), "Bo".into()]],
+            }]
+        );
+    }
+}
+
+#[test]
+fn table_renders_aligned_in_cli() {
+    let text = render_cli_to_string(&table_events(Engine::Vm));
+    assert_eq!(text, "| age | name |\n|-----|------|\n| 31  | Ann  |\n| 7   | Bo   |\n");
+}
+
+#[test]
+fn table_renders_html_table() {
+    let html = render_html(&table_events(Engine::Vm), &[]);
+    assert!(html.contains("<table class=\"table\"><tr><th>age</th><th>name</th></tr>"));
+    assert!(html.contains("<tr><td>7</td><td>Bo</td></tr>"));
+}
+
+#[test]
+fn table_serializes_structured_json() {
+    let json: serde_json::Value = serde_json::from_str(&render_json(&table_events(Engine::Vm), true)).unwrap();
+    let table = &json["events"][0];
+    assert_eq!(table["type"], "Table");
+    assert_eq!(table["value"]["columns"], serde_json::json!(["age", "name"]));
+    assert_eq!(table["value"]["rows"][1], serde_json::json!(["7", "Bo"]));
+}
+
+#[test]
+fn table_rejects_non_list() {
+    let out = run_source("!table 5\n", Engine::Interp).unwrap();
+    assert!(out.errors[0].message.contains("!table expects a list of maps"));
+}
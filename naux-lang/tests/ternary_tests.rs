@@ -0,0 +1,49 @@
+use naux::runtime::events::RuntimeEvent;
+use naux::runtime::value::Value;
+use naux::{run_source, Engine};
+
+mod common;
+use common::value_on_both;
+
+#[test]
+fn ternary_picks_branch() {
+    assert_eq!(value_on_both("$x = -4\n^ $x > 0 ? $x : -$x\n"), Value::SmallInt(4));
+    assert_eq!(value_on_both("$x = 3\n^ $x > 0 ? $x : -$x\n"), Value::SmallInt(3));
+}
+
+#[test]
+fn ternary_binds_looser_than_logic() {
+    assert_eq!(value_on_both("^ 1 > 2 || 3 > 2 ? \"yes\" : \"no\"\n"), Value::make_text("yes"));
+}
+
+#[test]
+fn nested_ternary_is_right_associative() {
+    let src = "~ fn sign($n)\n    ^ $n < 0 ? -1 : $n > 0 ? 1 : 0\n~ end\n^ [sign(-5), sign(0), sign(7)]\n";
+    let expected = Value::make_list(vec![Value::SmallInt(-1), Value::SmallInt(0), Value::SmallInt(1)]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn ternary_in_then_branch() {
+    assert_eq!(value_on_both("^ true ? false ? 1 : 2 : 3\n"), Value::SmallInt(2));
+}
+
+#[test]
+fn only_taken_branch_runs() {
+    let src = r#"~ fn loud($v)
+    !say "evaluated"
+    ^ $v
+~ end
+^ true ? 1 : loud(2)
+"#;
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert_eq!(out.value, Value::SmallInt(1));
+        assert!(!out.events.iter().any(|e| matches!(e, RuntimeEvent::Say(_))), "{:?}: {:?}", engine, out.events);
+    }
+}
+
+#[test]
+fn ternary_requires_colon() {
+    assert!(run_source("^ true ? 1\n", Engine::Vm).is_err());
+}
@@ -0,0 +1,29 @@
+use naux::runtime::value::Value;
+use naux::stdlib::time::set_deterministic;
+use naux::{run_source, Engine};
+
+mod common;
+use common::value_on;
+
+#[test]
+fn now_ms_is_deterministic_in_test_mode() {
+    let src = "$a = now_ms()\n$_ = sleep(3)\n$b = now_ms()\n^ [$a, $b]\n";
+    set_deterministic(true);
+    let first = value_on(src, Engine::Vm);
+    set_deterministic(true);
+    let second = value_on(src, Engine::Interp);
+    set_deterministic(false);
+    assert_eq!(first, second);
+    assert_eq!(first, Value::make_list(vec![Value::SmallInt(0), Value::SmallInt(4)]));
+}
+
+#[test]
+fn sleep_returns_null() {
+    assert_eq!(value_on("^ sleep(0)\n", Engine::Vm), Value::Null);
+}
+
+#[test]
+fn sleep_rejects_negative() {
+    let out = run_source("$x = sleep(0 - 5)\n", Engine::Interp).unwrap();
+    assert!(!out.is_ok());
+}
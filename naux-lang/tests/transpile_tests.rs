@@ -0,0 +1,55 @@
+use naux::cli::transpile::transpile_python;
+use naux::lexer::lex;
+use naux::parser::Parser;
+
+fn transpile(src: &str) -> Result<String, String> {
+    let ast = Parser::from_tokens(&lex(src).unwrap()).unwrap();
+    transpile_python(&ast)
+}
+
+#[test]
+fn emits_python_for_supported_subset() {
+    let src = r#"~ fn double($n)
+    ^ $n * 2
+~ end
+~ rite
+    $total = 0
+    ~ each $i, $v in [1, 2, 3]
+        ~ if $v > 1
+            !say double($v)
+        ~ else
+            !say "small"
+        ~ end
+    ~ end
+    ~ while $total < 3
+        $total = $total + 1
+    ~ end
+    ~ loop 2
+        !say $total % 2
+    ~ end
+~ end
+"#;
+    let py = transpile(src).unwrap();
+    assert!(py.contains("def double(n):\n    return (n * 2)\n"), "{}", py);
+    assert!(py.contains("total = 0\n"), "{}", py);
+    assert!(py.contains("for i, v in enumerate([1, 2, 3]):\n    if (v > 1):\n        print(double(v))\n    else:\n        print(\"small\")\n"), "{}", py);
+    assert!(py.contains("while (total < 3):\n    total = (total + 1)\n"), "{}", py);
+    assert!(py.contains("for _ in range(int(2)):\n    print(_naux_mod(total, 2))\n"), "{}", py);
+    assert!(py.starts_with("import math\n"), "{}", py);
+}
+
+#[test]
+fn renames_python_reserved_names() {
+    let py = transpile("$len = len([1])\n$print = 2\n").unwrap();
+    assert_eq!(py, "len_ = len([1])\nprint_ = 2\n");
+}
+
+#[test]
+fn rejects_unsupported_constructs() {
+    let err = transpile("!fetch \"card\"\n").unwrap_err();
+    assert!(err.contains("line 1") && err.contains("not supported"), "{}", err);
+    let err = transpile("$g = graph_new()\n").unwrap_err();
+    assert!(err.contains("builtin `graph_new`"), "{}", err);
+    let err = transpile("^ 1\n").unwrap_err();
+    assert!(err.contains("top-level"), "{}", err);
+}
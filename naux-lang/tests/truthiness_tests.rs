@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use naux::runtime::env::Env;
+use naux::runtime::value::{Function, Value};
+use naux::{run_source, Engine};
+
+mod common;
+use common::value_on_both;
+
+fn bool_of(expr: &str) -> Value {
+    value_on_both(&format!("^ bool({})\n", expr))
+}
+
+#[test]
+fn scalars() {
+    assert_eq!(bool_of("true"), Value::Bool(true));
+    assert_eq!(bool_of("false"), Value::Bool(false));
+    assert_eq!(bool_of("0"), Value::Bool(false));
+    assert_eq!(bool_of("0.0"), Value::Bool(false));
+    assert_eq!(bool_of("-3"), Value::Bool(true));
+    assert_eq!(bool_of("0.5"), Value::Bool(true));
+}
+
+#[test]
+fn null_is_falsy() {
+    // a function that falls off its body returns null
+    let src = "~ fn nothing()\n~ end\n^ bool(nothing())\n";
+    for engine in [Engine::Interp, Engine::Vm] {
+        let out = run_source(src, engine).unwrap();
+        assert_eq!(out.value, Value::Bool(false), "{:?}: {:?}", engine, out.errors);
+    }
+}
+
+#[test]
+fn text_and_containers_are_truthy_when_non_empty() {
+    assert_eq!(bool_of("\"\""), Value::Bool(false));
+    assert_eq!(bool_of("\"x\""), Value::Bool(true));
+    assert_eq!(bool_of("[]"), Value::Bool(false));
+    assert_eq!(bool_of("[0]"), Value::Bool(true));
+    assert_eq!(bool_of("{}"), Value::Bool(false));
+    assert_eq!(bool_of("{a: 0}"), Value::Bool(true));
+    assert_eq!(bool_of("set_new()"), Value::Bool(false));
+    assert_eq!(bool_of("set_add(set_new(), 1)"), Value::Bool(true));
+    assert_eq!(bool_of("pq_new()"), Value::Bool(false));
+    assert_eq!(bool_of("pq_push(pq_new(), 1)"), Value::Bool(true));
+}
+
+#[test]
+fn graphs_and_functions_are_always_truthy() {
+    assert_eq!(bool_of("graph_new()"), Value::Bool(true));
+    // function values exist only in the interpreter, so build one directly
+    let f = Value::make_function(Function { params: Vec::new(), body: Vec::new(), captured: HashMap::new() });
+    let bool_fn = Env::new().builtins()["bool"];
+    assert_eq!(bool_fn(vec![f]).unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn bool_requires_one_argument() {
+    let out = run_source("^ bool()\n", Engine::Interp).unwrap();
+    assert!(out.errors.iter().any(|e| e.message.contains("bool(value)")));
+}
@@ -0,0 +1,97 @@
+use naux::runtime::value::Value;
+
+mod common;
+use common::{run_both, text, value_on_both};
+
+#[test]
+fn catch_binds_the_error_message() {
+    let src = "~ try\n    $x = 1 / 0\n~ catch $err\n    ^ $err.message\n~ end\n^ \"unreachable\"\n";
+    assert_eq!(value_on_both(src), text("Division by zero"));
+}
+
+#[test]
+fn try_body_stops_at_the_first_error() {
+    let src = "$steps = 0\n~ try\n    $steps = 1\n    $y = 1 % 0\n    $steps = 2\n~ catch $e\n    $steps = $steps + 10\n~ end\n^ $steps\n";
+    assert_eq!(value_on_both(src), Value::SmallInt(11));
+}
+
+#[test]
+fn catch_is_skipped_when_nothing_fails() {
+    let src = "$r = 0\n~ try\n    $r = 5\n~ catch $e\n    $r = -1\n~ end\n^ $r\n";
+    assert_eq!(value_on_both(src), Value::SmallInt(5));
+}
+
+#[test]
+fn errors_inside_called_functions_are_caught_with_a_trace() {
+    let src = "~ fn inner($x)\n    ^ 10 / $x\n~ end\n~ fn outer($x)\n    ^ inner($x) + 1\n~ end\n~ try\n    $v = outer(0)\n~ catch $e\n    ^ [$e.message, $e.code, $e.trace]\n~ end\n";
+    let expected = Value::make_list(vec![
+        text("Division by zero"),
+        text("runtime"),
+        Value::make_list(vec![text("outer"), text("inner")]),
+    ]);
+    assert_eq!(value_on_both(src), expected);
+}
+
+#[test]
+fn throw_raises_a_user_error() {
+    let src = "~ fn check($n)\n    ~ if $n < 0\n        $_ = throw(\"negative: {$n}\")\n    ~ end\n    ^ $n\n~ end\n~ try\n    $v = check(-3)\n~ catch $e\n    ^ [$e.message, $e.code]\n~ end\n";
+    assert_eq!(value_on_both(src), Value::make_list(vec![text("negative: -3"), text("thrown")]));
+}
+
+#[test]
+fn no_events_are_emitted_after_the_failing_statement() {
+    let src = "~ try\n    !say \"before\"\n    !say 1 / 0\n    !say \"after\"\n~ catch $e\n    !say \"caught\"\n~ end\n";
+    for out in run_both(src) {
+        assert!(out.errors.is_empty(), "{:?}", out.errors);
+        let said: Vec<String> = out
+            .events
+            .iter()
+            .filter_map(|ev| match ev {
+                naux::runtime::events::RuntimeEvent::Say(s) => Some(s.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(said, vec!["before", "caught"]);
+    }
+}
+
+#[test]
+fn nested_try_rethrows_to_the_outer_handler() {
+    let src = "~ try\n    ~ try\n        $_ = throw(\"inner\")\n    ~ catch $e\n        $_ = throw(\"again: {$e.message}\")\n    ~ end\n~ catch $outer\n    ^ $outer.message\n~ end\n";
+    assert_eq!(value_on_both(src), text("again: inner"));
+}
+
+#[test]
+fn uncaught_errors_still_surface() {
+    let src = "~ try\n    $x = 1\n~ catch $e\n    $y = 2\n~ end\n$z = throw(\"boom\")\n";
+    for out in run_both(src) {
+        assert_eq!(out.errors.len(), 1, "{:?}", out.errors);
+        assert!(out.errors[0].message.contains("boom"), "{}", out.errors[0].message);
+    }
+}
+
+#[test]
+fn errors_from_a_catch_block_propagate() {
+    let src = "~ try\n    $x = 1 / 0\n~ catch $e\n    $y = 1 % 0\n~ end\n";
+    for out in run_both(src) {
+        assert_eq!(out.errors.len(), 1, "{:?}", out.errors);
+        assert!(out.errors[0].message.contains("Modulo by zero"), "{}", out.errors[0].message);
+    }
+}
+
+#[test]
+fn break_out_of_a_try_inside_a_loop() {
+    let src = "$n = 0\n~ while true\n    $n = $n + 1\n    ~ try\n        ~ if $n > 2\n            ~ break\n        ~ end\n    ~ catch $e\n        $n = -100\n    ~ end\n~ end\n$after = 1 / 0\n";
+    for out in run_both(src) {
+        // the loop's try was left by `~ break`, so the later error is not caught
+        assert_eq!(out.errors.len(), 1, "{:?}", out.errors);
+    }
+}
+
+#[test]
+fn multi_line_messages_are_caught_whole() {
+    let src = "~ fn inner()\n    $_ = throw(\"first line\\nsecond line\")\n~ end\n~ fn outer()\n    ^ inner()\n~ end\n~ try\n    $v = outer()\n~ catch $e\n    ^ $e.message\n~ end\n";
+    assert_eq!(value_on_both(src), text("first line\nsecond line"));
+    let src = "~ fn boom()\n    $_ = throw(\"bad\\nworse\")\n~ end\n^ assert_throws(boom, \"worse\")\n";
+    assert_eq!(value_on_both(src), Value::Bool(true));
+}
@@ -166,12 +166,30 @@ impl If {
     }
 }
 
+/// `~ ui card ... ~ end`: a UI frame whose start and end events are always paired.
+#[derive(Debug, Clone)]
+pub struct UiBlock {
+    pub kind: String,
+    pub body: Vec<Statement>,
+}
+
+impl UiBlock {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "type": "ui_block",
+            "kind": self.kind,
+            "body": self.body.iter().map(|s| s.to_json()).collect::<Vec<_>>(),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Statement {
     Action(Action),
     Assign(Assign),
     Loop(Loop),
     If(If),
+    Ui(UiBlock),
 }
 
 impl Statement {
@@ -181,6 +199,7 @@ impl Statement {
             Statement::Assign(a) => a.to_json(),
             Statement::Loop(l) => l.to_json(),
             Statement::If(i) => i.to_json(),
+            Statement::Ui(u) => u.to_json(),
         }
     }
 }
@@ -127,8 +127,14 @@ impl<'a> Lexer<'a> {
                 continue;
             }
 
-            // Comparison ops
-            if self.starts_with("==") || self.starts_with("!=") || self.starts_with(">=") || self.starts_with("<=") {
+            // Comparison and logic ops
+            if self.starts_with("==")
+                || self.starts_with("!=")
+                || self.starts_with(">=")
+                || self.starts_with("<=")
+                || self.starts_with("&&")
+                || self.starts_with("||")
+            {
                 let op = &self.src[self.pos..self.pos + 2];
                 tokens.push(Token {
                     kind: TokenKind::Op,
@@ -3,17 +3,26 @@ use std::path::Path;
 
 use naux::parser::{parse_file, format_parse_error};
 use naux::renderer;
-use naux::oracle::query_oracle;
 use naux::runtime::RuntimeEvent;
 use naux::runtime::{run_program, Context};
 
 fn main() {
     let mut path: Option<String> = None;
     let mut mode = "json".to_string();
+    let mut compact = false;
 
     for arg in env::args().skip(1) {
         if let Some(rest) = arg.strip_prefix("--mode=") {
             mode = rest.to_string();
+        } else if let Some(style) = arg.strip_prefix("--json=") {
+            compact = match style {
+                "compact" => true,
+                "pretty" => false,
+                other => {
+                    eprintln!("Unknown JSON style '{}' (expected pretty|compact)", other);
+                    std::process::exit(1);
+                }
+            };
         } else if path.is_none() {
             path = Some(arg);
         }
@@ -22,7 +31,7 @@ fn main() {
     let path = match path {
         Some(p) => p,
         None => {
-            eprintln!("Usage: cargo run -- <file.nx> [--mode=json|cli|html]");
+            eprintln!("Usage: cargo run -- <file.nx> [--mode=json|cli|html] [--json=pretty|compact]");
             std::process::exit(1);
         }
     };
@@ -49,24 +58,16 @@ fn main() {
         }
     }
 
-    let mut final_events: Vec<RuntimeEvent> = Vec::new();
-    for ev in &ctx.events {
-        final_events.push(ev.clone());
-        if let RuntimeEvent::OracleRequest(prompt) = ev {
-            let ans = query_oracle(prompt);
-            final_events.push(RuntimeEvent::OracleResponse(ans));
-        }
-    }
+    let final_events: &[RuntimeEvent] = &ctx.events;
 
     match mode.as_str() {
-        "cli" => renderer::render_cli(&final_events),
+        "cli" => renderer::render_cli(final_events),
         "html" => {
-            let html = renderer::render_html(&final_events);
+            let html = renderer::render_html(final_events);
             println!("{}", html);
         }
         "json" | _ => {
-            let json = serde_json::to_string_pretty(&final_events).unwrap();
-            println!("{}", json);
+            println!("{}", renderer::render_json(final_events, compact));
         }
     }
 }
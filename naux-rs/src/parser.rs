@@ -1,5 +1,5 @@
 use crate::ast::{
-    Action, Arg, Assign, Expr, If, Loop, Program, Ritual, Statement, VarRef,
+    Action, Arg, Assign, Expr, If, Loop, Program, Ritual, Statement, UiBlock, VarRef,
 };
 use crate::lexer::{LexError, Lexer, Token, TokenKind};
 use serde_json::Value;
@@ -155,6 +155,9 @@ impl Parser {
             TokenKind::Bang => Ok(Statement::Action(self.parse_action(true)?)),
             TokenKind::Var => Ok(Statement::Assign(self.parse_assign()?)),
             TokenKind::At => self.parse_control(),
+            TokenKind::Tilde if self.peek(1).kind == TokenKind::Ident && self.peek(1).lexeme == "ui" => {
+                Ok(Statement::Ui(self.parse_ui_block()?))
+            }
             other => Err(ParseError::Unexpected {
                 found: other.clone(),
                 line: self.current().line,
@@ -264,6 +267,36 @@ impl Parser {
         }
     }
 
+    /// `~ ui [kind]`, a body, then `~ end`; the kind defaults to "ui" like a bare `!ui`.
+    fn parse_ui_block(&mut self) -> Result<UiBlock, ParseError> {
+        self.advance(); // ~
+        self.advance(); // ui
+        let kind = match self.current().kind {
+            TokenKind::Ident | TokenKind::StringLit => self.advance().lexeme,
+            _ => "ui".to_string(),
+        };
+        self.match_kind(TokenKind::Newline);
+        let mut body = Vec::new();
+        self.skip_newlines();
+        while !self.is_end_of_ritual() {
+            if self.current().kind == TokenKind::Eof {
+                return Err(ParseError::Expected {
+                    msg: "Expected '~ end' to close '~ ui' block".into(),
+                    line: self.current().line,
+                    col: self.current().col,
+                });
+            }
+            body.push(self.parse_statement()?);
+            self.match_kind(TokenKind::Newline);
+            self.skip_newlines();
+        }
+        // consume ~ end
+        self.advance();
+        self.advance();
+        self.match_kind(TokenKind::Newline);
+        Ok(UiBlock { kind, body })
+    }
+
     fn parse_loop_body(&mut self) -> Result<Loop, ParseError> {
         let mut mode = "count".to_string();
         let mut source: Option<VarRef> = None;
@@ -366,31 +399,52 @@ impl Parser {
     }
 
     fn parse_condition(&mut self) -> Result<Expr, ParseError> {
-        let left = self.parse_expr()?;
-        if self.current().kind == TokenKind::Op {
-            let op = self.current().lexeme.clone();
-            self.advance();
-            let right = self.parse_expr()?;
-            Ok(Expr::Binary {
-                op,
-                left: Box::new(left),
-                right: Box::new(right),
-            })
-        } else {
-            Ok(left)
-        }
+        self.parse_expr()
     }
 
+    /// Binary operator precedence, loosest first. Every level is left-associative,
+    /// so `1 == 2 == 3` parses as `(1 == 2) == 3` and `a - b - c` as `(a - b) - c`.
+    ///
+    /// | level | operators          |
+    /// |-------|--------------------|
+    /// | 1     | `\|\|`             |
+    /// | 2     | `&&`               |
+    /// | 3     | `==` `!=`          |
+    /// | 4     | `<` `<=` `>` `>=`  |
+    /// | 5     | `+` `-`            |
+    /// | 6     | `*` `/`            |
+    /// | 7     | unary `-`          |
     fn parse_expr(&mut self) -> Result<Expr, ParseError> {
-        self.parse_comparison()
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        self.parse_op_level(&["||"], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        self.parse_op_level(&["&&"], Self::parse_equality)
+    }
+
+    fn parse_equality(&mut self) -> Result<Expr, ParseError> {
+        self.parse_op_level(&["==", "!="], Self::parse_relational)
+    }
+
+    fn parse_relational(&mut self) -> Result<Expr, ParseError> {
+        self.parse_op_level(&["<", "<=", ">", ">="], Self::parse_add)
     }
 
-    fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
-        let mut left = self.parse_add()?;
-        if self.current().kind == TokenKind::Op {
+    /// Left-associative loop over `TokenKind::Op` tokens whose lexeme is in `ops`.
+    fn parse_op_level(
+        &mut self,
+        ops: &[&str],
+        next: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let mut left = next(self)?;
+        while self.current().kind == TokenKind::Op && ops.contains(&self.current().lexeme.as_str()) {
             let op = self.current().lexeme.clone();
             self.advance();
-            let right = self.parse_add()?;
+            let right = next(self)?;
             left = Expr::Binary {
                 op,
                 left: Box::new(left),
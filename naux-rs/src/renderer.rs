@@ -1,30 +1,4 @@
-use crate::runtime::{RuntimeEvent, Value};
-
-fn value_to_string(v: &Value) -> String {
-    match v {
-        Value::String(s) => s.clone(),
-        Value::Number(n) => {
-            if n.fract() == 0.0 {
-                format!("{}", *n as i64)
-            } else {
-                n.to_string()
-            }
-        }
-        Value::Boolean(b) => b.to_string(),
-        Value::Null => "null".into(),
-        Value::List(items) => {
-            let parts: Vec<String> = items.iter().map(value_to_string).collect();
-            format!("[{}]", parts.join(", "))
-        }
-        Value::Object(map) => {
-            let parts: Vec<String> = map
-                .iter()
-                .map(|(k, v)| format!("{}: {}", k, value_to_string(v)))
-                .collect();
-            format!("{{{}}}", parts.join(", "))
-        }
-    }
-}
+use crate::runtime::{EventStream, RuntimeEvent};
 
 pub fn render_cli(events: &[RuntimeEvent]) {
     let mut stack: Vec<String> = Vec::new();
@@ -34,16 +8,31 @@ pub fn render_cli(events: &[RuntimeEvent]) {
             RuntimeEvent::Say(msg) => {
                 println!("> {}", msg);
             }
-            RuntimeEvent::SetVar(name, val) => {
-                println!("var {} = {}", name, value_to_string(val));
+            RuntimeEvent::SetVar { name, value } => {
+                println!("var {} = {}", name, value);
             }
-            RuntimeEvent::OracleRequest(q) => {
-                println!("? ASK: {}", q);
+            RuntimeEvent::Ask { prompt, answer } => {
+                println!("? ASK: {}", prompt);
+                println!("= ORACLE: {}", answer);
             }
-            RuntimeEvent::OracleResponse(a) => {
-                println!("= ORACLE: {}", a);
+            RuntimeEvent::Fetch { target } => {
+                println!("~ fetch: {}", target);
             }
-            RuntimeEvent::UiStart(kind) => {
+            RuntimeEvent::Log(msg) => {
+                eprintln!("log: {}", msg);
+            }
+            RuntimeEvent::Progress(f) => {
+                let filled = (f.clamp(0.0, 1.0) * 20.0).round() as usize;
+                println!("[{}{}] {:>3}%", "#".repeat(filled), " ".repeat(20 - filled), (f * 100.0).round() as i64);
+            }
+            RuntimeEvent::Table { columns, rows } => {
+                let indent = "  ".repeat(stack.len());
+                println!("{}{}", indent, columns.join(" | "));
+                for row in rows {
+                    println!("{}{}", indent, row.join(" | "));
+                }
+            }
+            RuntimeEvent::Ui { kind, .. } => {
                 let indent = "  ".repeat(stack.len());
                 if kind.eq_ignore_ascii_case("card") {
                     println!("{}┌────────────────────────────┐", indent);
@@ -65,7 +54,7 @@ pub fn render_cli(events: &[RuntimeEvent]) {
                     println!("(ui_end with no matching start)");
                 }
             }
-            RuntimeEvent::UiText(text) => {
+            RuntimeEvent::Text(text) => {
                 let depth = stack.len();
                 let card = stack
                     .last()
@@ -78,7 +67,7 @@ pub fn render_cli(events: &[RuntimeEvent]) {
                     println!("{}- TEXT: {}", "  ".repeat(depth), text);
                 }
             }
-            RuntimeEvent::UiButton(label) => {
+            RuntimeEvent::Button(label) => {
                 let depth = stack.len();
                 let card = stack
                     .last()
@@ -104,6 +93,16 @@ pub fn render_cli(events: &[RuntimeEvent]) {
     }
 }
 
+/// Versioned JSON envelope; `compact` emits a single line instead of pretty output.
+pub fn render_json(events: &[RuntimeEvent], compact: bool) -> String {
+    let stream = EventStream::new(events.to_vec());
+    if compact {
+        serde_json::to_string(&stream).unwrap()
+    } else {
+        serde_json::to_string_pretty(&stream).unwrap()
+    }
+}
+
 pub fn render_html(events: &[RuntimeEvent]) -> String {
     let mut out = String::new();
     out.push_str(
@@ -120,14 +119,14 @@ pub fn render_html(events: &[RuntimeEvent]) -> String {
                     escape_html(msg)
                 ));
             }
-            RuntimeEvent::SetVar(name, val) => {
+            RuntimeEvent::SetVar { name, value } => {
                 out.push_str(&format!(
                     "  <!-- set {} = {} -->\n",
                     escape_html(name),
-                    escape_html(&value_to_string(val))
+                    escape_html(value)
                 ));
             }
-            RuntimeEvent::UiStart(kind) => {
+            RuntimeEvent::Ui { kind, .. } => {
                 let class = match kind.as_str() {
                     "card" => "card",
                     "stack" => "stack",
@@ -141,30 +140,55 @@ pub fn render_html(events: &[RuntimeEvent]) -> String {
                     out.push_str("  </div>\n");
                 }
             }
-            RuntimeEvent::UiText(text) => {
+            RuntimeEvent::Text(text) => {
                 out.push_str(&format!(
                     "    <p class=\"text\">{}</p>\n",
                     escape_html(text)
                 ));
             }
-            RuntimeEvent::UiButton(label) => {
+            RuntimeEvent::Button(label) => {
                 out.push_str(&format!(
                     "    <button class=\"btn\">{}</button>\n",
                     escape_html(label)
                 ));
             }
-            RuntimeEvent::OracleRequest(q) => {
+            RuntimeEvent::Ask { prompt, answer } => {
                 out.push_str(&format!(
                     "  <p class=\"ask\">? {}</p>\n",
-                    escape_html(q)
+                    escape_html(prompt)
                 ));
-            }
-            RuntimeEvent::OracleResponse(a) => {
                 out.push_str(&format!(
                     "  <p class=\"oracle\">= {}</p>\n",
-                    escape_html(a)
+                    escape_html(answer)
+                ));
+            }
+            RuntimeEvent::Fetch { target } => {
+                out.push_str(&format!(
+                    "  <p class=\"fetch\">~ fetch {}</p>\n",
+                    escape_html(target)
                 ));
             }
+            RuntimeEvent::Log(msg) => {
+                out.push_str(&format!("  <!-- log {} -->\n", escape_html(msg)));
+            }
+            RuntimeEvent::Progress(f) => {
+                out.push_str(&format!("    <progress value=\"{}\" max=\"1\"></progress>\n", f));
+            }
+            RuntimeEvent::Table { columns, rows } => {
+                out.push_str("    <table>\n      <tr>");
+                for c in columns {
+                    out.push_str(&format!("<th>{}</th>", escape_html(c)));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("      <tr>");
+                    for cell in row {
+                        out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("    </table>\n");
+            }
         }
     }
 
@@ -4,6 +4,7 @@ use std::collections::{HashSet, VecDeque};
 use serde::Serialize;
 
 use crate::ast::{Action, Arg, Expr, Program, Ritual, Statement, VarRef};
+use crate::oracle::query_oracle;
 
 #[derive(Clone, Debug, Serialize, PartialEq)]
 #[serde(untagged)]
@@ -17,12 +18,14 @@ pub enum Value {
 }
 
 impl Value {
+    /// Truthiness for conditions and `&&`/`||`: `null` and the empty string are false,
+    /// booleans and numbers follow `coerce_bool`, and everything else is true. Strings go by
+    /// emptiness only, so `"false"` and `"0"` are true.
     pub fn as_bool(&self) -> bool {
         match self {
-            Value::Boolean(b) => *b,
-            Value::Number(n) => *n != 0.0,
             Value::Null => false,
-            _ => true,
+            Value::String(s) => !s.is_empty(),
+            other => other.coerce_bool().unwrap_or(true),
         }
     }
 
@@ -43,54 +46,60 @@ impl Value {
         }
     }
 
-    pub fn as_f64(&self) -> Option<f64> {
+    /// The number every numeric operation (arithmetic other than string `+`, ordering,
+    /// numeric action arguments) sees: numbers as-is, strings whose trimmed text parses as a
+    /// finite number, and nothing else. Booleans are not numbers.
+    pub fn coerce_number(&self) -> Option<f64> {
         match self {
             Value::Number(n) => Some(*n),
+            Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
             _ => None,
         }
     }
 
-    pub fn as_bool_value(&self) -> Option<bool> {
+    /// Booleans as-is, numbers as `!= 0` (NaN is false), and the strings `"true"`/`"false"`.
+    pub fn coerce_bool(&self) -> Option<bool> {
         match self {
             Value::Boolean(b) => Some(*b),
-            Value::Number(n) => Some(*n != 0.0),
+            Value::Number(n) => Some(*n != 0.0 && !n.is_nan()),
+            Value::String(s) if s == "true" => Some(true),
+            Value::String(s) if s == "false" => Some(false),
             _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
-#[serde(tag = "type", content = "value")]
-pub enum RuntimeEvent {
-    Say(String),
-    SetVar(String, Value),
-    UiStart(String),
-    UiEnd,
-    UiText(String),
-    UiButton(String),
-    OracleRequest(String),
-    OracleResponse(String),
-}
+pub use naux_events::{EventStream, RuntimeEvent, EVENTS_VERSION};
 
 #[derive(Debug, Clone)]
 pub enum RuntimeError {
     UnknownAction(String),
     InvalidArgument(String),
+    /// `!ui` / `!ui_end` that do not pair up.
+    UnbalancedUi(String),
 }
 
 impl RuntimeError {
     pub fn message(&self) -> String {
         match self {
             RuntimeError::UnknownAction(name) => format!("Unknown action '!{}'", name),
-            RuntimeError::InvalidArgument(msg) => msg.clone(),
+            RuntimeError::InvalidArgument(msg) | RuntimeError::UnbalancedUi(msg) => msg.clone(),
         }
     }
 }
 
+/// An open UI frame: from `!ui` (closed by `!ui_end`) or from a `~ ui` block (closed by its `~ end`).
+#[derive(Debug, Clone)]
+enum UiFrame {
+    Action(String),
+    Block(String),
+}
+
 pub struct Context {
     pub vars: HashMap<String, Value>,
     pub events: Vec<RuntimeEvent>,
     pub errors: Vec<RuntimeError>,
+    ui_frames: Vec<UiFrame>,
 }
 
 impl Context {
@@ -99,6 +108,7 @@ impl Context {
             vars: HashMap::new(),
             events: Vec::new(),
             errors: Vec::new(),
+            ui_frames: Vec::new(),
         }
     }
 
@@ -114,8 +124,10 @@ impl Context {
             set_nested(&mut current, &var.path, value.clone());
             self.vars.insert(var.base.clone(), current);
         }
-        self.events
-            .push(RuntimeEvent::SetVar(var.base.clone(), value));
+        self.events.push(RuntimeEvent::SetVar {
+            name: var.base.clone(),
+            value: value.to_string_lossy(),
+        });
     }
 
     pub fn get_var_ref(&self, var: &VarRef) -> Value {
@@ -136,8 +148,10 @@ impl Context {
 
     pub fn set_var(&mut self, name: &str, value: Value) {
         self.vars.insert(name.to_string(), value.clone());
-        self.events
-            .push(RuntimeEvent::SetVar(name.to_string(), value));
+        self.events.push(RuntimeEvent::SetVar {
+            name: name.to_string(),
+            value: value.to_string_lossy(),
+        });
     }
 
     pub fn get_var(&self, name: &str) -> Option<Value> {
@@ -185,31 +199,47 @@ impl Expr {
             Expr::Literal { kind, value } => literal_to_value(kind, value),
             Expr::Var(v) => ctx.get_var_ref(v),
             Expr::Ident(name) => Value::String(name.clone()),
+            Expr::Binary { op, left, right } if op == "&&" || op == "||" => {
+                // short-circuit: the right side only runs when it decides the result
+                let l = left.eval_value(ctx).as_bool();
+                if (op == "&&") != l {
+                    return Value::Boolean(l);
+                }
+                Value::Boolean(right.eval_value(ctx).as_bool())
+            }
             Expr::Binary { op, left, right } => {
                 let l = left.eval_value(ctx);
                 let r = right.eval_value(ctx);
-                match (l, op.as_str(), r) {
-                    (Value::Number(a), "+", Value::Number(b)) => Value::Number(a + b),
-                    (Value::String(a), "+", Value::String(b)) => Value::String(format!("{}{}", a, b)),
-                    (Value::String(a), "+", b) => Value::String(format!("{}{}", a, b.to_string_lossy())),
-                    (a @ Value::Number(_), "+", b) => Value::String(format!("{}{}", a.to_string_lossy(), b.to_string_lossy())),
-                    (Value::Number(a), "-", Value::Number(b)) => Value::Number(a - b),
-                    (Value::Number(a), "*", Value::Number(b)) => Value::Number(a * b),
-                    (Value::Number(a), "/", Value::Number(b)) => Value::Number(a / b),
-                    (Value::Number(a), ">", Value::Number(b)) => Value::Boolean(a > b),
-                    (Value::Number(a), "<", Value::Number(b)) => Value::Boolean(a < b),
-                    (Value::Number(a), ">=", Value::Number(b)) => Value::Boolean(a >= b),
-                    (Value::Number(a), "<=", Value::Number(b)) => Value::Boolean(a <= b),
-                    (Value::Number(a), "==", Value::Number(b)) => Value::Boolean((a - b).abs() < f64::EPSILON),
-                    (Value::Boolean(a), "==", Value::Boolean(b)) => Value::Boolean(a == b),
-                    (Value::String(a), "==", Value::String(b)) => Value::Boolean(a == b),
-                    _ => Value::Null,
+                match op.as_str() {
+                    "==" => Value::Boolean(values_equal(&l, &r)),
+                    "!=" => Value::Boolean(!values_equal(&l, &r)),
+                    "+" => match (l, r) {
+                        (Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+                        // `+` with a string operand concatenates
+                        (a @ (Value::String(_) | Value::Number(_)), b) => {
+                            Value::String(format!("{}{}", a.to_string_lossy(), b.to_string_lossy()))
+                        }
+                        _ => Value::Null,
+                    },
+                    _ => match (l.coerce_number(), r.coerce_number()) {
+                        (Some(a), Some(b)) => match op.as_str() {
+                            "-" => Value::Number(a - b),
+                            "*" => Value::Number(a * b),
+                            "/" => Value::Number(a / b),
+                            ">" => Value::Boolean(a > b),
+                            "<" => Value::Boolean(a < b),
+                            ">=" => Value::Boolean(a >= b),
+                            "<=" => Value::Boolean(a <= b),
+                            _ => Value::Null,
+                        },
+                        _ => Value::Null,
+                    },
                 }
             }
             Expr::Unary { op, expr } => {
                 let v = expr.eval_value(ctx);
-                match (op.as_str(), v) {
-                    ("-", Value::Number(n)) => Value::Number(-n),
+                match (op.as_str(), v.coerce_number()) {
+                    ("-", Some(n)) => Value::Number(-n),
                     _ => Value::Null,
                 }
             }
@@ -272,6 +302,23 @@ impl Eval for Statement {
                     _ => {}
                 }
             }
+            Statement::Ui(u) => {
+                ctx.events.push(RuntimeEvent::Ui { kind: u.kind.clone(), props: Vec::new() });
+                ctx.ui_frames.push(UiFrame::Block(u.kind.clone()));
+                for stmt in &u.body {
+                    stmt.eval(ctx);
+                }
+                // `!ui` frames left open inside the block are closed here so the block stays balanced;
+                // the loop stops after popping the block's own frame
+                while let Some(UiFrame::Action(kind)) = ctx.ui_frames.pop() {
+                    ctx.report_error(RuntimeError::UnbalancedUi(format!(
+                        "!ui {} is not closed before the end of its '~ ui {}' block",
+                        kind, u.kind
+                    )));
+                    ctx.events.push(RuntimeEvent::UiEnd);
+                }
+                ctx.events.push(RuntimeEvent::UiEnd);
+            }
             Statement::If(i) => {
                 if i.cond.eval_value(ctx).as_bool() {
                     for stmt in &i.then_body {
@@ -297,6 +344,11 @@ pub fn run_program(program: &Program, entry: Option<&str>, ctx: &mut Context) {
     } else {
         eval_ritual(&program[0], ctx);
     }
+    for frame in std::mem::take(&mut ctx.ui_frames) {
+        if let UiFrame::Action(kind) = frame {
+            ctx.report_error(RuntimeError::UnbalancedUi(format!("!ui {} is never closed with !ui_end", kind)));
+        }
+    }
 }
 
 fn eval_ritual(ritual: &Ritual, ctx: &mut Context) {
@@ -321,20 +373,28 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
             None
         }
         "ui" => {
-            if let Some(kind) = first_arg_as_string(&action.args, ctx) {
-                ctx.events.push(RuntimeEvent::UiStart(kind));
-            } else {
-                ctx.events.push(RuntimeEvent::UiStart("ui".into()));
-            }
+            let kind = first_arg_as_string(&action.args, ctx).unwrap_or_else(|| "ui".into());
+            ctx.events.push(RuntimeEvent::Ui { kind: kind.clone(), props: Vec::new() });
+            ctx.ui_frames.push(UiFrame::Action(kind));
             None
         }
         "ui_end" => {
-            ctx.events.push(RuntimeEvent::UiEnd);
+            match ctx.ui_frames.last() {
+                Some(UiFrame::Action(_)) => {
+                    ctx.ui_frames.pop();
+                    ctx.events.push(RuntimeEvent::UiEnd);
+                }
+                Some(UiFrame::Block(kind)) => {
+                    let msg = format!("!ui_end has no matching !ui inside '~ ui {}' block", kind);
+                    ctx.report_error(RuntimeError::UnbalancedUi(msg));
+                }
+                None => ctx.report_error(RuntimeError::UnbalancedUi("!ui_end has no matching !ui".into())),
+            }
             None
         }
         "text" => {
             if let Some(text) = first_arg_as_string(&action.args, ctx) {
-                ctx.events.push(RuntimeEvent::UiText(text));
+                ctx.events.push(RuntimeEvent::Text(text));
             } else {
                 ctx.report_error(RuntimeError::InvalidArgument(
                     "!text expects content".to_string(),
@@ -344,7 +404,7 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
         }
         "button" => {
             if let Some(text) = first_arg_as_string(&action.args, ctx) {
-                ctx.events.push(RuntimeEvent::UiButton(text));
+                ctx.events.push(RuntimeEvent::Button(text));
             } else {
                 ctx.report_error(RuntimeError::InvalidArgument(
                     "!button expects label".to_string(),
@@ -352,9 +412,34 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
             }
             None
         }
+        "repeat" => {
+            // !repeat over $items -> !text $item: runs the callback once per element
+            let callback = match &action.callback {
+                Some(cb) => cb,
+                None => {
+                    ctx.report_error(RuntimeError::InvalidArgument(
+                        "!repeat expects a callback: !repeat over $items -> !action".to_string(),
+                    ));
+                    return None;
+                }
+            };
+            match pos_args.into_iter().next() {
+                Some(Value::List(items)) => {
+                    for item in items {
+                        ctx.set_var("item", item);
+                        let _ = eval_action(callback, ctx);
+                    }
+                }
+                _ => ctx.report_error(RuntimeError::InvalidArgument(
+                    "!repeat over expects a list".to_string(),
+                )),
+            }
+            None
+        }
         "ask" => {
             if let Some(question) = first_arg_as_string(&action.args, ctx) {
-                ctx.events.push(RuntimeEvent::OracleRequest(question));
+                let answer = query_oracle(&question);
+                ctx.events.push(RuntimeEvent::Ask { prompt: question, answer });
             } else {
                 ctx.report_error(RuntimeError::InvalidArgument(
                     "!ask expects a prompt string".to_string(),
@@ -377,7 +462,12 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
                     })
                     .or_else(|| flags.get(0).cloned())
                     .unwrap_or_else(|| "quick".to_string());
-                match sort_value_list(list, &algo) {
+                let max_range = named_args
+                    .get("max_range")
+                    .and_then(Value::coerce_number)
+                    .map(|n| n.max(0.0) as usize)
+                    .unwrap_or(COUNTING_SORT_MAX_RANGE);
+                match sort_value_list(list, &algo, max_range) {
                     Ok(sorted) => Some(Value::List(sorted)),
                     Err(e) => {
                         ctx.report_error(RuntimeError::InvalidArgument(e));
@@ -422,7 +512,7 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
                 ));
                 return None;
             }
-            match (pos_args[0].as_f64(), pos_args[1].as_f64()) {
+            match (pos_args[0].coerce_number(), pos_args[1].coerce_number()) {
                 (Some(a), Some(b)) => Some(Value::Number(gcd(a as i64, b as i64) as f64)),
                 _ => {
                     ctx.report_error(RuntimeError::InvalidArgument(
@@ -433,7 +523,7 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
             }
         }
         "fib" | "fibonacci" => {
-            if let Some(Some(n)) = pos_args.get(0).map(|v| v.as_f64()) {
+            if let Some(Some(n)) = pos_args.get(0).map(|v| v.coerce_number()) {
                 let n_int = if n < 0.0 { 0 } else { n as usize };
                 Some(Value::Number(fib(n_int) as f64))
             } else {
@@ -444,7 +534,7 @@ fn eval_action(action: &Action, ctx: &mut Context) -> Option<Value> {
             }
         }
         "sieve" => {
-            if let Some(Some(n)) = pos_args.get(0).map(|v| v.as_f64()) {
+            if let Some(Some(n)) = pos_args.get(0).map(|v| v.coerce_number()) {
                 let n_int = if n < 0.0 { 0 } else { n as usize };
                 let primes: Vec<Value> = sieve(n_int).into_iter().map(|p| Value::Number(p as f64)).collect();
                 Some(Value::List(primes))
@@ -535,7 +625,7 @@ fn value_list_as_numbers(list: &Value) -> Result<Vec<f64>, String> {
     if let Value::List(items) = list {
         let mut out = Vec::new();
         for v in items {
-            if let Some(n) = v.as_f64() {
+            if let Some(n) = v.coerce_number() {
                 out.push(n);
             } else {
                 return Err("List must contain only numbers".into());
@@ -547,11 +637,7 @@ fn value_list_as_numbers(list: &Value) -> Result<Vec<f64>, String> {
     }
 }
 
-fn value_as_bool(v: &Value) -> Option<bool> {
-    v.as_bool_value()
-}
-
-fn sort_value_list(list: &Value, algo: &str) -> Result<Vec<Value>, String> {
+fn sort_value_list(list: &Value, algo: &str, max_range: usize) -> Result<Vec<Value>, String> {
     let mut nums = value_list_as_numbers(list)?;
     match algo {
         "bubble" => bubble_sort(&mut nums),
@@ -559,7 +645,7 @@ fn sort_value_list(list: &Value, algo: &str) -> Result<Vec<Value>, String> {
         "insertion" => insertion_sort(&mut nums),
         "merge" | "mergesort" => merge_sort(&mut nums),
         "heap" | "heapsort" => heap_sort(&mut nums),
-        "counting" | "countingsort" => counting_sort(&mut nums)?,
+        "counting" | "countingsort" => counting_sort(&mut nums, max_range)?,
         "quick" | "quicksort" | _ => quick_sort(&mut nums),
     }
     Ok(nums.into_iter().map(Value::Number).collect())
@@ -588,14 +674,14 @@ fn linear_search_value(haystack: &Value, target: &Value) -> Result<usize, String
 fn binary_search_value(haystack: &Value, target: &Value) -> Result<usize, String> {
     let nums = value_list_as_numbers(haystack)?;
     let t = target
-        .as_f64()
+        .coerce_number()
         .ok_or_else(|| "!search binary expects numeric target".to_string())?;
     let mut lo: isize = 0;
     let mut hi: isize = nums.len() as isize - 1;
     while lo <= hi {
         let mid = (lo + hi) / 2;
         let val = nums[mid as usize];
-        if (val - t).abs() < f64::EPSILON {
+        if numbers_equal(val, t) {
             return Ok(mid as usize);
         } else if val < t {
             lo = mid + 1;
@@ -606,15 +692,26 @@ fn binary_search_value(haystack: &Value, target: &Value) -> Result<usize, String
     Ok(usize::MAX)
 }
 
-fn values_equal(a: &Value, b: &Value) -> bool {
+/// Equality for `==`, `!=` and `!search`: values of different kinds are never equal (no
+/// coercion), numbers compare within `f64::EPSILON`, lists and objects compare element-wise.
+pub fn values_equal(a: &Value, b: &Value) -> bool {
     match (a, b) {
-        (Value::Number(x), Value::Number(y)) => (x - y).abs() < f64::EPSILON,
+        (Value::Number(x), Value::Number(y)) => numbers_equal(*x, *y),
         (Value::String(x), Value::String(y)) => x == y,
         (Value::Boolean(x), Value::Boolean(y)) => x == y,
+        (Value::Null, Value::Null) => true,
+        (Value::List(x), Value::List(y)) => x.len() == y.len() && x.iter().zip(y).all(|(a, b)| values_equal(a, b)),
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| values_equal(v, w)))
+        }
         _ => false,
     }
 }
 
+fn numbers_equal(a: f64, b: f64) -> bool {
+    (a - b).abs() < f64::EPSILON
+}
+
 fn bubble_sort(arr: &mut [f64]) {
     let n = arr.len();
     for i in 0..n {
@@ -691,36 +788,53 @@ fn merge(left: &[f64], right: &[f64], out: &mut [f64]) {
     }
 }
 
-fn quick_sort(arr: &mut [f64]) {
-    if arr.len() <= 1 {
-        return;
+fn quick_sort(mut arr: &mut [f64]) {
+    // recurse into the smaller side and loop on the larger one so stack depth stays O(log n)
+    while arr.len() > 1 {
+        let split = hoare_partition(arr);
+        let (lo, hi) = std::mem::take(&mut arr).split_at_mut(split);
+        if lo.len() < hi.len() {
+            quick_sort(lo);
+            arr = hi;
+        } else {
+            quick_sort(hi);
+            arr = lo;
+        }
     }
-    let pivot = arr[arr.len() / 2];
-    let (mut left, mut right) = (0usize, arr.len() - 1);
-    while left <= right {
-        while arr[left] < pivot {
-            left += 1;
+}
+
+/// Hoare partition around the median of the first, lower-middle and last elements. Returns
+/// `split` in `1..len` such that `arr[..split]` holds no element greater than the pivot and
+/// `arr[split..]` none smaller, so both halves are strictly shorter than `arr` even when
+/// every element is equal.
+fn hoare_partition(arr: &mut [f64]) -> usize {
+    let (last, mid) = (arr.len() - 1, (arr.len() - 1) / 2);
+    // order arr[0] <= arr[mid] <= arr[last]; the pivot must sit below `last` for the bound above
+    if arr[mid] < arr[0] {
+        arr.swap(mid, 0);
+    }
+    if arr[last] < arr[0] {
+        arr.swap(last, 0);
+    }
+    if arr[last] < arr[mid] {
+        arr.swap(last, mid);
+    }
+    let pivot = arr[mid];
+    let (mut i, mut j) = (0, arr.len() - 1);
+    loop {
+        while arr[i] < pivot {
+            i += 1;
         }
-        while arr[right] > pivot {
-            if right == 0 {
-                break;
-            }
-            right -= 1;
+        while arr[j] > pivot {
+            j -= 1;
         }
-        if left <= right {
-            arr.swap(left, right);
-            left += 1;
-            if right == 0 {
-                break;
-            }
-            right -= 1;
+        if i >= j {
+            return j + 1;
         }
+        arr.swap(i, j);
+        i += 1;
+        j -= 1;
     }
-    let (l, r) = arr.split_at_mut(left);
-    if right < l.len() {
-        quick_sort(&mut l[..=right]);
-    }
-    quick_sort(r);
 }
 
 fn heap_sort(arr: &mut [f64]) {
@@ -750,7 +864,13 @@ fn heapify(arr: &mut [f64], n: usize, i: usize) {
     }
 }
 
-fn counting_sort(arr: &mut [f64]) -> Result<(), String> {
+/// Default for `!sort algorithm="counting"`'s `max_range=`: the widest value range
+/// (`max - min + 1`) that gets a count table.
+const COUNTING_SORT_MAX_RANGE: usize = 100_000;
+
+/// Counting sort for integer values; a range wider than `max_range` falls back to quicksort
+/// instead of allocating the count table.
+fn counting_sort(arr: &mut [f64], max_range: usize) -> Result<(), String> {
     let ints: Vec<i64> = arr
         .iter()
         .map(|n| {
@@ -767,10 +887,12 @@ fn counting_sort(arr: &mut [f64]) -> Result<(), String> {
     }
     let min = *ints.iter().min().unwrap();
     let max = *ints.iter().max().unwrap();
-    let range = (max - min + 1) as usize;
-    if range > 100_000 {
-        return Err("Counting sort range too large".into());
+    let range = max as i128 - min as i128 + 1;
+    if range > max_range as i128 {
+        quick_sort(arr);
+        return Ok(());
     }
+    let range = range as usize;
     let mut count = vec![0usize; range];
     for &v in &ints {
         count[(v - min) as usize] += 1;
@@ -892,7 +1014,7 @@ fn parse_edges(val: &Value) -> Result<Vec<Edge>, String> {
                             .get("v")
                             .and_then(value_as_string)
                             .ok_or_else(|| "Edge missing 'v'".to_string())?;
-                        let w = map.get("w").and_then(|x| x.as_f64()).unwrap_or(1.0);
+                        let w = map.get("w").and_then(|x| x.coerce_number()).unwrap_or(1.0);
                         edges.push(Edge { u, v, w });
                     }
                     Value::List(items) => {
@@ -903,7 +1025,7 @@ fn parse_edges(val: &Value) -> Result<Vec<Edge>, String> {
                             .ok_or_else(|| "Edge u must be string/number".to_string())?;
                         let v = value_as_string(&items[1])
                             .ok_or_else(|| "Edge v must be string/number".to_string())?;
-                        let w = items.get(2).and_then(|x| x.as_f64()).unwrap_or(1.0);
+                        let w = items.get(2).and_then(|x| x.coerce_number()).unwrap_or(1.0);
                         edges.push(Edge { u, v, w });
                     }
                     _ => return Err("Edge must be object or list".into()),
@@ -952,7 +1074,7 @@ fn handle_graph_action(
     let edges = parse_edges(&pos_args[0])?;
     let directed = named_args
         .get("directed")
-        .and_then(value_as_bool)
+        .and_then(Value::coerce_bool)
         .unwrap_or(false);
 
     match name {
@@ -0,0 +1,74 @@
+use naux::parser::parse;
+use naux::runtime::{run_program, values_equal, Context, Value};
+
+fn eval(expr: &str) -> Option<Value> {
+    let src = format!("~ rite Main\n    $r = {}\n~ end\n", expr);
+    let program = parse(&src).expect("parse");
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    ctx.get_var("r")
+}
+
+fn s(text: &str) -> Value {
+    Value::String(text.into())
+}
+
+#[test]
+fn coerce_number_accepts_numbers_and_numeric_strings_only() {
+    assert_eq!(Value::Number(2.5).coerce_number(), Some(2.5));
+    assert_eq!(s(" 42 ").coerce_number(), Some(42.0));
+    assert_eq!(s("-1.5").coerce_number(), Some(-1.5));
+    assert_eq!(s("abc").coerce_number(), None);
+    assert_eq!(s("inf").coerce_number(), None);
+    assert_eq!(Value::Boolean(true).coerce_number(), None);
+    assert_eq!(Value::Null.coerce_number(), None);
+    assert_eq!(Value::List(vec![]).coerce_number(), None);
+}
+
+#[test]
+fn coerce_bool_and_truthiness() {
+    assert_eq!(Value::Boolean(false).coerce_bool(), Some(false));
+    assert_eq!(Value::Number(0.0).coerce_bool(), Some(false));
+    assert_eq!(Value::Number(-3.0).coerce_bool(), Some(true));
+    assert_eq!(Value::Number(f64::NAN).coerce_bool(), Some(false));
+    assert_eq!(s("true").coerce_bool(), Some(true));
+    assert_eq!(s("false").coerce_bool(), Some(false));
+    assert_eq!(s("yes").coerce_bool(), None);
+    assert_eq!(Value::Null.coerce_bool(), None);
+
+    assert!(!Value::Null.as_bool());
+    assert!(!s("").as_bool());
+    assert!(s("false").as_bool());
+    assert!(s("0").as_bool());
+    assert!(s("yes").as_bool());
+    assert!(Value::List(vec![]).as_bool());
+    assert_eq!(eval("\"false\" && \"0\""), Some(Value::Boolean(true)));
+    assert_eq!(eval("\"\" || false"), Some(Value::Boolean(false)));
+}
+
+#[test]
+fn numeric_operators_coerce_numeric_strings() {
+    assert_eq!(eval("\"6\" * 2"), Some(Value::Number(12.0)));
+    assert_eq!(eval("\"10\" - 4"), Some(Value::Number(6.0)));
+    assert_eq!(eval("\"10\" > 9"), Some(Value::Boolean(true)));
+    assert_eq!(eval("\"abc\" > 1"), Some(Value::Null));
+    assert_eq!(eval("true * 2"), Some(Value::Null));
+}
+
+#[test]
+fn plus_with_a_string_concatenates() {
+    assert_eq!(eval("\"1\" + 2"), Some(s("12")));
+    assert_eq!(eval("1 + \"2\""), Some(s("12")));
+    assert_eq!(eval("1 + 2"), Some(Value::Number(3.0)));
+}
+
+#[test]
+fn equality_never_coerces_across_kinds() {
+    assert_eq!(eval("\"1\" == 1"), Some(Value::Boolean(false)));
+    assert_eq!(eval("1 == true"), Some(Value::Boolean(false)));
+    assert_eq!(eval("\"true\" == true"), Some(Value::Boolean(false)));
+    assert_eq!(eval("[1, 2] == [1, 2]"), Some(Value::Boolean(true)));
+    assert!(values_equal(&Value::Null, &Value::Null));
+    assert!(values_equal(&Value::Number(0.1 + 0.2), &Value::Number(0.3)));
+    assert!(!values_equal(&Value::List(vec![Value::Number(1.0)]), &Value::List(vec![s("1")])));
+}
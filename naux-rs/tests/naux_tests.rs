@@ -1,20 +1,7 @@
-use naux::oracle::query_oracle;
 use naux::parser::{parse, format_parse_error};
 use naux::renderer;
 use naux::runtime::{run_program, Context, RuntimeEvent, Value};
 
-fn collect_final_events(ctx: &Context) -> Vec<RuntimeEvent> {
-    let mut final_events = Vec::new();
-    for ev in &ctx.events {
-        final_events.push(ev.clone());
-        if let RuntimeEvent::OracleRequest(prompt) = ev {
-            let ans = query_oracle(prompt);
-            final_events.push(RuntimeEvent::OracleResponse(ans));
-        }
-    }
-    final_events
-}
-
 #[test]
 fn unicode_strings_preserved() {
     let src = r#"
@@ -26,8 +13,7 @@ fn unicode_strings_preserved() {
     let program = parse(src).expect("parse");
     let mut ctx = Context::new();
     run_program(&program, Some("Main"), &mut ctx);
-    let events = collect_final_events(&ctx);
-    let rendered = renderer::render_html(&events);
+    let rendered = renderer::render_html(&ctx.events);
     assert!(rendered.contains("Cốt"));
 }
 
@@ -41,22 +27,23 @@ fn parser_error_snippet() {
 }
 
 #[test]
-fn oracle_request_response_added() {
+fn ask_carries_oracle_answer() {
     let src = "~ rite Main\n    !ask \"Hello?\"\n~ end\n";
     let program = parse(src).unwrap();
     let mut ctx = Context::new();
     run_program(&program, Some("Main"), &mut ctx);
-    let events = collect_final_events(&ctx);
-    assert!(matches!(events.get(0), Some(RuntimeEvent::OracleRequest(_))));
-    assert!(matches!(events.get(1), Some(RuntimeEvent::OracleResponse(resp)) if resp.contains("Hello?")));
+    assert!(matches!(
+        ctx.events.first(),
+        Some(RuntimeEvent::Ask { prompt, answer }) if prompt == "Hello?" && answer.contains("Hello?")
+    ));
 }
 
 #[test]
 fn renderer_cli_contains_ask_oracle() {
-    let events = vec![
-        RuntimeEvent::OracleRequest("What?".into()),
-        RuntimeEvent::OracleResponse("(oracle says) What?".into()),
-    ];
+    let events = vec![RuntimeEvent::Ask {
+        prompt: "What?".into(),
+        answer: "(oracle says) What?".into(),
+    }];
     // Just ensure it doesn't panic and contains markers.
     renderer::render_cli(&events);
 }
@@ -86,6 +73,21 @@ fn sort_and_search_work() {
     assert_eq!(ctx.get_var("idx"), Some(Value::Number(3.0)));
 }
 
+#[test]
+fn counting_sort_falls_back_for_wide_ranges() {
+    let src = "~ rite Main\n    $wide = !sort [1000000, -5, 3, 250000] algorithm=\"counting\"\n    $narrow = !sort [3, 1, 2, 1] algorithm=\"counting\" max_range=3\n    $capped = !sort [30, 10, 20] algorithm=\"counting\" max_range=2\n    $bad = !sort [1.5, 2] algorithm=\"counting\"\n~ end\n";
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    let nums = |xs: &[f64]| Some(Value::List(xs.iter().map(|n| Value::Number(*n)).collect()));
+    assert_eq!(ctx.get_var("wide"), nums(&[-5.0, 3.0, 250000.0, 1000000.0]));
+    assert_eq!(ctx.get_var("narrow"), nums(&[1.0, 1.0, 2.0, 3.0]));
+    assert_eq!(ctx.get_var("capped"), nums(&[10.0, 20.0, 30.0]));
+    // only the non-integer list is an error
+    assert_eq!(ctx.errors.len(), 1);
+    assert!(ctx.errors[0].message().contains("integer"));
+}
+
 #[test]
 fn gcd_and_fib() {
     let src = "~ rite Main\n    $g = !gcd 48 18\n    $f = !fib 10\n~ end\n";
@@ -95,3 +97,170 @@ fn gcd_and_fib() {
     assert_eq!(ctx.get_var("g"), Some(Value::Number(6.0)));
     assert_eq!(ctx.get_var("f"), Some(Value::Number(55.0)));
 }
+
+#[test]
+fn events_round_trip_through_json() {
+    let src = "~ rite Main\n    $x = 2\n    !ui \"card\"\n    !text \"hi\"\n    !ui_end\n    !ask \"Q\"\n~ end\n";
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    let json = serde_json::to_string(&ctx.events).unwrap();
+    let back: Vec<RuntimeEvent> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, ctx.events);
+    assert!(back.contains(&RuntimeEvent::SetVar { name: "x".into(), value: "2".into() }));
+    assert!(back.contains(&RuntimeEvent::UiEnd));
+}
+
+#[test]
+fn compact_json_has_no_newlines() {
+    let events = vec![RuntimeEvent::Say("a".into()), RuntimeEvent::UiEnd];
+    let compact = renderer::render_json(&events, true);
+    assert!(!compact.contains('\n'));
+    assert!(compact.starts_with("{\"naux_events_version\":1"));
+    assert!(renderer::render_json(&events, false).contains('\n'));
+}
+
+#[test]
+fn repeat_emits_callback_per_item() {
+    let src = "~ rite Main\n    $items = [\"a\", \"b\", \"c\"]\n    !repeat over $items -> !text $item\n~ end\n";
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    assert!(ctx.errors.is_empty());
+    let texts: Vec<&str> = ctx
+        .events
+        .iter()
+        .filter_map(|e| match e {
+            RuntimeEvent::Text(t) => Some(t.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn repeat_over_empty_list_emits_nothing() {
+    let src = "~ rite Main\n    $items = []\n    !repeat over $items -> !button $item\n~ end\n";
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    assert!(ctx.errors.is_empty());
+    assert!(!ctx.events.iter().any(|e| matches!(e, RuntimeEvent::Button(_))));
+}
+
+#[test]
+fn repeat_reports_non_list_and_missing_callback() {
+    let src = "~ rite Main\n    $n = 3\n    !repeat over $n -> !text $item\n    !repeat over $n\n~ end\n";
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    let messages: Vec<String> = ctx.errors.iter().map(|e| e.message()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "!repeat over expects a list".to_string(),
+            "!repeat expects a callback: !repeat over $items -> !action".to_string(),
+        ]
+    );
+    assert!(!ctx.events.iter().any(|e| matches!(e, RuntimeEvent::Text(_))));
+}
+
+/// UI structure of the events, e.g. `["ui card", "text hi", "end"]`.
+fn ui_shape(events: &[RuntimeEvent]) -> Vec<String> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            RuntimeEvent::Ui { kind, .. } => Some(format!("ui {}", kind)),
+            RuntimeEvent::UiEnd => Some("end".to_string()),
+            RuntimeEvent::Text(t) => Some(format!("text {}", t)),
+            RuntimeEvent::Button(b) => Some(format!("button {}", b)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn ui_blocks_nest_and_close_automatically() {
+    let src = r#"
+~ rite Main
+    ~ ui card
+        !text "title"
+        ~ ui "row"
+            !button "ok"
+            !button "cancel"
+        ~ end
+        !text "footer"
+    ~ end
+    ~ ui
+    ~ end
+~ end
+"#;
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    assert!(ctx.errors.is_empty());
+    assert_eq!(
+        ui_shape(&ctx.events),
+        vec![
+            "ui card", "text title", "ui row", "button ok", "button cancel", "end", "text footer", "end", "ui ui",
+            "end",
+        ]
+    );
+}
+
+#[test]
+fn ui_block_inside_loop_is_balanced_per_iteration() {
+    let src = "~ rite Main\n    @loop 2\n        ~ ui item\n            !text \"x\"\n        ~ end\n    @loop_end\n~ end\n";
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    assert_eq!(ui_shape(&ctx.events), vec!["ui item", "text x", "end", "ui item", "text x", "end"]);
+}
+
+#[test]
+fn unterminated_ui_block_is_a_parse_error() {
+    let src = "~ rite Main\n    ~ ui card\n        !text \"x\"\n";
+    let err = parse(src).err().expect("should fail");
+    assert!(err.to_string().contains("'~ ui'"), "{}", err);
+}
+
+fn run_errors(src: &str) -> (Vec<String>, Vec<String>) {
+    let program = parse(src).unwrap();
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    (ctx.errors.iter().map(|e| e.message()).collect(), ui_shape(&ctx.events))
+}
+
+#[test]
+fn balanced_ui_frames_report_no_errors() {
+    let (errors, shape) = run_errors("~ rite Main\n    !ui \"card\"\n    !ui \"row\"\n    !ui_end\n    !ui_end\n~ end\n");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(shape, vec!["ui card", "ui row", "end", "end"]);
+}
+
+#[test]
+fn unmatched_ui_end_is_an_error_and_emits_nothing() {
+    let (errors, shape) = run_errors("~ rite Main\n    !ui \"card\"\n    !ui_end\n    !ui_end\n~ end\n");
+    assert_eq!(errors, vec!["!ui_end has no matching !ui".to_string()]);
+    assert_eq!(shape, vec!["ui card", "end"]);
+}
+
+#[test]
+fn ui_frame_left_open_is_an_error_at_program_end() {
+    let (errors, _) = run_errors("~ rite Main\n    !ui \"card\"\n    !ui \"row\"\n    !ui_end\n~ end\n");
+    assert_eq!(errors, vec!["!ui card is never closed with !ui_end".to_string()]);
+}
+
+#[test]
+fn ui_block_keeps_its_frame_balanced_against_stray_actions() {
+    let src = "~ rite Main\n    ~ ui card\n        !ui_end\n        !ui \"row\"\n    ~ end\n~ end\n";
+    let (errors, shape) = run_errors(src);
+    assert_eq!(
+        errors,
+        vec![
+            "!ui_end has no matching !ui inside '~ ui card' block".to_string(),
+            "!ui row is not closed before the end of its '~ ui card' block".to_string(),
+        ]
+    );
+    assert_eq!(shape, vec!["ui card", "ui row", "end", "end"]);
+}
@@ -0,0 +1,114 @@
+use naux::ast::{Expr, Statement};
+use naux::parser::parse;
+use naux::runtime::{run_program, Context, Value};
+
+fn eval(expr: &str) -> Option<Value> {
+    let src = format!("~ rite Main\n    $r = {}\n~ end\n", expr);
+    let program = parse(&src).expect("parse");
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    ctx.get_var("r")
+}
+
+/// Render the parsed expression fully parenthesised, e.g. `((1 + 2) * 3)`.
+fn shape(expr: &str) -> String {
+    fn show(e: &Expr) -> String {
+        match e {
+            Expr::Literal { value, .. } => value.to_string(),
+            Expr::Var(v) => format!("${}", v.base),
+            Expr::Binary { op, left, right } => format!("({} {} {})", show(left), op, show(right)),
+            Expr::Unary { op, expr } => format!("({}{})", op, show(expr)),
+            other => format!("{:?}", other),
+        }
+    }
+    let src = format!("~ rite Main\n    $r = {}\n~ end\n", expr);
+    let program = parse(&src).expect("parse");
+    match &program[0].body[0] {
+        Statement::Assign(a) => show(&a.expr),
+        other => panic!("expected assignment, got {:?}", other),
+    }
+}
+
+#[test]
+fn arithmetic_binds_tighter_than_comparison() {
+    assert_eq!(shape("1 + 2 * 3"), "(1 + (2 * 3))");
+    assert_eq!(shape("1 + 2 < 3 * 4"), "((1 + 2) < (3 * 4))");
+    assert_eq!(eval("1 + 2 * 3"), Some(Value::Number(7.0)));
+    assert_eq!(eval("2 * 3 > 5"), Some(Value::Boolean(true)));
+}
+
+#[test]
+fn same_level_operators_are_left_associative() {
+    assert_eq!(shape("10 - 4 - 3"), "((10 - 4) - 3)");
+    assert_eq!(shape("24 / 4 / 2"), "((24 / 4) / 2)");
+    assert_eq!(shape("8 / 2 * 2"), "((8 / 2) * 2)");
+    assert_eq!(eval("10 - 4 - 3"), Some(Value::Number(3.0)));
+    assert_eq!(eval("24 / 4 / 2"), Some(Value::Number(3.0)));
+}
+
+#[test]
+fn chained_equality_does_not_truncate() {
+    assert_eq!(shape("1 == 2 == 3"), "((1 == 2) == 3)");
+    // (1 == 2) is a boolean, which never equals a number
+    assert_eq!(eval("1 == 2 == 3"), Some(Value::Boolean(false)));
+    assert_eq!(eval("1 == 1 == true"), Some(Value::Boolean(true)));
+    assert_eq!(eval("1 != 2 != false"), Some(Value::Boolean(true)));
+}
+
+#[test]
+fn relational_binds_tighter_than_equality() {
+    assert_eq!(shape("1 < 2 == 3 < 4"), "((1 < 2) == (3 < 4))");
+    assert_eq!(eval("1 < 2 == 3 < 4"), Some(Value::Boolean(true)));
+    assert_eq!(eval("1 < 2 != 4 <= 3"), Some(Value::Boolean(true)));
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    assert_eq!(shape("1 || 2 && 3"), "(1 || (2 && 3))");
+    assert_eq!(shape("1 && 2 || 3 && 4"), "((1 && 2) || (3 && 4))");
+    assert_eq!(eval("true || false && false"), Some(Value::Boolean(true)));
+    assert_eq!(eval("false && true || true"), Some(Value::Boolean(true)));
+}
+
+#[test]
+fn logic_binds_looser_than_comparison() {
+    assert_eq!(shape("1 < 2 && 3 == 3"), "((1 < 2) && (3 == 3))");
+    assert_eq!(shape("1 + 1 == 2 || 0 > 1"), "(((1 + 1) == 2) || (0 > 1))");
+    assert_eq!(eval("1 < 2 && 3 == 3"), Some(Value::Boolean(true)));
+    assert_eq!(eval("1 > 2 || 2 > 3"), Some(Value::Boolean(false)));
+}
+
+#[test]
+fn unary_minus_binds_tightest() {
+    assert_eq!(shape("- $a * 2"), "((-$a) * 2)");
+    assert_eq!(eval("- 2 * 3 + 10"), Some(Value::Number(4.0)));
+}
+
+#[test]
+fn not_equal_compares_all_scalar_kinds() {
+    assert_eq!(eval("3 != 3"), Some(Value::Boolean(false)));
+    assert_eq!(eval("\"a\" != \"b\""), Some(Value::Boolean(true)));
+    assert_eq!(eval("true != true"), Some(Value::Boolean(false)));
+    assert_eq!(eval("\"1\" != 1"), Some(Value::Boolean(true)));
+}
+
+#[test]
+fn logic_operators_short_circuit() {
+    // the right-hand unknown action would log an error if it ever ran
+    let src = "~ rite Main\n    $r = false && !nope\n    $s = true || !nope\n~ end\n";
+    let program = parse(src).expect("parse");
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    assert_eq!(ctx.get_var("r"), Some(Value::Boolean(false)));
+    assert_eq!(ctx.get_var("s"), Some(Value::Boolean(true)));
+    assert!(ctx.errors.is_empty());
+}
+
+#[test]
+fn if_condition_uses_full_precedence() {
+    let src = "~ rite Main\n    $x = 5\n    @if $x > 1 && $x < 10 == true\n        $r = \"in\"\n    @else\n        $r = \"out\"\n    @if_end\n~ end\n";
+    let program = parse(src).expect("parse");
+    let mut ctx = Context::new();
+    run_program(&program, Some("Main"), &mut ctx);
+    assert_eq!(ctx.get_var("r"), Some(Value::String("in".into())));
+}
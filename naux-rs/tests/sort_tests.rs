@@ -0,0 +1,73 @@
+use naux::parser::parse;
+use naux::runtime::{run_program, Context, Value};
+
+/// Sorts `xs` with `!sort ... algorithm="quick"` and returns the resulting numbers.
+fn quick_sort(xs: &[f64]) -> Vec<f64> {
+    let program = parse("~ rite Main\n    $out = !sort $xs algorithm=\"quick\"\n~ end\n").unwrap();
+    let mut ctx = Context::new();
+    ctx.set_var("xs", Value::List(xs.iter().map(|n| Value::Number(*n)).collect()));
+    run_program(&program, Some("Main"), &mut ctx);
+    assert!(ctx.errors.is_empty(), "{:?}", ctx.errors.iter().map(|e| e.message()).collect::<Vec<_>>());
+    match ctx.get_var("out") {
+        Some(Value::List(items)) => items
+            .into_iter()
+            .map(|v| match v {
+                Value::Number(n) => n,
+                other => panic!("non-number in sorted output: {:?}", other),
+            })
+            .collect(),
+        other => panic!("expected list, got {:?}", other),
+    }
+}
+
+fn assert_sorts(xs: &[f64]) {
+    let mut expected = xs.to_vec();
+    expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(quick_sort(xs), expected, "input: {:?}", xs);
+}
+
+/// Small deterministic xorshift generator so the property test needs no extra crates.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[test]
+fn quick_sort_edge_cases() {
+    assert_sorts(&[]);
+    assert_sorts(&[1.0]);
+    assert_sorts(&[2.0, 1.0]);
+    assert_sorts(&[1.0, 2.0]);
+    assert_sorts(&[1.0, 1.0]);
+    assert_sorts(&[5.0; 64]);
+    assert_sorts(&(0..200).map(f64::from).collect::<Vec<_>>());
+    assert_sorts(&(0..200).rev().map(f64::from).collect::<Vec<_>>());
+    assert_sorts(&[3.0, -1.0, 3.0, 0.5, -1.0, 3.0]);
+}
+
+#[test]
+fn quick_sort_handles_large_degenerate_inputs() {
+    // these must neither loop forever nor exhaust the stack
+    assert_sorts(&[7.0; 50_000]);
+    assert_sorts(&(0..50_000).map(f64::from).collect::<Vec<_>>());
+    let organ_pipe: Vec<f64> = (0..25_000).chain((0..25_000).rev()).map(f64::from).collect();
+    assert_sorts(&organ_pipe);
+}
+
+#[test]
+fn quick_sort_matches_std_sort_on_random_inputs() {
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for case in 0..500 {
+        let len = (rng.next() % 40) as usize;
+        // alternate narrow value ranges (many duplicates) with wide ones
+        let spread = if case % 2 == 0 { 4 } else { 1_000 };
+        let xs: Vec<f64> = (0..len).map(|_| (rng.next() % spread) as f64 - (spread / 2) as f64).collect();
+        assert_sorts(&xs);
+    }
+}